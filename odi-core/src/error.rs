@@ -26,6 +26,9 @@ pub enum CoreError {
     #[error("Validation failed: {field}: {message}")]
     ValidationError { field: String, message: String },
 
+    #[error("Object format version {found} is newer than this version of odi supports ({supported}); upgrade odi")]
+    UnsupportedFormat { found: u32, supported: u32 },
+
     #[error("Serialization error: {0}")]
     Serialization(#[from] serde_json::Error),
 
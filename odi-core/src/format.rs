@@ -0,0 +1,242 @@
+//! Versioned envelope for stored and transmitted objects
+//!
+//! Every object ODI writes to disk or sends to a remote is wrapped in an
+//! envelope recording the format version it was written with:
+//!
+//! ```json
+//! {"odi": 1, "kind": "issue", "data": { ... }}
+//! ```
+//!
+//! The payload is JSON, so fields added to an entity (with a serde default) can
+//! still be read from older objects. Changes that need more than a default are
+//! handled by an upgrade step that rewrites the payload of one version into the
+//! next. Objects written by a newer version of ODI are refused rather than
+//! silently misread. Bare JSON objects written before envelopes existed are
+//! read as version 0.
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{CoreError, Result};
+
+/// Current object format version
+pub const FORMAT_VERSION: u32 = 2;
+
+/// Types that can be wrapped in a versioned envelope
+pub trait Versioned: Serialize + DeserializeOwned {
+    /// Kind recorded in the envelope, used to pick upgrade steps
+    const KIND: &'static str;
+}
+
+impl Versioned for crate::Issue {
+    const KIND: &'static str = "issue";
+}
+
+impl Versioned for crate::Project {
+    const KIND: &'static str = "project";
+}
+
+impl Versioned for crate::Workspace {
+    const KIND: &'static str = "workspace";
+}
+
+impl Versioned for crate::Label {
+    const KIND: &'static str = "label";
+}
+
+impl Versioned for crate::View {
+    const KIND: &'static str = "view";
+}
+
+impl Versioned for crate::User {
+    const KIND: &'static str = "user";
+}
+
+impl Versioned for crate::Team {
+    const KIND: &'static str = "team";
+}
+
+impl Versioned for crate::Remote {
+    const KIND: &'static str = "remote";
+}
+
+/// Versioned wrapper around a serialized object
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Envelope {
+    /// Format version the payload was written with
+    pub odi: u32,
+    /// Kind of object in the payload
+    pub kind: String,
+    /// The object itself
+    pub data: Value,
+}
+
+/// Upgrade a payload of `kind` from `version` to `version + 1`
+///
+/// Add an arm here whenever `FORMAT_VERSION` is bumped.
+fn upgrade_step(kind: &str, version: u32, data: Value) -> Result<Value> {
+    match version {
+        // Version 1 introduced the envelope; payloads are unchanged
+        0 => Ok(data),
+        // Version 2 replaced the commit SHAs in `Issue::git_refs` with typed links
+        1 if kind == "issue" => Ok(typed_git_refs(data)),
+        1 => Ok(data),
+        _ => Err(CoreError::ValidationError {
+            field: "format".to_string(),
+            message: format!("No upgrade from object format version {}", version),
+        }),
+    }
+}
+
+/// Turn an issue's plain commit SHAs into commit links
+fn typed_git_refs(mut data: Value) -> Value {
+    let linked_at = data.get("updated_at").cloned().unwrap_or(Value::Null);
+    if let Some(Value::Array(refs)) = data.get_mut("git_refs") {
+        for git_ref in refs.iter_mut() {
+            if let Value::String(sha) = git_ref {
+                *git_ref = serde_json::json!({
+                    "type": "commit",
+                    "sha": sha,
+                    "role": "mentions",
+                    "linked_at": linked_at,
+                });
+            }
+        }
+    }
+    data
+}
+
+impl Envelope {
+    /// Wrap an object at the current format version
+    pub fn wrap<T: Versioned>(value: &T) -> Result<Self> {
+        Ok(Self {
+            odi: FORMAT_VERSION,
+            kind: T::KIND.to_string(),
+            data: serde_json::to_value(value)?,
+        })
+    }
+
+    /// Parse serialized bytes, treating a bare JSON object as a version 0 payload
+    pub fn parse(bytes: &[u8]) -> Result<Self> {
+        let value: Value = serde_json::from_slice(bytes)?;
+        let is_envelope = value.get("odi").is_some_and(Value::is_u64)
+            && value.get("kind").is_some_and(Value::is_string)
+            && value.get("data").is_some();
+
+        if is_envelope {
+            Ok(serde_json::from_value(value)?)
+        } else {
+            Ok(Self { odi: 0, kind: String::new(), data: value })
+        }
+    }
+
+    /// Upgrade the payload to the current format version
+    pub fn upgrade(mut self) -> Result<Self> {
+        if self.odi > FORMAT_VERSION {
+            return Err(CoreError::UnsupportedFormat {
+                found: self.odi,
+                supported: FORMAT_VERSION,
+            });
+        }
+
+        while self.odi < FORMAT_VERSION {
+            self.data = upgrade_step(&self.kind, self.odi, self.data)?;
+            self.odi += 1;
+        }
+        Ok(self)
+    }
+
+    /// Unwrap the payload as `T`, upgrading it first if needed
+    pub fn unwrap_as<T: Versioned>(self) -> Result<T> {
+        if !self.kind.is_empty() && self.kind != T::KIND {
+            return Err(CoreError::ValidationError {
+                field: "format".to_string(),
+                message: format!("Expected a {} object, found a {}", T::KIND, self.kind),
+            });
+        }
+        let kind = T::KIND.to_string();
+        let envelope = Self { kind, ..self }.upgrade()?;
+        Ok(serde_json::from_value(envelope.data)?)
+    }
+}
+
+/// Serialize an object inside a current-version envelope
+pub fn seal<T: Versioned>(value: &T) -> Result<Vec<u8>> {
+    Ok(serde_json::to_vec(&Envelope::wrap(value)?)?)
+}
+
+/// Deserialize an object from an envelope (or a bare legacy object)
+pub fn open<T: Versioned>(bytes: &[u8]) -> Result<T> {
+    Envelope::parse(bytes)?.unwrap_as()
+}
+
+/// Rewrite serialized bytes as a current-version envelope of `kind`
+///
+/// Used by workspace migrations, which upgrade objects without knowing their
+/// concrete type.
+pub fn reseal(bytes: &[u8], kind: &str) -> Result<Vec<u8>> {
+    let mut envelope = Envelope::parse(bytes)?;
+    if envelope.kind.is_empty() {
+        envelope.kind = kind.to_string();
+    }
+    Ok(serde_json::to_vec(&envelope.upgrade()?)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Issue;
+
+    #[test]
+    fn test_seal_and_open() {
+        let issue = Issue::new("Versioned".to_string(), "alice".to_string());
+        let bytes = seal(&issue).unwrap();
+
+        let envelope = Envelope::parse(&bytes).unwrap();
+        assert_eq!(envelope.odi, FORMAT_VERSION);
+        assert_eq!(envelope.kind, "issue");
+
+        let opened: Issue = open(&bytes).unwrap();
+        assert_eq!(opened.id, issue.id);
+    }
+
+    #[test]
+    fn test_open_legacy_bare_object() {
+        let issue = Issue::new("Legacy".to_string(), "alice".to_string());
+        let bytes = serde_json::to_vec(&issue).unwrap();
+
+        assert_eq!(Envelope::parse(&bytes).unwrap().odi, 0);
+        let opened: Issue = open(&bytes).unwrap();
+        assert_eq!(opened.title, "Legacy");
+    }
+
+    #[test]
+    fn test_upgrade_issue_git_refs() {
+        let issue = Issue::new("Linked".to_string(), "alice".to_string());
+        let mut data = serde_json::to_value(&issue).unwrap();
+        data["git_refs"] = serde_json::json!(["a1b2c3d4e5f6"]);
+        let envelope = Envelope { odi: 1, kind: "issue".to_string(), data };
+
+        let opened: Issue = open(&serde_json::to_vec(&envelope).unwrap()).unwrap();
+        assert_eq!(opened.git_refs, vec![crate::GitRef {
+            linked_at: issue.updated_at,
+            ..crate::GitRef::commit("a1b2c3d4e5f6", crate::LinkRole::Mentions)
+        }]);
+    }
+
+    #[test]
+    fn test_refuse_newer_format() {
+        let issue = Issue::new("Future".to_string(), "alice".to_string());
+        let mut envelope = Envelope::wrap(&issue).unwrap();
+        envelope.odi = FORMAT_VERSION + 1;
+        let bytes = serde_json::to_vec(&envelope).unwrap();
+
+        match open::<Issue>(&bytes) {
+            Err(CoreError::UnsupportedFormat { found, supported }) => {
+                assert_eq!(found, FORMAT_VERSION + 1);
+                assert_eq!(supported, FORMAT_VERSION);
+            }
+            other => panic!("expected unsupported format, got {:?}", other.map(|i| i.id)),
+        }
+    }
+}
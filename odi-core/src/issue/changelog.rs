@@ -0,0 +1,231 @@
+//! Changelogs and release notes built from issues
+//!
+//! A [`Changelog`] lists the issues of a release, grouped either by label or by
+//! the kind of change their labels suggest, using the sections of
+//! [Keep a Changelog](https://keepachangelog.com): a `bug` label makes an issue
+//! a fix, a `feature` label an addition, and so on.
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::{Issue, IssueId, IssueStatus};
+
+/// How changelog entries are grouped
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum ChangelogGrouping {
+    /// By kind of change (Added, Fixed, ...), from the issue's labels
+    #[default]
+    Type,
+    /// By the issue's first label
+    Label,
+}
+
+/// Kind of change, as the sections of Keep a Changelog
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum ChangeType {
+    Added,
+    Changed,
+    Deprecated,
+    Removed,
+    Fixed,
+    Security,
+}
+
+impl ChangeType {
+    /// Kind of change suggested by an issue's labels; `Changed` when none does
+    pub fn from_labels(labels: &[String]) -> Self {
+        let kinds: Vec<ChangeType> = labels.iter().filter_map(|label| match label.to_lowercase().as_str() {
+            "security" | "vulnerability" => Some(ChangeType::Security),
+            "bug" | "fix" | "bugfix" | "defect" | "regression" => Some(ChangeType::Fixed),
+            "feature" | "enhancement" | "new" => Some(ChangeType::Added),
+            "deprecation" | "deprecated" => Some(ChangeType::Deprecated),
+            "removal" | "removed" | "breaking-removal" => Some(ChangeType::Removed),
+            _ => None,
+        }).collect();
+        // Security notes must stand out, so they win over any other label
+        [ChangeType::Security, ChangeType::Removed, ChangeType::Deprecated, ChangeType::Fixed, ChangeType::Added]
+            .into_iter()
+            .find(|kind| kinds.contains(kind))
+            .unwrap_or(ChangeType::Changed)
+    }
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            ChangeType::Added => "Added",
+            ChangeType::Changed => "Changed",
+            ChangeType::Deprecated => "Deprecated",
+            ChangeType::Removed => "Removed",
+            ChangeType::Fixed => "Fixed",
+            ChangeType::Security => "Security",
+        }
+    }
+}
+
+/// An issue in a changelog
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChangelogEntry {
+    pub id: IssueId,
+    pub key: String,
+    pub title: String,
+    pub status: IssueStatus,
+    pub labels: Vec<String>,
+    /// Commits in the release linked to the issue
+    pub commits: Vec<String>,
+}
+
+/// A group of entries under one heading
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChangelogSection {
+    pub title: String,
+    pub entries: Vec<ChangelogEntry>,
+}
+
+/// The issues of a release, grouped into sections
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Changelog {
+    /// Revision the release starts after; `None` for everything up to `to`
+    pub from: Option<String>,
+    /// Revision of the release, usually its tag
+    pub to: String,
+    pub date: Option<DateTime<Utc>>,
+    pub sections: Vec<ChangelogSection>,
+}
+
+/// Section heading for issues without labels when grouping by label
+const UNLABELED: &str = "Other";
+
+impl Changelog {
+    /// Group issues, each with the commits linking it to the release
+    ///
+    /// Sections are ordered as in Keep a Changelog, or by label name; entries
+    /// keep the order they were given in.
+    pub fn new(from: Option<String>, to: String, date: Option<DateTime<Utc>>, issues: Vec<(Issue, Vec<String>)>, grouping: ChangelogGrouping) -> Self {
+        let mut by_type: BTreeMap<ChangeType, Vec<ChangelogEntry>> = BTreeMap::new();
+        let mut by_label: BTreeMap<String, Vec<ChangelogEntry>> = BTreeMap::new();
+        let mut unlabeled = Vec::new();
+
+        for (issue, commits) in issues {
+            let entry = ChangelogEntry {
+                id: issue.id,
+                key: issue.short_id(),
+                title: issue.title,
+                status: issue.status,
+                labels: issue.labels,
+                commits,
+            };
+            match grouping {
+                ChangelogGrouping::Type => by_type.entry(ChangeType::from_labels(&entry.labels)).or_default().push(entry),
+                ChangelogGrouping::Label => match entry.labels.first() {
+                    Some(label) => by_label.entry(label.clone()).or_default().push(entry),
+                    None => unlabeled.push(entry),
+                },
+            }
+        }
+
+        let mut sections: Vec<ChangelogSection> = by_type.into_iter()
+            .map(|(kind, entries)| ChangelogSection { title: kind.title().to_string(), entries })
+            .chain(by_label.into_iter().map(|(title, entries)| ChangelogSection { title, entries }))
+            .collect();
+        if !unlabeled.is_empty() {
+            sections.push(ChangelogSection { title: UNLABELED.to_string(), entries: unlabeled });
+        }
+        Self { from, to, date, sections }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sections.is_empty()
+    }
+
+    /// Release notes in Markdown, with the commits of each issue
+    pub fn to_markdown(&self) -> String {
+        let mut out = match &self.from {
+            Some(from) => format!("# Changes in {} since {}\n", self.to, from),
+            None => format!("# Changes in {}\n", self.to),
+        };
+        for section in &self.sections {
+            out.push_str(&format!("\n## {}\n\n", section.title));
+            for entry in &section.entries {
+                out.push_str(&format!("- {} ({})", entry.title, entry.key));
+                if !entry.commits.is_empty() {
+                    let commits: Vec<&str> = entry.commits.iter().map(|sha| &sha[..sha.len().min(7)]).collect();
+                    out.push_str(&format!(" — {}", commits.join(", ")));
+                }
+                out.push('\n');
+            }
+        }
+        if self.sections.is_empty() {
+            out.push_str("\nNo changes.\n");
+        }
+        out
+    }
+
+    /// A release section in the Keep a Changelog format, to paste into CHANGELOG.md
+    pub fn to_keep_a_changelog(&self) -> String {
+        // Changes not tagged yet go under Unreleased
+        let version = self.to.trim_start_matches('v');
+        let mut out = match self.date {
+            _ if self.to == "HEAD" => "## [Unreleased]\n".to_string(),
+            Some(date) => format!("## [{}] - {}\n", version, date.format("%Y-%m-%d")),
+            None => format!("## [{}]\n", version),
+        };
+        for section in &self.sections {
+            out.push_str(&format!("\n### {}\n\n", section.title));
+            for entry in &section.entries {
+                out.push_str(&format!("- {} ({})\n", entry.title, entry.key));
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn issue(title: &str, labels: &[&str]) -> Issue {
+        let mut issue = Issue::new(title.to_string(), "alice".to_string());
+        issue.labels = labels.iter().map(|label| label.to_string()).collect();
+        issue
+    }
+
+    #[test]
+    fn test_change_type_from_labels() {
+        assert_eq!(ChangeType::from_labels(&["ui".to_string(), "Bug".to_string()]), ChangeType::Fixed);
+        assert_eq!(ChangeType::from_labels(&["bug".to_string(), "security".to_string()]), ChangeType::Security);
+        assert_eq!(ChangeType::from_labels(&[]), ChangeType::Changed);
+    }
+
+    #[test]
+    fn test_changelog_by_type() {
+        let login = issue("Login fails on Safari", &["bug"]);
+        let export = issue("Export to CSV", &["feature"]);
+        let changelog = Changelog::new(Some("v1.0".to_string()), "v1.1".to_string(), None, vec![
+            (login.clone(), vec!["a1b2c3d4e5f6a7b8".to_string()]),
+            (export.clone(), Vec::new()),
+            (issue("Faster startup", &[]), Vec::new()),
+        ], ChangelogGrouping::Type);
+
+        let titles: Vec<&str> = changelog.sections.iter().map(|section| section.title.as_str()).collect();
+        assert_eq!(titles, vec!["Added", "Changed", "Fixed"]);
+        let markdown = changelog.to_markdown();
+        assert!(markdown.starts_with("# Changes in v1.1 since v1.0\n"));
+        assert!(markdown.contains(&format!("- Login fails on Safari ({}) — a1b2c3d\n", login.short_id())));
+        assert!(changelog.to_keep_a_changelog().starts_with(&format!("## [1.1]\n\n### Added\n\n- Export to CSV ({})\n", export.short_id())));
+    }
+
+    #[test]
+    fn test_changelog_by_label() {
+        let changelog = Changelog::new(None, "v1.1".to_string(), None, vec![
+            (issue("No label", &[]), Vec::new()),
+            (issue("Styling", &["ui", "bug"]), Vec::new()),
+            (issue("Crash", &["bug"]), Vec::new()),
+        ], ChangelogGrouping::Label);
+
+        let titles: Vec<&str> = changelog.sections.iter().map(|section| section.title.as_str()).collect();
+        assert_eq!(titles, vec!["bug", "ui", "Other"]);
+    }
+}
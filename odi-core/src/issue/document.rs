@@ -0,0 +1,384 @@
+//! Issues as Markdown documents with YAML front matter
+//!
+//! `odi issue edit` opens an issue in an editor as a document such as
+//!
+//! ```text
+//! ---
+//! title: Login page crashes on Safari
+//! status: open
+//! priority: high
+//! assignees:
+//! - alice
+//! labels:
+//! - bug
+//! project: web
+//! ---
+//!
+//! Open the login page in Safari 17 and submit the form.
+//! ```
+//!
+//! The front matter holds the fields people change by hand and the body is the
+//! description. Documents mirrored to files by `odi mirror` also carry the
+//! issue's `id`, to find the issue a file belongs to. Problems found when the document is read are reported with
+//! the line they are on, and can be written back into the front matter as
+//! comments for the next round of editing.
+
+use serde::{Deserialize, Serialize};
+
+use super::query::{parse_priority, parse_status};
+use super::{Issue, IssueId, IssueStatus, IssueUpdate, Priority};
+use crate::project::{LabelId, ProjectId};
+use crate::user::UserId;
+use crate::{CoreError, Result};
+
+/// Line opening and closing the front matter
+const DELIMITER: &str = "---";
+/// Start of the comment lines that carry errors in the front matter
+const ERROR_PREFIX: &str = "# error: ";
+
+/// Fields of an issue that can be edited as a document
+#[derive(Debug, Clone, PartialEq)]
+pub struct IssueDocument {
+    /// Issue the document belongs to; documents for the editor leave it out
+    pub id: Option<IssueId>,
+    pub title: String,
+    pub status: IssueStatus,
+    pub priority: Priority,
+    pub assignees: Vec<UserId>,
+    pub labels: Vec<LabelId>,
+    pub project: Option<ProjectId>,
+    pub description: Option<String>,
+}
+
+/// The front matter as written: statuses and priorities are kept as text so
+/// they can be spelled as in queries, like `in-progress`
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FrontMatter {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    id: Option<IssueId>,
+    title: String,
+    #[serde(default)]
+    status: Option<String>,
+    #[serde(default)]
+    priority: Option<String>,
+    #[serde(default)]
+    assignees: Vec<String>,
+    #[serde(default)]
+    labels: Vec<String>,
+    #[serde(default)]
+    project: Option<String>,
+}
+
+impl IssueDocument {
+    pub fn from_issue(issue: &Issue) -> Self {
+        Self {
+            id: None,
+            title: issue.title.clone(),
+            status: issue.status.clone(),
+            priority: issue.priority.clone(),
+            assignees: issue.assignees.clone(),
+            labels: issue.labels.clone(),
+            project: issue.project_id.clone(),
+            description: issue.description.clone(),
+        }
+    }
+
+    /// The document as Markdown
+    pub fn to_markdown(&self) -> String {
+        let front = FrontMatter {
+            id: self.id,
+            title: self.title.clone(),
+            status: Some(status_name(&self.status).to_string()),
+            priority: Some(format!("{:?}", self.priority).to_lowercase()),
+            assignees: self.assignees.clone(),
+            labels: self.labels.clone(),
+            project: self.project.clone(),
+        };
+        let yaml = serde_yaml::to_string(&front).expect("front matter of plain strings");
+        let mut text = format!("{}\n{}{}\n\n", DELIMITER, yaml, DELIMITER);
+        if let Some(description) = &self.description {
+            text.push_str(description.trim_end());
+            text.push('\n');
+        }
+        text
+    }
+
+    /// Read a document
+    ///
+    /// A missing status or priority is `open` or `medium`, as for new issues.
+    /// Every problem found is reported, one per line of the error message,
+    /// with the line of the document it is on, not counting error comments.
+    pub fn parse(text: &str) -> Result<Self> {
+        let lines: Vec<&str> = text.trim_start_matches('\u{feff}').lines()
+            .filter(|line| !line.starts_with(ERROR_PREFIX))
+            .collect();
+        let Some(open) = lines.iter().position(|line| !line.trim().is_empty()).filter(|&i| lines[i].trim_end() == DELIMITER) else {
+            return Err(invalid(vec![(Some(1), format!("The document must start with front matter between '{}' lines", DELIMITER))]));
+        };
+        let Some(close) = lines[open + 1..].iter().position(|line| line.trim_end() == DELIMITER).map(|i| open + 1 + i) else {
+            return Err(invalid(vec![(Some(open + 1), format!("The front matter has no closing '{}' line", DELIMITER))]));
+        };
+
+        let front = lines[open + 1..close].join("\n");
+        // Line of the document where a field of the front matter is
+        let line_of = |key: &str| lines[open + 1..close].iter()
+            .position(|line| line.strip_prefix(key).is_some_and(|rest| rest.starts_with(':')))
+            .map(|i| open + 2 + i);
+
+        let front: FrontMatter = serde_yaml::from_str(&front).map_err(|e| {
+            let line = e.location().map(|location| open + 1 + location.line());
+            // The location is given separately, relative to the document
+            let message = e.to_string();
+            let message = message.split(" at line ").next().unwrap_or(&message).to_string();
+            invalid(vec![(line, capitalize(&message))])
+        })?;
+
+        let mut errors = Vec::new();
+        let title = front.title.trim().to_string();
+        if !Issue::validate_title(&title) {
+            errors.push((line_of("title"), "Title must be 1-100 characters".to_string()));
+        }
+        let status = match front.status.as_deref().map(parse_status).transpose() {
+            Ok(status) => status.unwrap_or(IssueStatus::Open),
+            Err(e) => {
+                errors.push((line_of("status"), message_of(e)));
+                IssueStatus::Open
+            }
+        };
+        let priority = match front.priority.as_deref().map(parse_priority).transpose() {
+            Ok(priority) => priority.unwrap_or(Priority::Medium),
+            Err(e) => {
+                errors.push((line_of("priority"), message_of(e)));
+                Priority::Medium
+            }
+        };
+        if !errors.is_empty() {
+            return Err(invalid(errors));
+        }
+
+        let body = lines[close + 1..].join("\n");
+        let body = body.trim();
+        Ok(Self {
+            id: front.id,
+            title,
+            status,
+            priority,
+            assignees: names(front.assignees),
+            labels: names(front.labels),
+            project: front.project.map(|project| project.trim().to_string()).filter(|project| !project.is_empty()),
+            description: (!body.is_empty()).then(|| body.to_string()),
+        })
+    }
+
+    /// The change that makes an issue match this document, setting only the
+    /// fields that differ so concurrent changes to the others are kept
+    pub fn update(&self, issue: &Issue) -> Result<IssueUpdate> {
+        let mut update = IssueUpdate::new();
+        if self.title != issue.title {
+            update = update.title(self.title.clone());
+        }
+        if self.description != issue.description {
+            update = update.description(self.description.clone());
+        }
+        if self.status != issue.status {
+            if !issue.can_transition_to(&self.status) {
+                return Err(invalid(vec![(None, format!(
+                    "An issue can't go from {} to {}",
+                    status_name(&issue.status), status_name(&self.status)
+                ))]));
+            }
+            update = update.status(self.status.clone());
+        }
+        if self.priority != issue.priority {
+            update = update.priority(self.priority.clone());
+        }
+        if self.assignees != issue.assignees {
+            update = update.assignees(self.assignees.clone());
+        }
+        if self.labels != issue.labels {
+            update = update.labels(self.labels.clone());
+        }
+        if self.project != issue.project_id {
+            update = update.project(self.project.clone());
+        }
+        Ok(update)
+    }
+
+    /// Three-way merge of two edits of a document: fields changed on only one
+    /// side since `base` take that side's value, and fields changed differently
+    /// on both sides are returned as conflicts, taking the value of `theirs`
+    pub fn merge(base: &Self, ours: &Self, theirs: &Self) -> (Self, Vec<&'static str>) {
+        fn pick<T: PartialEq + Clone>(field: &'static str, base: &T, ours: &T, theirs: &T, conflicts: &mut Vec<&'static str>) -> T {
+            if theirs == base || theirs == ours {
+                return ours.clone();
+            }
+            if ours != base {
+                conflicts.push(field);
+            }
+            theirs.clone()
+        }
+        let mut conflicts = Vec::new();
+        let merged = Self {
+            id: ours.id.or(theirs.id),
+            title: pick("title", &base.title, &ours.title, &theirs.title, &mut conflicts),
+            status: pick("status", &base.status, &ours.status, &theirs.status, &mut conflicts),
+            priority: pick("priority", &base.priority, &ours.priority, &theirs.priority, &mut conflicts),
+            assignees: pick("assignees", &base.assignees, &ours.assignees, &theirs.assignees, &mut conflicts),
+            labels: pick("labels", &base.labels, &ours.labels, &theirs.labels, &mut conflicts),
+            project: pick("project", &base.project, &ours.project, &theirs.project, &mut conflicts),
+            description: pick("description", &base.description, &ours.description, &theirs.description, &mut conflicts),
+        };
+        (merged, conflicts)
+    }
+
+    /// A document with errors written into its front matter as comments,
+    /// replacing those from an earlier round
+    ///
+    /// `errors` holds one error per line, as in the message of [`parse`](Self::parse);
+    /// line numbers in them are moved down past the comments.
+    pub fn annotate(text: &str, errors: &str) -> String {
+        let mut lines: Vec<&str> = text.lines().filter(|line| !line.starts_with(ERROR_PREFIX)).collect();
+        let at = lines.iter().position(|line| line.trim_end() == DELIMITER).map_or(0, |i| i + 1);
+        let count = errors.lines().count();
+        let annotations: Vec<String> = errors.lines()
+            .map(|error| {
+                let shifted = error.strip_prefix("line ")
+                    .and_then(|rest| rest.split_once(": "))
+                    .and_then(|(line, message)| Some((line.parse::<usize>().ok()?, message)))
+                    .map(|(line, message)| format!("line {}: {}", if line > at { line + count } else { line }, message));
+                format!("{}{}", ERROR_PREFIX, shifted.unwrap_or_else(|| error.to_string()))
+            })
+            .collect();
+        lines.splice(at..at, annotations.iter().map(String::as_str));
+        lines.join("\n") + "\n"
+    }
+}
+
+/// Status as written in documents and queries
+fn status_name(status: &IssueStatus) -> &'static str {
+    match status {
+        IssueStatus::Open => "open",
+        IssueStatus::InProgress => "in-progress",
+        IssueStatus::Resolved => "resolved",
+        IssueStatus::Closed => "closed",
+    }
+}
+
+/// Names of assignees or labels, trimmed, without blanks and repeats
+fn names(values: Vec<String>) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for value in values.iter().map(|value| value.trim()).filter(|value| !value.is_empty()) {
+        if !names.iter().any(|name| name == value) {
+            names.push(value.to_string());
+        }
+    }
+    names
+}
+
+fn message_of(error: CoreError) -> String {
+    match error {
+        CoreError::ValidationError { message, .. } => message,
+        other => other.to_string(),
+    }
+}
+
+fn capitalize(message: &str) -> String {
+    let mut chars = message.chars();
+    chars.next().map(|first| first.to_uppercase().chain(chars).collect()).unwrap_or_default()
+}
+
+fn invalid(errors: Vec<(Option<usize>, String)>) -> CoreError {
+    CoreError::ValidationError {
+        field: "document".to_string(),
+        message: errors.into_iter()
+            .map(|(line, message)| match line {
+                Some(line) => format!("line {}: {}", line, message),
+                None => message,
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn errors(text: &str) -> String {
+        message_of(IssueDocument::parse(text).unwrap_err())
+    }
+
+    #[test]
+    fn test_document_round_trip() {
+        let mut issue = Issue::new("Login: page crashes".to_string(), "alice".to_string());
+        issue.description = Some("Steps:\n\n1. Open the page".to_string());
+        issue.status = IssueStatus::InProgress;
+        issue.labels = vec!["bug".to_string()];
+
+        let document = IssueDocument::from_issue(&issue);
+        let text = document.to_markdown();
+        assert!(text.starts_with("---\ntitle: 'Login: page crashes'\nstatus: in-progress\n"));
+        assert_eq!(IssueDocument::parse(&text).unwrap(), document);
+        assert!(document.update(&issue).unwrap().title.is_none());
+
+        let edited = IssueDocument::parse(&text
+            .replace("in-progress", "Resolved")
+            .replace("assignees: []", "assignees: [bob, ' bob ', '']")
+            .replace("1. Open the page", "1. Open the page\n2. Submit")).unwrap();
+        let update = edited.update(&issue).unwrap();
+        assert_eq!(update.status, Some(IssueStatus::Resolved));
+        assert_eq!(update.assignees, Some(vec!["bob".to_string()]));
+        assert_eq!(update.description, Some(Some("Steps:\n\n1. Open the page\n2. Submit".to_string())));
+        assert!(update.labels.is_none() && update.title.is_none());
+
+        let minimal = IssueDocument::parse("---\ntitle: Crash\n---\n").unwrap();
+        assert_eq!((&minimal.status, &minimal.priority, &minimal.description), (&IssueStatus::Open, &Priority::Medium, &None));
+        let reopened = IssueDocument { status: IssueStatus::Resolved, ..minimal };
+        assert!(reopened.update(&Issue::new("Crash".to_string(), "alice".to_string())).is_err());
+    }
+
+    #[test]
+    fn test_document_merge() {
+        let issue = Issue::new("Crash".to_string(), "alice".to_string());
+        let base = IssueDocument { id: Some(issue.id), ..IssueDocument::from_issue(&issue) };
+        let text = base.to_markdown();
+        assert!(text.starts_with(&format!("---\nid: {}\ntitle: Crash\n", issue.id)));
+        assert_eq!(IssueDocument::parse(&text).unwrap(), base);
+
+        let ours = IssueDocument { priority: Priority::High, labels: vec!["bug".to_string()], ..base.clone() };
+        let theirs = IssueDocument { title: "Crash on start".to_string(), labels: vec!["bug".to_string()], ..base.clone() };
+        let (merged, conflicts) = IssueDocument::merge(&base, &ours, &theirs);
+        assert!(conflicts.is_empty());
+        assert_eq!((merged.title.as_str(), &merged.priority, merged.labels.len()), ("Crash on start", &Priority::High, 1));
+
+        let theirs = IssueDocument { priority: Priority::Low, ..theirs };
+        let (merged, conflicts) = IssueDocument::merge(&base, &ours, &theirs);
+        assert_eq!(conflicts, vec!["priority"]);
+        assert_eq!(merged.priority, Priority::Low);
+    }
+
+    #[test]
+    fn test_document_errors() {
+        assert_eq!(errors("title: Crash\n"), "line 1: The document must start with front matter between '---' lines");
+        assert_eq!(errors("---\ntitle: Crash\n"), "line 1: The front matter has no closing '---' line");
+
+        let text = "---\ntitle: ''\nstatus: done\npriority: urgent\n---\nBody\n";
+        let message = errors(text);
+        let lines: Vec<&str> = message.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("line 2: Title"));
+        assert!(lines[1].starts_with("line 3: Unknown status 'done'"));
+        assert!(lines[2].starts_with("line 4: Unknown priority 'urgent'"));
+        assert!(errors("---\ntitle: Crash\nowner: bob\n---\n").starts_with("line 3: Unknown field `owner`"));
+
+        // Annotations are comments, replaced on each round and ignored when read
+        let annotated = IssueDocument::annotate(text, &message);
+        assert_eq!(annotated.lines().nth(1), Some("# error: line 5: Title must be 1-100 characters"));
+        assert_eq!(annotated.lines().nth(4), Some("title: ''"));
+        let annotated = IssueDocument::annotate(&annotated, "line 3: Still wrong");
+        assert_eq!(annotated.matches("# error:").count(), 1);
+        let fixed = annotated.replace("''", "Crash").replace("done", "closed").replace("urgent", "high");
+        assert_eq!(IssueDocument::parse(&fixed).unwrap().status, IssueStatus::Closed);
+    }
+}
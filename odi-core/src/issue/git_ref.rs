@@ -0,0 +1,255 @@
+//! Links from issues to Git commits, branches, tags, merge requests and lines
+//! of files in the working tree
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{CoreError, Result};
+
+/// What a link points at
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum GitTarget {
+    Commit { sha: String },
+    Branch { name: String },
+    Tag { name: String },
+    /// Merge or pull request, by its web URL
+    MergeRequest { url: String },
+    /// Line of a file, by its path from the root of the working tree
+    File { path: String, line: usize },
+}
+
+/// How the linked change relates to the issue, strongest first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum LinkRole {
+    /// The change resolves the issue
+    Fixes,
+    /// The change is work towards the issue
+    Implements,
+    /// The change refers to the issue
+    Mentions,
+}
+
+/// A link from an issue to something in a Git repository
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GitRef {
+    #[serde(flatten)]
+    pub target: GitTarget,
+    /// Repository the target lives in, as returned by [`repository_identity`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repository: Option<String>,
+    pub role: LinkRole,
+    pub linked_at: DateTime<Utc>,
+}
+
+impl GitRef {
+    /// Create a link to a target
+    pub fn new(target: GitTarget, role: LinkRole) -> Self {
+        Self {
+            target,
+            repository: None,
+            role,
+            linked_at: Utc::now(),
+        }
+    }
+
+    /// Link to a commit by full SHA
+    pub fn commit(sha: impl Into<String>, role: LinkRole) -> Self {
+        Self::new(GitTarget::Commit { sha: sha.into() }, role)
+    }
+
+    /// Link to a branch
+    pub fn branch(name: impl Into<String>, role: LinkRole) -> Self {
+        Self::new(GitTarget::Branch { name: name.into() }, role)
+    }
+
+    /// Link to a tag
+    pub fn tag(name: impl Into<String>, role: LinkRole) -> Self {
+        Self::new(GitTarget::Tag { name: name.into() }, role)
+    }
+
+    /// Link to a merge or pull request
+    pub fn merge_request(url: impl Into<String>, role: LinkRole) -> Self {
+        Self::new(GitTarget::MergeRequest { url: url.into() }, role)
+    }
+
+    /// Create a link to a line of a file (1-based)
+    pub fn file(path: impl Into<String>, line: usize, role: LinkRole) -> Self {
+        Self::new(GitTarget::File { path: path.into(), line }, role)
+    }
+
+    /// Record the repository the target lives in
+    pub fn with_repository(mut self, repository: Option<String>) -> Self {
+        self.repository = repository;
+        self
+    }
+
+    /// Whether two links point at the same thing, whatever their role
+    pub fn same_target(&self, other: &GitRef) -> bool {
+        self.target == other.target && self.repository == other.repository
+    }
+
+    /// Check the target is well-formed
+    pub fn validate(&self) -> Result<()> {
+        let invalid = |message: String| Err(CoreError::ValidationError {
+            field: "git_ref".to_string(),
+            message,
+        });
+
+        match &self.target {
+            GitTarget::Commit { sha } => {
+                if !(7..=64).contains(&sha.len()) || !sha.chars().all(|c| c.is_ascii_hexdigit()) {
+                    return invalid(format!("'{}' is not a commit SHA", sha));
+                }
+            }
+            GitTarget::Branch { name } | GitTarget::Tag { name } => {
+                if !is_valid_ref_name(name) {
+                    return invalid(format!("'{}' is not a valid Git ref name", name));
+                }
+            }
+            GitTarget::MergeRequest { url } => {
+                if !(url.starts_with("https://") || url.starts_with("http://")) {
+                    return invalid(format!("'{}' is not a merge request URL", url));
+                }
+            }
+            GitTarget::File { path, line } => {
+                let escapes = path.split('/').any(|part| part.is_empty() || part == "." || part == "..");
+                if escapes || path.contains('\\') {
+                    return invalid(format!("'{}' is not a path relative to the working tree", path));
+                }
+                if *line == 0 {
+                    return invalid(format!("Line numbers of '{}' start at 1", path));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for GitTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GitTarget::Commit { sha } => write!(f, "commit {}", &sha[..sha.len().min(7)]),
+            GitTarget::Branch { name } => write!(f, "branch {}", name),
+            GitTarget::Tag { name } => write!(f, "tag {}", name),
+            GitTarget::MergeRequest { url } => write!(f, "merge request {}", url),
+            GitTarget::File { path, line } => write!(f, "{}:{}", path, line),
+        }
+    }
+}
+
+impl std::fmt::Display for LinkRole {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LinkRole::Fixes => write!(f, "fixes"),
+            LinkRole::Implements => write!(f, "implements"),
+            LinkRole::Mentions => write!(f, "mentions"),
+        }
+    }
+}
+
+/// Branch and tag name rules of `git check-ref-format`
+fn is_valid_ref_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with(['-', '/', '.'])
+        && !name.ends_with(['/', '.'])
+        && !name.ends_with(".lock")
+        && !name.contains("..")
+        && !name.contains("@{")
+        && !name.contains("//")
+        && !name.chars().any(|c| c.is_ascii_control() || " ~^:?*[\\".contains(c))
+}
+
+/// Identity of a repository from any of its remote URLs
+///
+/// SSH, scp-style and HTTP URLs of the same repository give the same identity,
+/// e.g. `git@github.com:team/app.git` and `https://github.com/team/app` are
+/// both `github.com/team/app`.
+pub fn repository_identity(url: &str) -> String {
+    let url = url.trim().trim_end_matches('/');
+    let url = url.strip_suffix(".git").unwrap_or(url);
+
+    let rest = match url.split_once("://") {
+        Some((_, rest)) => rest.to_string(),
+        // scp-style `user@host:path`
+        None => match url.split_once(':') {
+            Some((host, path)) if !host.contains('/') => format!("{}/{}", host, path),
+            _ => return url.to_string(),
+        },
+    };
+
+    let (authority, path) = rest.split_once('/').unwrap_or((&rest, ""));
+    let host = authority.rsplit('@').next().unwrap_or(authority);
+    let host = host.split(':').next().unwrap_or(host);
+    format!("{}/{}", host.to_lowercase(), path.trim_start_matches('/'))
+}
+
+/// Combine two sets of links to the same issue
+///
+/// Every target linked on either side is kept. When both sides link the same
+/// target, the more recently made link wins, so role changes carry over.
+pub fn merge_links(ours: &[GitRef], theirs: &[GitRef]) -> Vec<GitRef> {
+    let mut merged = ours.to_vec();
+    for link in theirs {
+        match merged.iter_mut().find(|existing| existing.same_target(link)) {
+            Some(existing) if link.linked_at > existing.linked_at => *existing = link.clone(),
+            Some(_) => {}
+            None => merged.push(link.clone()),
+        }
+    }
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate() {
+        assert!(GitRef::commit("a1b2c3d4e5f6", LinkRole::Fixes).validate().is_ok());
+        assert!(GitRef::commit("not-a-sha", LinkRole::Fixes).validate().is_err());
+        assert!(GitRef::branch("feature/login", LinkRole::Implements).validate().is_ok());
+        assert!(GitRef::branch("bad..name", LinkRole::Implements).validate().is_err());
+        assert!(GitRef::tag("v1.0 final", LinkRole::Mentions).validate().is_err());
+        assert!(GitRef::merge_request("https://github.com/team/app/pull/7", LinkRole::Fixes).validate().is_ok());
+        assert!(GitRef::merge_request("pull/7", LinkRole::Fixes).validate().is_err());
+        assert!(GitRef::file("src/auth/login.rs", 42, LinkRole::Mentions).validate().is_ok());
+        assert!(GitRef::file("../secrets", 1, LinkRole::Mentions).validate().is_err());
+        assert!(GitRef::file("/etc/passwd", 1, LinkRole::Mentions).validate().is_err());
+        assert!(GitRef::file("src/lib.rs", 0, LinkRole::Mentions).validate().is_err());
+    }
+
+    #[test]
+    fn test_repository_identity() {
+        for url in [
+            "git@github.com:team/app.git",
+            "https://github.com/team/app",
+            "ssh://git@GitHub.com:22/team/app.git",
+            "https://user@github.com/team/app.git/",
+        ] {
+            assert_eq!(repository_identity(url), "github.com/team/app", "{}", url);
+        }
+        assert_eq!(repository_identity("/srv/git/app.git"), "/srv/git/app");
+    }
+
+    #[test]
+    fn test_merge_links() {
+        let older = Utc::now() - chrono::Duration::hours(1);
+        let mut mention = GitRef::commit("a1b2c3d", LinkRole::Mentions);
+        mention.linked_at = older;
+        let fix = GitRef::commit("a1b2c3d", LinkRole::Fixes);
+        let branch = GitRef::branch("login", LinkRole::Implements);
+        let elsewhere = GitRef::commit("a1b2c3d", LinkRole::Mentions)
+            .with_repository(Some("github.com/team/other".to_string()));
+
+        let merged = merge_links(&[mention.clone(), branch.clone()], &[fix.clone(), elsewhere.clone()]);
+        assert_eq!(merged, vec![fix.clone(), branch.clone(), elsewhere.clone()]);
+
+        // Either side first gives the same links
+        let reversed = merge_links(&[fix.clone(), elsewhere], &[mention, branch]);
+        assert_eq!(reversed.len(), 3);
+        assert_eq!(reversed[0].role, LinkRole::Fixes);
+    }
+}
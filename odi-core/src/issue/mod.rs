@@ -1,6 +1,13 @@
 //! Issue entity and related operations
 
+mod changelog;
+mod document;
+mod git_ref;
+mod query;
+mod reference;
 mod repository;
+mod todo;
+mod view;
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
@@ -9,7 +16,14 @@ use uuid::Uuid;
 use crate::user::UserId;
 use crate::project::{ProjectId, LabelId};
 
+pub use changelog::{Changelog, ChangelogEntry, ChangelogGrouping, ChangelogSection, ChangeType};
+pub use document::IssueDocument;
+pub use git_ref::{GitRef, GitTarget, LinkRole, merge_links, repository_identity};
+pub use reference::{IssueReference, branch_reference, parse_references, MIN_REFERENCE_LEN};
+pub use query::{Comparison, Cursor, DateField, DateRange, EmptyField, Filter, QueryContext, SortField, SortKey};
 pub use repository::{IssueRepository, IssueQuery, IssueUpdate};
+pub use todo::{TodoComment, TodoKind, add_reference, find_todos, FINGERPRINT_TRAILER};
+pub use view::{Column, View, ViewName};
 
 /// Issue identifier type
 pub type IssueId = Uuid;
@@ -50,7 +64,8 @@ pub struct Issue {
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub closed_at: Option<DateTime<Utc>>,
-    pub git_refs: Vec<String>, // Placeholder for GitRef
+    /// Commits, branches, tags, merge requests and lines of files linked to the issue
+    pub git_refs: Vec<GitRef>,
 }
 
 impl Issue {
@@ -104,6 +119,19 @@ impl Issue {
         self.assignees.retain(|id| id != user_id);
     }
 
+    /// Link a Git target to the issue, or change the role of an existing link
+    ///
+    /// Returns whether anything changed.
+    pub fn link(&mut self, git_ref: GitRef) -> bool {
+        match self.git_refs.iter_mut().find(|existing| existing.same_target(&git_ref)) {
+            Some(existing) if existing.role == git_ref.role => return false,
+            Some(existing) => *existing = git_ref,
+            None => self.git_refs.push(git_ref),
+        }
+        self.updated_at = Utc::now();
+        true
+    }
+
     /// Update status and timestamps
     pub fn update_status(&mut self, new_status: IssueStatus) -> Result<(), String> {
         if !self.can_transition_to(&new_status) {
@@ -183,4 +211,16 @@ mod tests {
         assert_eq!(issue.assignees.len(), 1);
         assert!(!issue.assignees.contains(&"dev1".to_string()));
     }
+
+    #[test]
+    fn test_link() {
+        let mut issue = Issue::new("Linked".to_string(), "alice".to_string());
+        
+        assert!(issue.link(GitRef::commit("a1b2c3d", LinkRole::Mentions)));
+        assert!(!issue.link(GitRef::commit("a1b2c3d", LinkRole::Mentions)));
+        assert!(issue.link(GitRef::commit("a1b2c3d", LinkRole::Fixes)));
+        
+        assert_eq!(issue.git_refs.len(), 1);
+        assert_eq!(issue.git_refs[0].role, LinkRole::Fixes);
+    }
 }
\ No newline at end of file
@@ -0,0 +1,878 @@
+//! Issue query language
+//!
+//! `odi issue list` takes queries such as
+//! `status:open label:bug,-wontfix assignee:@me updated:>7d "login page" sort:-priority`.
+//! Terms must all hold unless `OR` is written between them, `NOT` or a leading
+//! `-` negates a term, and parentheses group. Values separated by commas are
+//! alternatives, except those starting with `-`, which are excluded.
+//! [`IssueQuery::parse`] turns a query into an [`IssueQuery`], which every
+//! repository runs the same way.
+
+use std::cmp::Ordering;
+use std::fmt;
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::{Issue, IssueQuery, IssueStatus, Priority};
+use crate::project::{LabelId, ProjectId};
+use crate::user::UserId;
+use crate::{CoreError, Result};
+
+/// Fields a query can name, for error messages
+const FIELDS: &str = "status, priority, label, assignee, author, project, id, created, updated, closed, no, sort";
+
+/// What `@me` and relative dates such as `7d` stand for in a query
+#[derive(Debug, Clone)]
+pub struct QueryContext {
+    /// User that `@me` names
+    pub me: Option<UserId>,
+    /// Time relative dates count back from
+    pub now: DateTime<Utc>,
+}
+
+impl QueryContext {
+    pub fn new(me: Option<UserId>) -> Self {
+        Self { me, now: Utc::now() }
+    }
+}
+
+/// A condition on issues
+#[derive(Debug, Clone, PartialEq)]
+pub enum Filter {
+    /// Every condition holds; an empty list always holds
+    And(Vec<Filter>),
+    /// At least one condition holds
+    Or(Vec<Filter>),
+    Not(Box<Filter>),
+    Status(IssueStatus),
+    Priority(Comparison, Priority),
+    Label(LabelId),
+    Assignee(UserId),
+    Author(UserId),
+    Project(ProjectId),
+    /// ID starting with this, in lowercase
+    IdPrefix(String),
+    /// Timestamp within a range; issues never closed have no closing date
+    Date(DateField, DateRange),
+    /// The field has no value
+    Empty(EmptyField),
+    /// Title or description contains this, in lowercase
+    Text(String),
+}
+
+/// How a value compares with the one in a query
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Eq,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateField {
+    Created,
+    Updated,
+    Closed,
+}
+
+/// Times from `start` (inclusive) up to `end` (exclusive); a missing end is open
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DateRange {
+    pub start: Option<DateTime<Utc>>,
+    pub end: Option<DateTime<Utc>>,
+}
+
+/// Fields `no:` can ask to be empty
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EmptyField {
+    Assignee,
+    Label,
+    Project,
+    Description,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortField {
+    Created,
+    Updated,
+    Priority,
+    Status,
+    Title,
+    Id,
+}
+
+/// One key issues are ordered by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SortKey {
+    pub field: SortField,
+    pub descending: bool,
+}
+
+/// Position after the last issue of a page, to continue listing from
+///
+/// Cursors hold the sort values of that issue rather than its position, so
+/// pages neither skip nor repeat issues when others are created or deleted
+/// between requests. They are opaque strings on the command line.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Cursor {
+    /// Sort order the cursor was made for, as written in `sort:`
+    sort: String,
+    values: Vec<SortValue>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(untagged)]
+enum SortValue {
+    Int(i64),
+    Text(String),
+}
+
+fn invalid(message: impl Into<String>) -> CoreError {
+    CoreError::ValidationError { field: "query".to_string(), message: message.into() }
+}
+
+impl Comparison {
+    fn holds(self, ordering: Ordering) -> bool {
+        match self {
+            Comparison::Eq => ordering == Ordering::Equal,
+            Comparison::Lt => ordering == Ordering::Less,
+            Comparison::Le => ordering != Ordering::Greater,
+            Comparison::Gt => ordering == Ordering::Greater,
+            Comparison::Ge => ordering != Ordering::Less,
+        }
+    }
+}
+
+impl DateRange {
+    pub fn contains(&self, time: DateTime<Utc>) -> bool {
+        self.start.is_none_or(|start| time >= start) && self.end.is_none_or(|end| time < end)
+    }
+}
+
+fn priority_rank(priority: &Priority) -> i64 {
+    match priority {
+        Priority::Low => 0,
+        Priority::Medium => 1,
+        Priority::High => 2,
+        Priority::Critical => 3,
+    }
+}
+
+fn status_rank(status: &IssueStatus) -> i64 {
+    match status {
+        IssueStatus::Open => 0,
+        IssueStatus::InProgress => 1,
+        IssueStatus::Resolved => 2,
+        IssueStatus::Closed => 3,
+    }
+}
+
+impl Filter {
+    /// Whether an issue meets the condition
+    pub fn matches(&self, issue: &Issue) -> bool {
+        match self {
+            Filter::And(filters) => filters.iter().all(|filter| filter.matches(issue)),
+            Filter::Or(filters) => filters.iter().any(|filter| filter.matches(issue)),
+            Filter::Not(filter) => !filter.matches(issue),
+            Filter::Status(status) => issue.status == *status,
+            Filter::Priority(comparison, priority) => {
+                comparison.holds(priority_rank(&issue.priority).cmp(&priority_rank(priority)))
+            }
+            Filter::Label(label) => issue.labels.contains(label),
+            Filter::Assignee(user) => issue.assignees.contains(user),
+            Filter::Author(user) => issue.author == *user,
+            Filter::Project(project) => issue.project_id.as_ref() == Some(project),
+            Filter::IdPrefix(prefix) => issue.id.to_string().starts_with(prefix.as_str()),
+            Filter::Date(field, range) => match field {
+                DateField::Created => range.contains(issue.created_at),
+                DateField::Updated => range.contains(issue.updated_at),
+                DateField::Closed => issue.closed_at.is_some_and(|closed_at| range.contains(closed_at)),
+            },
+            Filter::Empty(field) => match field {
+                EmptyField::Assignee => issue.assignees.is_empty(),
+                EmptyField::Label => issue.labels.is_empty(),
+                EmptyField::Project => issue.project_id.is_none(),
+                EmptyField::Description => issue.description.as_deref().is_none_or(|d| d.trim().is_empty()),
+            },
+            Filter::Text(text) => {
+                issue.title.to_lowercase().contains(text.as_str())
+                    || issue.description.as_ref().is_some_and(|d| d.to_lowercase().contains(text.as_str()))
+            }
+        }
+    }
+
+    /// ID prefixes every matching issue has one of, if the filter pins them
+    ///
+    /// Lets a repository read only the issues whose refs start with them.
+    pub fn id_prefixes(&self) -> Option<Vec<String>> {
+        match self {
+            Filter::IdPrefix(prefix) => Some(vec![prefix.clone()]),
+            Filter::And(filters) => filters.iter().find_map(Filter::id_prefixes),
+            Filter::Or(filters) if !filters.is_empty() => {
+                let mut prefixes = Vec::new();
+                for filter in filters {
+                    prefixes.extend(filter.id_prefixes()?);
+                }
+                Some(prefixes)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl SortField {
+    fn parse(name: &str) -> Result<Self> {
+        match name {
+            "created" => Ok(SortField::Created),
+            "updated" => Ok(SortField::Updated),
+            "priority" => Ok(SortField::Priority),
+            "status" => Ok(SortField::Status),
+            "title" => Ok(SortField::Title),
+            "id" => Ok(SortField::Id),
+            _ => Err(invalid(format!(
+                "Can't sort by '{}': expected created, updated, priority, status, title or id", name
+            ))),
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            SortField::Created => "created",
+            SortField::Updated => "updated",
+            SortField::Priority => "priority",
+            SortField::Status => "status",
+            SortField::Title => "title",
+            SortField::Id => "id",
+        }
+    }
+
+    fn value(&self, issue: &Issue) -> SortValue {
+        match self {
+            SortField::Created => SortValue::Int(issue.created_at.timestamp_micros()),
+            SortField::Updated => SortValue::Int(issue.updated_at.timestamp_micros()),
+            SortField::Priority => SortValue::Int(priority_rank(&issue.priority)),
+            SortField::Status => SortValue::Int(status_rank(&issue.status)),
+            SortField::Title => SortValue::Text(issue.title.to_lowercase()),
+            SortField::Id => SortValue::Text(issue.id.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for SortKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", if self.descending { "-" } else { "" }, self.field.name())
+    }
+}
+
+impl std::str::FromStr for SortKey {
+    type Err = CoreError;
+
+    /// A key as written in `sort:`, like `-priority`
+    fn from_str(text: &str) -> Result<Self> {
+        let (descending, name) = match text.strip_prefix('-') {
+            Some(name) => (true, name),
+            None => (false, text),
+        };
+        Ok(SortKey { field: SortField::parse(&name.to_lowercase())?, descending })
+    }
+}
+
+// Sort keys are stored in views as they are written in `sort:`
+impl Serialize for SortKey {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for SortKey {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        text.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+impl fmt::Display for Cursor {
+    /// The cursor as hex-encoded JSON, safe to pass around in shells and URLs
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let json = serde_json::to_vec(self).map_err(|_| fmt::Error)?;
+        for byte in json {
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::str::FromStr for Cursor {
+    type Err = CoreError;
+
+    fn from_str(text: &str) -> Result<Self> {
+        let malformed = || invalid(format!("Malformed cursor '{}'", text));
+        let bytes = text.as_bytes().chunks(2)
+            .map(|pair| std::str::from_utf8(pair).ok()
+                .filter(|_| pair.len() == 2)
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok()))
+            .collect::<Option<Vec<u8>>>()
+            .ok_or_else(malformed)?;
+        serde_json::from_slice(&bytes).map_err(|_| malformed())
+    }
+}
+
+impl IssueQuery {
+    /// Parse a query written in the query language
+    pub fn parse(text: &str, context: &QueryContext) -> Result<Self> {
+        let mut parser = Parser { tokens: tokenize(text)?, pos: 0, context, sort: Vec::new() };
+        let filter = if parser.tokens.is_empty() { None } else { Some(parser.parse_or()?) };
+        if let Some(token) = parser.tokens.get(parser.pos) {
+            return Err(invalid(format!("Unexpected {}", token)));
+        }
+        Ok(Self {
+            filter: filter.filter(|filter| *filter != Filter::And(Vec::new())),
+            sort: parser.sort,
+            ..Default::default()
+        })
+    }
+
+    /// Add a condition that must hold along with the others
+    pub fn filter(mut self, filter: Filter) -> Self {
+        self.filter = Some(match self.filter.take() {
+            Some(Filter::And(mut filters)) => {
+                filters.push(filter);
+                Filter::And(filters)
+            }
+            Some(existing) => Filter::And(vec![existing, filter]),
+            None => filter,
+        });
+        self
+    }
+
+    /// Set the sort order
+    pub fn sort(mut self, sort: Vec<SortKey>) -> Self {
+        self.sort = sort;
+        self
+    }
+
+    /// Start after the issue a cursor was made for; the sort order must match
+    pub fn after(mut self, cursor: Cursor) -> Result<Self> {
+        if cursor.sort != self.sort_spec() || cursor.values.len() != self.sort_keys().len() + 1 {
+            return Err(invalid("The cursor was made for a different sort order; run the query again without it"));
+        }
+        self.after = Some(cursor);
+        Ok(self)
+    }
+
+    /// Cursor to continue after this issue, for the next page
+    pub fn cursor(&self, issue: &Issue) -> Cursor {
+        Cursor { sort: self.sort_spec(), values: self.sort_values(issue) }
+    }
+
+    /// Keys issues are ordered by: the requested ones, or oldest first
+    fn sort_keys(&self) -> Vec<SortKey> {
+        if self.sort.is_empty() {
+            vec![SortKey { field: SortField::Created, descending: false }]
+        } else {
+            self.sort.clone()
+        }
+    }
+
+    fn sort_spec(&self) -> String {
+        self.sort_keys().iter().map(SortKey::to_string).collect::<Vec<_>>().join(",")
+    }
+
+    /// Sort values of an issue, ending with its ID so no two issues tie
+    fn sort_values(&self, issue: &Issue) -> Vec<SortValue> {
+        let mut values: Vec<SortValue> = self.sort_keys().iter().map(|key| key.field.value(issue)).collect();
+        values.push(SortValue::Text(issue.id.to_string()));
+        values
+    }
+
+    fn compare_values(keys: &[SortKey], a: &[SortValue], b: &[SortValue]) -> Ordering {
+        for (index, (a, b)) in a.iter().zip(b).enumerate() {
+            let ordering = a.cmp(b);
+            // The trailing ID always ascends
+            let descending = keys.get(index).is_some_and(|key| key.descending);
+            let ordering = if descending { ordering.reverse() } else { ordering };
+            if ordering != Ordering::Equal {
+                return ordering;
+            }
+        }
+        Ordering::Equal
+    }
+
+    /// Filter, order and paginate a set of issues
+    ///
+    /// Repository implementations use this so every backend returns the same
+    /// results for the same query. With a limit, only the issues on the page
+    /// are fully sorted.
+    pub fn apply(&self, issues: Vec<Issue>) -> Vec<Issue> {
+        let keys = self.sort_keys();
+        let mut keyed: Vec<(Vec<SortValue>, Issue)> = issues.into_iter()
+            .filter(|issue| self.matches(issue))
+            .map(|issue| (self.sort_values(&issue), issue))
+            .filter(|(values, _)| self.after.as_ref().is_none_or(|cursor| {
+                Self::compare_values(&keys, values, &cursor.values) == Ordering::Greater
+            }))
+            .collect();
+        let compare = |a: &(Vec<SortValue>, Issue), b: &(Vec<SortValue>, Issue)| Self::compare_values(&keys, &a.0, &b.0);
+
+        if let Some(limit) = self.limit {
+            let keep = self.offset.unwrap_or(0).saturating_add(limit);
+            if keep < keyed.len() {
+                keyed.select_nth_unstable_by(keep, compare);
+                keyed.truncate(keep);
+            }
+        }
+        keyed.sort_by(compare);
+        crate::paginate(keyed.into_iter().map(|(_, issue)| issue).collect(), self.offset, self.limit)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Open,
+    Close,
+    And,
+    Or,
+    Not,
+    /// A field term or text, as written apart from a leading `-`
+    Term { negated: bool, text: String },
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Open => write!(f, "'('"),
+            Token::Close => write!(f, "')'"),
+            Token::And => write!(f, "AND"),
+            Token::Or => write!(f, "OR"),
+            Token::Not => write!(f, "NOT"),
+            Token::Term { negated, text } => write!(f, "'{}{}'", if *negated { "-" } else { "" }, text),
+        }
+    }
+}
+
+/// Split a query into tokens; quotes are kept in terms so values can be split later
+fn tokenize(text: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' => {
+                chars.next();
+                tokens.push(Token::Open);
+            }
+            ')' => {
+                chars.next();
+                tokens.push(Token::Close);
+            }
+            _ => {
+                let mut word = String::new();
+                let mut quoted = false;
+                while let Some(&c) = chars.peek() {
+                    if !quoted && (c.is_whitespace() || c == '(' || c == ')') {
+                        break;
+                    }
+                    if c == '"' {
+                        quoted = !quoted;
+                    }
+                    word.push(c);
+                    chars.next();
+                }
+                if quoted {
+                    return Err(invalid(format!("Unterminated quote in {}", word)));
+                }
+                tokens.push(match word.as_str() {
+                    "AND" => Token::And,
+                    "OR" => Token::Or,
+                    "NOT" | "-" => Token::Not,
+                    _ => match word.strip_prefix('-') {
+                        Some(rest) => Token::Term { negated: true, text: rest.to_string() },
+                        None => Token::Term { negated: false, text: word },
+                    },
+                });
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+/// Split on a separator outside quotes
+fn split_unquoted(text: &str, separator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut quoted = false;
+    let mut start = 0;
+    for (index, c) in text.char_indices() {
+        if c == '"' {
+            quoted = !quoted;
+        } else if c == separator && !quoted {
+            parts.push(&text[start..index]);
+            start = index + c.len_utf8();
+        }
+    }
+    parts.push(&text[start..]);
+    parts
+}
+
+fn unquote(text: &str) -> String {
+    text.replace('"', "")
+}
+
+/// A single condition, or all of several
+fn all(mut filters: Vec<Filter>) -> Filter {
+    if filters.len() == 1 {
+        filters.remove(0)
+    } else {
+        Filter::And(filters)
+    }
+}
+
+struct Parser<'a> {
+    tokens: Vec<Token>,
+    pos: usize,
+    context: &'a QueryContext,
+    /// Sort keys from `sort:` terms, which aren't conditions
+    sort: Vec<SortKey>,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn parse_or(&mut self) -> Result<Filter> {
+        let mut operands = vec![self.parse_and()?];
+        while self.peek() == Some(&Token::Or) {
+            self.pos += 1;
+            operands.push(self.parse_and()?);
+        }
+        if operands.len() == 1 {
+            return Ok(operands.remove(0));
+        }
+        if operands.contains(&Filter::And(Vec::new())) {
+            return Err(invalid("sort: can't be an operand of OR"));
+        }
+        Ok(Filter::Or(operands))
+    }
+
+    fn parse_and(&mut self) -> Result<Filter> {
+        let mut filters = Vec::new();
+        let mut terms = 0;
+        loop {
+            match self.peek() {
+                None | Some(Token::Close) | Some(Token::Or) => break,
+                Some(Token::And) => self.pos += 1,
+                Some(_) => {
+                    terms += 1;
+                    if let Some(filter) = self.parse_unary()? {
+                        filters.push(filter);
+                    }
+                    continue;
+                }
+            }
+            if !matches!(self.peek(), Some(Token::Term { .. } | Token::Not | Token::Open)) {
+                return Err(invalid("Expected a term after AND"));
+            }
+        }
+        if terms == 0 {
+            return Err(invalid(match self.peek() {
+                Some(token) => format!("Expected a term before {}", token),
+                None => "Expected a term at the end of the query".to_string(),
+            }));
+        }
+        Ok(all(filters))
+    }
+
+    /// One negated, grouped or single term; `None` for `sort:`
+    fn parse_unary(&mut self) -> Result<Option<Filter>> {
+        let token = self.tokens[self.pos].clone();
+        self.pos += 1;
+        match token {
+            Token::Not => {
+                if self.peek().is_none() {
+                    return Err(invalid("Expected a term after NOT"));
+                }
+                match self.parse_unary()? {
+                    Some(filter) => Ok(Some(Filter::Not(Box::new(filter)))),
+                    None => Err(invalid("sort: can't be negated")),
+                }
+            }
+            Token::Open => {
+                let filter = self.parse_or()?;
+                if self.peek() != Some(&Token::Close) {
+                    return Err(invalid("Expected ')'"));
+                }
+                self.pos += 1;
+                Ok(Some(filter))
+            }
+            Token::Term { negated, text } => match self.parse_term(&text)? {
+                Some(filter) if negated => Ok(Some(Filter::Not(Box::new(filter)))),
+                Some(filter) => Ok(Some(filter)),
+                None if negated => Err(invalid("sort: can't be negated")),
+                None => Ok(None),
+            },
+            token => Err(invalid(format!("Unexpected {}", token))),
+        }
+    }
+
+    fn parse_term(&mut self, text: &str) -> Result<Option<Filter>> {
+        let parts = split_unquoted(text, ':');
+        if parts.len() == 1 {
+            let text = unquote(text).to_lowercase();
+            if text.is_empty() {
+                return Err(invalid("Empty search text"));
+            }
+            return Ok(Some(Filter::Text(text)));
+        }
+
+        let field = parts[0].to_lowercase();
+        let value = &text[parts[0].len() + 1..];
+        let values: Vec<String> = split_unquoted(value, ',').into_iter().map(unquote).collect();
+        if values.iter().any(|value| value.is_empty() || value == "-") {
+            return Err(invalid(format!("Missing value in '{}'", text)));
+        }
+
+        let context = self.context;
+        let filter = match field.as_str() {
+            "status" => any_of(&values, |value| parse_status(value).map(Filter::Status))?,
+            "priority" => any_of(&values, |value| {
+                let (comparison, value) = parse_comparison(value);
+                Ok(Filter::Priority(comparison, parse_priority(value)?))
+            })?,
+            "label" => any_of(&values, |value| Ok(Filter::Label(value.to_string())))?,
+            "assignee" => any_of(&values, |value| Ok(Filter::Assignee(user(value, context)?)))?,
+            "author" => any_of(&values, |value| Ok(Filter::Author(user(value, context)?)))?,
+            "project" => any_of(&values, |value| Ok(Filter::Project(value.to_string())))?,
+            "id" => any_of(&values, |value| Ok(Filter::IdPrefix(value.to_lowercase())))?,
+            "created" => any_of(&values, |value| Ok(Filter::Date(DateField::Created, parse_range(value, context.now)?)))?,
+            "updated" => any_of(&values, |value| Ok(Filter::Date(DateField::Updated, parse_range(value, context.now)?)))?,
+            "closed" => any_of(&values, |value| Ok(Filter::Date(DateField::Closed, parse_range(value, context.now)?)))?,
+            "no" => any_of(&values, |value| parse_empty(value).map(Filter::Empty))?,
+            "sort" => {
+                for value in &values {
+                    self.sort.push(value.parse()?);
+                }
+                return Ok(None);
+            }
+            _ => return Err(invalid(format!("Unknown field '{}'; fields are {}", parts[0], FIELDS))),
+        };
+        Ok(Some(filter))
+    }
+}
+
+/// Comma-separated values: any of those without `-`, and none of those with it
+fn any_of(values: &[String], parse: impl Fn(&str) -> Result<Filter>) -> Result<Filter> {
+    let mut wanted = Vec::new();
+    let mut excluded = Vec::new();
+    for value in values {
+        match value.strip_prefix('-') {
+            Some(value) => excluded.push(Filter::Not(Box::new(parse(value)?))),
+            None => wanted.push(parse(value)?),
+        }
+    }
+    let mut filters = excluded;
+    match wanted.len() {
+        0 => {}
+        1 => filters.insert(0, wanted.remove(0)),
+        _ => filters.insert(0, Filter::Or(wanted)),
+    }
+    Ok(all(filters))
+}
+
+fn user(value: &str, context: &QueryContext) -> Result<UserId> {
+    if value != "@me" {
+        return Ok(value.to_string());
+    }
+    context.me.clone().ok_or_else(|| invalid("@me needs a user name; set user.name in the config"))
+}
+
+pub(super) fn parse_status(value: &str) -> Result<IssueStatus> {
+    match value.to_lowercase().replace(['-', '_'], "").as_str() {
+        "open" => Ok(IssueStatus::Open),
+        "inprogress" => Ok(IssueStatus::InProgress),
+        "resolved" => Ok(IssueStatus::Resolved),
+        "closed" => Ok(IssueStatus::Closed),
+        _ => Err(invalid(format!("Unknown status '{}': expected open, in-progress, resolved or closed", value))),
+    }
+}
+
+pub(super) fn parse_priority(value: &str) -> Result<Priority> {
+    match value.to_lowercase().as_str() {
+        "low" => Ok(Priority::Low),
+        "medium" => Ok(Priority::Medium),
+        "high" => Ok(Priority::High),
+        "critical" => Ok(Priority::Critical),
+        _ => Err(invalid(format!("Unknown priority '{}': expected low, medium, high or critical", value))),
+    }
+}
+
+fn parse_empty(value: &str) -> Result<EmptyField> {
+    match value.to_lowercase().as_str() {
+        "assignee" => Ok(EmptyField::Assignee),
+        "label" => Ok(EmptyField::Label),
+        "project" => Ok(EmptyField::Project),
+        "description" => Ok(EmptyField::Description),
+        _ => Err(invalid(format!("Can't search for no '{}': expected assignee, label, project or description", value))),
+    }
+}
+
+fn parse_comparison(value: &str) -> (Comparison, &str) {
+    for (prefix, comparison) in [(">=", Comparison::Ge), ("<=", Comparison::Le), (">", Comparison::Gt), ("<", Comparison::Lt)] {
+        if let Some(rest) = value.strip_prefix(prefix) {
+            return (comparison, rest);
+        }
+    }
+    (Comparison::Eq, value)
+}
+
+/// A date range: `2024-05-01`, `>7d`, `<=2024-05-01`, `2024-05-01..2024-05-31` or `..7d`
+///
+/// A day covers all of it, so `<=2024-05-01` includes that day. Relative ages
+/// count back from now: `>7d` is after seven days ago.
+fn parse_range(value: &str, now: DateTime<Utc>) -> Result<DateRange> {
+    if let Some((from, to)) = value.split_once("..") {
+        let start = if from.is_empty() { None } else { Some(parse_time(from, now)?.0) };
+        let end = if to.is_empty() { None } else { Some(parse_time(to, now)?.1) };
+        return Ok(DateRange { start, end });
+    }
+    let (comparison, value) = parse_comparison(value);
+    let (start, end) = parse_time(value, now)?;
+    Ok(match comparison {
+        Comparison::Eq => DateRange { start: Some(start), end: Some(end) },
+        Comparison::Gt => DateRange { start: Some(end), end: None },
+        Comparison::Ge => DateRange { start: Some(start), end: None },
+        Comparison::Lt => DateRange { start: None, end: Some(start) },
+        Comparison::Le => DateRange { start: None, end: Some(end) },
+    })
+}
+
+/// The span a date or time covers: a whole day, or a single instant
+fn parse_time(value: &str, now: DateTime<Utc>) -> Result<(DateTime<Utc>, DateTime<Utc>)> {
+    let instant = |time: DateTime<Utc>| (time, time + Duration::nanoseconds(1));
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        let start = date.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc();
+        return Ok((start, start + Duration::days(1)));
+    }
+    if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        return Ok(instant(time.with_timezone(&Utc)));
+    }
+    let unit_at = value.char_indices().last().map_or(0, |(index, _)| index);
+    if let (Ok(count), Some(unit)) = (value[..unit_at].parse::<i64>(), value.get(unit_at..)) {
+        let age = match unit {
+            "h" => Some(Duration::hours(count)),
+            "d" => Some(Duration::days(count)),
+            "w" => Some(Duration::weeks(count)),
+            _ => None,
+        };
+        if let Some(age) = age {
+            return Ok(instant(now - age));
+        }
+    }
+    Err(invalid(format!(
+        "Invalid date '{}': expected YYYY-MM-DD, RFC 3339, or an age such as 12h, 7d or 2w", value
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn issue(title: &str, status: IssueStatus, priority: Priority, labels: &[&str]) -> Issue {
+        let mut issue = Issue::new(title.to_string(), "alice".to_string());
+        issue.status = status;
+        issue.priority = priority;
+        issue.labels = labels.iter().map(|label| label.to_string()).collect();
+        issue
+    }
+
+    #[test]
+    fn test_parse_query() {
+        let context = QueryContext::new(Some("bob".to_string()));
+        let query = IssueQuery::parse(
+            r#"status:open label:bug,-wontfix assignee:@me updated:>7d "login page" sort:-priority"#,
+            &context,
+        ).unwrap();
+        assert_eq!(query.sort, vec![SortKey { field: SortField::Priority, descending: true }]);
+
+        let mut hit = issue("Login page crashes", IssueStatus::Open, Priority::High, &["bug"]);
+        hit.assignees.push("bob".to_string());
+        assert!(query.matches(&hit));
+
+        let mut wontfix = hit.clone();
+        wontfix.labels.push("wontfix".to_string());
+        assert!(!query.matches(&wontfix));
+
+        let mut stale = hit.clone();
+        stale.updated_at = context.now - Duration::days(8);
+        assert!(!query.matches(&stale));
+
+        let mut other = hit.clone();
+        other.title = "Logout".to_string();
+        assert!(!query.matches(&other));
+
+        // OR binds looser than the implied AND, NOT and - negate
+        let query = IssueQuery::parse("label:crash OR priority:>=high -status:closed", &context).unwrap();
+        assert!(query.matches(&issue("a", IssueStatus::Closed, Priority::Low, &["crash"])));
+        assert!(query.matches(&issue("b", IssueStatus::Open, Priority::Critical, &[])));
+        assert!(!query.matches(&issue("c", IssueStatus::Closed, Priority::High, &[])));
+
+        let query = IssueQuery::parse("NOT (label:crash OR no:assignee)", &context).unwrap();
+        assert!(!query.matches(&issue("d", IssueStatus::Open, Priority::Low, &[])));
+        assert!(query.matches(&hit));
+
+        for bad in ["status:", "colour:red", "sort:-priority OR label:bug", "-sort:title", "(label:bug", "label:bug OR", "\"open", "created:soon", "assignee:@me"] {
+            let context = QueryContext::new(None);
+            assert!(IssueQuery::parse(bad, &context).is_err(), "{} should not parse", bad);
+        }
+    }
+
+    #[test]
+    fn test_date_ranges() {
+        let now = DateTime::parse_from_rfc3339("2024-05-10T12:00:00Z").unwrap().with_timezone(&Utc);
+        let at = |text: &str| DateTime::parse_from_rfc3339(text).unwrap().with_timezone(&Utc);
+
+        let day = parse_range("2024-05-01", now).unwrap();
+        assert!(day.contains(at("2024-05-01T00:00:00Z")) && day.contains(at("2024-05-01T23:59:59Z")));
+        assert!(!day.contains(at("2024-05-02T00:00:00Z")));
+
+        assert!(parse_range("<=2024-05-01", now).unwrap().contains(at("2024-05-01T18:00:00Z")));
+        assert!(!parse_range(">2024-05-01", now).unwrap().contains(at("2024-05-01T18:00:00Z")));
+
+        let span = parse_range("2024-05-01..2024-05-03", now).unwrap();
+        assert!(span.contains(at("2024-05-03T10:00:00Z")) && !span.contains(at("2024-04-30T10:00:00Z")));
+
+        let recent = parse_range(">7d", now).unwrap();
+        assert!(recent.contains(at("2024-05-05T00:00:00Z")) && !recent.contains(at("2024-05-02T00:00:00Z")));
+        assert!(parse_range("<2w", now).unwrap().contains(at("2024-04-01T00:00:00Z")));
+    }
+
+    #[test]
+    fn test_sort_and_cursor() {
+        let context = QueryContext::new(None);
+        let priorities = [Priority::Low, Priority::Critical, Priority::Medium, Priority::High, Priority::Medium];
+        let issues: Vec<Issue> = priorities.iter().enumerate()
+            .map(|(i, priority)| issue(&format!("Issue {}", i), IssueStatus::Open, priority.clone(), &[]))
+            .collect();
+
+        let query = IssueQuery::parse("sort:-priority,title", &context).unwrap();
+        let titles = |issues: &[Issue]| issues.iter().map(|issue| issue.title.clone()).collect::<Vec<_>>();
+        assert_eq!(titles(&query.apply(issues.clone())), ["Issue 1", "Issue 3", "Issue 2", "Issue 4", "Issue 0"]);
+
+        // Pages continue after the cursor even when an issue on an earlier page is gone
+        let first = IssueQuery::parse("sort:-priority,title", &context).unwrap().limit(2).apply(issues.clone());
+        assert_eq!(titles(&first), ["Issue 1", "Issue 3"]);
+        let cursor: Cursor = query.cursor(&first[1]).to_string().parse().unwrap();
+        let remaining: Vec<Issue> = issues.iter().filter(|issue| issue.title != "Issue 1").cloned().collect();
+        let second = IssueQuery::parse("sort:-priority,title", &context).unwrap()
+            .limit(2).after(cursor.clone()).unwrap().apply(remaining);
+        assert_eq!(titles(&second), ["Issue 2", "Issue 4"]);
+
+        assert!(IssueQuery::parse("sort:title", &context).unwrap().after(cursor).is_err());
+        assert!("zz".parse::<Cursor>().is_err());
+        assert!("7b2".parse::<Cursor>().is_err());
+    }
+}
@@ -0,0 +1,207 @@
+//! Issue references in commit messages
+//!
+//! Commits name issues by ID or ID prefix, either in a trailer in the last
+//! paragraph (`Fixes: 1a2b3c4d`) or after a keyword anywhere in the message
+//! (`closes #1a2b3c4d`).
+//! The trailer key or keyword gives the role of the link.
+
+use std::sync::OnceLock;
+
+use regex::Regex;
+
+use super::{Issue, LinkRole};
+
+/// Shortest ID prefix accepted as a reference
+pub const MIN_REFERENCE_LEN: usize = 6;
+
+/// An issue named in a commit message
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IssueReference {
+    pub role: LinkRole,
+    /// The issue as written, without a leading `#`
+    pub reference: String,
+}
+
+/// Role given by a trailer key or keyword, if it is one
+fn role_for(word: &str) -> Option<LinkRole> {
+    match word.to_lowercase().as_str() {
+        "fix" | "fixes" | "fixed" | "close" | "closes" | "closed" | "resolve" | "resolves" | "resolved" => Some(LinkRole::Fixes),
+        "implement" | "implements" | "implemented" => Some(LinkRole::Implements),
+        "ref" | "refs" | "references" | "see" | "related" | "related-to" => Some(LinkRole::Mentions),
+        _ => None,
+    }
+}
+
+fn trailer_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| Regex::new(r"(?m)^\s*([A-Za-z-]+)\s*:\s*(.+?)\s*$").expect("valid trailer pattern"))
+}
+
+fn keyword_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| Regex::new(r"(?i)\b([a-z]+)\s+#([0-9a-f][0-9a-f-]*)\b").expect("valid keyword pattern"))
+}
+
+/// Whether a word could be an issue ID or ID prefix
+///
+/// Issue numbers of other trackers (`#123`) and plain words in trailers
+/// (`See: the encoding docs`) are not.
+fn is_reference(word: &str) -> bool {
+    word.len() >= MIN_REFERENCE_LEN
+        && word.starts_with(|c: char| c.is_ascii_hexdigit())
+        && word.chars().all(|c| c.is_ascii_hexdigit() || c == '-')
+}
+
+/// Find every issue reference in a commit message
+///
+/// An issue referenced more than once is returned once, with its strongest
+/// role (fixes, then implements, then mentions).
+pub fn parse_references(message: &str) -> Vec<IssueReference> {
+    let mut found: Vec<IssueReference> = Vec::new();
+    let mut add = |role: LinkRole, reference: &str| {
+        let reference = reference.trim_start_matches('#').to_string();
+        if !is_reference(&reference) {
+            return;
+        }
+        match found.iter_mut().find(|existing| existing.reference.eq_ignore_ascii_case(&reference)) {
+            Some(existing) => existing.role = existing.role.min(role),
+            None => found.push(IssueReference { role, reference }),
+        }
+    };
+
+    // Trailers are only read from the last paragraph, never the subject line
+    let trailers = match message.trim_end().rsplit_once("\n\n") {
+        Some((_, last)) => last,
+        None => "",
+    };
+    for captures in trailer_pattern().captures_iter(trailers) {
+        if let Some(role) = role_for(&captures[1]) {
+            for reference in captures[2].split([',', ' ']).filter(|r| !r.trim_start_matches('#').is_empty()) {
+                add(role, reference);
+            }
+        }
+    }
+    for captures in keyword_pattern().captures_iter(message) {
+        if let Some(role) = role_for(&captures[1]) {
+            add(role, &captures[2]);
+        }
+    }
+    found
+}
+
+/// Issue reference in a branch name, such as `1a2b3c4d` in `feature/1a2b3c4d-login`
+///
+/// The first hex word of at least [`MIN_REFERENCE_LEN`] characters that holds
+/// a digit, or is as long as a short ID, is taken, so plain words such as
+/// `facade` are not mistaken for IDs.
+pub fn branch_reference(branch: &str) -> Option<String> {
+    branch
+        .split(['/', '-', '_', '.'])
+        .find(|word| {
+            word.len() >= MIN_REFERENCE_LEN
+                && word.chars().all(|c| c.is_ascii_hexdigit())
+                && (word.len() == 8 || word.chars().any(|c| c.is_ascii_digit()))
+        })
+        .map(str::to_lowercase)
+}
+
+/// Longest slug [`Issue::slug`] makes of a title, for branch and file names
+const MAX_SLUG_LEN: usize = 40;
+
+/// Lowercase ASCII words of a title joined by `-`
+fn slugify(title: &str) -> String {
+    let mut slug = String::new();
+    for word in title.split(|c: char| !c.is_ascii_alphanumeric()).filter(|word| !word.is_empty()) {
+        if !slug.is_empty() && slug.len() + word.len() + 1 > MAX_SLUG_LEN {
+            break;
+        }
+        if !slug.is_empty() {
+            slug.push('-');
+        }
+        slug.push_str(&word.to_ascii_lowercase());
+    }
+    slug.truncate(MAX_SLUG_LEN);
+    slug
+}
+
+impl Issue {
+    /// Short form of the ID, for display and for references in commit messages
+    pub fn short_id(&self) -> String {
+        self.id.simple().to_string()[..8].to_string()
+    }
+
+    /// The title in lowercase words joined by `-`, for branch and file names
+    pub fn slug(&self) -> String {
+        slugify(&self.title)
+    }
+
+    /// Branch name for this issue from a template
+    ///
+    /// The template may use `{key}` (the short ID), `{id}` and `{slug}`, the
+    /// title in lowercase words joined by `-`, e.g. `{key}-{slug}` gives
+    /// `1a2b3c4d-login-fails-on-safari`.
+    pub fn branch_name(&self, template: &str) -> String {
+        template
+            .replace("{key}", &self.short_id())
+            .replace("{id}", &self.id.to_string())
+            .replace("{slug}", &self.slug())
+    }
+
+    /// Whether a reference such as `1a2b3c4d` names this issue
+    pub fn matches_reference(&self, reference: &str) -> bool {
+        let reference = reference.trim_start_matches('#').to_lowercase();
+        reference.len() >= MIN_REFERENCE_LEN
+            && (self.id.to_string().starts_with(&reference) || self.id.simple().to_string().starts_with(&reference))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reference(role: LinkRole, reference: &str) -> IssueReference {
+        IssueReference { role, reference: reference.to_string() }
+    }
+
+    #[test]
+    fn test_parse_references() {
+        let message = "Fix login crash\n\nThe session was dropped, see #abc123de.\nCloses #ABC123DE too.\n\nFixes: 1a2b3c4d, #99887766\nRefs: API-12\nSigned-off-by: Alice <alice@example.com>\n";
+        assert_eq!(parse_references(message), vec![
+            reference(LinkRole::Fixes, "1a2b3c4d"),
+            reference(LinkRole::Fixes, "99887766"),
+            reference(LinkRole::Fixes, "abc123de"),
+        ]);
+        assert!(parse_references("Prefix the fix with #1 of many").is_empty());
+        assert!(parse_references("Handle UTF-16\n\nFixes #123\nSee: the encoding docs\n").is_empty());
+    }
+
+    #[test]
+    fn test_branch_reference() {
+        assert_eq!(branch_reference("1a2b3c4d-login-fails").as_deref(), Some("1a2b3c4d"));
+        assert_eq!(branch_reference("feature/facade-1A2B3C4D").as_deref(), Some("1a2b3c4d"));
+        assert_eq!(branch_reference("main"), None);
+        assert_eq!(branch_reference("feature/decade-cafe12"), Some("cafe12".to_string()));
+    }
+
+    #[test]
+    fn test_branch_name() {
+        let issue = Issue::new("Login fails on Safari (iOS 17)!".to_string(), "alice".to_string());
+        let branch = issue.branch_name("feature/{key}-{slug}");
+
+        assert_eq!(branch, format!("feature/{}-login-fails-on-safari-ios-17", issue.short_id()));
+        assert_eq!(branch_reference(&branch), Some(issue.short_id()));
+        assert_eq!(slugify(&"word ".repeat(20)).len(), 39);
+    }
+
+    #[test]
+    fn test_matches_reference() {
+        let issue = Issue::new("Referenced".to_string(), "alice".to_string());
+        let short = issue.short_id();
+
+        assert_eq!(short.len(), 8);
+        assert!(issue.matches_reference(&short));
+        assert!(issue.matches_reference(&format!("#{}", short.to_uppercase())));
+        assert!(issue.matches_reference(&issue.id.to_string()));
+        assert!(!issue.matches_reference(&short[..4]));
+    }
+}
@@ -1,9 +1,9 @@
 //! Issue repository trait and related types
 
-use crate::{Result, issue::{Issue, IssueId, IssueStatus, Priority}, user::UserId, project::{ProjectId, LabelId}};
+use crate::{Result, issue::{Cursor, Filter, GitRef, Issue, IssueId, IssueStatus, Priority, SortKey}, user::UserId, project::{ProjectId, LabelId}};
 
 /// Issue query filters
-#[derive(Debug, Default)]
+#[derive(Debug, Clone, Default)]
 pub struct IssueQuery {
     pub project_id: Option<ProjectId>,
     pub assignee: Option<UserId>,
@@ -11,6 +11,12 @@ pub struct IssueQuery {
     pub status: Option<IssueStatus>,
     pub priority: Option<Priority>,
     pub labels: Vec<LabelId>,
+    /// Further conditions, from the query language
+    pub filter: Option<Filter>,
+    /// Order of results; oldest first when empty
+    pub sort: Vec<SortKey>,
+    /// Continue after the issue this cursor was made for
+    pub after: Option<Cursor>,
     pub limit: Option<usize>,
     pub offset: Option<usize>,
 }
@@ -26,6 +32,7 @@ pub struct IssueUpdate {
     pub co_authors: Option<Vec<UserId>>,
     pub labels: Option<Vec<LabelId>>,
     pub project_id: Option<Option<ProjectId>>,
+    pub git_refs: Option<Vec<GitRef>>,
 }
 
 /// Issue repository trait for data access operations
@@ -118,6 +125,46 @@ impl IssueQuery {
         self.offset = Some(offset);
         self
     }
+    
+    /// Check whether an issue passes the query filters (ignores sorting and pagination)
+    pub fn matches(&self, issue: &Issue) -> bool {
+        if let Some(project_id) = &self.project_id {
+            if issue.project_id.as_ref() != Some(project_id) {
+                return false;
+            }
+        }
+        
+        if let Some(assignee) = &self.assignee {
+            if !issue.assignees.contains(assignee) {
+                return false;
+            }
+        }
+        
+        if let Some(author) = &self.author {
+            if &issue.author != author {
+                return false;
+            }
+        }
+        
+        if let Some(status) = &self.status {
+            if &issue.status != status {
+                return false;
+            }
+        }
+        
+        if let Some(priority) = &self.priority {
+            if &issue.priority != priority {
+                return false;
+            }
+        }
+        
+        // Any one of the requested labels is enough
+        if !self.labels.is_empty() && !self.labels.iter().any(|l| issue.labels.contains(l)) {
+            return false;
+        }
+        
+        self.filter.as_ref().is_none_or(|filter| filter.matches(issue))
+    }
 }
 
 impl IssueUpdate {
@@ -132,6 +179,7 @@ impl IssueUpdate {
             co_authors: None,
             labels: None,
             project_id: None,
+            git_refs: None,
         }
     }
     
@@ -182,4 +230,76 @@ impl IssueUpdate {
         self.project_id = Some(project_id);
         self
     }
-}
\ No newline at end of file
+    
+    /// Update Git links
+    pub fn git_refs(mut self, git_refs: Vec<GitRef>) -> Self {
+        self.git_refs = Some(git_refs);
+        self
+    }
+    
+    /// Apply the update to an issue, bumping its `updated_at` timestamp
+    pub fn apply_to(self, issue: &mut Issue) {
+        if let Some(title) = self.title {
+            issue.title = title;
+        }
+        if let Some(description) = self.description {
+            issue.description = description;
+        }
+        if let Some(status) = self.status {
+            issue.status = status;
+        }
+        if let Some(priority) = self.priority {
+            issue.priority = priority;
+        }
+        if let Some(assignees) = self.assignees {
+            issue.assignees = assignees;
+        }
+        if let Some(co_authors) = self.co_authors {
+            issue.co_authors = co_authors;
+        }
+        if let Some(labels) = self.labels {
+            issue.labels = labels;
+        }
+        if let Some(project_id) = self.project_id {
+            issue.project_id = project_id;
+        }
+        if let Some(git_refs) = self.git_refs {
+            issue.git_refs = git_refs;
+        }
+        
+        issue.updated_at = chrono::Utc::now();
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_issue_query_apply() {
+        let mut first = Issue::new("First".to_string(), "alice".to_string());
+        first.labels = vec!["bug".to_string()];
+        let mut second = Issue::new("Second".to_string(), "bob".to_string());
+        second.created_at = first.created_at + chrono::Duration::seconds(1);
+        second.status = IssueStatus::Resolved;
+        let mut third = Issue::new("Third".to_string(), "alice".to_string());
+        third.created_at = first.created_at + chrono::Duration::seconds(2);
+        
+        let issues = vec![third.clone(), second.clone(), first.clone()];
+        
+        let all = IssueQuery::new().apply(issues.clone());
+        assert_eq!(all.iter().map(|i| i.id).collect::<Vec<_>>(), vec![first.id, second.id, third.id]);
+        
+        let by_author = IssueQuery::new().author("alice".to_string()).apply(issues.clone());
+        assert_eq!(by_author.len(), 2);
+        
+        let by_label = IssueQuery::new().labels(vec!["bug".to_string()]).apply(issues.clone());
+        assert_eq!(by_label.len(), 1);
+        assert_eq!(by_label[0].id, first.id);
+        
+        let page = IssueQuery::new().offset(1).limit(1).apply(issues.clone());
+        assert_eq!(page.len(), 1);
+        assert_eq!(page[0].id, second.id);
+        
+        assert!(IssueQuery::new().offset(5).apply(issues).is_empty());
+    }
+}
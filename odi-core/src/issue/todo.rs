@@ -0,0 +1,251 @@
+//! TODO, FIXME and HACK comments in source code
+//!
+//! `odi scan-todos` turns such comments into issues. Each comment gets a
+//! fingerprint from its file, kind and text but not its line, so scanning again
+//! after the code around it moved finds the same issue instead of creating
+//! another. Issues keep the fingerprint in a `Todo-Fingerprint:` trailer at the
+//! end of their description.
+
+use std::sync::OnceLock;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use super::{GitRef, Issue, LinkRole, MIN_REFERENCE_LEN};
+use crate::user::UserId;
+
+/// Trailer holding a comment's fingerprint in the description of its issue
+pub const FINGERPRINT_TRAILER: &str = "Todo-Fingerprint";
+
+/// Longest issue title, as checked by [`Issue::validate_title`]
+const MAX_TITLE_LEN: usize = 100;
+
+/// Kind of comment, from its marker word
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TodoKind {
+    Todo,
+    Fixme,
+    Hack,
+}
+
+impl TodoKind {
+    fn parse(word: &str) -> Option<Self> {
+        match word {
+            "TODO" => Some(TodoKind::Todo),
+            "FIXME" => Some(TodoKind::Fixme),
+            "HACK" => Some(TodoKind::Hack),
+            _ => None,
+        }
+    }
+
+    /// Label given to issues created from this kind of comment
+    pub fn label(&self) -> &'static str {
+        match self {
+            TodoKind::Todo => "todo",
+            TodoKind::Fixme => "fixme",
+            TodoKind::Hack => "hack",
+        }
+    }
+
+    pub fn marker(&self) -> &'static str {
+        match self {
+            TodoKind::Todo => "TODO",
+            TodoKind::Fixme => "FIXME",
+            TodoKind::Hack => "HACK",
+        }
+    }
+}
+
+/// A TODO comment found in a file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TodoComment {
+    pub kind: TodoKind,
+    /// Path from the root of the working tree
+    pub path: String,
+    /// Line of the comment, starting at 1
+    pub line: usize,
+    /// Text after the marker, without comment delimiters and with runs of
+    /// whitespace collapsed
+    pub text: String,
+    /// Issue already named by the comment, as in `TODO(1a2b3c4d): ...`
+    pub reference: Option<String>,
+    pub fingerprint: String,
+}
+
+/// A marker after the start of a line comment (`//`, `#`, `--`, `;`, `%`) or
+/// a block comment (`/*`, `*`, `<!--`, `{-`, `(*`), with optional parentheses
+fn todo_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| {
+        Regex::new(r"(?:^|\s)(?://+!?|/\*+!?|\*|#+|--|;+|%+|<!--|\{-|\(\*)[ \t]*(TODO|FIXME|HACK)\b(\(([^)]*)\))?:?[ \t]*(.*)$")
+            .expect("valid todo pattern")
+    })
+}
+
+/// Text of a comment without the closing delimiter of a block comment
+fn comment_text(rest: &str) -> String {
+    let mut text = rest.trim();
+    for end in ["*/", "-->", "-}", "*)"] {
+        text = text.strip_suffix(end).unwrap_or(text).trim_end();
+    }
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// An issue ID prefix among the words in a comment's parentheses
+fn reference_in(words: &str) -> Option<String> {
+    words
+        .split([',', ' '])
+        .map(|word| word.trim().trim_start_matches('#'))
+        .find(|word| {
+            word.len() >= MIN_REFERENCE_LEN
+                && word.chars().all(|c| c.is_ascii_hexdigit() || c == '-')
+                && word.chars().any(|c| c.is_ascii_digit())
+        })
+        .map(str::to_lowercase)
+}
+
+/// 64-bit FNV-1a, which unlike the standard library's hasher never changes
+/// between releases
+fn fnv1a(parts: &[&str]) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for part in parts {
+        for byte in part.bytes().chain(std::iter::once(0)) {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+    hash
+}
+
+/// Find the TODO comments in a file's content
+///
+/// Identical comments in the same file are told apart by their order, so each
+/// keeps its own fingerprint.
+pub fn find_todos(path: &str, content: &str) -> Vec<TodoComment> {
+    let mut found: Vec<TodoComment> = Vec::new();
+    for (index, line) in content.lines().enumerate() {
+        let Some(captures) = todo_pattern().captures(line) else {
+            continue;
+        };
+        let Some(kind) = TodoKind::parse(&captures[1]) else {
+            continue;
+        };
+        let text = comment_text(&captures[4]);
+        let occurrence = found.iter().filter(|todo| todo.kind == kind && todo.text == text).count();
+
+        found.push(TodoComment {
+            kind,
+            path: path.to_string(),
+            line: index + 1,
+            reference: captures.get(3).and_then(|words| reference_in(words.as_str())),
+            fingerprint: format!("{:016x}", fnv1a(&[path, kind.marker(), &text, &occurrence.to_string()])),
+            text,
+        });
+    }
+    found
+}
+
+/// A line with its TODO comment naming an issue, as `TODO(1a2b3c4d): ...`
+///
+/// Words already in parentheses, such as an author, are kept. `None` if the
+/// line has no TODO comment or it already names the issue.
+pub fn add_reference(line: &str, key: &str) -> Option<String> {
+    let captures = todo_pattern().captures(line)?;
+    let marker = captures.get(1)?;
+    match captures.get(3) {
+        Some(words) if reference_in(words.as_str()).is_some_and(|reference| key.starts_with(&reference)) => None,
+        Some(words) if words.as_str().trim().is_empty() => {
+            Some(format!("{}{}{}", &line[..words.start()], key, &line[words.end()..]))
+        }
+        Some(words) => Some(format!("{}{}, {}{}", &line[..words.start()], words.as_str(), key, &line[words.end()..])),
+        None => Some(format!("{}({}){}", &line[..marker.end()], key, &line[marker.end()..])),
+    }
+}
+
+impl TodoComment {
+    /// Issue title: the comment's text, or where it is if it has none
+    pub fn title(&self) -> String {
+        let title = if self.text.is_empty() {
+            format!("{} in {}", self.kind.marker(), self.path)
+        } else {
+            self.text.clone()
+        };
+        if title.len() <= MAX_TITLE_LEN {
+            return title;
+        }
+        let mut end = MAX_TITLE_LEN - 3;
+        while !title.is_char_boundary(end) {
+            end -= 1;
+        }
+        format!("{}...", &title[..end])
+    }
+
+    /// A new issue for the comment, labelled with its kind and linked to its line
+    pub fn to_issue(&self, author: UserId, repository: Option<String>) -> Issue {
+        let mut issue = Issue::new(self.title(), author);
+        let text = if self.text.is_empty() { String::new() } else { format!("{}\n\n", self.text) };
+        issue.description = Some(format!("{}{}: {}", text, FINGERPRINT_TRAILER, self.fingerprint));
+        issue.labels.push(self.kind.label().to_string());
+        issue.link(GitRef::file(self.path.clone(), self.line, LinkRole::Mentions).with_repository(repository));
+        issue
+    }
+}
+
+impl Issue {
+    /// Fingerprint of the TODO comment the issue was created from, if any
+    pub fn todo_fingerprint(&self) -> Option<&str> {
+        let prefix = format!("{}: ", FINGERPRINT_TRAILER);
+        self.description.as_deref()?
+            .lines()
+            .rev()
+            .find_map(|line| line.trim().strip_prefix(prefix.as_str()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_todos() {
+        let content = "fn main() {\n    // TODO: handle errors\n    let x = 1; // FIXME(alice) overflow */\n    /* HACK: until the API is fixed */\n}\n# TODO(#1a2b3c4d, bob): retry\nlet todo = \"TODO: not a comment\";\n<!-- TODO -->\n";
+        let todos = find_todos("src/main.rs", content);
+
+        let found: Vec<(TodoKind, usize, &str)> = todos.iter().map(|todo| (todo.kind, todo.line, todo.text.as_str())).collect();
+        assert_eq!(found, vec![
+            (TodoKind::Todo, 2, "handle errors"),
+            (TodoKind::Fixme, 3, "overflow"),
+            (TodoKind::Hack, 4, "until the API is fixed"),
+            (TodoKind::Todo, 6, "retry"),
+            (TodoKind::Todo, 8, ""),
+        ]);
+        assert_eq!(todos[1].reference, None);
+        assert_eq!(todos[3].reference.as_deref(), Some("1a2b3c4d"));
+        assert_eq!(todos[4].title(), "TODO in src/main.rs");
+    }
+
+    #[test]
+    fn test_fingerprint_ignores_line() {
+        let before = find_todos("src/lib.rs", "// TODO: cache this\n// TODO: cache this\n");
+        let after = find_todos("src/lib.rs", "\n\nfn f() {}\n// TODO:   cache   this\n// TODO: cache this\n");
+        assert_eq!(before[0].fingerprint, after[0].fingerprint);
+        assert_eq!(before[1].fingerprint, after[1].fingerprint);
+        assert_ne!(before[0].fingerprint, before[1].fingerprint);
+        assert_ne!(before[0].fingerprint, find_todos("src/main.rs", "// TODO: cache this\n")[0].fingerprint);
+
+        let issue = after[0].to_issue("alice".to_string(), None);
+        assert_eq!(issue.todo_fingerprint(), Some(after[0].fingerprint.as_str()));
+        assert_eq!(issue.labels, vec!["todo"]);
+        assert_eq!(issue.git_refs[0].target, super::super::GitTarget::File { path: "src/lib.rs".to_string(), line: 4 });
+    }
+
+    #[test]
+    fn test_add_reference() {
+        assert_eq!(add_reference("    // TODO: handle errors", "1a2b3c4d").as_deref(), Some("    // TODO(1a2b3c4d): handle errors"));
+        assert_eq!(add_reference("# FIXME(alice) overflow", "1a2b3c4d").as_deref(), Some("# FIXME(alice, 1a2b3c4d) overflow"));
+        assert_eq!(add_reference("/* HACK() */", "1a2b3c4d").as_deref(), Some("/* HACK(1a2b3c4d) */"));
+        assert_eq!(add_reference("// TODO(1a2b3c4d): done", "1a2b3c4d"), None);
+        assert_eq!(add_reference("let todo = 1;", "1a2b3c4d"), None);
+    }
+}
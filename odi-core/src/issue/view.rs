@@ -0,0 +1,209 @@
+//! Saved views: named issue queries with their sort order and columns
+//!
+//! A view is kept either for one user, in their global configuration, or for
+//! everyone working on a project, as an object stored and synced with the
+//! project's labels. The query is kept as written, so `@me` and relative dates
+//! are resolved each time the view is used.
+
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::{Issue, IssueQuery, QueryContext, SortKey};
+use crate::{CoreError, Result};
+
+/// View name, unique among a user's views or within a project
+pub type ViewName = String;
+
+/// Saved issue query
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct View {
+    pub name: ViewName,
+    /// Query in the query language; may be empty to list every issue
+    pub query: String,
+    /// Sort order, overriding any `sort:` in the query; empty keeps the query's
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sort: Vec<SortKey>,
+    /// Columns of the text listing; empty uses the default listing
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub columns: Vec<Column>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Field of an issue shown as a column of a view
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Column {
+    Id,
+    Title,
+    Status,
+    Priority,
+    Assignees,
+    Labels,
+    Project,
+    Author,
+    Created,
+    Updated,
+    Closed,
+}
+
+impl View {
+    /// Create a view of a query
+    pub fn new(name: ViewName, query: String) -> Self {
+        Self {
+            name,
+            query,
+            sort: Vec::new(),
+            columns: Vec::new(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    /// Set the sort order
+    pub fn with_sort(mut self, sort: Vec<SortKey>) -> Self {
+        self.sort = sort;
+        self
+    }
+
+    /// Set the columns
+    pub fn with_columns(mut self, columns: Vec<Column>) -> Self {
+        self.columns = columns;
+        self
+    }
+
+    /// Validate view name (1-50 characters, alphanumeric + ._-)
+    pub fn validate_name(name: &str) -> bool {
+        !name.is_empty() && name.len() <= 50 &&
+        name.chars().all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '_' || c == '-')
+    }
+
+    /// The view's query, narrowed by `refine` when it isn't empty
+    ///
+    /// Terms of `refine` must hold as well as the view's, and a `sort:` in
+    /// `refine` replaces the view's sort order.
+    pub fn to_query(&self, refine: &str, context: &QueryContext) -> Result<IssueQuery> {
+        let mut query = IssueQuery::parse(&self.query, context)?;
+        if !self.sort.is_empty() {
+            query = query.sort(self.sort.clone());
+        }
+        let refine = IssueQuery::parse(refine, context)?;
+        if let Some(filter) = refine.filter {
+            query = query.filter(filter);
+        }
+        if !refine.sort.is_empty() {
+            query = query.sort(refine.sort);
+        }
+        Ok(query)
+    }
+}
+
+impl Column {
+    /// Columns shown by `odi issue list` without a view
+    pub const DEFAULT: [Column; 4] = [Column::Id, Column::Title, Column::Status, Column::Assignees];
+
+    /// Name of the column in views and on the command line
+    pub fn name(&self) -> &'static str {
+        match self {
+            Column::Id => "id",
+            Column::Title => "title",
+            Column::Status => "status",
+            Column::Priority => "priority",
+            Column::Assignees => "assignees",
+            Column::Labels => "labels",
+            Column::Project => "project",
+            Column::Author => "author",
+            Column::Created => "created",
+            Column::Updated => "updated",
+            Column::Closed => "closed",
+        }
+    }
+
+    /// The value of this column for an issue, as text
+    pub fn cell(&self, issue: &Issue) -> String {
+        let day = |at: &DateTime<Utc>| at.format("%Y-%m-%d").to_string();
+        match self {
+            // Short IDs are enough to pass to other commands
+            Column::Id => issue.id.to_string()[..8].to_string(),
+            Column::Title => issue.title.clone(),
+            Column::Status => format!("{:?}", issue.status),
+            Column::Priority => format!("{:?}", issue.priority),
+            Column::Assignees => issue.assignees.join(", "),
+            Column::Labels => issue.labels.join(", "),
+            Column::Project => issue.project_id.clone().unwrap_or_default(),
+            Column::Author => issue.author.clone(),
+            Column::Created => day(&issue.created_at),
+            Column::Updated => day(&issue.updated_at),
+            Column::Closed => issue.closed_at.as_ref().map(day).unwrap_or_default(),
+        }
+    }
+}
+
+impl fmt::Display for Column {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl std::str::FromStr for Column {
+    type Err = CoreError;
+
+    fn from_str(name: &str) -> Result<Self> {
+        const COLUMNS: [Column; 11] = [
+            Column::Id, Column::Title, Column::Status, Column::Priority, Column::Assignees, Column::Labels,
+            Column::Project, Column::Author, Column::Created, Column::Updated, Column::Closed,
+        ];
+        COLUMNS.into_iter()
+            .find(|column| column.name() == name.trim().to_lowercase())
+            .ok_or_else(|| CoreError::ValidationError {
+                field: "column".to_string(),
+                message: format!(
+                    "Unknown column '{}': expected one of {}",
+                    name, COLUMNS.map(|column| column.name()).join(", ")
+                ),
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::issue::SortField;
+
+    #[test]
+    fn test_view_query() {
+        let context = QueryContext::new(Some("alice".to_string()));
+        let view = View::new("mine".to_string(), "assignee:@me sort:created".to_string())
+            .with_sort(vec!["-priority".parse().unwrap()]);
+
+        let query = view.to_query("", &context).unwrap();
+        assert_eq!(query.sort, vec![SortKey { field: SortField::Priority, descending: true }]);
+
+        let mut issue = Issue::new("Crash".to_string(), "bob".to_string());
+        issue.assignees.push("alice".to_string());
+        assert!(query.matches(&issue));
+
+        let refined = view.to_query("label:bug sort:updated", &context).unwrap();
+        assert!(!refined.matches(&issue));
+        assert_eq!(refined.sort, vec![SortKey { field: SortField::Updated, descending: false }]);
+        issue.labels.push("bug".to_string());
+        assert!(refined.matches(&issue));
+    }
+
+    #[test]
+    fn test_view_serialization() {
+        let view = View::new("triage".to_string(), "status:open -label:triaged".to_string())
+            .with_sort(vec!["-updated".parse().unwrap()])
+            .with_columns(vec![Column::Id, Column::Title]);
+
+        let json = serde_json::to_value(&view).unwrap();
+        assert_eq!(json["sort"], serde_json::json!(["-updated"]));
+        assert_eq!(json["columns"], serde_json::json!(["id", "title"]));
+        assert_eq!(serde_json::from_value::<View>(json).unwrap(), view);
+
+        assert!("Labels".parse::<Column>().is_ok());
+        assert!("owner".parse::<Column>().is_err());
+        assert!(View::validate_name("needs-triage"));
+        assert!(!View::validate_name("needs triage"));
+    }
+}
@@ -8,7 +8,7 @@
 //!
 //! ODI Core follows Domain-Driven Design principles with clear separation between:
 //! - **Entities**: Core business objects (Issue, User, Project, etc.)
-//! - **Repositories**: Data access trait definitions
+//! - **Repositories**: Data access trait definitions, with in-memory implementations
 //! - **Services**: Business logic and workflow coordination
 //!
 //! ## Example Usage
@@ -41,7 +41,9 @@
 //! - **Error Handling**: Comprehensive error types with context
 
 pub mod error;
+pub mod format;
 pub mod issue;
+pub mod memory;
 pub mod project;
 pub mod sync;
 pub mod user;
@@ -55,7 +57,13 @@ pub use CoreError as Error;
 // Issue entities and types
 pub use issue::{
     Issue, IssueId, IssueStatus, Priority,
-    IssueRepository, IssueQuery, IssueUpdate,
+    IssueRepository, IssueQuery, IssueUpdate, Filter, QueryContext, Cursor, SortKey, SortField,
+    GitRef, GitTarget, LinkRole, merge_links, repository_identity,
+    IssueReference, branch_reference, parse_references,
+    Changelog, ChangelogEntry, ChangelogGrouping, ChangelogSection, ChangeType,
+    TodoComment, TodoKind, add_reference, find_todos,
+    View, ViewName, Column,
+    IssueDocument,
 };
 
 // User entities and types
@@ -74,7 +82,30 @@ pub use project::{
 pub use sync::{
     Remote, RemoteId, RemoteRepository, SyncEngine, SyncResult, Conflict, ConflictType, ConflictResolution,
     SyncOptions, SyncStats, BatchConflictStrategy, RemoteInfo, detect_conflict_type,
+    FieldConflict, MergedObject, merge_objects,
 };
 
+// Object format versioning
+pub use format::{Versioned, FORMAT_VERSION};
+
+// In-memory repositories
+pub use memory::{InMemoryIssueRepository, InMemoryProjectRepository, InMemoryUserRepository, InMemoryRemoteRepository};
+
 /// Current version of the ODI core library
-pub const VERSION: &str = env!("CARGO_PKG_VERSION");
\ No newline at end of file
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+/// Apply offset then limit to an already filtered and ordered result set
+pub(crate) fn paginate<T>(mut items: Vec<T>, offset: Option<usize>, limit: Option<usize>) -> Vec<T> {
+    if let Some(offset) = offset {
+        if offset >= items.len() {
+            items.clear();
+        } else {
+            items.drain(0..offset);
+        }
+    }
+    
+    if let Some(limit) = limit {
+        items.truncate(limit);
+    }
+    
+    items
+}
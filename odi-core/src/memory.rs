@@ -0,0 +1,564 @@
+//! In-memory repository implementations
+//!
+//! Dependency-free implementations of every repository trait, for tools that
+//! embed odi-core and for tests. Nothing touches disk and all data is lost when
+//! the repository is dropped. Queries use the same semantics as the persistent
+//! backends (`IssueQuery::apply` and friends), so results are interchangeable.
+
+use std::collections::HashMap;
+use std::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+use crate::{
+    CoreError, Result,
+    Issue, IssueId, IssueQuery, IssueRepository, IssueUpdate,
+    Label, LabelId, LabelQuery, View, ViewName, Project, ProjectId, ProjectQuery, ProjectRepository, ProjectUpdate,
+    Workspace, WorkspaceId, WorkspaceQuery, WorkspaceUpdate,
+    Remote, RemoteId, RemoteRepository,
+    Team, TeamId, TeamQuery, TeamUpdate, User, UserId, UserQuery, UserRepository, UserUpdate,
+};
+
+/// Read a lock, ignoring poisoning (no invariant spans more than one map operation)
+fn read<T>(lock: &RwLock<T>) -> RwLockReadGuard<'_, T> {
+    lock.read().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Write a lock, ignoring poisoning (no invariant spans more than one map operation)
+fn write<T>(lock: &RwLock<T>) -> RwLockWriteGuard<'_, T> {
+    lock.write().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Issue repository kept in memory
+#[derive(Debug, Default)]
+pub struct InMemoryIssueRepository {
+    issues: RwLock<HashMap<IssueId, Issue>>,
+}
+
+impl InMemoryIssueRepository {
+    /// Create an empty repository
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait::async_trait]
+impl IssueRepository for InMemoryIssueRepository {
+    async fn create(&self, issue: Issue) -> Result<Issue> {
+        write(&self.issues).insert(issue.id, issue.clone());
+        Ok(issue)
+    }
+
+    async fn get(&self, id: &IssueId) -> Result<Option<Issue>> {
+        Ok(read(&self.issues).get(id).cloned())
+    }
+
+    async fn update(&self, id: &IssueId, update: IssueUpdate) -> Result<Option<Issue>> {
+        let mut issues = write(&self.issues);
+        Ok(issues.get_mut(id).map(|issue| {
+            update.apply_to(issue);
+            issue.clone()
+        }))
+    }
+
+    async fn delete(&self, id: &IssueId) -> Result<bool> {
+        Ok(write(&self.issues).remove(id).is_some())
+    }
+
+    async fn list(&self, query: IssueQuery) -> Result<Vec<Issue>> {
+        let issues = read(&self.issues).values().cloned().collect();
+        Ok(query.apply(issues))
+    }
+
+    async fn count(&self, query: IssueQuery) -> Result<usize> {
+        Ok(self.list(query).await?.len())
+    }
+
+    async fn get_assigned_to(&self, user_id: &UserId) -> Result<Vec<Issue>> {
+        self.list(IssueQuery { assignee: Some(user_id.clone()), ..Default::default() }).await
+    }
+
+    async fn get_authored_by(&self, user_id: &UserId) -> Result<Vec<Issue>> {
+        self.list(IssueQuery { author: Some(user_id.clone()), ..Default::default() }).await
+    }
+
+    async fn get_by_project(&self, project_id: &ProjectId) -> Result<Vec<Issue>> {
+        self.list(IssueQuery { project_id: Some(project_id.clone()), ..Default::default() }).await
+    }
+
+    async fn search(&self, query: &str) -> Result<Vec<Issue>> {
+        let needle = query.to_lowercase();
+        let issues = self.list(IssueQuery::default()).await?;
+        Ok(issues.into_iter()
+            .filter(|issue| issue.title.to_lowercase().contains(&needle)
+                || issue.description.as_ref().is_some_and(|d| d.to_lowercase().contains(&needle)))
+            .collect())
+    }
+
+    async fn get_recent(&self, limit: usize) -> Result<Vec<Issue>> {
+        let mut issues = self.list(IssueQuery::default()).await?;
+        issues.sort_by_key(|issue| std::cmp::Reverse(issue.updated_at));
+        issues.truncate(limit);
+        Ok(issues)
+    }
+}
+
+#[derive(Debug, Default)]
+struct ProjectData {
+    projects: HashMap<ProjectId, Project>,
+    workspaces: HashMap<WorkspaceId, Workspace>,
+    /// Labels are scoped to the project they were created in
+    labels: HashMap<(ProjectId, LabelId), Label>,
+    /// Shared views, like labels, belong to one project
+    views: HashMap<(ProjectId, ViewName), View>,
+}
+
+/// Project, workspace, label and view repository kept in memory
+#[derive(Debug, Default)]
+pub struct InMemoryProjectRepository {
+    data: RwLock<ProjectData>,
+}
+
+impl InMemoryProjectRepository {
+    /// Create an empty repository
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait::async_trait]
+impl ProjectRepository for InMemoryProjectRepository {
+    async fn create_project(&self, project: Project) -> Result<Project> {
+        write(&self.data).projects.insert(project.id.clone(), project.clone());
+        Ok(project)
+    }
+
+    async fn get_project(&self, id: &ProjectId) -> Result<Option<Project>> {
+        Ok(read(&self.data).projects.get(id).cloned())
+    }
+
+    async fn update_project(&self, id: &ProjectId, update: ProjectUpdate) -> Result<Option<Project>> {
+        let mut data = write(&self.data);
+        Ok(data.projects.get_mut(id).map(|project| {
+            update.apply_to(project);
+            project.clone()
+        }))
+    }
+
+    async fn delete_project(&self, id: &ProjectId) -> Result<bool> {
+        Ok(write(&self.data).projects.remove(id).is_some())
+    }
+
+    async fn list_projects(&self, query: ProjectQuery) -> Result<Vec<Project>> {
+        let projects = read(&self.data).projects.values().cloned().collect();
+        Ok(query.apply(projects))
+    }
+
+    async fn count_projects(&self, query: ProjectQuery) -> Result<usize> {
+        Ok(self.list_projects(query).await?.len())
+    }
+
+    async fn search_projects(&self, query: &str) -> Result<Vec<Project>> {
+        let needle = query.to_lowercase();
+        let projects = self.list_projects(ProjectQuery::default()).await?;
+        Ok(projects.into_iter()
+            .filter(|project| project.name.to_lowercase().contains(&needle)
+                || project.description.as_ref().is_some_and(|d| d.to_lowercase().contains(&needle)))
+            .collect())
+    }
+
+    async fn create_workspace(&self, workspace: Workspace) -> Result<Workspace> {
+        write(&self.data).workspaces.insert(workspace.id.clone(), workspace.clone());
+        Ok(workspace)
+    }
+
+    async fn get_workspace(&self, id: &WorkspaceId) -> Result<Option<Workspace>> {
+        Ok(read(&self.data).workspaces.get(id).cloned())
+    }
+
+    async fn get_workspace_by_path(&self, path: &std::path::PathBuf) -> Result<Option<Workspace>> {
+        Ok(read(&self.data).workspaces.values().find(|workspace| workspace.path == *path).cloned())
+    }
+
+    async fn update_workspace(&self, id: &WorkspaceId, update: WorkspaceUpdate) -> Result<Option<Workspace>> {
+        let mut data = write(&self.data);
+        Ok(data.workspaces.get_mut(id).map(|workspace| {
+            update.apply_to(workspace);
+            workspace.clone()
+        }))
+    }
+
+    async fn delete_workspace(&self, id: &WorkspaceId) -> Result<bool> {
+        Ok(write(&self.data).workspaces.remove(id).is_some())
+    }
+
+    async fn list_workspaces(&self, query: WorkspaceQuery) -> Result<Vec<Workspace>> {
+        let workspaces = read(&self.data).workspaces.values().cloned().collect();
+        Ok(query.apply(workspaces))
+    }
+
+    async fn count_workspaces(&self, query: WorkspaceQuery) -> Result<usize> {
+        Ok(self.list_workspaces(query).await?.len())
+    }
+
+    async fn get_project_workspaces(&self, project_id: &ProjectId) -> Result<Vec<Workspace>> {
+        self.list_workspaces(WorkspaceQuery { project_id: Some(project_id.clone()), ..Default::default() }).await
+    }
+
+    async fn get_workspace_projects(&self, workspace_id: &WorkspaceId) -> Result<Vec<Project>> {
+        self.list_projects(ProjectQuery { workspace_id: Some(workspace_id.clone()), ..Default::default() }).await
+    }
+
+    async fn link_project_workspace(&self, project_id: &ProjectId, workspace_id: &WorkspaceId) -> Result<()> {
+        let mut data = write(&self.data);
+        if let Some(project) = data.projects.get_mut(project_id) {
+            if !project.workspaces.contains(workspace_id) {
+                project.workspaces.push(workspace_id.clone());
+                project.updated_at = chrono::Utc::now();
+            }
+        }
+        if let Some(workspace) = data.workspaces.get_mut(workspace_id) {
+            if !workspace.projects.contains(project_id) {
+                workspace.projects.push(project_id.clone());
+            }
+        }
+        Ok(())
+    }
+
+    async fn unlink_project_workspace(&self, project_id: &ProjectId, workspace_id: &WorkspaceId) -> Result<()> {
+        let mut data = write(&self.data);
+        if let Some(project) = data.projects.get_mut(project_id) {
+            project.workspaces.retain(|w| w != workspace_id);
+            project.updated_at = chrono::Utc::now();
+        }
+        if let Some(workspace) = data.workspaces.get_mut(workspace_id) {
+            workspace.projects.retain(|p| p != project_id);
+        }
+        Ok(())
+    }
+
+    async fn create_label(&self, project_id: &ProjectId, label: Label) -> Result<Label> {
+        write(&self.data).labels.insert((project_id.clone(), label.id.clone()), label.clone());
+        Ok(label)
+    }
+
+    async fn get_label(&self, project_id: &ProjectId, label_id: &LabelId) -> Result<Option<Label>> {
+        Ok(read(&self.data).labels.get(&(project_id.clone(), label_id.clone())).cloned())
+    }
+
+    async fn update_label(&self, project_id: &ProjectId, label_id: &LabelId, label: Label) -> Result<Option<Label>> {
+        // The label may be renamed, so replace the old entry
+        let mut data = write(&self.data);
+        data.labels.remove(&(project_id.clone(), label_id.clone()));
+        data.labels.insert((project_id.clone(), label.id.clone()), label.clone());
+        Ok(Some(label))
+    }
+
+    async fn delete_label(&self, project_id: &ProjectId, label_id: &LabelId) -> Result<bool> {
+        Ok(write(&self.data).labels.remove(&(project_id.clone(), label_id.clone())).is_some())
+    }
+
+    async fn list_labels(&self, project_id: &ProjectId, query: LabelQuery) -> Result<Vec<Label>> {
+        let labels = read(&self.data).labels.iter()
+            .filter(|((project, _), _)| project == project_id)
+            .map(|(_, label)| label.clone())
+            .collect();
+        Ok(query.apply(labels))
+    }
+
+    async fn get_all_labels(&self, query: LabelQuery) -> Result<Vec<Label>> {
+        let labels = read(&self.data).labels.iter()
+            .filter(|((project, _), _)| query.project_id.as_ref().is_none_or(|id| project == id))
+            .map(|(_, label)| label.clone())
+            .collect();
+        Ok(query.apply(labels))
+    }
+
+    async fn save_view(&self, project_id: &ProjectId, view: View) -> Result<View> {
+        write(&self.data).views.insert((project_id.clone(), view.name.clone()), view.clone());
+        Ok(view)
+    }
+
+    async fn get_view(&self, project_id: &ProjectId, name: &ViewName) -> Result<Option<View>> {
+        Ok(read(&self.data).views.get(&(project_id.clone(), name.clone())).cloned())
+    }
+
+    async fn delete_view(&self, project_id: &ProjectId, name: &ViewName) -> Result<bool> {
+        Ok(write(&self.data).views.remove(&(project_id.clone(), name.clone())).is_some())
+    }
+
+    async fn list_views(&self, project_id: &ProjectId) -> Result<Vec<View>> {
+        let mut views: Vec<View> = read(&self.data).views.iter()
+            .filter(|((project, _), _)| project == project_id)
+            .map(|(_, view)| view.clone())
+            .collect();
+        views.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(views)
+    }
+
+    async fn add_project_issue(&self, _project_id: &ProjectId, _issue_id: &IssueId) -> Result<()> {
+        // Issues carry their project_id; nothing to store here
+        Ok(())
+    }
+
+    async fn remove_project_issue(&self, _project_id: &ProjectId, _issue_id: &IssueId) -> Result<()> {
+        // Issues carry their project_id; nothing to store here
+        Ok(())
+    }
+
+    async fn add_project_team(&self, project_id: &ProjectId, team_id: &TeamId) -> Result<()> {
+        if let Some(project) = write(&self.data).projects.get_mut(project_id) {
+            if !project.teams.contains(team_id) {
+                project.teams.push(team_id.clone());
+                project.updated_at = chrono::Utc::now();
+            }
+        }
+        Ok(())
+    }
+
+    async fn remove_project_team(&self, project_id: &ProjectId, team_id: &TeamId) -> Result<()> {
+        if let Some(project) = write(&self.data).projects.get_mut(project_id) {
+            project.teams.retain(|t| t != team_id);
+            project.updated_at = chrono::Utc::now();
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default)]
+struct UserData {
+    users: HashMap<UserId, User>,
+    teams: HashMap<TeamId, Team>,
+}
+
+/// User and team repository kept in memory
+#[derive(Debug, Default)]
+pub struct InMemoryUserRepository {
+    data: RwLock<UserData>,
+}
+
+impl InMemoryUserRepository {
+    /// Create an empty repository
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait::async_trait]
+impl UserRepository for InMemoryUserRepository {
+    async fn create_user(&self, user: User) -> Result<User> {
+        write(&self.data).users.insert(user.id.clone(), user.clone());
+        Ok(user)
+    }
+
+    async fn get_user(&self, id: &UserId) -> Result<Option<User>> {
+        Ok(read(&self.data).users.get(id).cloned())
+    }
+
+    async fn get_user_by_email(&self, email: &str) -> Result<Option<User>> {
+        Ok(read(&self.data).users.values().find(|user| user.email == email).cloned())
+    }
+
+    async fn update_user(&self, id: &UserId, update: UserUpdate) -> Result<Option<User>> {
+        let mut data = write(&self.data);
+        Ok(data.users.get_mut(id).map(|user| {
+            update.apply_to(user);
+            user.clone()
+        }))
+    }
+
+    async fn delete_user(&self, id: &UserId) -> Result<bool> {
+        Ok(write(&self.data).users.remove(id).is_some())
+    }
+
+    async fn list_users(&self, query: UserQuery) -> Result<Vec<User>> {
+        let users = read(&self.data).users.values().cloned().collect();
+        Ok(query.apply(users))
+    }
+
+    async fn count_users(&self, query: UserQuery) -> Result<usize> {
+        Ok(self.list_users(query).await?.len())
+    }
+
+    async fn search_users(&self, _query: &str) -> Result<Vec<User>> {
+        Ok(Vec::new())
+    }
+
+    async fn get_user_teams(&self, _user_id: &UserId) -> Result<Vec<Team>> {
+        Ok(Vec::new())
+    }
+
+    async fn get_team_members(&self, team_id: &TeamId) -> Result<Vec<User>> {
+        self.list_users(UserQuery { team_id: Some(team_id.clone()), ..Default::default() }).await
+    }
+
+    async fn add_team_member(&self, team_id: &TeamId, user_id: &UserId) -> Result<()> {
+        if let Some(user) = write(&self.data).users.get_mut(user_id) {
+            if !user.teams.contains(team_id) {
+                user.teams.push(team_id.clone());
+            }
+        }
+        Ok(())
+    }
+
+    async fn remove_team_member(&self, team_id: &TeamId, user_id: &UserId) -> Result<()> {
+        if let Some(user) = write(&self.data).users.get_mut(user_id) {
+            user.teams.retain(|t| t != team_id);
+        }
+        Ok(())
+    }
+
+    async fn create_team(&self, team: Team) -> Result<Team> {
+        write(&self.data).teams.insert(team.id.clone(), team.clone());
+        Ok(team)
+    }
+
+    async fn get_team(&self, id: &TeamId) -> Result<Option<Team>> {
+        Ok(read(&self.data).teams.get(id).cloned())
+    }
+
+    async fn update_team(&self, id: &TeamId, update: TeamUpdate) -> Result<Option<Team>> {
+        let mut data = write(&self.data);
+        Ok(data.teams.get_mut(id).map(|team| {
+            update.apply_to(team);
+            team.clone()
+        }))
+    }
+
+    async fn delete_team(&self, id: &TeamId) -> Result<bool> {
+        Ok(write(&self.data).teams.remove(id).is_some())
+    }
+
+    async fn list_teams(&self, query: TeamQuery) -> Result<Vec<Team>> {
+        let teams = read(&self.data).teams.values().cloned().collect();
+        Ok(query.apply(teams))
+    }
+
+    async fn count_teams(&self, query: TeamQuery) -> Result<usize> {
+        Ok(self.list_teams(query).await?.len())
+    }
+}
+
+/// Remote repository kept in memory
+#[derive(Debug, Default)]
+pub struct InMemoryRemoteRepository {
+    remotes: RwLock<HashMap<RemoteId, Remote>>,
+}
+
+impl InMemoryRemoteRepository {
+    /// Create an empty repository
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait::async_trait]
+impl RemoteRepository for InMemoryRemoteRepository {
+    async fn create(&self, remote: Remote) -> Result<Remote> {
+        // Remote names are unique, as they are with config-file remotes
+        let mut remotes = write(&self.remotes);
+        if remotes.values().any(|r| r.name == remote.name) {
+            return Err(CoreError::ValidationError {
+                field: "remote".to_string(),
+                message: format!("Remote '{}' already exists", remote.name),
+            });
+        }
+        remotes.insert(remote.id.clone(), remote.clone());
+        Ok(remote)
+    }
+
+    async fn get(&self, id: &RemoteId) -> Result<Option<Remote>> {
+        Ok(read(&self.remotes).get(id).cloned())
+    }
+
+    async fn update(&self, id: &RemoteId, remote: Remote) -> Result<Option<Remote>> {
+        let mut remotes = write(&self.remotes);
+        Ok(remotes.get_mut(id).map(|existing| {
+            *existing = remote;
+            existing.clone()
+        }))
+    }
+
+    async fn delete(&self, id: &RemoteId) -> Result<bool> {
+        Ok(write(&self.remotes).remove(id).is_some())
+    }
+
+    async fn list(&self) -> Result<Vec<Remote>> {
+        let mut remotes: Vec<Remote> = read(&self.remotes).values().cloned().collect();
+        remotes.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(remotes)
+    }
+
+    async fn get_by_project(&self, project_id: &ProjectId) -> Result<Vec<Remote>> {
+        let remotes = self.list().await?;
+        Ok(remotes.into_iter().filter(|remote| remote.has_project(project_id)).collect())
+    }
+
+    async fn exists(&self, name: &str) -> Result<bool> {
+        Ok(read(&self.remotes).values().any(|remote| remote.name == name))
+    }
+
+    async fn get_by_name(&self, name: &str) -> Result<Option<Remote>> {
+        Ok(read(&self.remotes).values().find(|remote| remote.name == name).cloned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{IssueStatus, Priority};
+
+    #[tokio::test]
+    async fn test_issue_queries_match_fs_semantics() {
+        let repo = InMemoryIssueRepository::new();
+
+        let mut first = Issue::new("First".to_string(), "alice".to_string());
+        first.priority = Priority::High;
+        let mut second = Issue::new("Second".to_string(), "bob".to_string());
+        second.created_at = first.created_at + chrono::Duration::seconds(1);
+        second.assignees.push("alice".to_string());
+        repo.create(second.clone()).await.unwrap();
+        repo.create(first.clone()).await.unwrap();
+
+        // Oldest first regardless of insertion order, then paginated
+        let all = repo.list(IssueQuery::default()).await.unwrap();
+        assert_eq!(all.iter().map(|i| i.id).collect::<Vec<_>>(), vec![first.id, second.id]);
+        let page = repo.list(IssueQuery::new().offset(1).limit(1)).await.unwrap();
+        assert_eq!(page[0].id, second.id);
+
+        assert_eq!(repo.get_assigned_to(&"alice".to_string()).await.unwrap().len(), 1);
+        assert_eq!(repo.list(IssueQuery::new().priority(Priority::High)).await.unwrap()[0].id, first.id);
+
+        let updated = repo.update(&first.id, IssueUpdate::new().status(IssueStatus::Closed)).await.unwrap().unwrap();
+        assert_eq!(updated.status, IssueStatus::Closed);
+        assert!(updated.updated_at >= first.updated_at);
+        assert!(repo.delete(&first.id).await.unwrap());
+        assert_eq!(repo.count(IssueQuery::default()).await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_labels_and_workspace_links() {
+        let repo = InMemoryProjectRepository::new();
+        let core = Project::new("core".to_string(), "Core".to_string());
+        repo.create_project(core.clone()).await.unwrap();
+        repo.create_workspace(Workspace::new("ws".to_string(), "/tmp/ws".into())).await.unwrap();
+
+        repo.link_project_workspace(&core.id, &"ws".to_string()).await.unwrap();
+        assert_eq!(repo.get_workspace_projects(&"ws".to_string()).await.unwrap().len(), 1);
+        assert_eq!(repo.get_project_workspaces(&core.id).await.unwrap().len(), 1);
+
+        let label = Label::new("bug".to_string(), "Bug".to_string(), "#ff0000".to_string());
+        repo.create_label(&core.id, label.clone()).await.unwrap();
+        repo.create_label(&"web".to_string(), label).await.unwrap();
+        assert_eq!(repo.list_labels(&core.id, LabelQuery::default()).await.unwrap().len(), 1);
+        assert_eq!(repo.get_all_labels(LabelQuery::default()).await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_remote_names_are_unique() {
+        let repo = InMemoryRemoteRepository::new();
+        let origin = Remote::new("origin".to_string(), "origin".to_string(), "ssh://git@example.com/odi".to_string());
+        repo.create(origin.clone()).await.unwrap();
+
+        assert!(repo.create(origin).await.is_err());
+        assert!(repo.exists("origin").await.unwrap());
+    }
+}
@@ -1,10 +1,10 @@
-//! Project, Workspace, and Label repository traits
+//! Project, Workspace, Label, and View repository traits
 
 use crate::{
     Result,
     project::{Project, ProjectId, Workspace, WorkspaceId, Label, LabelId},
     user::TeamId,
-    issue::IssueId,
+    issue::{IssueId, View, ViewName},
 };
 use std::path::PathBuf;
 
@@ -127,6 +127,18 @@ pub trait ProjectRepository: Send + Sync {
     /// Get all labels across projects
     async fn get_all_labels(&self, query: LabelQuery) -> Result<Vec<Label>>;
     
+    /// Save a view shared in project, replacing any view of the same name
+    async fn save_view(&self, project_id: &ProjectId, view: View) -> Result<View>;
+    
+    /// Get a shared view by name in project
+    async fn get_view(&self, project_id: &ProjectId, name: &ViewName) -> Result<Option<View>>;
+    
+    /// Delete a shared view from project
+    async fn delete_view(&self, project_id: &ProjectId, name: &ViewName) -> Result<bool>;
+    
+    /// List views shared in project, ordered by name
+    async fn list_views(&self, project_id: &ProjectId) -> Result<Vec<View>>;
+    
     /// Add issue to project
     async fn add_project_issue(&self, project_id: &ProjectId, issue_id: &IssueId) -> Result<()>;
     
@@ -174,6 +186,29 @@ impl ProjectQuery {
     pub fn offset(mut self, offset: usize) -> Self {
         self.offset = Some(offset);
         self
+    }    
+    /// Check whether a project passes the query filters (ignores limit and offset)
+    pub fn matches(&self, project: &Project) -> bool {
+        if let Some(workspace_id) = &self.workspace_id {
+            if !project.workspaces.contains(workspace_id) {
+                return false;
+            }
+        }
+        
+        if let Some(team_id) = &self.team_id {
+            if !project.teams.contains(team_id) {
+                return false;
+            }
+        }
+        
+        true
+    }
+    
+    /// Filter, order (oldest first) and paginate a set of projects
+    pub fn apply(&self, mut projects: Vec<Project>) -> Vec<Project> {
+        projects.retain(|project| self.matches(project));
+        projects.sort_by(|a, b| a.created_at.cmp(&b.created_at).then_with(|| a.id.cmp(&b.id)));
+        crate::paginate(projects, self.offset, self.limit)
     }
 }
 
@@ -211,6 +246,24 @@ impl ProjectUpdate {
         self.workspaces = Some(workspaces);
         self
     }
+    
+    /// Apply the update to a project, bumping its `updated_at` timestamp
+    pub fn apply_to(self, project: &mut Project) {
+        if let Some(name) = self.name {
+            project.name = name;
+        }
+        if let Some(description) = self.description {
+            project.description = description;
+        }
+        if let Some(teams) = self.teams {
+            project.teams = teams;
+        }
+        if let Some(workspaces) = self.workspaces {
+            project.workspaces = workspaces;
+        }
+        
+        project.updated_at = chrono::Utc::now();
+    }
 }
 
 impl WorkspaceQuery {
@@ -242,6 +295,30 @@ impl WorkspaceQuery {
         self.offset = Some(offset);
         self
     }
+    
+    /// Check whether a workspace satisfies every filter in the query
+    pub fn matches(&self, workspace: &Workspace) -> bool {
+        if let Some(project_id) = &self.project_id {
+            if !workspace.projects.contains(project_id) {
+                return false;
+            }
+        }
+        
+        if let Some(prefix) = &self.path_prefix {
+            if !workspace.path.starts_with(prefix) {
+                return false;
+            }
+        }
+        
+        true
+    }
+    
+    /// Filter, order (by path) and paginate a set of workspaces
+    pub fn apply(&self, mut workspaces: Vec<Workspace>) -> Vec<Workspace> {
+        workspaces.retain(|workspace| self.matches(workspace));
+        workspaces.sort_by(|a, b| a.path.cmp(&b.path).then_with(|| a.id.cmp(&b.id)));
+        crate::paginate(workspaces, self.offset, self.limit)
+    }
 }
 
 impl WorkspaceUpdate {
@@ -264,6 +341,18 @@ impl WorkspaceUpdate {
         self.projects = Some(projects);
         self
     }
+    
+    /// Apply the update to a workspace, bumping its `updated_at` timestamp
+    pub fn apply_to(self, workspace: &mut Workspace) {
+        if let Some(path) = self.path {
+            workspace.path = path;
+        }
+        if let Some(projects) = self.projects {
+            workspace.projects = projects;
+        }
+        
+        workspace.updated_at = chrono::Utc::now();
+    }
 }
 
 impl LabelQuery {
@@ -294,5 +383,22 @@ impl LabelQuery {
     pub fn offset(mut self, offset: usize) -> Self {
         self.offset = Some(offset);
         self
+    }    
+    /// Check whether a label passes the query filters
+    ///
+    /// Labels carry no project field; the project filter is applied by
+    /// repositories through where the label is stored.
+    pub fn matches(&self, label: &Label) -> bool {
+        match &self.color {
+            Some(color) => &label.color == color,
+            None => true,
+        }
+    }
+    
+    /// Filter, order (by name) and paginate a set of labels
+    pub fn apply(&self, mut labels: Vec<Label>) -> Vec<Label> {
+        labels.retain(|label| self.matches(label));
+        labels.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| a.id.cmp(&b.id)));
+        crate::paginate(labels, self.offset, self.limit)
     }
 }
\ No newline at end of file
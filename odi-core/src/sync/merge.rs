@@ -0,0 +1,189 @@
+//! Three-way merge of stored objects
+//!
+//! Used when two copies of an object changed independently since a common
+//! ancestor, such as a ref edited on two Git branches. Objects are merged field
+//! by field on their JSON payloads:
+//!
+//! - a field changed on one side only takes that side's value
+//! - lists (assignees, labels, ...) keep what either side added and drop what
+//!   either side removed
+//! - nested objects are merged the same way, field by field
+//! - `updated_at` takes the later time, and an issue's Git links are merged
+//!   with [`merge_links`]
+//!
+//! A field both sides changed to different values is a conflict: the value of
+//! the more recently updated copy is kept and the conflict reported.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::issue::{merge_links, GitRef};
+
+/// A field both sides changed to different values
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldConflict {
+    /// Path of the field, e.g. `title` or `settings.color`
+    pub field: String,
+    /// Value in the common ancestor; `None` if the field or object didn't exist
+    pub base: Option<Value>,
+    pub ours: Value,
+    pub theirs: Value,
+    /// Whether `theirs` was kept, being the more recently updated copy
+    pub kept_theirs: bool,
+}
+
+/// Result of merging two copies of an object
+#[derive(Debug, Clone, PartialEq)]
+pub struct MergedObject {
+    pub value: Value,
+    pub conflicts: Vec<FieldConflict>,
+}
+
+/// Merge two JSON payloads of an object of `kind` against their common ancestor
+///
+/// `base` is `None` when both sides created the object independently.
+pub fn merge_objects(kind: &str, base: Option<&Value>, ours: &Value, theirs: &Value) -> MergedObject {
+    let prefer_theirs = updated_at(theirs) > updated_at(ours);
+    let mut conflicts = Vec::new();
+    let mut value = merge_value("", base, ours, theirs, prefer_theirs, &mut conflicts);
+
+    if kind == "issue" {
+        if let (Some(fields), Ok(ours), Ok(theirs)) = (value.as_object_mut(), links(ours), links(theirs)) {
+            if let Ok(merged) = serde_json::to_value(merge_links(&ours, &theirs)) {
+                fields.insert("git_refs".to_string(), merged);
+            }
+        }
+    }
+    MergedObject { value, conflicts }
+}
+
+fn updated_at(value: &Value) -> Option<DateTime<Utc>> {
+    serde_json::from_value(value.get("updated_at")?.clone()).ok()
+}
+
+fn links(issue: &Value) -> serde_json::Result<Vec<GitRef>> {
+    serde_json::from_value(issue.get("git_refs").cloned().unwrap_or(Value::Array(Vec::new())))
+}
+
+fn merge_value(path: &str, base: Option<&Value>, ours: &Value, theirs: &Value, prefer_theirs: bool, conflicts: &mut Vec<FieldConflict>) -> Value {
+    if ours == theirs || base == Some(theirs) {
+        return ours.clone();
+    }
+    if base == Some(ours) {
+        return theirs.clone();
+    }
+
+    match (ours, theirs) {
+        (Value::Object(ours), Value::Object(theirs)) => {
+            let base = base.and_then(Value::as_object);
+            Value::Object(merge_fields(path, base, ours, theirs, prefer_theirs, conflicts))
+        }
+        (Value::Array(ours), Value::Array(theirs)) => {
+            let base = base.and_then(Value::as_array).map(Vec::as_slice).unwrap_or(&[]);
+            Value::Array(merge_lists(base, ours, theirs))
+        }
+        _ if path == "updated_at" => {
+            // Both sides touched the object; the merge is as recent as the later one
+            if prefer_theirs { theirs.clone() } else { ours.clone() }
+        }
+        _ => {
+            conflicts.push(FieldConflict {
+                field: path.to_string(),
+                base: base.cloned(),
+                ours: ours.clone(),
+                theirs: theirs.clone(),
+                kept_theirs: prefer_theirs,
+            });
+            if prefer_theirs { theirs.clone() } else { ours.clone() }
+        }
+    }
+}
+
+fn merge_fields(path: &str, base: Option<&Map<String, Value>>, ours: &Map<String, Value>, theirs: &Map<String, Value>, prefer_theirs: bool, conflicts: &mut Vec<FieldConflict>) -> Map<String, Value> {
+    let mut merged = Map::new();
+    let keys = ours.keys().chain(theirs.keys().filter(|key| !ours.contains_key(*key)));
+    for key in keys {
+        let field = if path.is_empty() { key.clone() } else { format!("{}.{}", path, key) };
+        // A missing field reads as null, as serde reads a missing optional field
+        let base = base.map(|base| base.get(key).unwrap_or(&Value::Null));
+        let ours = ours.get(key).unwrap_or(&Value::Null);
+        let theirs = theirs.get(key).unwrap_or(&Value::Null);
+        merged.insert(key.clone(), merge_value(&field, base, ours, theirs, prefer_theirs, conflicts));
+    }
+    merged
+}
+
+/// Items in both lists, plus those either side added since `base`
+fn merge_lists(base: &[Value], ours: &[Value], theirs: &[Value]) -> Vec<Value> {
+    let kept = |item: &Value, other: &[Value]| other.contains(item) || !base.contains(item);
+    let mut merged: Vec<Value> = ours.iter().filter(|item| kept(item, theirs)).cloned().collect();
+    for item in theirs {
+        if !merged.contains(item) && kept(item, ours) {
+            merged.push(item.clone());
+        }
+    }
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::issue::{Issue, IssueStatus, LinkRole};
+
+    fn value(issue: &Issue) -> Value {
+        serde_json::to_value(issue).unwrap()
+    }
+
+    #[test]
+    fn test_merge_changes_to_different_fields() {
+        let base = Issue::new("Login fails".to_string(), "alice".to_string());
+        let mut ours = base.clone();
+        ours.title = "Login fails on Safari".to_string();
+        ours.labels = vec!["bug".to_string()];
+        ours.git_refs.push(GitRef::branch("fix-login", LinkRole::Implements));
+        let mut theirs = base.clone();
+        theirs.status = IssueStatus::InProgress;
+        theirs.labels = vec!["ui".to_string()];
+        theirs.git_refs.push(GitRef::commit("a1b2c3d4e5f6", LinkRole::Fixes));
+        theirs.updated_at = base.updated_at + chrono::Duration::seconds(5);
+
+        let merged = merge_objects("issue", Some(&value(&base)), &value(&ours), &value(&theirs));
+        assert!(merged.conflicts.is_empty());
+        let issue: Issue = serde_json::from_value(merged.value).unwrap();
+        assert_eq!(issue.title, "Login fails on Safari");
+        assert_eq!(issue.status, IssueStatus::InProgress);
+        assert_eq!(issue.labels, vec!["bug", "ui"]);
+        assert_eq!(issue.git_refs.len(), 2);
+        assert_eq!(issue.updated_at, theirs.updated_at);
+    }
+
+    #[test]
+    fn test_merge_lists_drops_removed_items() {
+        let base = [Value::from("a"), Value::from("b")];
+        let ours = [Value::from("a"), Value::from("c")];
+        let theirs = [Value::from("b"), Value::from("a"), Value::from("d")];
+        assert_eq!(merge_lists(&base, &ours, &theirs), vec![Value::from("a"), Value::from("c"), Value::from("d")]);
+    }
+
+    #[test]
+    fn test_conflicting_field_keeps_newer_copy() {
+        let base = Issue::new("Login fails".to_string(), "alice".to_string());
+        let mut ours = base.clone();
+        ours.title = "Ours".to_string();
+        let mut theirs = base.clone();
+        theirs.title = "Theirs".to_string();
+        theirs.updated_at = base.updated_at + chrono::Duration::seconds(5);
+
+        let merged = merge_objects("issue", Some(&value(&base)), &value(&ours), &value(&theirs));
+        assert_eq!(merged.conflicts.len(), 1);
+        assert_eq!(merged.conflicts[0].field, "title");
+        assert_eq!(merged.conflicts[0].ours, Value::from("Ours"));
+        assert!(merged.conflicts[0].kept_theirs);
+        assert_eq!(merged.value["title"], "Theirs");
+
+        // Without a common ancestor every differing field conflicts
+        let merged = merge_objects("issue", None, &value(&ours), &value(&theirs));
+        assert_eq!(merged.conflicts.iter().map(|c| c.field.as_str()).collect::<Vec<_>>(), vec!["title"]);
+    }
+}
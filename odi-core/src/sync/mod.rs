@@ -1,6 +1,7 @@
 //! Synchronization and Remote entities
 
 mod engine;
+mod merge;
 mod remote;
 mod repository;
 
@@ -10,6 +11,7 @@ use serde::{Deserialize, Serialize};
 use crate::issue::{Issue, IssueId};
 
 pub use engine::{SyncEngine, SyncOptions, SyncStats, BatchConflictStrategy, RemoteInfo, detect_conflict_type};
+pub use merge::{merge_objects, FieldConflict, MergedObject};
 pub use remote::Remote;
 pub use repository::RemoteRepository;
 
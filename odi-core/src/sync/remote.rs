@@ -20,6 +20,11 @@ pub struct Remote {
 pub enum RemoteProtocol {
     Ssh,
     Https,
+    /// Read-only bundle file (`bundle:///path/to/file.odib`)
+    Bundle,
+    /// The `refs/odi/` namespace of a Git repository (`git+origin`, `git+/srv/git/app.git`),
+    /// moved with git fetch and push
+    Git,
 }
 
 impl Remote {
@@ -52,12 +57,19 @@ impl Remote {
         url.starts_with("ssh://") || 
         url.starts_with("https://") || 
         url.starts_with("http://") ||
+        url.starts_with("bundle://") ||
+        (url.starts_with("git+") && url.len() > 4) ||
         url.contains('@') // SSH format: user@host:path
     }
 
     /// Get remote protocol from URL
     pub fn get_protocol(&self) -> Option<RemoteProtocol> {
-        if self.url.starts_with("ssh://") || self.url.contains('@') {
+        if self.url.starts_with("bundle://") {
+            Some(RemoteProtocol::Bundle)
+        } else if self.url.starts_with("git+") {
+            // Checked before SSH: git+ssh://user@host/repo is still a Git remote
+            Some(RemoteProtocol::Git)
+        } else if self.url.starts_with("ssh://") || self.url.contains('@') {
             Some(RemoteProtocol::Ssh)
         } else if self.url.starts_with("https://") || self.url.starts_with("http://") {
             Some(RemoteProtocol::Https)
@@ -168,6 +180,8 @@ impl Remote {
                         }
                     }
                 }
+                // Bundles are local files; Git remotes are resolved by git itself
+                RemoteProtocol::Bundle | RemoteProtocol::Git => {}
             }
         }
         None
@@ -216,6 +230,9 @@ mod tests {
         assert!(Remote::validate_url("ssh://user@example.com/repo"));
         assert!(Remote::validate_url("https://github.com/user/repo"));
         assert!(Remote::validate_url("user@example.com:repo"));
+        assert!(Remote::validate_url("bundle:///media/usb/site-a.odib"));
+        assert!(Remote::validate_url("git+origin"));
+        assert!(!Remote::validate_url("git+"));
         assert!(!Remote::validate_url("invalid-url"));
     }
 
@@ -242,6 +259,21 @@ mod tests {
         );
         assert_eq!(https_remote.get_protocol(), Some(RemoteProtocol::Https));
 
+        let bundle_remote = Remote::new(
+            "usb".to_string(),
+            "Bundle Remote".to_string(),
+            "bundle:///media/usb/site-a.odib".to_string(),
+        );
+        assert_eq!(bundle_remote.get_protocol(), Some(RemoteProtocol::Bundle));
+        assert_eq!(bundle_remote.get_hostname(), None);
+
+        let git_remote = Remote::new(
+            "code".to_string(),
+            "Git Remote".to_string(),
+            "git+ssh://git@example.com/team/app.git".to_string(),
+        );
+        assert_eq!(git_remote.get_protocol(), Some(RemoteProtocol::Git));
+
         let invalid_remote = Remote::new(
             "invalid".to_string(),
             "Invalid Remote".to_string(),
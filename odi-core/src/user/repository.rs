@@ -132,6 +132,37 @@ impl UserQuery {
         self.offset = Some(offset);
         self
     }
+    
+    /// Check whether a user satisfies every filter in the query
+    pub fn matches(&self, user: &User) -> bool {
+        if let Some(team_id) = &self.team_id {
+            if !user.teams.contains(team_id) {
+                return false;
+            }
+        }
+        
+        if let Some(domain) = &self.email_domain {
+            match user.email.rsplit_once('@') {
+                Some((_, user_domain)) if user_domain.eq_ignore_ascii_case(domain) => {}
+                _ => return false,
+            }
+        }
+        
+        if let Some(since) = &self.active_since {
+            if user.last_active < *since {
+                return false;
+            }
+        }
+        
+        true
+    }
+    
+    /// Filter, order (by ID) and paginate a set of users
+    pub fn apply(&self, mut users: Vec<User>) -> Vec<User> {
+        users.retain(|user| self.matches(user));
+        users.sort_by(|a, b| a.id.cmp(&b.id));
+        crate::paginate(users, self.offset, self.limit)
+    }
 }
 
 impl UserUpdate {
@@ -168,6 +199,22 @@ impl UserUpdate {
         self.teams = Some(teams);
         self
     }
+    
+    /// Apply the update to a user
+    pub fn apply_to(self, user: &mut User) {
+        if let Some(name) = self.name {
+            user.name = name;
+        }
+        if let Some(email) = self.email {
+            user.email = email;
+        }
+        if let Some(avatar) = self.avatar {
+            user.avatar = avatar;
+        }
+        if let Some(teams) = self.teams {
+            user.teams = teams;
+        }
+    }
 }
 
 impl TeamQuery {
@@ -205,6 +252,32 @@ impl TeamQuery {
         self.offset = Some(offset);
         self
     }
+    
+    /// Check whether a team satisfies every filter in the query
+    pub fn matches(&self, team: &Team) -> bool {
+        if let Some(member_id) = &self.member_id {
+            if !team.members.contains(member_id) {
+                return false;
+            }
+        }
+        
+        if self.min_members.is_some_and(|min| team.members.len() < min) {
+            return false;
+        }
+        
+        if self.max_members.is_some_and(|max| team.members.len() > max) {
+            return false;
+        }
+        
+        true
+    }
+    
+    /// Filter, order (by ID) and paginate a set of teams
+    pub fn apply(&self, mut teams: Vec<Team>) -> Vec<Team> {
+        teams.retain(|team| self.matches(team));
+        teams.sort_by(|a, b| a.id.cmp(&b.id));
+        crate::paginate(teams, self.offset, self.limit)
+    }
 }
 
 impl TeamUpdate {
@@ -234,4 +307,19 @@ impl TeamUpdate {
         self.members = Some(members);
         self
     }
-}
\ No newline at end of file
+    
+    /// Apply the update to a team, bumping its `updated_at` timestamp
+    pub fn apply_to(self, team: &mut Team) {
+        if let Some(name) = self.name {
+            team.name = name;
+        }
+        if let Some(description) = self.description {
+            team.description = description;
+        }
+        if let Some(members) = self.members {
+            team.members = members;
+        }
+        
+        team.updated_at = chrono::Utc::now();
+    }
+}
@@ -0,0 +1,246 @@
+//! Bundles for offline transfer
+//!
+//! A bundle is a single file holding objects and the refs pointing at them, for
+//! moving issues between workspaces with no network path between them. It
+//! starts with `ODIB` and a format version, followed by a JSON manifest
+//! describing what the bundle holds, the objects as pack bytes (see
+//! [`crate::pack`]), and a SHA-256 checksum of everything before it.
+//!
+//! Reading a bundle verifies the checksum, every object hash and every ref, so
+//! a bundle that reads at all is complete. A bundle file can also be used as a
+//! read-only remote with a `bundle://` URL.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::pack::{self, PackObject};
+use crate::storage::{ObjectType, StoredObject};
+use crate::{FsError, Result};
+
+/// URL scheme of bundle remotes, e.g. `bundle:///media/usb/site-a.odib`
+pub const URL_SCHEME: &str = "bundle://";
+
+const MAGIC: &[u8; 4] = b"ODIB";
+const BUNDLE_VERSION: u32 = 1;
+const CHECKSUM_LEN: usize = 32;
+
+/// A ref recorded in a bundle
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleRef {
+    pub name: String,
+    pub hash: String,
+    pub object_type: ObjectType,
+}
+
+/// Self-description at the start of every bundle
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleManifest {
+    pub created_at: DateTime<Utc>,
+    /// Only objects changed since this time were included
+    pub since: Option<DateTime<Utc>>,
+    /// Only this project's issues and labels were included
+    pub project: Option<String>,
+    pub refs: Vec<BundleRef>,
+}
+
+/// Collects objects and writes them as a bundle
+#[derive(Debug, Default)]
+pub struct BundleWriter {
+    since: Option<DateTime<Utc>>,
+    project: Option<String>,
+    refs: Vec<BundleRef>,
+    objects: Vec<PackObject>,
+}
+
+impl BundleWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the `--since` cut-off in the manifest
+    pub fn with_since(mut self, since: Option<DateTime<Utc>>) -> Self {
+        self.since = since;
+        self
+    }
+
+    /// Record the project filter in the manifest
+    pub fn with_project(mut self, project: Option<String>) -> Self {
+        self.project = project;
+        self
+    }
+
+    /// Add an object under a ref name such as `issues/<id>`
+    pub fn add<T: StoredObject>(&mut self, ref_name: &str, value: &T) -> Result<()> {
+        let object = PackObject::new(T::OBJECT_TYPE, odi_core::format::seal(value)?);
+        self.refs.push(BundleRef {
+            name: ref_name.to_string(),
+            hash: object.hash.clone(),
+            object_type: T::OBJECT_TYPE,
+        });
+        self.objects.push(object);
+        Ok(())
+    }
+
+    /// Number of refs added so far
+    pub fn len(&self) -> usize {
+        self.refs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.refs.is_empty()
+    }
+
+    /// The objects added so far, with their ref names
+    #[cfg(feature = "git-integration")]
+    pub(crate) fn into_objects(self) -> Vec<(String, PackObject)> {
+        self.refs.into_iter().map(|r| r.name).zip(self.objects).collect()
+    }
+
+    /// Encode the bundle
+    pub fn finish(self) -> Result<Vec<u8>> {
+        let manifest = BundleManifest {
+            created_at: Utc::now(),
+            since: self.since,
+            project: self.project,
+            refs: self.refs,
+        };
+        let manifest = serde_json::to_vec(&manifest)?;
+        let (objects, _) = pack::encode(self.objects)?;
+
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend_from_slice(&BUNDLE_VERSION.to_le_bytes());
+        bytes.extend_from_slice(&(manifest.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&manifest);
+        bytes.extend_from_slice(&objects);
+        let checksum = Sha256::digest(&bytes);
+        bytes.extend_from_slice(&checksum);
+        Ok(bytes)
+    }
+}
+
+/// A verified bundle
+#[derive(Debug)]
+pub struct Bundle {
+    pub manifest: BundleManifest,
+    objects: HashMap<String, PackObject>,
+}
+
+fn corrupt(message: impl Into<String>) -> FsError {
+    FsError::StorageError { message: format!("Corrupt bundle: {}", message.into()) }
+}
+
+impl Bundle {
+    /// Decode and verify bundle bytes
+    pub fn read(bytes: &[u8]) -> Result<Self> {
+        let header = MAGIC.len() + 4;
+        if bytes.len() < header + 4 + CHECKSUM_LEN || &bytes[..MAGIC.len()] != MAGIC {
+            return Err(FsError::StorageError { message: "Not an odi bundle".to_string() });
+        }
+        let version = u32::from_le_bytes(bytes[MAGIC.len()..header].try_into().unwrap_or_default());
+        if version > BUNDLE_VERSION {
+            return Err(FsError::StorageError {
+                message: format!("Bundle version {} is newer than this version of odi supports ({})", version, BUNDLE_VERSION),
+            });
+        }
+
+        let (body, checksum) = bytes.split_at(bytes.len() - CHECKSUM_LEN);
+        if Sha256::digest(body).as_slice() != checksum {
+            return Err(corrupt("checksum mismatch"));
+        }
+
+        let length = u32::from_le_bytes(body[header..header + 4].try_into().unwrap_or_default()) as usize;
+        let manifest = body.get(header + 4..header + 4 + length).ok_or_else(|| corrupt("truncated manifest"))?;
+        let manifest: BundleManifest = serde_json::from_slice(manifest)?;
+
+        // Pack decoding verifies every object against its hash
+        let objects: HashMap<String, PackObject> = pack::decode(&body[header + 4 + length..])?
+            .into_iter()
+            .map(|object| (object.hash.clone(), object))
+            .collect();
+        if let Some(dangling) = manifest.refs.iter().find(|r| !objects.contains_key(&r.hash)) {
+            return Err(corrupt(format!("ref {} points at missing object {}", dangling.name, dangling.hash)));
+        }
+
+        Ok(Self { manifest, objects })
+    }
+
+    /// A bundle of objects read from somewhere other than a bundle file, such
+    /// as a Git tree
+    #[cfg(feature = "git-integration")]
+    pub(crate) fn from_objects(objects: Vec<(String, PackObject)>) -> Self {
+        let refs = objects.iter().map(|(name, object)| BundleRef {
+            name: name.clone(),
+            hash: object.hash.clone(),
+            object_type: object.object_type,
+        }).collect();
+        let manifest = BundleManifest {
+            created_at: Utc::now(),
+            since: None,
+            project: None,
+            refs,
+        };
+        let objects = objects.into_iter().map(|(_, object)| (object.hash.clone(), object)).collect();
+        Self { manifest, objects }
+    }
+
+    /// Read and verify a bundle file
+    pub fn load(path: &Path) -> Result<Self> {
+        Self::read(&fs::read(path)?)
+    }
+
+    /// Objects of every ref under `prefix`, with their ref names
+    pub fn read_all<T: StoredObject>(&self, prefix: &str) -> Result<Vec<(String, T)>> {
+        self.manifest.refs.iter()
+            .filter(|r| r.name.starts_with(prefix) && r.object_type == T::OBJECT_TYPE)
+            .map(|r| Ok((r.name.clone(), odi_core::format::open(&self.objects[&r.hash].data)?)))
+            .collect()
+    }
+}
+
+/// Bundle file named by a `bundle://` remote URL
+pub fn path_from_url(url: &str) -> Option<PathBuf> {
+    url.strip_prefix(URL_SCHEME).filter(|path| !path.is_empty()).map(PathBuf::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use odi_core::{Issue, Label};
+
+    #[test]
+    fn test_bundle_round_trip() {
+        let issue = Issue::new("Offline".to_string(), "alice".to_string());
+        let label = Label::new("bug".to_string(), "Bug".to_string(), "#ff0000".to_string());
+
+        let mut writer = BundleWriter::new().with_project(Some("core".to_string()));
+        writer.add(&format!("issues/{}", issue.id), &issue).unwrap();
+        writer.add("labels/core/bug", &label).unwrap();
+        let bytes = writer.finish().unwrap();
+
+        let bundle = Bundle::read(&bytes).unwrap();
+        assert_eq!(bundle.manifest.project.as_deref(), Some("core"));
+        assert_eq!(bundle.manifest.refs.len(), 2);
+        let issues: Vec<(String, Issue)> = bundle.read_all("issues/").unwrap();
+        assert_eq!(issues[0].1.title, "Offline");
+        let labels: Vec<(String, Label)> = bundle.read_all("labels/").unwrap();
+        assert_eq!(labels[0].0, "labels/core/bug");
+
+        // Any damage is caught by the checksum
+        let mut damaged = bytes.clone();
+        let middle = damaged.len() / 2;
+        damaged[middle] ^= 0xff;
+        assert!(Bundle::read(&damaged).is_err());
+    }
+
+    #[test]
+    fn test_path_from_url() {
+        assert_eq!(path_from_url("bundle:///media/usb/a.odib"), Some(PathBuf::from("/media/usb/a.odib")));
+        assert_eq!(path_from_url("bundle://"), None);
+        assert_eq!(path_from_url("https://example.com"), None);
+    }
+}
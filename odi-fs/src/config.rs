@@ -1,5 +1,6 @@
+use odi_core::{IssueStatus, LinkRole, View};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
 use std::path::{Path, PathBuf};
 use thiserror::Error;
@@ -23,6 +24,10 @@ pub struct Config {
     pub user: UserConfig,
     pub project: ProjectConfig,
     pub remotes: HashMap<String, RemoteConfig>,
+    #[serde(default)]
+    pub storage: StorageConfig,
+    #[serde(default)]
+    pub git: GitConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -47,6 +52,98 @@ pub struct RemoteConfig {
     pub last_sync: Option<chrono::DateTime<chrono::Utc>>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct StorageConfig {
+    /// Seconds to wait for a storage lock held by another process
+    #[serde(default = "default_lock_timeout")]
+    pub lock_timeout: u64,
+    /// Backend holding issues, projects, users and remotes
+    #[serde(default)]
+    pub backend: StorageBackend,
+}
+
+/// Storage backend for workspace data
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageBackend {
+    /// Content-addressed object store under `.odi/objects`
+    #[default]
+    Fs,
+    /// Embedded SQLite database at `.odi/odi.db` (requires the `sqlite` feature)
+    Sqlite,
+}
+
+impl StorageBackend {
+    /// Name used in configuration and on the command line
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StorageBackend::Fs => "fs",
+            StorageBackend::Sqlite => "sqlite",
+        }
+    }
+}
+
+impl std::fmt::Display for StorageBackend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for StorageBackend {
+    type Err = crate::FsError;
+    
+    fn from_str(s: &str) -> crate::Result<Self> {
+        match s {
+            "fs" => Ok(StorageBackend::Fs),
+            "sqlite" => Ok(StorageBackend::Sqlite),
+            other => Err(crate::FsError::ConfigError {
+                message: format!("Unknown storage backend '{}' (expected 'fs' or 'sqlite')", other),
+            }),
+        }
+    }
+}
+
+fn default_lock_timeout() -> u64 {
+    10
+}
+
+impl Default for StorageConfig {
+    fn default() -> Self {
+        StorageConfig {
+            lock_timeout: default_lock_timeout(),
+            backend: StorageBackend::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct GitConfig {
+    /// Status an issue moves to when a commit on the default branch links it
+    /// with a role, e.g. `fixes = "Resolved"`
+    #[serde(default = "default_transitions")]
+    pub transitions: BTreeMap<LinkRole, IssueStatus>,
+    /// Name of the branch `odi issue start` creates, from `{key}`, `{id}` and `{slug}`
+    #[serde(default = "default_branch_template")]
+    pub branch_template: String,
+}
+
+fn default_branch_template() -> String {
+    "{key}-{slug}".to_string()
+}
+
+fn default_transitions() -> BTreeMap<LinkRole, IssueStatus> {
+    BTreeMap::from([(LinkRole::Fixes, IssueStatus::Resolved)])
+}
+
+impl Default for GitConfig {
+    fn default() -> Self {
+        GitConfig {
+            transitions: default_transitions(),
+            branch_template: default_branch_template(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct WorkspaceConfig {
     pub name: String,
@@ -67,6 +164,8 @@ impl Default for Config {
                 default_branch: Some("main".to_string()),
             },
             remotes: HashMap::new(),
+            storage: StorageConfig::default(),
+            git: GitConfig::default(),
         }
     }
 }
@@ -121,6 +220,8 @@ impl ConfigLoader for FileConfigLoader {
                 // Local config takes precedence
                 g.user = l.user;
                 g.project = l.project;
+                g.storage = l.storage;
+                g.git = l.git;
                 // Merge remotes (local takes precedence)
                 for (name, remote) in l.remotes {
                     g.remotes.insert(name, remote);
@@ -169,11 +270,11 @@ impl ConfigLoader for FileConfigLoader {
                 });
             }
             
-            // Validate URL format supports ssh:// or https:// or SSH shorthand
+            // Validate URL format supports ssh://, https://, SSH shorthand, a bundle file or a Git repository
             let url = &remote.url;
-            if !(url.starts_with("ssh://") || url.starts_with("https://") || url.contains('@')) {
+            if !(url.starts_with("ssh://") || url.starts_with("https://") || url.contains('@') || url.starts_with(crate::bundle::URL_SCHEME) || crate::git_store::repository_from_url(url).is_some()) {
                 return Err(crate::FsError::ConfigError {
-                    message: format!("Remote '{}' URL must be SSH (ssh://user@host/path or user@host:path) or HTTPS (https://host/path)", name),
+                    message: format!("Remote '{}' URL must be SSH (ssh://user@host/path or user@host:path), HTTPS (https://host/path), a bundle (bundle:///path/to/file) or a Git repository (git+<remote or URL>)", name),
                 });
             }
         }
@@ -208,4 +309,73 @@ pub fn save_config(config: &Config) -> crate::Result<()> {
     
     fs::write(config_path, toml_string).map_err(|e| crate::FsError::Io(e))?;
     Ok(())
-}
\ No newline at end of file
+}
+/// Personal settings file in the user's home directory
+pub fn user_config_path() -> PathBuf {
+    let home_dir = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
+    PathBuf::from(home_dir).join(".odiconfig")
+}
+
+/// Views saved for one user, from the `[[views]]` tables of their settings file
+pub fn load_user_views(path: &Path) -> crate::Result<Vec<View>> {
+    let settings = read_settings(path)?;
+    match settings.get("views") {
+        Some(views) => views.clone().try_into().map_err(|e| crate::FsError::ConfigError {
+            message: format!("Invalid views in {}: {}", path.display(), e),
+        }),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// Replace the views saved for one user, keeping their other settings
+pub fn save_user_views(path: &Path, views: &[View]) -> crate::Result<()> {
+    let mut settings = read_settings(path)?;
+    if views.is_empty() {
+        settings.remove("views");
+    } else {
+        let views = toml::Value::try_from(views).map_err(|e| crate::FsError::ConfigError {
+            message: format!("Failed to serialize views: {}", e),
+        })?;
+        settings.insert("views".to_string(), views);
+    }
+    let toml_string = toml::to_string_pretty(&settings).map_err(|e| crate::FsError::ConfigError {
+        message: format!("Failed to serialize config: {}", e),
+    })?;
+    fs::write(path, toml_string).map_err(crate::FsError::Io)
+}
+
+/// A settings file as plain TOML, empty if it doesn't exist
+fn read_settings(path: &Path) -> crate::Result<toml::Table> {
+    match fs::read_to_string(path) {
+        Ok(content) => content.parse().map_err(crate::FsError::TomlError),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(toml::Table::new()),
+        Err(e) => Err(crate::FsError::Io(e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use odi_core::Column;
+
+    #[test]
+    fn test_user_views_keep_other_settings() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(".odiconfig");
+        assert!(load_user_views(&path).unwrap().is_empty());
+
+        fs::write(&path, "[user]\nname = \"alice\"\n").unwrap();
+        let view = View::new("triage".to_string(), "status:open -label:triaged".to_string())
+            .with_sort(vec!["-priority".parse().unwrap()])
+            .with_columns(vec![Column::Id, Column::Title]);
+        save_user_views(&path, std::slice::from_ref(&view)).unwrap();
+
+        assert_eq!(load_user_views(&path).unwrap(), vec![view]);
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(content.contains("name = \"alice\""));
+
+        save_user_views(&path, &[]).unwrap();
+        assert!(load_user_views(&path).unwrap().is_empty());
+        assert!(fs::read_to_string(&path).unwrap().contains("[user]"));
+    }
+}
@@ -0,0 +1,364 @@
+//! Encryption at rest for the object store
+//!
+//! Encrypted workspaces have a keyring in `.odi/keyring`. Objects are encrypted
+//! with XChaCha20-Poly1305 under a random data key; the data keys are stored in
+//! the keyring wrapped by a key-encryption key derived from a passphrase
+//! (Argon2id) or a key file. Changing the passphrase only rewraps the data
+//! keys, while rotation adds a new data key and re-encrypts every object.
+//!
+//! An encrypted object file is `ODIE`, the data key ID, a random nonce and the
+//! ciphertext. Encrypted objects are addressed by the SHA-256 of that file, so
+//! object names reveal nothing about their contents and integrity can be
+//! checked without the key.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{FsError, Result};
+
+/// Keyring file inside the `.odi` directory
+pub const KEYRING_FILE: &str = "keyring";
+
+/// Environment variable holding the workspace passphrase
+pub const PASSPHRASE_ENV: &str = "ODI_PASSPHRASE";
+
+/// Environment variable holding the path of the workspace key file
+pub const KEY_FILE_ENV: &str = "ODI_KEY_FILE";
+
+const MAGIC: &[u8; 4] = b"ODIE";
+const KEYRING_VERSION: u32 = 1;
+const NONCE_LEN: usize = 24;
+const HEADER_LEN: usize = MAGIC.len() + 4 + NONCE_LEN;
+
+/// Where the key-encryption key comes from
+#[derive(Debug, Clone)]
+pub enum KeySource {
+    Passphrase(String),
+    KeyFile(PathBuf),
+}
+
+impl KeySource {
+    /// Key source from `ODI_KEY_FILE` or `ODI_PASSPHRASE`, key file first
+    pub fn from_env() -> Option<Self> {
+        if let Ok(path) = std::env::var(KEY_FILE_ENV) {
+            return Some(Self::KeyFile(PathBuf::from(path)));
+        }
+        std::env::var(PASSPHRASE_ENV).ok().map(Self::Passphrase)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+enum Kdf {
+    Argon2id { salt: String, m_cost: u32, t_cost: u32, p_cost: u32 },
+    KeyFile { salt: String },
+}
+
+#[derive(Serialize, Deserialize)]
+struct WrappedKey {
+    id: u32,
+    nonce: String,
+    key: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Keyring {
+    version: u32,
+    kdf: Kdf,
+    /// Data key new objects are encrypted with
+    current: u32,
+    keys: Vec<WrappedKey>,
+}
+
+/// Unlocked data keys of an encrypted workspace
+#[derive(Clone)]
+pub struct Cipher {
+    keys: HashMap<u32, Key>,
+    current: u32,
+}
+
+impl std::fmt::Debug for Cipher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Never print key material
+        f.debug_struct("Cipher").field("current", &self.current).finish_non_exhaustive()
+    }
+}
+
+fn crypto_error(message: impl Into<String>) -> FsError {
+    FsError::StorageError { message: message.into() }
+}
+
+fn decode_hex(value: &str) -> Result<Vec<u8>> {
+    hex::decode(value).map_err(|e| crypto_error(format!("Invalid keyring: {}", e)))
+}
+
+fn random_bytes<const N: usize>() -> [u8; N] {
+    let mut bytes = [0u8; N];
+    OsRng.fill_bytes(&mut bytes);
+    bytes
+}
+
+impl Kdf {
+    fn new_for(source: &KeySource) -> Self {
+        let salt = hex::encode(random_bytes::<16>());
+        match source {
+            KeySource::Passphrase(_) => Kdf::Argon2id {
+                salt,
+                m_cost: Params::DEFAULT_M_COST,
+                t_cost: Params::DEFAULT_T_COST,
+                p_cost: Params::DEFAULT_P_COST,
+            },
+            KeySource::KeyFile(_) => Kdf::KeyFile { salt },
+        }
+    }
+
+    /// Derive the key-encryption key
+    fn derive(&self, source: &KeySource) -> Result<Key> {
+        match (self, source) {
+            (Kdf::Argon2id { salt, m_cost, t_cost, p_cost }, KeySource::Passphrase(passphrase)) => {
+                let params = Params::new(*m_cost, *t_cost, *p_cost, Some(32))
+                    .map_err(|e| crypto_error(format!("Invalid key derivation parameters: {}", e)))?;
+                let mut key = Key::default();
+                Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+                    .hash_password_into(passphrase.as_bytes(), &decode_hex(salt)?, &mut key)
+                    .map_err(|e| crypto_error(format!("Key derivation failed: {}", e)))?;
+                Ok(key)
+            }
+            (Kdf::KeyFile { salt }, KeySource::KeyFile(path)) => {
+                let contents = fs::read(path)?;
+                if contents.len() < 32 {
+                    return Err(crypto_error(format!("Key file {} is shorter than 32 bytes", path.display())));
+                }
+                let mut hasher = Sha256::new();
+                hasher.update(decode_hex(salt)?);
+                hasher.update(&contents);
+                Ok(hasher.finalize())
+            }
+            (Kdf::Argon2id { .. }, KeySource::KeyFile(_)) => Err(FsError::InvalidKey {
+                message: "workspace is unlocked with a passphrase, not a key file".to_string(),
+            }),
+            (Kdf::KeyFile { .. }, KeySource::Passphrase(_)) => Err(FsError::InvalidKey {
+                message: "workspace is unlocked with a key file, not a passphrase".to_string(),
+            }),
+        }
+    }
+}
+
+impl Keyring {
+    fn load(odi_dir: &Path) -> Result<Self> {
+        let keyring: Keyring = serde_json::from_slice(&fs::read(odi_dir.join(KEYRING_FILE))?)?;
+        if keyring.version > KEYRING_VERSION {
+            return Err(crypto_error(format!(
+                "Keyring version {} is newer than this version of odi supports ({})",
+                keyring.version, KEYRING_VERSION
+            )));
+        }
+        Ok(keyring)
+    }
+
+    fn save(&self, odi_dir: &Path) -> Result<()> {
+        let tmp_file = odi_dir.join(format!("{}.tmp", KEYRING_FILE));
+        fs::write(&tmp_file, serde_json::to_vec_pretty(self)?)?;
+        fs::rename(&tmp_file, odi_dir.join(KEYRING_FILE))?;
+        Ok(())
+    }
+
+    fn wrap(kek: &Key, keys: &HashMap<u32, Key>) -> Result<Vec<WrappedKey>> {
+        let aead = XChaCha20Poly1305::new(kek);
+        let mut ids: Vec<_> = keys.keys().copied().collect();
+        ids.sort();
+
+        ids.into_iter()
+            .map(|id| {
+                let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+                let wrapped = aead
+                    .encrypt(&nonce, Payload { msg: &keys[&id], aad: &id.to_le_bytes() })
+                    .map_err(|_| crypto_error("Failed to wrap data key"))?;
+                Ok(WrappedKey { id, nonce: hex::encode(nonce), key: hex::encode(wrapped) })
+            })
+            .collect()
+    }
+
+    fn unwrap(&self, kek: &Key) -> Result<Cipher> {
+        let aead = XChaCha20Poly1305::new(kek);
+        let mut keys = HashMap::new();
+
+        for wrapped in &self.keys {
+            let nonce = decode_hex(&wrapped.nonce)?;
+            if nonce.len() != NONCE_LEN {
+                return Err(crypto_error("Invalid keyring: bad nonce length"));
+            }
+            let key = aead
+                .decrypt(XNonce::from_slice(&nonce), Payload { msg: &decode_hex(&wrapped.key)?, aad: &wrapped.id.to_le_bytes() })
+                .map_err(|_| FsError::InvalidKey { message: "incorrect passphrase or key file".to_string() })?;
+            if key.len() != 32 {
+                return Err(crypto_error("Invalid keyring: bad key length"));
+            }
+            keys.insert(wrapped.id, *Key::from_slice(&key));
+        }
+
+        if !keys.contains_key(&self.current) {
+            return Err(crypto_error("Invalid keyring: current key is missing"));
+        }
+        Ok(Cipher { keys, current: self.current })
+    }
+}
+
+/// Whether a `.odi` directory belongs to an encrypted workspace
+pub fn is_encrypted(odi_dir: &Path) -> bool {
+    odi_dir.join(KEYRING_FILE).exists()
+}
+
+/// Whether object file bytes are encrypted
+pub fn is_sealed(bytes: &[u8]) -> bool {
+    bytes.starts_with(MAGIC)
+}
+
+/// Set up encryption for a new workspace
+pub fn create(odi_dir: &Path, source: &KeySource) -> Result<Cipher> {
+    if is_encrypted(odi_dir) {
+        return Err(crypto_error("Workspace is already encrypted"));
+    }
+
+    let kdf = Kdf::new_for(source);
+    let kek = kdf.derive(source)?;
+    let cipher = Cipher {
+        keys: HashMap::from([(1, XChaCha20Poly1305::generate_key(&mut OsRng))]),
+        current: 1,
+    };
+
+    let keyring = Keyring {
+        version: KEYRING_VERSION,
+        kdf,
+        current: cipher.current,
+        keys: Keyring::wrap(&kek, &cipher.keys)?,
+    };
+    keyring.save(odi_dir)?;
+    Ok(cipher)
+}
+
+/// Unlock the data keys of an encrypted workspace
+pub fn unlock(odi_dir: &Path, source: &KeySource) -> Result<Cipher> {
+    let keyring = Keyring::load(odi_dir)?;
+    let kek = keyring.kdf.derive(source)?;
+    keyring.unwrap(&kek)
+}
+
+/// Add a fresh data key and make it current
+///
+/// All keys are rewrapped under `new_source` when given, otherwise under `source`.
+pub(crate) fn add_key(odi_dir: &Path, source: &KeySource, new_source: Option<&KeySource>) -> Result<Cipher> {
+    let keyring = Keyring::load(odi_dir)?;
+    let mut cipher = keyring.unwrap(&keyring.kdf.derive(source)?)?;
+
+    let id = cipher.keys.keys().max().copied().unwrap_or(0) + 1;
+    cipher.keys.insert(id, XChaCha20Poly1305::generate_key(&mut OsRng));
+    cipher.current = id;
+
+    let (kdf, kek) = match new_source {
+        Some(new_source) => {
+            let kdf = Kdf::new_for(new_source);
+            let kek = kdf.derive(new_source)?;
+            (kdf, kek)
+        }
+        None => {
+            let kek = keyring.kdf.derive(source)?;
+            (keyring.kdf, kek)
+        }
+    };
+
+    Keyring { version: KEYRING_VERSION, kdf, current: id, keys: Keyring::wrap(&kek, &cipher.keys)? }.save(odi_dir)?;
+    Ok(cipher)
+}
+
+/// Drop every data key except the current one from the keyring
+pub(crate) fn retire_old_keys(odi_dir: &Path) -> Result<()> {
+    let mut keyring = Keyring::load(odi_dir)?;
+    let current = keyring.current;
+    keyring.keys.retain(|key| key.id == current);
+    keyring.save(odi_dir)
+}
+
+impl Cipher {
+    /// Encrypt object file bytes under the current data key
+    pub fn encrypt(&self, plaintext: &[u8]) -> Result<Vec<u8>> {
+        let mut sealed = MAGIC.to_vec();
+        sealed.extend_from_slice(&self.current.to_le_bytes());
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        sealed.extend_from_slice(&nonce);
+
+        let ciphertext = XChaCha20Poly1305::new(&self.keys[&self.current])
+            .encrypt(&nonce, Payload { msg: plaintext, aad: &sealed[..MAGIC.len() + 4] })
+            .map_err(|_| crypto_error("Failed to encrypt object"))?;
+        sealed.extend_from_slice(&ciphertext);
+        Ok(sealed)
+    }
+
+    /// Decrypt object file bytes written by [`Cipher::encrypt`]
+    pub fn decrypt(&self, sealed: &[u8]) -> Result<Vec<u8>> {
+        if sealed.len() < HEADER_LEN || !is_sealed(sealed) {
+            return Err(crypto_error("Object is not encrypted"));
+        }
+
+        let key_id = u32::from_le_bytes(sealed[MAGIC.len()..MAGIC.len() + 4].try_into().unwrap_or_default());
+        let key = self.keys.get(&key_id)
+            .ok_or_else(|| crypto_error(format!("Object is encrypted with unknown key {}", key_id)))?;
+        let nonce = XNonce::from_slice(&sealed[MAGIC.len() + 4..HEADER_LEN]);
+
+        XChaCha20Poly1305::new(key)
+            .decrypt(nonce, Payload { msg: &sealed[HEADER_LEN..], aad: &sealed[..MAGIC.len() + 4] })
+            .map_err(|_| crypto_error("Encrypted object failed authentication"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_passphrase_round_trip() {
+        let dir = TempDir::new().unwrap();
+        let source = KeySource::Passphrase("correct horse".to_string());
+        let cipher = create(dir.path(), &source).unwrap();
+
+        let sealed = cipher.encrypt(b"customer data").unwrap();
+        assert!(is_sealed(&sealed));
+        assert!(!sealed.windows(8).any(|w| w == b"customer"));
+
+        let unlocked = unlock(dir.path(), &source).unwrap();
+        assert_eq!(unlocked.decrypt(&sealed).unwrap(), b"customer data");
+
+        let wrong = KeySource::Passphrase("wrong".to_string());
+        assert!(matches!(unlock(dir.path(), &wrong), Err(FsError::InvalidKey { .. })));
+    }
+
+    #[test]
+    fn test_rotation_keeps_old_keys_until_retired() {
+        let dir = TempDir::new().unwrap();
+        let key_file = dir.path().join("key");
+        fs::write(&key_file, [7u8; 32]).unwrap();
+        let source = KeySource::KeyFile(key_file);
+
+        let old = create(dir.path(), &source).unwrap();
+        let sealed = old.encrypt(b"before rotation").unwrap();
+
+        let new_source = KeySource::Passphrase("rotated".to_string());
+        let rotated = add_key(dir.path(), &source, Some(&new_source)).unwrap();
+        assert_eq!(rotated.decrypt(&sealed).unwrap(), b"before rotation");
+        assert!(unlock(dir.path(), &source).is_err());
+
+        retire_old_keys(dir.path()).unwrap();
+        let unlocked = unlock(dir.path(), &new_source).unwrap();
+        assert!(unlocked.decrypt(&sealed).is_err());
+        assert!(unlocked.decrypt(&rotated.encrypt(b"after").unwrap()).is_ok());
+    }
+}
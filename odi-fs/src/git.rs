@@ -1,157 +1,813 @@
 //! Git integration
+//!
+//! Reads the Git repository an ODI workspace lives in through libgit2 (the
+//! `git-integration` feature), so loose and packed refs, packfiles, detached
+//! HEADs and linked worktrees are all handled the way Git itself does.
+//! Links between issues and commits or branches are recorded on the issues in
+//! the ODI store (see [`odi_core::GitRef`]), not in the Git repository.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
-use crate::Result;
-use odi_core::IssueId;
+use crate::storage::{FileSystemStorage, ObjectStorage, ObjectType};
+use crate::{FsError, Result};
+use odi_core::{GitRef, Issue, IssueId};
 
 /// Git repository information
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GitRepository {
+    /// Root of the working tree
     pub path: PathBuf,
+    /// Git directory of this working tree; a linked worktree has its own
+    pub git_dir: PathBuf,
+    /// Remote names and their fetch URLs
     pub remotes: HashMap<String, String>,
     pub current_branch: Option<String>,
+    pub head: GitHead,
+    /// Whether this is a linked worktree (`git worktree add`)
+    pub is_worktree: bool,
+    /// Where Git looks for hooks: `core.hooksPath`, or else `hooks` in the
+    /// Git directory shared by all worktrees
+    pub hooks_dir: PathBuf,
 }
 
-/// Git reference
+impl GitRepository {
+    /// Identity recorded on links to this repository, from `origin` or else
+    /// the first remote by name; `None` for a repository without remotes
+    pub fn identity(&self) -> Option<String> {
+        let url = self.remotes.get("origin").or_else(|| {
+            let first = self.remotes.keys().min()?;
+            self.remotes.get(first)
+        })?;
+        Some(odi_core::repository_identity(url))
+    }
+}
+
+/// What HEAD points at
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GitHead {
+    /// On a branch with at least one commit
+    Branch { name: String, sha: String },
+    /// Checked out at a commit rather than a branch
+    Detached { sha: String },
+    /// On a branch with no commits yet
+    Unborn { name: String },
+}
+
+/// A commit read from the repository
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct GitRef {
+pub struct GitCommit {
     pub sha: String,
     pub message: Option<String>,
     pub author: Option<String>,
     pub timestamp: DateTime<Utc>,
 }
 
+impl GitCommit {
+    /// First line of the commit message
+    pub fn summary(&self) -> &str {
+        self.message.as_deref().and_then(|message| message.lines().next()).unwrap_or("")
+    }
+
+    /// Abbreviated SHA for display
+    pub fn short_sha(&self) -> &str {
+        &self.sha[..self.sha.len().min(7)]
+    }
+}
+
+/// File in the `.odi` directory listing commits `odi git scan` is done with
+pub const SCANNED_FILE: &str = "git-scanned";
+
+/// Commits already scanned on the default branch, one SHA per line
+pub fn read_scanned(odi_dir: &Path) -> Result<HashSet<String>> {
+    match std::fs::read_to_string(odi_dir.join(SCANNED_FILE)) {
+        Ok(content) => Ok(content.lines().filter(|line| !line.is_empty()).map(str::to_string).collect()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(HashSet::new()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Add commits to the scanned list
+pub fn record_scanned(odi_dir: &Path, shas: &[String]) -> Result<()> {
+    use std::io::Write;
+
+    if shas.is_empty() {
+        return Ok(());
+    }
+    let mut file = std::fs::OpenOptions::new().create(true).append(true).open(odi_dir.join(SCANNED_FILE))?;
+    for sha in shas {
+        writeln!(file, "{}", sha)?;
+    }
+    Ok(())
+}
+
+/// File in the `.odi` directory caching the paths each commit changed
+pub const PATHS_FILE: &str = "git-paths";
+
+/// One line of [`PATHS_FILE`]
+#[derive(Serialize, Deserialize)]
+struct CommitPaths {
+    sha: String,
+    paths: Vec<String>,
+}
+
+/// Which issues' linked commits touched each path
+///
+/// Commits never change, so the paths of each are read from Git once and
+/// cached in [`PATHS_FILE`].
+#[derive(Debug, Default)]
+pub struct PathIndex {
+    by_path: HashMap<String, Vec<(IssueId, String)>>,
+}
+
+impl PathIndex {
+    /// Index the commits linked to `issues`; commits not in the repository are skipped
+    pub fn build<G: GitIntegration>(git: &G, odi_dir: &Path, issues: &[Issue]) -> Result<Self> {
+        use std::io::Write;
+
+        let cache_path = odi_dir.join(PATHS_FILE);
+        let mut cached: HashMap<String, Vec<String>> = HashMap::new();
+        match std::fs::read_to_string(&cache_path) {
+            Ok(content) => {
+                // A torn last line from an interrupted run is simply read again from Git
+                for entry in content.lines().filter_map(|line| serde_json::from_str::<CommitPaths>(line).ok()) {
+                    cached.insert(entry.sha, entry.paths);
+                }
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e.into()),
+        }
+
+        let mut index = Self::default();
+        let mut added = Vec::new();
+        for issue in issues {
+            for link in &issue.git_refs {
+                let odi_core::GitTarget::Commit { sha } = &link.target else {
+                    continue;
+                };
+                // Links may hold abbreviated SHAs; cache by the full one
+                let full = match cached.keys().find(|full| full.starts_with(sha.as_str())) {
+                    Some(full) => full.clone(),
+                    None => match git.find_commit(sha) {
+                        Ok(Some(commit)) => commit.sha,
+                        _ => continue,
+                    },
+                };
+                if !cached.contains_key(&full) {
+                    let paths = git.changed_paths(&full)?;
+                    added.push(CommitPaths { sha: full.clone(), paths: paths.clone() });
+                    cached.insert(full.clone(), paths);
+                }
+                for path in &cached[&full] {
+                    let entries = index.by_path.entry(path.clone()).or_default();
+                    if !entries.contains(&(issue.id, full.clone())) {
+                        entries.push((issue.id, full.clone()));
+                    }
+                }
+            }
+        }
+
+        if !added.is_empty() {
+            let mut file = std::fs::OpenOptions::new().create(true).append(true).open(&cache_path)?;
+            for entry in &added {
+                writeln!(file, "{}", serde_json::to_string(entry)?)?;
+            }
+        }
+        Ok(index)
+    }
+
+    /// Issues and the commits of theirs that touched a file, or anything under
+    /// a directory
+    pub fn lookup(&self, path: &str) -> Vec<(IssueId, String)> {
+        let path = path.trim_end_matches('/');
+        let directory = format!("{}/", path);
+        let mut found: Vec<(IssueId, String)> = Vec::new();
+        for (indexed, entries) in &self.by_path {
+            if indexed == path || indexed.starts_with(&directory) {
+                for entry in entries {
+                    if !found.contains(entry) {
+                        found.push(entry.clone());
+                    }
+                }
+            }
+        }
+        found
+    }
+}
+
+/// Whether text such as an issue description mentions a file, or anything
+/// under a directory
+///
+/// Paths are matched as whole words, so `see src/a` doesn't mention the file
+/// `a`. A word may start with `./`, end with a line number such as `:40`, or,
+/// for paths with a directory, start with more directories, such as
+/// `crates/app/src/main.rs` for `src/main.rs`.
+pub fn mentions_path(text: &str, path: &str) -> bool {
+    let path = path.trim_end_matches('/');
+    if path.is_empty() {
+        return false;
+    }
+    let directory = format!("{}/", path);
+    let nested = format!("/{}", path);
+    text.split(|c: char| c.is_whitespace() || "()[]<>\"'`,;".contains(c))
+        .map(|word| {
+            let word = word.trim_end_matches(['.', ':', '!', '?']);
+            let word = match word.rsplit_once(':') {
+                Some((file, lines)) if !lines.is_empty() && lines.chars().all(|c| c.is_ascii_digit() || c == '-') => file,
+                _ => word,
+            };
+            word.trim_start_matches("./")
+        })
+        .any(|word| word == path || word.starts_with(&directory) || (path.contains('/') && word.ends_with(&nested)))
+}
+
 /// Git integration trait
 pub trait GitIntegration {
     fn detect_repository(path: &Path) -> Result<Option<GitRepository>>;
     fn get_current_branch(&self) -> Result<Option<String>>;
     fn get_remote_url(&self, remote: &str) -> Result<Option<String>>;
-    fn list_commits(&self, branch: &str) -> Result<Vec<GitRef>>;
-    fn associate_issue(&self, issue_id: &IssueId, git_ref: &GitRef) -> Result<()>;
+    /// Commits reachable from a revision, newest first; `a..b` lists those in `b` but not `a`
+    fn list_commits(&self, revision: &str) -> Result<Vec<GitCommit>>;
+    /// Look up a single commit by SHA, abbreviated SHA or any other revision
+    fn find_commit(&self, revision: &str) -> Result<Option<GitCommit>>;
+    /// Whether a local or remote-tracking branch of this name exists
+    fn branch_exists(&self, name: &str) -> Result<bool>;
+    /// Create a local branch at HEAD
+    fn create_branch(&self, name: &str) -> Result<()>;
+    /// Switch the working tree to a local branch without overwriting local changes
+    fn checkout_branch(&self, name: &str) -> Result<()>;
+    /// Whether tracked files have staged or unstaged changes
+    fn has_uncommitted_changes(&self) -> Result<bool>;
+    /// Files in the working tree, tracked or not, leaving out those Git ignores
+    fn working_tree_files(&self) -> Result<Vec<String>>;
+    /// Paths a commit added, changed or deleted, compared with its first parent
+    fn changed_paths(&self, revision: &str) -> Result<Vec<String>>;
+    /// Commits that last changed lines `start..=end` (1-based) of a committed
+    /// file, or any of its lines if no range is given
+    fn blame(&self, path: &str, lines: Option<(usize, usize)>) -> Result<Vec<String>>;
+    /// Link an issue to a Git target; returns whether the issue changed
+    fn associate_issue(&self, issue_id: &IssueId, git_ref: &GitRef) -> Result<bool>;
 }
 
 /// Default Git integration implementation
 pub struct DefaultGitIntegration {
     repo_path: PathBuf,
+    storage: Option<FileSystemStorage>,
 }
 
 impl DefaultGitIntegration {
     pub fn new(repo_path: PathBuf) -> Self {
-        Self { repo_path }
+        Self { repo_path, storage: None }
+    }
+
+    /// Record issue links in this ODI store
+    pub fn with_storage(mut self, storage: FileSystemStorage) -> Self {
+        self.storage = Some(storage);
+        self
+    }
+
+    /// Add a link to an issue, once
+    fn record_association(storage: &FileSystemStorage, issue_id: &IssueId, git_ref: &GitRef) -> Result<bool> {
+        let _guard = storage.acquire_guard(crate::lock::ISSUES_LOCK)?;
+        let name = format!("issues/{}", issue_id);
+        let not_found = || FsError::GitError { message: format!("Issue {} not found", issue_id) };
+
+        let object_ref = storage.get_ref(&name)?.ok_or_else(not_found)?;
+        let object = storage.retrieve_object(&object_ref.hash)?.ok_or_else(not_found)?;
+        let mut issue: Issue = odi_core::format::open(&object.data)?;
+        if !issue.link(git_ref.clone()) {
+            return Ok(false);
+        }
+
+        let hash = storage.store_object(ObjectType::Issue, &odi_core::format::seal(&issue)?)?;
+        storage.create_ref(&name, &hash, ObjectType::Issue)?;
+        Ok(true)
     }
 }
 
+#[cfg(feature = "git-integration")]
+impl DefaultGitIntegration {
+    fn open(&self) -> Result<git2::Repository> {
+        git2::Repository::discover(&self.repo_path).map_err(git_error)
+    }
+
+    fn read_head(repo: &git2::Repository) -> Result<GitHead> {
+        match repo.head() {
+            Ok(head) => {
+                let sha = head.peel_to_commit().map_err(git_error)?.id().to_string();
+                match head.shorthand().filter(|_| head.is_branch()) {
+                    Some(name) => Ok(GitHead::Branch { name: name.to_string(), sha }),
+                    None => Ok(GitHead::Detached { sha }),
+                }
+            }
+            // HEAD names a branch that has no commits yet
+            Err(e) if e.code() == git2::ErrorCode::UnbornBranch => {
+                let head = repo.find_reference("HEAD").map_err(git_error)?;
+                let target = head.symbolic_target().unwrap_or_default();
+                let name = target.strip_prefix("refs/heads/").unwrap_or(target);
+                Ok(GitHead::Unborn { name: name.to_string() })
+            }
+            Err(e) => Err(git_error(e)),
+        }
+    }
+
+    /// Git directory shared by all worktrees; a linked worktree names it in
+    /// its `commondir` file
+    pub(crate) fn common_dir(repo: &git2::Repository) -> PathBuf {
+        match std::fs::read_to_string(repo.path().join("commondir")) {
+            Ok(common) => repo.path().join(common.trim()),
+            Err(_) => repo.path().to_path_buf(),
+        }
+    }
+
+    fn read_commit(commit: &git2::Commit) -> GitCommit {
+        let author = commit.author();
+        let author = match (author.name(), author.email()) {
+            (Some(name), Some(email)) => Some(format!("{} <{}>", name, email)),
+            (name, email) => name.or(email).map(str::to_string),
+        };
+        GitCommit {
+            sha: commit.id().to_string(),
+            message: commit.message().map(str::to_string),
+            author,
+            timestamp: DateTime::from_timestamp(commit.time().seconds(), 0).unwrap_or_default(),
+        }
+    }
+}
+
+#[cfg(feature = "git-integration")]
+fn git_error(e: git2::Error) -> FsError {
+    FsError::GitError { message: e.message().to_string() }
+}
+
+#[cfg(feature = "git-integration")]
 impl GitIntegration for DefaultGitIntegration {
     fn detect_repository(path: &Path) -> Result<Option<GitRepository>> {
-        let git_dir = path.join(".git");
-        
-        if git_dir.exists() {
-            let mut remotes = HashMap::new();
-            
-            // Try to read Git config for remotes
-            let config_path = git_dir.join("config");
-            if config_path.exists() {
-                // Simple parsing - in production, would use git2 crate
-                if let Ok(content) = std::fs::read_to_string(config_path) {
-                    // Very basic remote parsing
-                    if content.contains("origin") {
-                        remotes.insert("origin".to_string(), "unknown".to_string());
-                    }
-                }
+        let repo = match git2::Repository::discover(path) {
+            Ok(repo) => repo,
+            Err(e) if e.code() == git2::ErrorCode::NotFound => return Ok(None),
+            Err(e) => return Err(git_error(e)),
+        };
+
+        let mut remotes = HashMap::new();
+        for name in repo.remotes().map_err(git_error)?.iter().flatten() {
+            let remote = repo.find_remote(name).map_err(git_error)?;
+            if let Some(url) = remote.url() {
+                remotes.insert(name.to_string(), url.to_string());
             }
-            
-            let current_branch = Self::read_current_branch(&git_dir);
-            
-            Ok(Some(GitRepository {
-                path: path.to_path_buf(),
-                remotes,
-                current_branch,
-            }))
-        } else {
-            Ok(None)
         }
+
+        let workdir = repo.workdir().unwrap_or_else(|| repo.path()).to_path_buf();
+        let hooks_dir = match repo.config().and_then(|config| config.get_path("core.hooksPath")) {
+            Ok(path) => workdir.join(path),
+            Err(_) => Self::common_dir(&repo).join("hooks"),
+        };
+
+        let head = Self::read_head(&repo)?;
+        let current_branch = match &head {
+            GitHead::Branch { name, .. } | GitHead::Unborn { name } => Some(name.clone()),
+            GitHead::Detached { .. } => None,
+        };
+
+        Ok(Some(GitRepository {
+            path: workdir,
+            git_dir: repo.path().to_path_buf(),
+            remotes,
+            current_branch,
+            head,
+            is_worktree: repo.is_worktree(),
+            hooks_dir,
+        }))
     }
 
     fn get_current_branch(&self) -> Result<Option<String>> {
-        let git_dir = self.repo_path.join(".git");
-        Ok(Self::read_current_branch(&git_dir))
+        match Self::read_head(&self.open()?)? {
+            GitHead::Branch { name, .. } | GitHead::Unborn { name } => Ok(Some(name)),
+            GitHead::Detached { .. } => Ok(None),
+        }
     }
 
     fn get_remote_url(&self, remote: &str) -> Result<Option<String>> {
-        let git_dir = self.repo_path.join(".git");
-        let config_path = git_dir.join("config");
-        
-        if config_path.exists() {
-            let content = std::fs::read_to_string(config_path)?;
-            
-            // Simple parsing to find remote URL
-            let lines: Vec<&str> = content.lines().collect();
-            let mut in_remote_section = false;
-            
-            for line in lines {
-                let trimmed = line.trim();
-                if trimmed == &format!("[remote \"{}\"]", remote) {
-                    in_remote_section = true;
-                    continue;
-                }
-                
-                if in_remote_section {
-                    if trimmed.starts_with('[') {
-                        break; // End of remote section
-                    }
-                    
-                    if trimmed.starts_with("url = ") {
-                        let url = trimmed.strip_prefix("url = ").unwrap_or("").to_string();
-                        return Ok(Some(url));
-                    }
-                }
-            }
+        match self.open()?.find_remote(remote) {
+            Ok(remote) => Ok(remote.url().map(str::to_string)),
+            Err(e) if e.code() == git2::ErrorCode::NotFound => Ok(None),
+            Err(e) => Err(git_error(e)),
         }
-        
-        Ok(None)
     }
 
-    fn list_commits(&self, _branch: &str) -> Result<Vec<GitRef>> {
-        // Simple implementation - would use git2 in production
-        let git_refs = vec![
-            GitRef {
-                sha: "a1b2c3d4e5f6789012345678901234567890abcd".to_string(),
-                message: Some("Initial commit".to_string()),
-                author: Some("Developer <dev@example.com>".to_string()),
-                timestamp: Utc::now(),
+    fn list_commits(&self, revision: &str) -> Result<Vec<GitCommit>> {
+        let repo = self.open()?;
+        let mut walk = repo.revwalk().map_err(git_error)?;
+        walk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::TIME).map_err(git_error)?;
+        if revision.contains("..") {
+            walk.push_range(revision).map_err(git_error)?;
+        } else {
+            let commit = repo.revparse_single(revision)
+                .and_then(|object| object.peel_to_commit())
+                .map_err(git_error)?;
+            walk.push(commit.id()).map_err(git_error)?;
+        }
+
+        walk.map(|oid| {
+            let commit = repo.find_commit(oid.map_err(git_error)?).map_err(git_error)?;
+            Ok(Self::read_commit(&commit))
+        }).collect()
+    }
+
+    fn find_commit(&self, revision: &str) -> Result<Option<GitCommit>> {
+        let repo = self.open()?;
+        let commit = match repo.revparse_single(revision).and_then(|object| object.peel_to_commit()) {
+            Ok(commit) => Self::read_commit(&commit),
+            Err(e) if matches!(e.code(), git2::ErrorCode::NotFound | git2::ErrorCode::Ambiguous) => return Ok(None),
+            Err(e) if e.class() == git2::ErrorClass::Object => return Ok(None),
+            Err(e) => return Err(git_error(e)),
+        };
+        Ok(Some(commit))
+    }
+
+    fn branch_exists(&self, name: &str) -> Result<bool> {
+        let repo = self.open()?;
+        for branch_type in [git2::BranchType::Local, git2::BranchType::Remote] {
+            if repo.find_branch(name, branch_type).is_ok() {
+                return Ok(true);
+            }
+        }
+        // A remote-tracking branch under any remote, e.g. `feature` as `origin/feature`
+        for remote in repo.remotes().map_err(git_error)?.iter().flatten() {
+            if repo.find_branch(&format!("{}/{}", remote, name), git2::BranchType::Remote).is_ok() {
+                return Ok(true);
             }
-        ];
-        
-        Ok(git_refs)
-    }
-
-    fn associate_issue(&self, issue_id: &IssueId, _git_ref: &GitRef) -> Result<()> {
-        // Create association file
-        let associations_dir = self.repo_path.join(".odi").join("git-associations");
-        std::fs::create_dir_all(&associations_dir)?;
-        
-        let association_file = associations_dir.join(format!("{}.txt", issue_id));
-        std::fs::write(association_file, format!("Associated with issue {}", issue_id))?;
-        
+        }
+        Ok(false)
+    }
+
+    fn create_branch(&self, name: &str) -> Result<()> {
+        let repo = self.open()?;
+        let head = repo.head().and_then(|head| head.peel_to_commit()).map_err(git_error)?;
+        repo.branch(name, &head, false).map_err(git_error)?;
         Ok(())
     }
-}
 
-impl DefaultGitIntegration {
-    fn read_current_branch(git_dir: &Path) -> Option<String> {
-        let head_path = git_dir.join("HEAD");
-        if head_path.exists() {
-            if let Ok(content) = std::fs::read_to_string(head_path) {
-                let trimmed = content.trim();
-                if trimmed.starts_with("ref: refs/heads/") {
-                    return Some(trimmed.strip_prefix("ref: refs/heads/")?.to_string());
+    fn checkout_branch(&self, name: &str) -> Result<()> {
+        let repo = self.open()?;
+        let branch = repo.find_branch(name, git2::BranchType::Local).map_err(git_error)?;
+        let commit = branch.get().peel_to_commit().map_err(git_error)?;
+        let refname = format!("refs/heads/{}", name);
+
+        // Safe checkout stops rather than overwrite files changed in the working tree
+        repo.checkout_tree(commit.as_object(), Some(git2::build::CheckoutBuilder::new().safe())).map_err(git_error)?;
+        repo.set_head(&refname).map_err(git_error)?;
+        Ok(())
+    }
+
+    fn has_uncommitted_changes(&self) -> Result<bool> {
+        let repo = self.open()?;
+        let mut options = git2::StatusOptions::new();
+        options.include_untracked(false).include_ignored(false);
+        let statuses = repo.statuses(Some(&mut options)).map_err(git_error)?;
+        Ok(!statuses.is_empty())
+    }
+
+    fn working_tree_files(&self) -> Result<Vec<String>> {
+        let repo = self.open()?;
+        let workdir = repo.workdir().ok_or_else(|| FsError::GitError {
+            message: "Repository has no working tree".to_string(),
+        })?.to_path_buf();
+
+        let index = repo.index().map_err(git_error)?;
+        let mut files: Vec<String> = index.iter().filter_map(|entry| String::from_utf8(entry.path).ok()).collect();
+        let mut options = git2::StatusOptions::new();
+        options.include_untracked(true).recurse_untracked_dirs(true).include_ignored(false);
+        for entry in repo.statuses(Some(&mut options)).map_err(git_error)?.iter() {
+            if entry.status().contains(git2::Status::WT_NEW) {
+                files.extend(entry.path().map(str::to_string));
+            }
+        }
+
+        // Tracked files may have been deleted, and submodules are directories
+        files.retain(|path| workdir.join(path).is_file());
+        files.sort();
+        files.dedup();
+        Ok(files)
+    }
+
+    fn changed_paths(&self, revision: &str) -> Result<Vec<String>> {
+        let repo = self.open()?;
+        let commit = repo.revparse_single(revision)
+            .and_then(|object| object.peel_to_commit())
+            .map_err(git_error)?;
+        let tree = commit.tree().map_err(git_error)?;
+        let parent_tree = match commit.parent(0) {
+            Ok(parent) => Some(parent.tree().map_err(git_error)?),
+            Err(_) => None,
+        };
+
+        let diff = repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), None).map_err(git_error)?;
+        let mut paths = Vec::new();
+        for delta in diff.deltas() {
+            for file in [delta.old_file(), delta.new_file()] {
+                if let Some(path) = file.path().and_then(Path::to_str) {
+                    if !paths.iter().any(|seen| seen == path) {
+                        paths.push(path.to_string());
+                    }
                 }
             }
         }
-        None
+        Ok(paths)
+    }
+
+    fn blame(&self, path: &str, lines: Option<(usize, usize)>) -> Result<Vec<String>> {
+        let repo = self.open()?;
+        let mut options = git2::BlameOptions::new();
+        if let Some((start, end)) = lines {
+            options.min_line(start).max_line(end);
+        }
+        let blame = repo.blame_file(Path::new(path), Some(&mut options)).map_err(git_error)?;
+
+        let mut shas: Vec<String> = Vec::new();
+        for hunk in blame.iter() {
+            let sha = hunk.final_commit_id().to_string();
+            if !shas.contains(&sha) {
+                shas.push(sha);
+            }
+        }
+        Ok(shas)
+    }
+
+    fn associate_issue(&self, issue_id: &IssueId, git_ref: &GitRef) -> Result<bool> {
+        let storage = self.storage.as_ref().ok_or_else(|| FsError::GitError {
+            message: "No ODI store to record the link in".to_string(),
+        })?;
+        Self::record_association(storage, issue_id, git_ref)
+    }
+}
+
+#[cfg(not(feature = "git-integration"))]
+impl GitIntegration for DefaultGitIntegration {
+    fn detect_repository(_path: &Path) -> Result<Option<GitRepository>> {
+        Ok(None)
+    }
+
+    fn get_current_branch(&self) -> Result<Option<String>> {
+        Err(disabled())
+    }
+
+    fn get_remote_url(&self, _remote: &str) -> Result<Option<String>> {
+        Err(disabled())
+    }
+
+    fn list_commits(&self, _revision: &str) -> Result<Vec<GitCommit>> {
+        Err(disabled())
+    }
+
+    fn find_commit(&self, _revision: &str) -> Result<Option<GitCommit>> {
+        Err(disabled())
+    }
+
+    fn branch_exists(&self, _name: &str) -> Result<bool> {
+        Err(disabled())
+    }
+
+    fn create_branch(&self, _name: &str) -> Result<()> {
+        Err(disabled())
+    }
+
+    fn checkout_branch(&self, _name: &str) -> Result<()> {
+        Err(disabled())
+    }
+
+    fn has_uncommitted_changes(&self) -> Result<bool> {
+        Err(disabled())
+    }
+
+    fn working_tree_files(&self) -> Result<Vec<String>> {
+        Err(disabled())
+    }
+
+    fn changed_paths(&self, _revision: &str) -> Result<Vec<String>> {
+        Err(disabled())
+    }
+
+    fn blame(&self, _path: &str, _lines: Option<(usize, usize)>) -> Result<Vec<String>> {
+        Err(disabled())
     }
-}
\ No newline at end of file
+
+    fn associate_issue(&self, issue_id: &IssueId, git_ref: &GitRef) -> Result<bool> {
+        let storage = self.storage.as_ref().ok_or_else(disabled)?;
+        Self::record_association(storage, issue_id, git_ref)
+    }
+}
+
+#[cfg(not(feature = "git-integration"))]
+fn disabled() -> FsError {
+    FsError::GitError { message: "odi was built without the 'git-integration' feature".to_string() }
+}
+
+#[cfg(all(test, feature = "git-integration"))]
+mod tests {
+    use super::*;
+    use crate::storage::StorageEngine;
+    use git2::{Repository, Signature};
+    use odi_core::LinkRole;
+    use tempfile::TempDir;
+
+    fn commit(repo: &Repository, message: &str) -> git2::Oid {
+        let signature = Signature::now("Alice", "alice@example.com").unwrap();
+        let tree = repo.find_tree(repo.index().unwrap().write_tree().unwrap()).unwrap();
+        let parents = match repo.head() {
+            Ok(head) => vec![head.peel_to_commit().unwrap()],
+            Err(_) => Vec::new(),
+        };
+        let parents: Vec<&git2::Commit> = parents.iter().collect();
+        repo.commit(Some("HEAD"), &signature, &signature, message, &tree, &parents).unwrap()
+    }
+
+    #[test]
+    fn test_detect_repository() {
+        let dir = TempDir::new().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        repo.remote("origin", "git@example.com:team/app.git").unwrap();
+        repo.remote("upstream", "https://example.com/app.git").unwrap();
+        repo.set_head("refs/heads/main").unwrap();
+
+        // Found from a subdirectory, before the first commit
+        std::fs::create_dir(dir.path().join("src")).unwrap();
+        let detected = DefaultGitIntegration::detect_repository(&dir.path().join("src")).unwrap().unwrap();
+        assert_eq!(detected.remotes["origin"], "git@example.com:team/app.git");
+        assert_eq!(detected.remotes["upstream"], "https://example.com/app.git");
+        assert_eq!(detected.head, GitHead::Unborn { name: "main".to_string() });
+
+        let first = commit(&repo, "First");
+        let detected = DefaultGitIntegration::detect_repository(dir.path()).unwrap().unwrap();
+        assert_eq!(detected.current_branch.as_deref(), Some("main"));
+        assert!(!detected.is_worktree);
+
+        // A linked worktree has its own HEAD but shares remotes
+        let worktrees = TempDir::new().unwrap();
+        let path = worktrees.path().join("feature");
+        repo.worktree("feature", &path, None).unwrap();
+        let linked = DefaultGitIntegration::detect_repository(&path).unwrap().unwrap();
+        assert!(linked.is_worktree);
+        assert_eq!(linked.current_branch.as_deref(), Some("feature"));
+        assert_eq!(linked.remotes.len(), 2);
+        assert!(linked.hooks_dir.ends_with(".git/hooks"));
+
+        repo.set_head_detached(first).unwrap();
+        let detected = DefaultGitIntegration::detect_repository(dir.path()).unwrap().unwrap();
+        assert_eq!(detected.head, GitHead::Detached { sha: first.to_string() });
+        assert!(detected.current_branch.is_none());
+
+        let elsewhere = TempDir::new().unwrap();
+        assert!(DefaultGitIntegration::detect_repository(elsewhere.path()).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_list_commits_through_packed_refs() {
+        let dir = TempDir::new().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        repo.set_head("refs/heads/main").unwrap();
+        let first = commit(&repo, "First");
+        let second = commit(&repo, "Second\n\nFixes: API-12");
+
+        // Move the branch into packed-refs, the way `git pack-refs` does
+        std::fs::remove_file(dir.path().join(".git/refs/heads/main")).unwrap();
+        std::fs::write(dir.path().join(".git/packed-refs"), format!("{} refs/heads/main\n", second)).unwrap();
+
+        let git = DefaultGitIntegration::new(dir.path().to_path_buf());
+        let commits = git.list_commits("main").unwrap();
+        assert_eq!(commits.iter().map(|c| c.sha.clone()).collect::<Vec<_>>(), vec![second.to_string(), first.to_string()]);
+        assert_eq!(commits[0].summary(), "Second");
+        assert_eq!(commits[0].author.as_deref(), Some("Alice <alice@example.com>"));
+
+        let range = git.list_commits(&format!("{}..main", first)).unwrap();
+        assert_eq!(range.len(), 1);
+        assert_eq!(git.find_commit(&first.to_string()[..7]).unwrap().unwrap().summary(), "First");
+        assert!(git.find_commit("0000000").unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_associate_issue() {
+        let dir = TempDir::new().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let sha = commit(&repo, "Fix login");
+
+        let storage = FileSystemStorage::new(dir.path().join(".odi")).unwrap();
+        let issue = Issue::new("Login fails".to_string(), "alice".to_string());
+        let hash = storage.write_object(&issue).await.unwrap();
+        storage.write_ref(&format!("issues/{}", issue.id), &hash, ObjectType::Issue).await.unwrap();
+
+        let git = DefaultGitIntegration::new(dir.path().to_path_buf()).with_storage(storage.clone());
+        let commit = git.find_commit(&sha.to_string()).unwrap().unwrap();
+        let link = GitRef::commit(commit.sha, LinkRole::Fixes);
+        assert!(git.associate_issue(&issue.id, &link).unwrap());
+        assert!(!git.associate_issue(&issue.id, &link).unwrap());
+
+        let stored: Vec<Issue> = storage.read_all("issues/").await.unwrap();
+        assert_eq!(stored[0].git_refs, vec![link]);
+        assert!(git.branch_exists("master").unwrap() || git.branch_exists("main").unwrap());
+        assert!(!git.branch_exists("missing").unwrap());
+    }
+
+    #[test]
+    fn test_create_and_checkout_branch() {
+        let dir = TempDir::new().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        repo.set_head("refs/heads/main").unwrap();
+        std::fs::write(dir.path().join("README"), "hello\n").unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("README")).unwrap();
+        index.write().unwrap();
+        commit(&repo, "First");
+
+        let git = DefaultGitIntegration::new(dir.path().to_path_buf());
+        git.create_branch("1a2b3c4d-login").unwrap();
+        git.checkout_branch("1a2b3c4d-login").unwrap();
+        assert_eq!(git.get_current_branch().unwrap().as_deref(), Some("1a2b3c4d-login"));
+        assert!(git.create_branch("1a2b3c4d-login").is_err());
+
+        assert!(!git.has_uncommitted_changes().unwrap());
+        std::fs::write(dir.path().join("README"), "changed\n").unwrap();
+        assert!(git.has_uncommitted_changes().unwrap());
+
+        std::fs::create_dir_all(dir.path().join("src/target")).unwrap();
+        std::fs::write(dir.path().join("src/lib.rs"), "").unwrap();
+        std::fs::write(dir.path().join("src/target/out.o"), "").unwrap();
+        std::fs::write(dir.path().join(".gitignore"), "target/\n").unwrap();
+        assert_eq!(git.working_tree_files().unwrap(), vec![".gitignore", "README", "src/lib.rs"]);
+        git.checkout_branch("main").unwrap();
+        assert_eq!(git.get_current_branch().unwrap().as_deref(), Some("main"));
+    }
+
+    #[test]
+    fn test_path_index_and_blame() {
+        let dir = TempDir::new().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let write = |path: &str, content: &str| {
+            let file = dir.path().join(path);
+            std::fs::create_dir_all(file.parent().unwrap()).unwrap();
+            std::fs::write(file, content).unwrap();
+            let mut index = repo.index().unwrap();
+            index.add_path(Path::new(path)).unwrap();
+            index.write().unwrap();
+        };
+        write("src/auth/login.rs", "fn login() {}\n");
+        let first = commit(&repo, "Add login");
+        write("src/auth/login.rs", "fn login() {}\nfn logout() {}\n");
+        write("README", "hello\n");
+        let second = commit(&repo, "Add logout");
+
+        let git = DefaultGitIntegration::new(dir.path().to_path_buf());
+        assert_eq!(git.changed_paths(&first.to_string()).unwrap(), vec!["src/auth/login.rs"]);
+        assert_eq!(git.changed_paths(&second.to_string()).unwrap(), vec!["README", "src/auth/login.rs"]);
+        assert_eq!(git.blame("src/auth/login.rs", Some((2, 2))).unwrap(), vec![second.to_string()]);
+        assert_eq!(git.blame("src/auth/login.rs", None).unwrap().len(), 2);
+
+        let mut login = Issue::new("Login fails".to_string(), "alice".to_string());
+        login.git_refs.push(GitRef::commit(first.to_string()[..7].to_string(), LinkRole::Fixes));
+        let mut docs = Issue::new("Document setup".to_string(), "alice".to_string());
+        docs.git_refs.push(GitRef::commit(second.to_string(), LinkRole::Implements));
+        docs.git_refs.push(GitRef::commit("0000000".to_string(), LinkRole::Mentions));
+        let issues = vec![login.clone(), docs.clone()];
+
+        let odi_dir = dir.path().join(".odi");
+        std::fs::create_dir_all(&odi_dir).unwrap();
+        let index = PathIndex::build(&git, &odi_dir, &issues).unwrap();
+        assert_eq!(index.lookup("README"), vec![(docs.id, second.to_string())]);
+        assert_eq!(index.lookup("src/auth/").len(), 2);
+        assert!(index.lookup("src/aut").is_empty());
+
+        // The second build reads the cache instead of Git
+        let cached = std::fs::read_to_string(odi_dir.join(PATHS_FILE)).unwrap();
+        assert_eq!(cached.lines().count(), 2);
+        let index = PathIndex::build(&git, &odi_dir, &issues).unwrap();
+        assert_eq!(index.lookup("src/auth/login.rs").len(), 2);
+        assert_eq!(std::fs::read_to_string(odi_dir.join(PATHS_FILE)).unwrap(), cached);
+    }
+
+    #[test]
+    fn test_mentions_path() {
+        assert!(mentions_path("Crashes in src/auth/login.rs:40, see below.", "src/auth/login.rs"));
+        assert!(mentions_path("Everything under `src/auth/` is slow", "src/auth"));
+        assert!(mentions_path("See [login](./src/auth/login.rs)", "src/auth/"));
+        assert!(mentions_path("Broken in crates/app/src/main.rs", "src/main.rs"));
+        assert!(mentions_path("Update the README.", "README"));
+        assert!(!mentions_path("see src/a", "a"));
+        assert!(!mentions_path("metadata is stale", "data"));
+        assert!(!mentions_path("src/auth/login.rs.bak", "src/auth/login.rs"));
+        assert!(!mentions_path("anything", ""));
+    }
+
+    #[test]
+    fn test_record_scanned() {
+        let dir = TempDir::new().unwrap();
+        assert!(read_scanned(dir.path()).unwrap().is_empty());
+
+        record_scanned(dir.path(), &["a1b2c3d".to_string()]).unwrap();
+        record_scanned(dir.path(), &["e4f5a6b".to_string(), "a1b2c3d".to_string()]).unwrap();
+        let scanned = read_scanned(dir.path()).unwrap();
+        assert_eq!(scanned.len(), 2);
+        assert!(scanned.contains("e4f5a6b"));
+    }
+}
@@ -0,0 +1,318 @@
+//! ODI data in the project's own Git repository
+//!
+//! A `git+` remote keeps ODI objects in the `refs/odi/` namespace of a Git
+//! repository, so issues travel with the code to any Git host. The workspace
+//! is committed to [`ODI_REF`] as a tree laid out like the store's refs
+//! (`issues/<id>`, `labels/<project>/<label>`, ...), one blob per object, and
+//! moved with `git fetch` and `git push`. As with a branch, a push must
+//! fast-forward: changes pushed from elsewhere are pulled and merged first.
+//!
+//! Objects are committed unencrypted, as in bundles, so encrypted workspaces
+//! can't use Git remotes. Deleted objects stay in the tree, just as pulls
+//! never delete local objects.
+
+#[cfg(feature = "git-integration")]
+use std::collections::BTreeMap;
+#[cfg(feature = "git-integration")]
+use std::path::Path;
+#[cfg(feature = "git-integration")]
+use std::process::Command;
+
+#[cfg(feature = "git-integration")]
+use crate::bundle::{Bundle, BundleWriter};
+#[cfg(feature = "git-integration")]
+use crate::pack::PackObject;
+#[cfg(feature = "git-integration")]
+use crate::storage::ObjectType;
+#[cfg(feature = "git-integration")]
+use crate::{FsError, Result};
+
+/// URL scheme of Git remotes, followed by a Git remote name or URL,
+/// e.g. `git+origin` or `git+/srv/git/app.git`
+pub const URL_SCHEME: &str = "git+";
+
+/// Ref holding the committed ODI objects
+pub const ODI_REF: &str = "refs/odi/main";
+
+/// Git remote name or URL named by a `git+` remote URL
+///
+/// Names starting with `-` are refused, as Git would read them as options.
+pub fn repository_from_url(url: &str) -> Option<&str> {
+    url.strip_prefix(URL_SCHEME).filter(|repository| !repository.is_empty() && !repository.starts_with('-'))
+}
+
+/// Where `ODI_REF` of a remote is fetched to, like `refs/remotes/<remote>/main` for branches
+pub fn tracking_ref(remote: &str) -> String {
+    format!("refs/odi/remotes/{}/main", remote)
+}
+
+/// Object type of a ref by its first component
+#[cfg(feature = "git-integration")]
+fn object_type(name: &str) -> Option<ObjectType> {
+    match name.split('/').next()? {
+        "issues" => Some(ObjectType::Issue),
+        "projects" => Some(ObjectType::Project),
+        "labels" => Some(ObjectType::Label),
+        "views" => Some(ObjectType::View),
+        "users" => Some(ObjectType::User),
+        "teams" => Some(ObjectType::Team),
+        _ => None,
+    }
+}
+
+/// The `refs/odi/` namespace of a Git repository
+#[cfg(feature = "git-integration")]
+pub struct GitStore {
+    repo: git2::Repository,
+}
+
+#[cfg(feature = "git-integration")]
+fn git_error(e: git2::Error) -> FsError {
+    FsError::GitError { message: e.message().to_string() }
+}
+
+#[cfg(feature = "git-integration")]
+impl GitStore {
+    /// Open the Git repository containing a path
+    pub fn open(path: &Path) -> Result<Self> {
+        let repo = git2::Repository::discover(path).map_err(|_| FsError::GitError {
+            message: format!("No Git repository found at {}", path.display()),
+        })?;
+        Ok(Self { repo })
+    }
+
+    fn find_ref(&self, name: &str) -> Result<Option<git2::Commit<'_>>> {
+        match self.repo.find_reference(name) {
+            Ok(reference) => Ok(Some(reference.peel_to_commit().map_err(git_error)?)),
+            Err(e) if e.code() == git2::ErrorCode::NotFound => Ok(None),
+            Err(e) => Err(git_error(e)),
+        }
+    }
+
+    fn find_commit(&self, sha: &str) -> Result<git2::Commit<'_>> {
+        let oid = git2::Oid::from_str(sha).map_err(git_error)?;
+        self.repo.find_commit(oid).map_err(git_error)
+    }
+
+    /// Add every blob in a commit's tree to `entries`, by path
+    fn read_tree(&self, commit: &git2::Commit, entries: &mut BTreeMap<String, git2::Oid>) -> Result<()> {
+        let tree = commit.tree().map_err(git_error)?;
+        tree.walk(git2::TreeWalkMode::PreOrder, |root, entry| {
+            if entry.kind() == Some(git2::ObjectType::Blob) {
+                if let Some(name) = entry.name() {
+                    entries.insert(format!("{}{}", root, name), entry.id());
+                }
+            }
+            git2::TreeWalkResult::Ok
+        }).map_err(git_error)
+    }
+
+    /// Write nested trees for blobs by path
+    fn write_tree(&self, entries: &[(&str, git2::Oid)]) -> Result<git2::Oid> {
+        let mut builder = self.repo.treebuilder(None).map_err(git_error)?;
+        let mut i = 0;
+        while i < entries.len() {
+            let (path, oid) = entries[i];
+            match path.split_once('/') {
+                None => {
+                    builder.insert(path, oid, 0o100644).map_err(git_error)?;
+                    i += 1;
+                }
+                Some((dir, _)) => {
+                    // Sorted paths keep a directory's entries together
+                    let prefix = format!("{}/", dir);
+                    let children: Vec<(&str, git2::Oid)> = entries[i..].iter()
+                        .take_while(|(path, _)| path.starts_with(&prefix))
+                        .map(|(path, oid)| (&path[prefix.len()..], *oid))
+                        .collect();
+                    i += children.len();
+                    let subtree = self.write_tree(&children)?;
+                    builder.insert(dir, subtree, 0o040000).map_err(git_error)?;
+                }
+            }
+        }
+        builder.write().map_err(git_error)
+    }
+
+    /// Commit objects to [`ODI_REF`], merging in a fetched commit if given
+    ///
+    /// Objects already committed but missing from `writer` are kept. Merging a
+    /// commit the ref already contains is a no-op, and one that adds nothing
+    /// local is a fast-forward, as with `git merge`. Returns the new commit,
+    /// or `None` if nothing changed.
+    pub fn commit(&self, writer: BundleWriter, merge: Option<&str>, message: &str) -> Result<Option<String>> {
+        let parent = self.find_ref(ODI_REF)?;
+        let merged = match merge {
+            Some(sha) if !self.contains(sha)? => Some(self.find_commit(sha)?),
+            _ => None,
+        };
+
+        let mut entries = BTreeMap::new();
+        for commit in parent.iter().chain(merged.iter()) {
+            self.read_tree(commit, &mut entries)?;
+        }
+        for (name, object) in writer.into_objects() {
+            entries.insert(name, self.repo.blob(&object.data).map_err(git_error)?);
+        }
+        let entries: Vec<(&str, git2::Oid)> = entries.iter().map(|(path, oid)| (path.as_str(), *oid)).collect();
+        let tree = self.write_tree(&entries)?;
+
+        match (&parent, &merged) {
+            (_, Some(merged)) if merged.tree_id() == tree
+                && parent.as_ref().is_none_or(|parent| self.repo.graph_descendant_of(merged.id(), parent.id()).unwrap_or(false)) => {
+                self.repo.reference(ODI_REF, merged.id(), true, message).map_err(git_error)?;
+                return Ok(Some(merged.id().to_string()));
+            }
+            (Some(parent), None) if parent.tree_id() == tree => return Ok(None),
+            _ => {}
+        }
+
+        let tree = self.repo.find_tree(tree).map_err(git_error)?;
+        let signature = self.repo.signature()
+            .or_else(|_| git2::Signature::now("odi", "odi@localhost"))
+            .map_err(git_error)?;
+        let parents: Vec<&git2::Commit> = parent.iter().chain(merged.iter()).collect();
+        let oid = self.repo.commit(Some(ODI_REF), &signature, &signature, message, &tree, &parents).map_err(git_error)?;
+        Ok(Some(oid.to_string()))
+    }
+
+    /// Whether [`ODI_REF`] is, or descends from, a commit
+    pub fn contains(&self, sha: &str) -> Result<bool> {
+        let Some(tip) = self.find_ref(ODI_REF)? else {
+            return Ok(false);
+        };
+        let oid = git2::Oid::from_str(sha).map_err(git_error)?;
+        Ok(tip.id() == oid || self.repo.graph_descendant_of(tip.id(), oid).map_err(git_error)?)
+    }
+
+    /// The objects in a commit
+    pub fn snapshot(&self, sha: &str) -> Result<Bundle> {
+        let mut entries = BTreeMap::new();
+        self.read_tree(&self.find_commit(sha)?, &mut entries)?;
+
+        let mut objects = Vec::new();
+        for (name, oid) in entries {
+            let Some(object_type) = object_type(&name) else {
+                continue;
+            };
+            let blob = self.repo.find_blob(oid).map_err(git_error)?;
+            objects.push((name, PackObject::new(object_type, blob.content().to_vec())));
+        }
+        Ok(Bundle::from_objects(objects))
+    }
+
+    /// Fetch [`ODI_REF`] of a Git remote or URL to the remote's tracking ref
+    ///
+    /// Returns the fetched commit, or `None` if the remote has no ODI data yet.
+    pub fn fetch(&self, repository: &str, remote: &str) -> Result<Option<String>> {
+        if self.git(&["ls-remote", "--", repository, ODI_REF])?.trim().is_empty() {
+            return Ok(None);
+        }
+        let tracking = tracking_ref(remote);
+        self.git(&["fetch", "--quiet", "--no-tags", "--", repository, &format!("+{}:{}", ODI_REF, tracking)])?;
+        Ok(self.find_ref(&tracking)?.map(|commit| commit.id().to_string()))
+    }
+
+    /// Push [`ODI_REF`], which must fast-forward the remote's unless forced
+    pub fn push(&self, repository: &str, force: bool) -> Result<()> {
+        if self.find_ref(ODI_REF)?.is_none() {
+            return Err(FsError::GitError { message: "No ODI data committed to push".to_string() });
+        }
+        let refspec = format!("{}{}:{}", if force { "+" } else { "" }, ODI_REF, ODI_REF);
+        self.git(&["push", "--quiet", "--", repository, &refspec]).map_err(|e| match e {
+            FsError::GitError { message } if message.contains("rejected") => FsError::GitError {
+                message: "The remote has ODI changes that aren't here yet; pull them first, or push with --force to overwrite them".to_string(),
+            },
+            e => e,
+        })
+        .map(|_| ())
+    }
+
+    /// Run git in the repository and return its output
+    fn git(&self, args: &[&str]) -> Result<String> {
+        let dir = self.repo.workdir().unwrap_or_else(|| self.repo.path());
+        let output = Command::new("git").args(args).current_dir(dir).output().map_err(|e| FsError::GitError {
+            message: format!("Could not run git: {}", e),
+        })?;
+        if !output.status.success() {
+            return Err(FsError::GitError {
+                message: format!("git {} failed: {}", args[0], String::from_utf8_lossy(&output.stderr).trim()),
+            });
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+}
+
+#[cfg(all(test, feature = "git-integration"))]
+mod tests {
+    use super::*;
+    use odi_core::Issue;
+    use tempfile::TempDir;
+
+    fn writer(issues: &[&Issue]) -> BundleWriter {
+        let mut writer = BundleWriter::new();
+        for issue in issues {
+            writer.add(&format!("issues/{}", issue.id), *issue).unwrap();
+        }
+        writer
+    }
+
+    fn titles(store: &GitStore, sha: &str) -> Vec<String> {
+        let mut titles: Vec<String> = store.snapshot(sha).unwrap()
+            .read_all::<Issue>("issues/").unwrap()
+            .into_iter()
+            .map(|(_, issue)| issue.title)
+            .collect();
+        titles.sort();
+        titles
+    }
+
+    #[test]
+    fn test_push_and_pull_through_bare_repository() {
+        let dir = TempDir::new().unwrap();
+        let remote = dir.path().join("remote.git");
+        git2::Repository::init_bare(&remote).unwrap();
+        let remote = remote.to_str().unwrap();
+        git2::Repository::init(dir.path().join("a")).unwrap();
+        git2::Repository::init(dir.path().join("b")).unwrap();
+        let a = GitStore::open(&dir.path().join("a")).unwrap();
+        let b = GitStore::open(&dir.path().join("b")).unwrap();
+
+        assert!(a.fetch(remote, "origin").unwrap().is_none());
+        let first = Issue::new("First".to_string(), "alice".to_string());
+        assert!(a.commit(writer(&[&first]), None, "Update").unwrap().is_some());
+        assert!(a.commit(writer(&[&first]), None, "Update").unwrap().is_none());
+        a.push(remote, false).unwrap();
+
+        // b merges what a pushed and pushes on top of it
+        let fetched = b.fetch(remote, "origin").unwrap().unwrap();
+        assert_eq!(titles(&b, &fetched), vec!["First"]);
+        let second = Issue::new("Second".to_string(), "bob".to_string());
+        b.commit(writer(&[&second]), Some(&fetched), "Merge").unwrap();
+        b.push(remote, false).unwrap();
+
+        // a's next push no longer fast-forwards until it pulls
+        let mut changed = first.clone();
+        changed.title = "First, edited".to_string();
+        a.commit(writer(&[&changed]), None, "Update").unwrap();
+        assert!(a.push(remote, false).is_err());
+        let fetched = a.fetch(remote, "origin").unwrap().unwrap();
+        let merge = a.commit(writer(&[&changed]), Some(&fetched), "Merge").unwrap().unwrap();
+        assert_eq!(titles(&a, &merge), vec!["First, edited", "Second"]);
+        a.push(remote, false).unwrap();
+
+        // Merging what's already contained changes nothing; b fast-forwards
+        assert!(a.commit(writer(&[&changed]), Some(&fetched), "Merge").unwrap().is_none());
+        let fetched = b.fetch(remote, "origin").unwrap().unwrap();
+        assert_eq!(b.commit(writer(&[]), Some(&fetched), "Merge").unwrap(), Some(merge));
+    }
+
+    #[test]
+    fn test_repository_from_url() {
+        assert_eq!(repository_from_url("git+origin"), Some("origin"));
+        assert_eq!(repository_from_url("git+ssh://git@example.com/app.git"), Some("ssh://git@example.com/app.git"));
+        assert_eq!(repository_from_url("git+"), None);
+        assert_eq!(repository_from_url("git+--upload-pack=touch"), None);
+        assert_eq!(repository_from_url("bundle:///a.odib"), None);
+    }
+}
@@ -0,0 +1,176 @@
+//! Git hooks that connect commits and checkouts with issues
+//!
+//! Each hook is a small shell script that runs `odi hooks run <hook>`. A hook
+//! the user already had is not overwritten: it is renamed with
+//! [`BACKUP_SUFFIX`] and run first, and restored on uninstall.
+
+use std::path::{Path, PathBuf};
+
+use crate::{FsError, Result};
+
+/// Hooks managed by `odi hooks install`
+pub const HOOKS: [&str; 3] = ["prepare-commit-msg", "commit-msg", "post-checkout"];
+
+/// Suffix of a pre-existing hook that an ODI hook chains to
+pub const BACKUP_SUFFIX: &str = ".pre-odi";
+
+/// Marks a hook script as written by ODI
+const MARKER: &str = "# Installed by odi hooks install";
+
+/// What installing a hook did
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Installed {
+    /// No hook existed
+    Created,
+    /// A user hook existed and now runs first
+    Chained,
+    /// The ODI hook was already installed and was rewritten
+    Updated,
+}
+
+/// What uninstalling a hook did
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Removed {
+    Removed,
+    /// Removed, and the user hook it chained to put back
+    Restored,
+    /// No ODI hook was installed
+    NotInstalled,
+}
+
+fn script(hook: &str) -> String {
+    format!(
+        r#"#!/bin/sh
+{marker}; the hook it replaced, if any, is {hook}{suffix}
+previous="$(dirname "$0")/{hook}{suffix}"
+if [ -x "$previous" ]; then
+    "$previous" "$@" || exit $?
+fi
+if [ -d .odi ] && command -v odi >/dev/null 2>&1; then
+    exec odi hooks run {hook} "$@"
+fi
+"#,
+        marker = MARKER,
+        hook = hook,
+        suffix = BACKUP_SUFFIX,
+    )
+}
+
+fn backup_path(hooks_dir: &Path, hook: &str) -> PathBuf {
+    hooks_dir.join(format!("{}{}", hook, BACKUP_SUFFIX))
+}
+
+/// Whether the hook at a path was written by ODI
+pub fn is_installed(path: &Path) -> bool {
+    std::fs::read_to_string(path).is_ok_and(|content| content.contains(MARKER))
+}
+
+fn make_executable(path: &Path) -> Result<()> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o755))?;
+    }
+    #[cfg(not(unix))]
+    let _ = path;
+    Ok(())
+}
+
+/// Install every ODI hook, chaining to hooks already present
+pub fn install(hooks_dir: &Path) -> Result<Vec<(&'static str, Installed)>> {
+    // Check every hook first so a conflict leaves nothing half installed
+    for hook in HOOKS {
+        let path = hooks_dir.join(hook);
+        let backup = backup_path(hooks_dir, hook);
+        if path.exists() && !is_installed(&path) && backup.exists() {
+            return Err(FsError::GitError {
+                message: format!("Both {} and {} exist; merge them by hand before installing", path.display(), backup.display()),
+            });
+        }
+    }
+
+    std::fs::create_dir_all(hooks_dir)?;
+    let mut installed = Vec::new();
+    for hook in HOOKS {
+        let path = hooks_dir.join(hook);
+        let outcome = if !path.exists() {
+            Installed::Created
+        } else if is_installed(&path) {
+            Installed::Updated
+        } else {
+            let backup = backup_path(hooks_dir, hook);
+            std::fs::rename(&path, &backup)?;
+            Installed::Chained
+        };
+
+        std::fs::write(&path, script(hook))?;
+        make_executable(&path)?;
+        installed.push((hook, outcome));
+    }
+    Ok(installed)
+}
+
+/// Remove every ODI hook, putting back the hooks they chained to
+pub fn uninstall(hooks_dir: &Path) -> Result<Vec<(&'static str, Removed)>> {
+    let mut removed = Vec::new();
+
+    for hook in HOOKS {
+        let path = hooks_dir.join(hook);
+        let backup = backup_path(hooks_dir, hook);
+        let outcome = if !is_installed(&path) {
+            Removed::NotInstalled
+        } else if backup.exists() {
+            std::fs::rename(&backup, &path)?;
+            Removed::Restored
+        } else {
+            std::fs::remove_file(&path)?;
+            Removed::Removed
+        };
+        removed.push((hook, outcome));
+    }
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_install_chains_existing_hooks() {
+        let dir = TempDir::new().unwrap();
+        let hooks = dir.path().join("hooks");
+        std::fs::create_dir(&hooks).unwrap();
+        std::fs::write(hooks.join("commit-msg"), "#!/bin/sh\necho user hook\n").unwrap();
+
+        let installed = install(&hooks).unwrap();
+        assert_eq!(installed, vec![
+            ("prepare-commit-msg", Installed::Created),
+            ("commit-msg", Installed::Chained),
+            ("post-checkout", Installed::Created),
+        ]);
+        assert!(is_installed(&hooks.join("commit-msg")));
+        assert!(std::fs::read_to_string(hooks.join("commit-msg.pre-odi")).unwrap().contains("user hook"));
+
+        // Installing again keeps the chained hook
+        assert!(install(&hooks).unwrap().iter().all(|(_, outcome)| *outcome == Installed::Updated));
+        assert!(hooks.join("commit-msg.pre-odi").exists());
+
+        let removed = uninstall(&hooks).unwrap();
+        assert_eq!(removed[1], ("commit-msg", Removed::Restored));
+        assert_eq!(removed[0], ("prepare-commit-msg", Removed::Removed));
+        assert!(!hooks.join("prepare-commit-msg").exists());
+        assert_eq!(std::fs::read_to_string(hooks.join("commit-msg")).unwrap(), "#!/bin/sh\necho user hook\n");
+        assert!(uninstall(&hooks).unwrap().iter().all(|(_, outcome)| *outcome == Removed::NotInstalled));
+    }
+
+    #[test]
+    fn test_install_refuses_to_overwrite_backup() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("commit-msg"), "#!/bin/sh\n").unwrap();
+        std::fs::write(dir.path().join("commit-msg.pre-odi"), "#!/bin/sh\n").unwrap();
+
+        assert!(install(dir.path()).is_err());
+        assert!(!dir.path().join("prepare-commit-msg").exists());
+    }
+}
@@ -7,26 +7,36 @@
 //!
 //! This crate implements the storage layer for ODI with:
 //! - **Object Storage**: Git-like binary object storage in `.odi/objects/`
+//! - **Packfiles**: Delta-compressed packs of objects written by `odi gc`
+//! - **Bundles**: Single-file, checksummed archives of objects and refs for offline transfer
 //! - **Configuration**: TOML-based configuration with hierarchy support
 //! - **Repositories**: Filesystem implementations of domain repository traits
-//! - **Git Integration**: Automatic detection and association with Git repositories
+//! - **Encryption at Rest**: Optional AEAD encryption of object files, unlocked from a passphrase or key file
+//! - **Search Index**: Inverted full-text index of issue and project text in `.odi/index/`, ranked with BM25
+//! - **Reflog**: Per-ref history of every ref change in `.odi/logs/`, for undo and restore
+//! - **Format Versioning**: Workspace version in `.odi/version` and step-by-step migrations
+//! - **SQLite Backend**: Optional (`sqlite` feature) database-backed repositories
+//! - **Git Integration**: Repository detection, commit history and issue↔commit associations via libgit2
+//! - **Merge Driver**: Git merge driver that merges refs of a committed workspace object by object
+//! - **Git Store**: ODI objects committed under `refs/odi/` of the project repository and synced with git fetch/push
 //!
 //! ## Example Usage
 //!
 //! ### Object Storage
 //! ```rust,ignore
-//! use odi_fs::{FileSystemStorage, ObjectStorage};
-//! use std::path::Path;
+//! use odi_fs::{FileSystemStorage, ObjectType, StorageEngine};
+//! use std::path::PathBuf;
 //!
 //! // Initialize storage engine
-//! let storage = FileSystemStorage::new(Path::new(".odi")).await?;
+//! let storage = FileSystemStorage::new(PathBuf::from(".odi"))?;
 //!
-//! // Store objects (issues, projects, etc.) as binary data
-//! let object_data = bincode::serialize(&issue)?;
-//! let hash = storage.store_object(&object_data).await?;
+//! // Store typed objects (issues, projects, etc.) and point refs at them
+//! let hash = storage.write_object(&issue).await?;
+//! storage.write_ref(&format!("issues/{}", issue.id), &hash, ObjectType::Issue).await?;
 //!
-//! // Retrieve objects by hash
-//! let retrieved = storage.load_object(&hash).await?;
+//! // Retrieve objects by hash, or everything under a ref prefix
+//! let retrieved: Option<Issue> = storage.read_object(&hash).await?;
+//! let all: Vec<Issue> = storage.read_all("issues/").await?;
 //! ```
 //!
 //! ### Configuration Management
@@ -62,15 +72,38 @@
 use thiserror::Error;
 
 // Re-export main modules
+pub mod bundle;
 pub mod config;
 pub mod storage;
+pub mod lock;
+pub mod crypto;
+pub mod pack;
+pub mod reflog;
 pub mod git;
+pub mod git_store;
+pub mod hooks;
+#[cfg(feature = "git-integration")]
+pub mod merge_driver;
 pub mod repository;
+pub mod search;
+pub mod version;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
 
 // Re-export important types
-pub use config::{Config, UserConfig, ProjectConfig, RemoteConfig, WorkspaceConfig, ConfigLoader, FileConfigLoader, load_config, save_config};
-pub use storage::{ObjectType, ObjectHash, StorageObject, ObjectRef, StorageLock, Lock, ObjectStorage, StorageEngine, FileSystemStorage};
+pub use config::{Config, UserConfig, ProjectConfig, RemoteConfig, StorageConfig, StorageBackend, GitConfig, WorkspaceConfig, ConfigLoader, FileConfigLoader, load_config, save_config, user_config_path, load_user_views, save_user_views};
+pub use storage::{ObjectType, ObjectHash, StorageObject, ObjectRef, StorageLock, Lock, ObjectStorage, StorageEngine, StoredObject, FileSystemStorage};
+pub use lock::{LockGuard, LockOptions};
+pub use crypto::{Cipher, KeySource};
+pub use bundle::{Bundle, BundleManifest, BundleRef, BundleWriter};
+pub use search::{SearchHit, SearchIndex, SearchQuery, Snippet};
+pub use reflog::{Operation, ReflogContext, ReflogEntry};
+pub use git::{DefaultGitIntegration, GitCommit, GitHead, GitIntegration, GitRepository, PathIndex, mentions_path};
+pub use pack::{GcStats, PackObject, PackStats};
+pub use version::{Migration, CURRENT_VERSION, check_version, read_version, write_version, pending_migrations, migrate};
 pub use repository::{FsIssueRepository, FsProjectRepository, FsUserRepository, FsRemoteRepository, ConfigRemoteRepository};
+#[cfg(feature = "sqlite")]
+pub use sqlite::{SqliteStorage, SqliteIssueRepository, SqliteProjectRepository, SqliteUserRepository, SqliteRemoteRepository};
 
 #[derive(Error, Debug)]
 pub enum FsError {
@@ -89,6 +122,21 @@ pub enum FsError {
     #[error("Lock error: {message}")]
     LockError { message: String },
 
+    #[error("Timed out waiting for lock '{name}' held by {holder}")]
+    LockTimeout { name: String, holder: String },
+
+    #[error("Workspace format version {found} is newer than this version of odi supports ({supported}); upgrade odi")]
+    UnsupportedWorkspace { found: u32, supported: u32 },
+
+    #[error("Workspace format version {found} is older than the current version {current}; run 'odi migrate'")]
+    MigrationRequired { found: u32, current: u32 },
+
+    #[error("Workspace is encrypted; set ODI_PASSPHRASE or ODI_KEY_FILE to unlock it")]
+    KeyRequired,
+
+    #[error("Cannot unlock workspace: {message}")]
+    InvalidKey { message: String },
+
     #[error("Git integration error: {message}")]
     GitError { message: String },
 
@@ -97,6 +145,13 @@ pub enum FsError {
     
     #[error("JSON error: {0}")]
     JsonError(#[from] serde_json::Error),
+    
+    #[error("{0}")]
+    Format(#[from] odi_core::CoreError),
+    
+    #[cfg(feature = "sqlite")]
+    #[error("Database error: {0}")]
+    Database(#[from] rusqlite::Error),
 }
 
 pub type Result<T> = std::result::Result<T, FsError>;
@@ -0,0 +1,362 @@
+//! Advisory locking for the object store
+//!
+//! Each lock is a file in `.odi/locks/` held with an OS-level advisory lock
+//! (`flock(2)` on Unix, `LockFile` on Windows). The kernel drops the lock when
+//! the holding process exits, so a crashed `odi` never leaves a workspace
+//! locked. The lock file also records the holder's PID; that record is used in
+//! contention errors and, on filesystems without advisory lock support, to
+//! detect locks left behind by dead processes.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use chrono::Utc;
+use fs4::FileExt;
+
+use crate::storage::StorageLock;
+use crate::{FsError, Result};
+
+/// Lock taken by issue mutations
+pub(crate) const ISSUES_LOCK: &str = "issues";
+/// Lock taken by project, workspace, label and view mutations
+pub(crate) const PROJECTS_LOCK: &str = "projects";
+/// Lock taken by user and team mutations
+pub(crate) const USERS_LOCK: &str = "users";
+/// Lock taken by remote mutations
+pub(crate) const REMOTES_LOCK: &str = "remotes";
+/// Lock taken by mutations of the workspace config file
+pub(crate) const CONFIG_LOCK: &str = "config";
+
+/// Locks of every repository, taken by operations that rewrite the whole store
+pub(crate) const REPOSITORY_LOCKS: [&str; 4] = [ISSUES_LOCK, PROJECTS_LOCK, USERS_LOCK, REMOTES_LOCK];
+
+/// Options controlling how long lock acquisition waits
+#[derive(Debug, Clone)]
+pub struct LockOptions {
+    /// Give up after this long; `None` waits indefinitely
+    pub timeout: Option<Duration>,
+    /// Delay between attempts while the lock is contended
+    pub retry_interval: Duration,
+}
+
+impl LockOptions {
+    /// Create default lock options (10 second timeout)
+    pub fn new() -> Self {
+        Self {
+            timeout: Some(Duration::from_secs(10)),
+            retry_interval: Duration::from_millis(25),
+        }
+    }
+
+    /// Set the acquisition timeout
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Wait for the lock indefinitely
+    pub fn wait_forever(mut self) -> Self {
+        self.timeout = None;
+        self
+    }
+
+    /// Fail immediately if the lock is held
+    pub fn no_wait(mut self) -> Self {
+        self.timeout = Some(Duration::ZERO);
+        self
+    }
+
+    /// Set the retry interval
+    pub fn retry_interval(mut self, interval: Duration) -> Self {
+        self.retry_interval = interval;
+        self
+    }
+}
+
+impl Default for LockOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A held lock, released when dropped
+#[derive(Debug)]
+pub struct LockGuard {
+    lock: StorageLock,
+    path: PathBuf,
+    file: Option<File>,
+    os_locked: bool,
+}
+
+impl LockGuard {
+    /// Lock record written to the lock file
+    pub fn lock(&self) -> &StorageLock {
+        &self.lock
+    }
+
+    /// Name of the locked resource
+    pub fn name(&self) -> &str {
+        &self.lock.name
+    }
+
+    /// Path of the backing lock file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Release the lock, reporting any error instead of ignoring it as `Drop` does
+    pub fn release(mut self) -> Result<()> {
+        self.release_inner()
+    }
+
+    fn release_inner(&mut self) -> Result<()> {
+        if let Some(file) = self.file.take() {
+            // Clear the record first so a fallback (PID-based) reader sees the lock as free
+            file.set_len(0)?;
+            if self.os_locked {
+                FileExt::unlock(&file)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Drop for LockGuard {
+    fn drop(&mut self) {
+        let _ = self.release_inner();
+    }
+}
+
+/// Path of the lock file for a resource
+pub fn lock_path(locks_path: &Path, name: &str) -> PathBuf {
+    locks_path.join(format!("{}.lock", name))
+}
+
+/// Acquire a lock, waiting according to `options`
+pub fn acquire(locks_path: &Path, name: &str, options: &LockOptions) -> Result<LockGuard> {
+    let started = Instant::now();
+
+    loop {
+        if let Some(guard) = try_acquire(locks_path, name)? {
+            return Ok(guard);
+        }
+
+        if let Some(timeout) = options.timeout {
+            if started.elapsed() >= timeout {
+                return Err(timeout_error(locks_path, name)?);
+            }
+        }
+
+        std::thread::sleep(options.retry_interval);
+    }
+}
+
+/// Acquire a lock without blocking the async runtime while waiting
+pub async fn acquire_async(locks_path: &Path, name: &str, options: &LockOptions) -> Result<LockGuard> {
+    let started = Instant::now();
+
+    loop {
+        let (path, resource) = (locks_path.to_path_buf(), name.to_string());
+        let attempt = tokio::task::spawn_blocking(move || try_acquire(&path, &resource))
+            .await
+            .map_err(|e| FsError::LockError { message: e.to_string() })??;
+        if let Some(guard) = attempt {
+            return Ok(guard);
+        }
+
+        if let Some(timeout) = options.timeout {
+            if started.elapsed() >= timeout {
+                return Err(timeout_error(locks_path, name)?);
+            }
+        }
+
+        tokio::time::sleep(options.retry_interval).await;
+    }
+}
+
+/// Try to acquire a lock without waiting
+///
+/// Returns `Ok(None)` if the lock is held by a live process.
+pub fn try_acquire(locks_path: &Path, name: &str) -> Result<Option<LockGuard>> {
+    let path = lock_path(locks_path, name);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    // Lock files are never removed; holding the OS lock on them is what counts
+    let mut file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(&path)?;
+
+    let os_locked = match FileExt::try_lock_exclusive(&file) {
+        Ok(()) => true,
+        Err(e) if e.kind() == fs4::lock_contended_error().kind() => return Ok(None),
+        Err(_) => {
+            // Advisory locks unsupported here; trust the record unless its holder is gone
+            if let Some(existing) = read_record(&mut file)? {
+                if process_is_alive(existing.process_id) {
+                    return Ok(None);
+                }
+            }
+            false
+        }
+    };
+
+    let lock = StorageLock {
+        name: name.to_string(),
+        acquired_at: Utc::now(),
+        process_id: std::process::id(),
+    };
+
+    let serialized = bincode::serialize(&lock).map_err(|e| FsError::SerializationError {
+        message: e.to_string(),
+    })?;
+    file.set_len(0)?;
+    file.seek(SeekFrom::Start(0))?;
+    file.write_all(&serialized)?;
+    file.flush()?;
+
+    Ok(Some(LockGuard {
+        lock,
+        path,
+        file: Some(file),
+        os_locked,
+    }))
+}
+
+/// Current holder of a lock, or `None` if it is free
+///
+/// A lock file whose holder has exited (or that was left behind by an older
+/// version of ODI) is reported as free.
+pub fn holder(locks_path: &Path, name: &str) -> Result<Option<StorageLock>> {
+    let path = lock_path(locks_path, name);
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let mut file = OpenOptions::new().read(true).write(true).open(&path)?;
+
+    match FileExt::try_lock_exclusive(&file) {
+        Ok(()) => {
+            FileExt::unlock(&file)?;
+            Ok(None)
+        }
+        Err(e) if e.kind() == fs4::lock_contended_error().kind() => {
+            let record = read_record(&mut file)?;
+            Ok(Some(record.unwrap_or_else(|| StorageLock {
+                name: name.to_string(),
+                acquired_at: Utc::now(),
+                process_id: 0,
+            })))
+        }
+        Err(_) => Ok(read_record(&mut file)?.filter(|lock| process_is_alive(lock.process_id))),
+    }
+}
+
+/// Check whether a process with the given PID is still running
+pub fn process_is_alive(pid: u32) -> bool {
+    if pid == std::process::id() {
+        return true;
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        Path::new("/proc").join(pid.to_string()).exists()
+    }
+
+    #[cfg(all(unix, not(target_os = "linux")))]
+    {
+        std::process::Command::new("kill")
+            .arg("-0")
+            .arg(pid.to_string())
+            .stderr(std::process::Stdio::null())
+            .status()
+            .map(|status| status.success())
+            .unwrap_or(true)
+    }
+
+    #[cfg(not(unix))]
+    {
+        // No cheap liveness check; assume the holder is alive
+        true
+    }
+}
+
+fn timeout_error(locks_path: &Path, name: &str) -> Result<FsError> {
+    let holder = match holder(locks_path, name)? {
+        Some(lock) => format!("process {} since {}", lock.process_id, lock.acquired_at),
+        None => "another process".to_string(),
+    };
+    Ok(FsError::LockTimeout {
+        name: name.to_string(),
+        holder,
+    })
+}
+
+fn read_record(file: &mut File) -> Result<Option<StorageLock>> {
+    let mut contents = Vec::new();
+    file.seek(SeekFrom::Start(0))?;
+    file.read_to_end(&mut contents)?;
+
+    if contents.is_empty() {
+        return Ok(None);
+    }
+
+    // An unreadable record cannot name a live holder
+    Ok(bincode::deserialize(&contents).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_guard_releases_on_drop() {
+        let dir = TempDir::new().unwrap();
+
+        let guard = try_acquire(dir.path(), "issues").unwrap().expect("lock should be free");
+        assert_eq!(guard.name(), "issues");
+        assert_eq!(holder(dir.path(), "issues").unwrap().map(|l| l.process_id), Some(std::process::id()));
+        assert!(try_acquire(dir.path(), "issues").unwrap().is_none());
+
+        drop(guard);
+        assert!(holder(dir.path(), "issues").unwrap().is_none());
+        assert!(try_acquire(dir.path(), "issues").unwrap().is_some());
+    }
+
+    #[test]
+    fn test_acquire_times_out() {
+        let dir = TempDir::new().unwrap();
+        let _guard = acquire(dir.path(), "users", &LockOptions::new()).unwrap();
+
+        let options = LockOptions::new()
+            .timeout(Duration::from_millis(50))
+            .retry_interval(Duration::from_millis(10));
+        match acquire(dir.path(), "users", &options) {
+            Err(FsError::LockTimeout { name, .. }) => assert_eq!(name, "users"),
+            other => panic!("expected lock timeout, got {:?}", other.map(|g| g.name().to_string())),
+        }
+    }
+
+    #[test]
+    fn test_stale_record_is_free() {
+        let dir = TempDir::new().unwrap();
+
+        // A record left by a process that no longer holds the OS lock
+        let stale = StorageLock {
+            name: "projects".to_string(),
+            acquired_at: Utc::now(),
+            process_id: u32::MAX,
+        };
+        fs::write(lock_path(dir.path(), "projects"), bincode::serialize(&stale).unwrap()).unwrap();
+
+        assert!(holder(dir.path(), "projects").unwrap().is_none());
+        assert!(try_acquire(dir.path(), "projects").unwrap().is_some());
+    }
+}
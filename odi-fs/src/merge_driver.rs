@@ -0,0 +1,535 @@
+//! Git merge driver for workspaces committed to their project's repository
+//!
+//! Refs are binary, so Git can't merge two branches that both changed an
+//! issue. `odi merge-driver install` marks them in `.gitattributes` to be
+//! merged by `odi merge-driver run`, which merges the objects they point at
+//! field by field (see [`odi_core::merge_objects`]) and always leaves a valid
+//! ref behind. Reflogs and other line-based files use Git's `union` driver;
+//! objects are content-addressed, so they never conflict.
+//!
+//! A merge that needs a new object, or where both sides changed the same
+//! field, is reported to Git as a conflict: the new object is written to the
+//! working tree but must still be staged, and conflicting fields are written
+//! to a [`ConflictRecord`] under `.odi/conflicts/` for review.
+//!
+//! Conflict records, locks, the search index and other state of one clone are
+//! excluded from Git, so they are neither committed nor merged.
+
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use odi_core::format::{Envelope, FORMAT_VERSION};
+use odi_core::{merge_objects, FieldConflict};
+use serde::{Deserialize, Serialize};
+
+use crate::crypto::{self, KeySource};
+use crate::git::DefaultGitIntegration;
+use crate::storage::{FileSystemStorage, ObjectStorage, StorageObject};
+use crate::{FsError, Result};
+
+/// Name of the merge driver in Git config and `.gitattributes`
+pub const DRIVER: &str = "odi";
+
+/// Directory inside `.odi` holding conflict records
+pub const CONFLICTS_DIR: &str = "conflicts";
+
+const BEGIN_MARKER: &str = "# BEGIN odi merge driver (odi merge-driver install)";
+const END_MARKER: &str = "# END odi merge driver";
+
+/// `.gitattributes` lines for a workspace at `prefix`, e.g. `.odi`
+fn attributes(prefix: &str) -> Vec<String> {
+    vec![
+        format!("{}/refs/** merge={}", prefix, DRIVER),
+        format!("{}/logs/** merge=union", prefix),
+        format!("{}/git-scanned merge=union", prefix),
+        format!("{}/objects/pack/deleted merge=union", prefix),
+    ]
+}
+
+/// `.gitattributes` content without the ODI block
+fn without_block(content: &str) -> String {
+    let mut kept = Vec::new();
+    let mut in_block = false;
+    for line in content.lines() {
+        match line {
+            BEGIN_MARKER => in_block = true,
+            END_MARKER => in_block = false,
+            _ if !in_block => kept.push(line),
+            _ => {}
+        }
+    }
+    while kept.last().is_some_and(|line| line.is_empty()) {
+        kept.pop();
+    }
+    kept.join("\n")
+}
+
+/// Paths inside the workspace that only concern one clone: conflict records,
+/// locks, temporary files, the search index, the cached Git paths of issues,
+/// the packs already pulled from each remote and the state of `odi mirror`
+fn local_state() -> Vec<String> {
+    vec![
+        format!("{}/", CONFLICTS_DIR),
+        "locks/".to_string(),
+        "tmp/".to_string(),
+        format!("{}/", crate::search::INDEX_DIR),
+        crate::git::PATHS_FILE.to_string(),
+        "fetched-packs.json".to_string(),
+        "mirror.json".to_string(),
+    ]
+}
+
+/// Exclude the workspace's local state from Git, as it only concerns this clone
+fn exclude_local_state(repo: &git2::Repository, prefix: &str, excluded: bool) -> Result<()> {
+    let path = DefaultGitIntegration::common_dir(repo).join("info").join("exclude");
+    let patterns: Vec<String> = local_state().iter().map(|name| format!("/{}/{}", prefix, name)).collect();
+    let content = std::fs::read_to_string(&path).unwrap_or_default();
+    let mut lines: Vec<&str> = content.lines().filter(|line| !patterns.iter().any(|pattern| pattern == line)).collect();
+    if excluded {
+        lines.extend(patterns.iter().map(String::as_str));
+    }
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&path, lines.join("\n") + "\n")?;
+    Ok(())
+}
+
+fn git_error(e: git2::Error) -> FsError {
+    FsError::GitError { message: e.message().to_string() }
+}
+
+/// Register the driver in the repository's config, mark the workspace's files
+/// in `.gitattributes` and keep its local state out of Git
+///
+/// `prefix` is the `.odi` directory relative to the working tree. Returns
+/// whether the attributes were already installed.
+pub fn install(workdir: &Path, prefix: &str) -> Result<bool> {
+    let repo = git2::Repository::open(workdir).map_err(git_error)?;
+    let mut config = repo.config().and_then(|config| config.open_level(git2::ConfigLevel::Local)).map_err(git_error)?;
+    config.set_str(&format!("merge.{}.name", DRIVER), "ODI objects and refs").map_err(git_error)?;
+    config.set_str(&format!("merge.{}.driver", DRIVER), "odi merge-driver run %O %A %B %P").map_err(git_error)?;
+    exclude_local_state(&repo, prefix, true)?;
+
+    let path = workdir.join(".gitattributes");
+    let content = match std::fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e.into()),
+    };
+    let existed = content.lines().any(|line| line == BEGIN_MARKER);
+
+    let mut updated = without_block(&content);
+    if !updated.is_empty() {
+        updated.push_str("\n\n");
+    }
+    updated.push_str(BEGIN_MARKER);
+    for line in attributes(prefix) {
+        updated.push('\n');
+        updated.push_str(&line);
+    }
+    updated.push('\n');
+    updated.push_str(END_MARKER);
+    updated.push('\n');
+    std::fs::write(&path, updated)?;
+    Ok(existed)
+}
+
+/// Remove the driver from the repository's config and `.gitattributes`, and
+/// the workspace at `prefix` from its exclusions
+///
+/// Returns whether it was installed.
+pub fn uninstall(workdir: &Path, prefix: &str) -> Result<bool> {
+    let repo = git2::Repository::open(workdir).map_err(git_error)?;
+    let mut config = repo.config().and_then(|config| config.open_level(git2::ConfigLevel::Local)).map_err(git_error)?;
+    for key in ["name", "driver"] {
+        // Missing entries are fine; the driver may only be half installed
+        let _ = config.remove(&format!("merge.{}.{}", DRIVER, key));
+    }
+    exclude_local_state(&repo, prefix, false)?;
+
+    let path = workdir.join(".gitattributes");
+    let Ok(content) = std::fs::read_to_string(&path) else {
+        return Ok(false);
+    };
+    if !content.lines().any(|line| line == BEGIN_MARKER) {
+        return Ok(false);
+    }
+    let remaining = without_block(&content);
+    if remaining.is_empty() {
+        std::fs::remove_file(&path)?;
+    } else {
+        std::fs::write(&path, remaining + "\n")?;
+    }
+    Ok(true)
+}
+
+/// A ref the driver could not merge cleanly
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConflictRecord {
+    /// Ref name, e.g. `issues/<id>`
+    pub name: String,
+    /// Object hashes of each side; `base` is `None` when both sides created the ref
+    pub base: Option<String>,
+    pub ours: String,
+    pub theirs: String,
+    /// Object the merged ref points at
+    pub result: String,
+    /// Fields both sides changed to different values
+    pub fields: Vec<FieldConflict>,
+    /// Why the objects couldn't be merged at all, if they couldn't
+    pub reason: Option<String>,
+    pub detected_at: DateTime<Utc>,
+}
+
+fn record_path(odi_dir: &Path, name: &str) -> PathBuf {
+    odi_dir.join(CONFLICTS_DIR).join(format!("{}.json", name))
+}
+
+fn write_record(odi_dir: &Path, record: &ConflictRecord) -> Result<()> {
+    let path = record_path(odi_dir, &record.name);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, serde_json::to_vec_pretty(record)?)?;
+    Ok(())
+}
+
+/// Every conflict recorded by the driver, by ref name
+pub fn read_conflicts(odi_dir: &Path) -> Result<Vec<ConflictRecord>> {
+    fn walk(dir: &Path, records: &mut Vec<ConflictRecord>) -> Result<()> {
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                walk(&path, records)?;
+            } else if path.extension().is_some_and(|ext| ext == "json") {
+                records.push(serde_json::from_slice(&std::fs::read(&path)?)?);
+            }
+        }
+        Ok(())
+    }
+
+    let mut records = Vec::new();
+    let dir = odi_dir.join(CONFLICTS_DIR);
+    if dir.exists() {
+        walk(&dir, &mut records)?;
+    }
+    records.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(records)
+}
+
+/// Remove every conflict record, returning how many there were
+pub fn clear_conflicts(odi_dir: &Path) -> Result<usize> {
+    let count = read_conflicts(odi_dir)?.len();
+    let dir = odi_dir.join(CONFLICTS_DIR);
+    if dir.exists() {
+        std::fs::remove_dir_all(dir)?;
+    }
+    Ok(count)
+}
+
+/// What merging a ref did
+#[derive(Debug, Clone)]
+pub enum MergeOutcome {
+    /// One side's ref was kept; Git can finish the merge
+    Clean,
+    /// Both sides' changes were merged into a new object, which must be staged
+    Merged,
+    /// Some changes conflicted, or the objects couldn't be read
+    Conflicted(ConflictRecord),
+}
+
+/// Merge the three versions of a ref file, leaving the result in `ours`
+///
+/// Called by Git with the temporary files of the common ancestor (empty when
+/// there is none), our side and their side, and `path`, the ref file's path
+/// in the working tree, e.g. `.odi/refs/issues/<id>`.
+pub fn merge_ref(workdir: &Path, path: &str, base: &Path, ours: &Path, theirs: &Path) -> Result<MergeOutcome> {
+    let base_bytes = std::fs::read(base)?;
+    let ours_bytes = std::fs::read(ours)?;
+    let theirs_bytes = std::fs::read(theirs)?;
+    if ours_bytes == theirs_bytes || base_bytes == theirs_bytes {
+        return Ok(MergeOutcome::Clean);
+    }
+    if base_bytes == ours_bytes {
+        std::fs::write(ours, &theirs_bytes)?;
+        return Ok(MergeOutcome::Clean);
+    }
+
+    let prefix = path.find(".odi/refs/").map(|i| &path[..i + 4]).ok_or_else(|| FsError::StorageError {
+        message: format!("{} is not a ref of an ODI workspace", path),
+    })?;
+    let base_ref = if base_bytes.is_empty() { None } else { Some(FileSystemStorage::decode_ref(&base_bytes)?) };
+    let ours_ref = FileSystemStorage::decode_ref(&ours_bytes)?;
+    let theirs_ref = FileSystemStorage::decode_ref(&theirs_bytes)?;
+    if ours_ref.hash == theirs_ref.hash {
+        return Ok(MergeOutcome::Clean);
+    }
+
+    let odi_dir = workdir.join(prefix);
+    let mut record = ConflictRecord {
+        name: ours_ref.name.clone(),
+        base: base_ref.as_ref().map(|base| base.hash.clone()),
+        ours: ours_ref.hash.clone(),
+        theirs: theirs_ref.hash.clone(),
+        result: ours_ref.hash.clone(),
+        fields: Vec::new(),
+        reason: None,
+        detected_at: Utc::now(),
+    };
+
+    let objects = ObjectReader::open(workdir, &odi_dir, prefix)?;
+    let payloads = (|| -> Result<_> {
+        let base = base_ref.as_ref().map(|base| objects.payload(&base.hash)).transpose()?;
+        Ok((base, objects.payload(&ours_ref.hash)?, objects.payload(&theirs_ref.hash)?))
+    })();
+    let (base, ours_payload, theirs_payload) = match payloads {
+        Ok(payloads) => payloads,
+        Err(e) => {
+            // Keep our ref rather than write something half merged
+            record.reason = Some(e.to_string());
+            write_record(&odi_dir, &record)?;
+            return Ok(MergeOutcome::Conflicted(record));
+        }
+    };
+
+    let merged = merge_objects(&ours_payload.kind, base.as_ref().map(|base| &base.data), &ours_payload.data, &theirs_payload.data);
+    let outcome_hash = if merged.value == ours_payload.data {
+        None
+    } else if merged.value == theirs_payload.data {
+        std::fs::write(ours, &theirs_bytes)?;
+        record.result = theirs_ref.hash.clone();
+        None
+    } else {
+        let envelope = Envelope { odi: FORMAT_VERSION, kind: ours_payload.kind.clone(), data: merged.value };
+        let hash = objects.storage.store_object(ours_ref.object_type, &serde_json::to_vec(&envelope)?)?;
+        std::fs::write(ours, FileSystemStorage::encode_ref(&ours_ref.name, &hash, ours_ref.object_type)?)?;
+        record.result = hash.clone();
+        Some(hash)
+    };
+
+    if !merged.conflicts.is_empty() {
+        record.fields = merged.conflicts;
+        write_record(&odi_dir, &record)?;
+        return Ok(MergeOutcome::Conflicted(record));
+    }
+    Ok(if outcome_hash.is_some() { MergeOutcome::Merged } else { MergeOutcome::Clean })
+}
+
+/// Reads objects from the working tree, or from Git history for objects the
+/// other side of a merge added and Git hasn't checked out yet
+struct ObjectReader {
+    storage: FileSystemStorage,
+    repo: Option<git2::Repository>,
+    prefix: String,
+}
+
+impl ObjectReader {
+    fn open(workdir: &Path, odi_dir: &Path, prefix: &str) -> Result<Self> {
+        let mut storage = FileSystemStorage::new(odi_dir.to_path_buf())?;
+        if storage.is_encrypted() {
+            if let Some(source) = KeySource::from_env() {
+                storage = storage.with_cipher(crypto::unlock(odi_dir, &source)?);
+            }
+        }
+        let repo = git2::Repository::open(workdir).ok();
+        Ok(Self { storage, repo, prefix: prefix.to_string() })
+    }
+
+    fn object(&self, hash: &str) -> Result<StorageObject> {
+        if let Some(object) = self.storage.retrieve_object(hash)? {
+            return Ok(object);
+        }
+        let bytes = self.read_from_git(hash).ok_or_else(|| FsError::StorageError {
+            message: format!("Object {} is neither in the workspace nor in Git history", hash),
+        })?;
+        self.storage.decode_foreign(hash, &bytes)
+    }
+
+    /// The object file as committed on any branch, or on a commit being merged
+    fn read_from_git(&self, hash: &str) -> Option<Vec<u8>> {
+        let repo = self.repo.as_ref()?;
+        let path = PathBuf::from(format!("{}/objects/{}/{}", self.prefix, &hash[..2], &hash[2..]));
+
+        let mut revisions: Vec<String> = ["HEAD", "MERGE_HEAD", "CHERRY_PICK_HEAD", "REVERT_HEAD", "REBASE_HEAD"]
+            .iter()
+            .map(|name| name.to_string())
+            .collect();
+        // git merge names the commits being merged in GITHEAD_<sha> variables
+        revisions.extend(std::env::vars().filter_map(|(key, _)| key.strip_prefix("GITHEAD_").map(str::to_string)));
+        if let Ok(references) = repo.references() {
+            revisions.extend(references.flatten().filter_map(|reference| reference.name().map(str::to_string)));
+        }
+
+        revisions.iter().find_map(|revision| {
+            let tree = repo.revparse_single(revision).ok()?.peel_to_tree().ok()?;
+            let entry = tree.get_path(&path).ok()?;
+            Some(repo.find_blob(entry.id()).ok()?.content().to_vec())
+        })
+    }
+
+    /// The object's payload, upgraded to the current format
+    fn payload(&self, hash: &str) -> Result<Envelope> {
+        Ok(Envelope::parse(&self.object(hash)?.data)?.upgrade()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use odi_core::{format, Issue, IssueStatus};
+    use crate::storage::ObjectType;
+    use tempfile::TempDir;
+
+    struct Fixture {
+        dir: TempDir,
+        storage: FileSystemStorage,
+    }
+
+    impl Fixture {
+        fn new() -> Self {
+            let dir = TempDir::new().unwrap();
+            git2::Repository::init(dir.path()).unwrap();
+            let storage = FileSystemStorage::new(dir.path().join(".odi")).unwrap();
+            Self { dir, storage }
+        }
+
+        /// Store an issue and write a ref file for it outside the workspace
+        fn version(&self, file: &str, issue: &Issue) -> (PathBuf, String) {
+            let hash = self.storage.store_object(ObjectType::Issue, &format::seal(issue).unwrap()).unwrap();
+            let path = self.dir.path().join(file);
+            let name = format!("issues/{}", issue.id);
+            std::fs::write(&path, FileSystemStorage::encode_ref(&name, &hash, ObjectType::Issue).unwrap()).unwrap();
+            (path, hash)
+        }
+
+        fn merge(&self, base: &Path, ours: &Path, theirs: &Path, issue: &Issue) -> MergeOutcome {
+            merge_ref(self.dir.path(), &format!(".odi/refs/issues/{}", issue.id), base, ours, theirs).unwrap()
+        }
+
+        fn merged_issue(&self, ours: &Path) -> Issue {
+            let object_ref = FileSystemStorage::decode_ref(&std::fs::read(ours).unwrap()).unwrap();
+            let object = self.storage.retrieve_object(&object_ref.hash).unwrap().unwrap();
+            format::open(&object.data).unwrap()
+        }
+    }
+
+    #[test]
+    fn test_merge_ref_combines_both_sides() {
+        let fixture = Fixture::new();
+        let issue = Issue::new("Login fails".to_string(), "alice".to_string());
+        let (base, _) = fixture.version("base", &issue);
+        let mut ours = issue.clone();
+        ours.title = "Login fails on Safari".to_string();
+        let (ours_path, _) = fixture.version("ours", &ours);
+        let mut theirs = issue.clone();
+        theirs.status = IssueStatus::InProgress;
+        let (theirs_path, theirs_hash) = fixture.version("theirs", &theirs);
+
+        // Their object was only committed on their branch
+        let repo = git2::Repository::open(fixture.dir.path()).unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new(&format!(".odi/objects/{}/{}", &theirs_hash[..2], &theirs_hash[2..]))).unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = git2::Signature::now("a", "a@example.com").unwrap();
+        repo.commit(Some("refs/heads/theirs"), &signature, &signature, "theirs", &tree, &[]).unwrap();
+        std::fs::remove_file(fixture.dir.path().join(format!(".odi/objects/{}/{}", &theirs_hash[..2], &theirs_hash[2..]))).unwrap();
+
+        assert!(matches!(fixture.merge(&base, &ours_path, &theirs_path, &issue), MergeOutcome::Merged));
+        let merged = fixture.merged_issue(&ours_path);
+        assert_eq!(merged.title, "Login fails on Safari");
+        assert_eq!(merged.status, IssueStatus::InProgress);
+
+        // A side that changed nothing is simply replaced
+        let (unchanged, _) = fixture.version("unchanged", &issue);
+        assert!(matches!(fixture.merge(&base, &unchanged, &theirs_path, &issue), MergeOutcome::Clean));
+        assert_eq!(std::fs::read(&unchanged).unwrap(), std::fs::read(&theirs_path).unwrap());
+    }
+
+    #[test]
+    fn test_conflicting_fields_are_recorded() {
+        let fixture = Fixture::new();
+        let issue = Issue::new("Login fails".to_string(), "alice".to_string());
+        let (base, _) = fixture.version("base", &issue);
+        let mut ours = issue.clone();
+        ours.title = "Ours".to_string();
+        let (ours_path, ours_hash) = fixture.version("ours", &ours);
+        let mut theirs = issue.clone();
+        theirs.title = "Theirs".to_string();
+        let (theirs_path, _) = fixture.version("theirs", &theirs);
+
+        let MergeOutcome::Conflicted(record) = fixture.merge(&base, &ours_path, &theirs_path, &issue) else {
+            panic!("expected a conflict");
+        };
+        assert_eq!(record.fields[0].field, "title");
+        assert_eq!(record.result, ours_hash);
+        assert_eq!(fixture.merged_issue(&ours_path).title, "Ours");
+
+        let odi_dir = fixture.dir.path().join(".odi");
+        assert_eq!(read_conflicts(&odi_dir).unwrap()[0].name, format!("issues/{}", issue.id));
+        assert_eq!(clear_conflicts(&odi_dir).unwrap(), 1);
+        assert!(read_conflicts(&odi_dir).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_install_keeps_other_attributes() {
+        let fixture = Fixture::new();
+        let workdir = fixture.dir.path();
+        std::fs::write(workdir.join(".gitattributes"), "*.png binary\n").unwrap();
+
+        assert!(!install(workdir, ".odi").unwrap());
+        assert!(install(workdir, ".odi").unwrap());
+        let content = std::fs::read_to_string(workdir.join(".gitattributes")).unwrap();
+        assert!(content.starts_with("*.png binary\n\n"));
+        assert_eq!(content.matches(".odi/refs/** merge=odi").count(), 1);
+        let config = git2::Repository::open(workdir).unwrap().config().unwrap();
+        assert_eq!(config.get_string("merge.odi.driver").unwrap(), "odi merge-driver run %O %A %B %P");
+
+        let exclude = workdir.join(".git/info/exclude");
+        let excluded = std::fs::read_to_string(&exclude).unwrap();
+        assert_eq!(excluded.matches("/.odi/conflicts/").count(), 1);
+        assert!(excluded.contains("/.odi/index/") && excluded.contains("/.odi/locks/"));
+
+        assert!(uninstall(workdir, ".odi").unwrap());
+        assert_eq!(std::fs::read_to_string(workdir.join(".gitattributes")).unwrap(), "*.png binary\n");
+        assert!(!std::fs::read_to_string(&exclude).unwrap().contains("/.odi/"));
+        assert!(!uninstall(workdir, ".odi").unwrap());
+    }
+
+    #[test]
+    fn test_local_state_is_not_merged() {
+        let fixture = Fixture::new();
+        let workdir = fixture.dir.path();
+        install(workdir, ".odi").unwrap();
+        let repo = git2::Repository::open(workdir).unwrap();
+        let signature = git2::Signature::now("a", "a@example.com").unwrap();
+        let odi_dir = workdir.join(".odi");
+
+        // Commit everything Git doesn't ignore, as `git add .odi` would
+        let commit = |branch: &str, search_index: &str, parents: &[&git2::Commit]| {
+            std::fs::create_dir_all(odi_dir.join("index")).unwrap();
+            std::fs::write(odi_dir.join("index/search.idx"), search_index).unwrap();
+            std::fs::write(odi_dir.join("locks/issues.lock"), branch).unwrap();
+            std::fs::write(odi_dir.join("mirror.json"), branch).unwrap();
+            let issue = Issue::new(branch.to_string(), "alice".to_string());
+            fixture.storage.store_object(ObjectType::Issue, &format::seal(&issue).unwrap()).unwrap();
+
+            let mut index = repo.index().unwrap();
+            index.add_all([".odi"], git2::IndexAddOption::DEFAULT, None).unwrap();
+            let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+            let oid = repo.commit(Some(&format!("refs/heads/{}", branch)), &signature, &signature, branch, &tree, parents).unwrap();
+            repo.find_commit(oid).unwrap()
+        };
+        let base = commit("base", "base index", &[]);
+        let ours = commit("ours", "our index", &[&base]);
+        let theirs = commit("theirs", "their index", &[&base]);
+
+        let merged = repo.merge_commits(&ours, &theirs, None).unwrap();
+        assert!(!merged.has_conflicts());
+        let paths: Vec<String> = merged.iter().map(|entry| String::from_utf8(entry.path).unwrap()).collect();
+        assert_eq!(paths.iter().filter(|path| path.starts_with(".odi/objects/")).count(), 3);
+        for path in &paths {
+            assert!(!path.starts_with(".odi/index/") && !path.starts_with(".odi/locks/") && path != ".odi/mirror.json", "{} was committed", path);
+        }
+    }
+}
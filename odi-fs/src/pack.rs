@@ -0,0 +1,643 @@
+//! Packfiles for the object store
+//!
+//! `odi gc` moves loose objects into a single packfile under
+//! `.odi/objects/pack/`. A pack is a sequence of length-prefixed entries; each
+//! entry holds either a whole gzip-compressed object or a delta against an
+//! earlier entry in the same pack. Successive versions of the same entity
+//! (objects whose envelopes share a kind and `id`) are delta-encoded against
+//! each other, so the history of an issue costs little more than its latest
+//! version. As in Git, the deltas run backwards: the newest version, which is
+//! read most often, is stored whole and each older one as a delta against the
+//! next newer one.
+//!
+//! Every `pack-<hash>.pack` has a `pack-<hash>.idx` alongside it, sorted by
+//! object hash, for random access. A pack only becomes visible once its index
+//! exists. The same pack bytes, without the index, are used to transfer many
+//! objects to and from remotes at once.
+//!
+//! Packed objects cannot be removed individually. Deleting one records its hash
+//! in `pack/deleted`; it is hidden from then on and dropped by the next gc.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::ffi::OsString;
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use serde::{Deserialize, Serialize};
+
+use crate::storage::{FileSystemStorage, ObjectStorage, ObjectType, StorageObject};
+use crate::{FsError, Result};
+
+/// Directory inside `objects/` holding packfiles
+pub const PACK_DIR: &str = "pack";
+
+/// Hashes of packed objects deleted since the last gc
+const DELETED_FILE: &str = "deleted";
+
+const MAGIC: &[u8; 4] = b"ODIP";
+const PACK_VERSION: u32 = 1;
+
+/// Longest chain of deltas before an entry is stored whole again
+const MAX_DELTA_DEPTH: usize = 50;
+
+/// Size of the blocks matched between a delta base and its target
+const BLOCK_SIZE: usize = 16;
+
+/// An object with its uncompressed data, as written to or read from a pack
+#[derive(Debug, Clone)]
+pub struct PackObject {
+    pub hash: String,
+    pub object_type: ObjectType,
+    pub data: Vec<u8>,
+}
+
+impl PackObject {
+    pub fn new(object_type: ObjectType, data: Vec<u8>) -> Self {
+        Self {
+            hash: FileSystemStorage::get_object_hash(&data),
+            object_type,
+            data,
+        }
+    }
+}
+
+/// Summary of a written pack
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PackStats {
+    /// Objects in the pack
+    pub objects: usize,
+    /// Objects stored as deltas
+    pub deltas: usize,
+    /// Size of the pack in bytes
+    pub size: usize,
+}
+
+/// Result of packing the object store
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct GcStats {
+    /// Loose objects moved into the pack
+    pub loose: usize,
+    /// Deleted objects dropped from earlier packs
+    pub pruned: usize,
+    pub pack: PackStats,
+}
+
+#[derive(Serialize, Deserialize)]
+enum Payload {
+    Full(Vec<u8>),
+    Delta { base: String, delta: Vec<u8> },
+}
+
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    hash: String,
+    object_type: ObjectType,
+    payload: Payload,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct IndexEntry {
+    hash: String,
+    object_type: ObjectType,
+    offset: u64,
+    length: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Index {
+    version: u32,
+    entries: Vec<IndexEntry>,
+}
+
+#[derive(Serialize, Deserialize)]
+enum DeltaOp {
+    Copy { offset: u32, len: u32 },
+    Insert(Vec<u8>),
+}
+
+fn corrupt(message: impl Into<String>) -> FsError {
+    FsError::StorageError { message: format!("Corrupt pack: {}", message.into()) }
+}
+
+fn serialization_error(e: bincode::Error) -> FsError {
+    FsError::SerializationError { message: e.to_string() }
+}
+
+fn compress(data: &[u8]) -> Result<Vec<u8>> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(data)?;
+    Ok(encoder.finish()?)
+}
+
+fn decompress(data: &[u8]) -> Result<Vec<u8>> {
+    let mut result = Vec::new();
+    GzDecoder::new(data).read_to_end(&mut result)?;
+    Ok(result)
+}
+
+/// Describe `target` as copies from `base` and inserted bytes
+fn diff(base: &[u8], target: &[u8]) -> Vec<DeltaOp> {
+    let mut blocks: HashMap<&[u8], usize> = HashMap::new();
+    for start in (0..base.len().saturating_sub(BLOCK_SIZE - 1)).step_by(BLOCK_SIZE) {
+        blocks.entry(&base[start..start + BLOCK_SIZE]).or_insert(start);
+    }
+
+    let mut ops = Vec::new();
+    let mut pending = Vec::new();
+    let mut pos = 0;
+
+    while pos < target.len() {
+        let found = target.get(pos..pos + BLOCK_SIZE).and_then(|block| blocks.get(block).copied());
+        let Some(start) = found else {
+            pending.push(target[pos]);
+            pos += 1;
+            continue;
+        };
+
+        // Grow the match forwards, then backwards over bytes not yet emitted
+        let mut len = BLOCK_SIZE;
+        while start + len < base.len() && pos + len < target.len() && base[start + len] == target[pos + len] {
+            len += 1;
+        }
+        let mut back = 0;
+        while back < pending.len() && back < start && base[start - back - 1] == target[pos - back - 1] {
+            back += 1;
+        }
+        pending.truncate(pending.len() - back);
+
+        if !pending.is_empty() {
+            ops.push(DeltaOp::Insert(std::mem::take(&mut pending)));
+        }
+        ops.push(DeltaOp::Copy { offset: (start - back) as u32, len: (len + back) as u32 });
+        pos += len;
+    }
+
+    if !pending.is_empty() {
+        ops.push(DeltaOp::Insert(pending));
+    }
+    ops
+}
+
+/// Rebuild a target from its base and delta
+fn patch(base: &[u8], ops: &[DeltaOp]) -> Result<Vec<u8>> {
+    let mut result = Vec::new();
+    for op in ops {
+        match op {
+            DeltaOp::Copy { offset, len } => {
+                let range = *offset as usize..(*offset as usize + *len as usize);
+                let bytes = base.get(range).ok_or_else(|| corrupt("delta copies past the end of its base"))?;
+                result.extend_from_slice(bytes);
+            }
+            DeltaOp::Insert(bytes) => result.extend_from_slice(bytes),
+        }
+    }
+    Ok(result)
+}
+
+/// Entity an object is a version of, and when that version was written
+fn identity(data: &[u8]) -> Option<((String, String), String)> {
+    let envelope = odi_core::format::Envelope::parse(data).ok()?;
+    let id = match envelope.data.get("id")? {
+        serde_json::Value::String(id) => id.clone(),
+        other => other.to_string(),
+    };
+    let written = envelope.data.get("updated_at").and_then(|v| v.as_str()).unwrap_or_default().to_string();
+    Some(((envelope.kind, id), written))
+}
+
+/// Order objects so versions of the same entity are adjacent, newest first
+fn delta_chains(objects: Vec<PackObject>) -> Vec<Vec<PackObject>> {
+    let mut seen = HashSet::new();
+    let mut versions: BTreeMap<(String, String), Vec<(String, PackObject)>> = BTreeMap::new();
+    let mut chains = Vec::new();
+
+    for object in objects {
+        if !seen.insert(object.hash.clone()) {
+            continue;
+        }
+        match identity(&object.data) {
+            Some((key, written)) => versions.entry(key).or_default().push((written, object)),
+            None => chains.push(vec![object]),
+        }
+    }
+
+    for (_, mut chain) in versions {
+        chain.sort_by(|a, b| (&b.0, &b.1.hash).cmp(&(&a.0, &a.1.hash)));
+        chains.push(chain.into_iter().map(|(_, object)| object).collect());
+    }
+    chains
+}
+
+/// Encode objects as pack bytes, returning the index alongside
+fn encode_indexed(objects: Vec<PackObject>) -> Result<(Vec<u8>, Vec<IndexEntry>, PackStats)> {
+    let mut bytes = MAGIC.to_vec();
+    bytes.extend_from_slice(&PACK_VERSION.to_le_bytes());
+    let mut index = Vec::new();
+    let mut stats = PackStats::default();
+
+    for chain in delta_chains(objects) {
+        // Each version is a delta against the next newer one, written just before it
+        let mut previous: Option<&PackObject> = None;
+        let mut depth = 0;
+
+        for object in &chain {
+            let full = compress(&object.data)?;
+            let mut payload = Payload::Full(full);
+
+            if let Some(base) = previous.filter(|_| depth < MAX_DELTA_DEPTH) {
+                let ops = bincode::serialize(&diff(&base.data, &object.data)).map_err(serialization_error)?;
+                let delta = compress(&ops)?;
+                if matches!(&payload, Payload::Full(full) if delta.len() < full.len()) {
+                    payload = Payload::Delta { base: base.hash.clone(), delta };
+                }
+            }
+
+            if matches!(payload, Payload::Delta { .. }) {
+                depth += 1;
+                stats.deltas += 1;
+            } else {
+                depth = 0;
+            }
+
+            let entry = Entry { hash: object.hash.clone(), object_type: object.object_type, payload };
+            let serialized = bincode::serialize(&entry).map_err(serialization_error)?;
+            bytes.extend_from_slice(&(serialized.len() as u32).to_le_bytes());
+            index.push(IndexEntry {
+                hash: object.hash.clone(),
+                object_type: object.object_type,
+                offset: bytes.len() as u64,
+                length: serialized.len() as u32,
+            });
+            bytes.extend_from_slice(&serialized);
+
+            stats.objects += 1;
+            previous = Some(object);
+        }
+    }
+
+    index.sort_by(|a, b| a.hash.cmp(&b.hash));
+    stats.size = bytes.len();
+    Ok((bytes, index, stats))
+}
+
+/// Encode objects as a pack, delta-encoding successive versions of each entity
+pub fn encode(objects: Vec<PackObject>) -> Result<(Vec<u8>, PackStats)> {
+    let (bytes, _, stats) = encode_indexed(objects)?;
+    Ok((bytes, stats))
+}
+
+/// Name of a pack, derived from its contents
+pub fn pack_name(bytes: &[u8]) -> String {
+    format!("pack-{}", FileSystemStorage::get_object_hash(bytes))
+}
+
+/// Resolve an entry to the object it stores, verifying its hash
+fn resolve(entry: Entry, base: impl FnOnce(&str) -> Result<Vec<u8>>) -> Result<PackObject> {
+    let data = match entry.payload {
+        Payload::Full(compressed) => decompress(&compressed)?,
+        Payload::Delta { base: base_hash, delta } => {
+            let ops: Vec<DeltaOp> = bincode::deserialize(&decompress(&delta)?).map_err(serialization_error)?;
+            patch(&base(&base_hash)?, &ops)?
+        }
+    };
+
+    let object = PackObject::new(entry.object_type, data);
+    if object.hash != entry.hash {
+        return Err(corrupt(format!("object {} does not match its hash", entry.hash)));
+    }
+    Ok(object)
+}
+
+/// Decode every object in pack bytes
+pub fn decode(bytes: &[u8]) -> Result<Vec<PackObject>> {
+    let header = MAGIC.len() + 4;
+    if bytes.len() < header || &bytes[..MAGIC.len()] != MAGIC {
+        return Err(corrupt("missing pack header"));
+    }
+    let version = u32::from_le_bytes(bytes[MAGIC.len()..header].try_into().unwrap_or_default());
+    if version > PACK_VERSION {
+        return Err(FsError::StorageError {
+            message: format!("Pack version {} is newer than this version of odi supports ({})", version, PACK_VERSION),
+        });
+    }
+
+    let mut objects: Vec<PackObject> = Vec::new();
+    let mut positions: HashMap<String, usize> = HashMap::new();
+    let mut pos = header;
+
+    while pos < bytes.len() {
+        let length = bytes.get(pos..pos + 4).ok_or_else(|| corrupt("truncated entry"))?;
+        let length = u32::from_le_bytes(length.try_into().unwrap_or_default()) as usize;
+        let serialized = bytes.get(pos + 4..pos + 4 + length).ok_or_else(|| corrupt("truncated entry"))?;
+        pos += 4 + length;
+
+        let entry: Entry = bincode::deserialize(serialized).map_err(serialization_error)?;
+        let object = resolve(entry, |base| {
+            positions.get(base)
+                .map(|&i| objects[i].data.clone())
+                .ok_or_else(|| corrupt(format!("delta base {} precedes its use", base)))
+        })?;
+        positions.insert(object.hash.clone(), objects.len());
+        objects.push(object);
+    }
+    Ok(objects)
+}
+
+/// A pack on disk with its index loaded
+struct Pack {
+    path: PathBuf,
+    index: Vec<IndexEntry>,
+}
+
+impl Pack {
+    fn load(idx_path: &Path) -> Result<Self> {
+        let index: Index = bincode::deserialize(&fs::read(idx_path)?).map_err(serialization_error)?;
+        if index.version > PACK_VERSION {
+            return Err(FsError::StorageError {
+                message: format!("Pack index version {} is newer than this version of odi supports ({})", index.version, PACK_VERSION),
+            });
+        }
+        Ok(Self {
+            path: idx_path.with_extension("pack"),
+            index: index.entries,
+        })
+    }
+
+    fn find(&self, hash: &str) -> Option<&IndexEntry> {
+        self.index
+            .binary_search_by(|entry| entry.hash.as_str().cmp(hash))
+            .ok()
+            .map(|i| &self.index[i])
+    }
+
+    fn read(&self, hash: &str) -> Result<Option<PackObject>> {
+        if self.find(hash).is_none() {
+            return Ok(None);
+        }
+        self.read_from(&mut File::open(&self.path)?, hash, 0)
+    }
+
+    /// Read an entry and, for a delta, its bases from an open pack file
+    fn read_from(&self, file: &mut File, hash: &str, depth: usize) -> Result<Option<PackObject>> {
+        let Some(location) = self.find(hash) else {
+            return Ok(None);
+        };
+        if depth > MAX_DELTA_DEPTH {
+            return Err(corrupt(format!("delta chain for {} is too long", hash)));
+        }
+
+        file.seek(SeekFrom::Start(location.offset))?;
+        let mut serialized = vec![0; location.length as usize];
+        file.read_exact(&mut serialized)?;
+
+        let entry: Entry = bincode::deserialize(&serialized).map_err(serialization_error)?;
+        resolve(entry, |base| {
+            self.read_from(file, base, depth + 1)?
+                .map(|object| object.data)
+                .ok_or_else(|| corrupt(format!("missing delta base {}", base)))
+        })
+        .map(Some)
+    }
+}
+
+/// Directory listing used to notice packs written or removed by other processes
+type Stamp = Vec<(OsString, u64, Option<SystemTime>)>;
+
+/// All packs in the store, as of `stamp`
+struct PackSet {
+    packs: Vec<Pack>,
+    deleted: HashSet<String>,
+    stamp: Stamp,
+}
+
+/// Packed half of the object store
+pub(crate) struct PackStore {
+    dir: PathBuf,
+    cache: Mutex<Option<Arc<PackSet>>>,
+}
+
+impl PackStore {
+    pub(crate) fn new(objects_path: &Path) -> Self {
+        Self {
+            dir: objects_path.join(PACK_DIR),
+            cache: Mutex::new(None),
+        }
+    }
+
+    fn stamp(&self) -> Result<Stamp> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut stamp = Vec::new();
+        for entry in entries {
+            let entry = entry?;
+            let metadata = entry.metadata()?;
+            stamp.push((entry.file_name(), metadata.len(), metadata.modified().ok()));
+        }
+        stamp.sort();
+        Ok(stamp)
+    }
+
+    /// The current pack set, reloaded if the pack directory changed
+    fn current(&self) -> Result<Arc<PackSet>> {
+        let stamp = self.stamp()?;
+        let mut cache = self.cache.lock().map_err(|_| FsError::StorageError { message: "Pack cache poisoned".to_string() })?;
+        if let Some(set) = cache.as_ref().filter(|set| set.stamp == stamp) {
+            return Ok(set.clone());
+        }
+
+        let mut packs = Vec::new();
+        for (name, _, _) in &stamp {
+            let path = self.dir.join(name);
+            if path.extension().is_some_and(|ext| ext == "idx") {
+                packs.push(Pack::load(&path)?);
+            }
+        }
+        let deleted = match fs::read_to_string(self.dir.join(DELETED_FILE)) {
+            Ok(contents) => contents.lines().map(str::to_string).collect(),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => HashSet::new(),
+            Err(e) => return Err(e.into()),
+        };
+
+        let set = Arc::new(PackSet { packs, deleted, stamp });
+        *cache = Some(set.clone());
+        Ok(set)
+    }
+
+    /// Read a packed object
+    pub(crate) fn get(&self, hash: &str) -> Result<Option<StorageObject>> {
+        let mut retried = false;
+        loop {
+            let set = self.current()?;
+            if set.deleted.contains(hash) {
+                return Ok(None);
+            }
+
+            let mut result = Ok(None);
+            for pack in &set.packs {
+                if let Some(location) = pack.find(hash) {
+                    let length = location.length as usize;
+                    result = pack.read(hash).map(|object| object.map(|object| StorageObject {
+                        object_type: object.object_type,
+                        hash: object.hash,
+                        size: object.data.len(),
+                        compressed_size: length,
+                        data: object.data,
+                    }));
+                    break;
+                }
+            }
+
+            // A concurrent gc may have replaced the pack since it was listed
+            match result {
+                Err(FsError::Io(e)) if e.kind() == std::io::ErrorKind::NotFound && !retried => retried = true,
+                result => return result,
+            }
+        }
+    }
+
+    /// Whether a live packed object has this hash
+    pub(crate) fn contains(&self, hash: &str) -> Result<bool> {
+        let set = self.current()?;
+        Ok(!set.deleted.contains(hash) && set.packs.iter().any(|pack| pack.find(hash).is_some()))
+    }
+
+    /// Hashes and types of every live packed object
+    pub(crate) fn entries(&self) -> Result<Vec<(String, ObjectType)>> {
+        let set = self.current()?;
+        let mut seen = HashSet::new();
+        let mut entries = Vec::new();
+        for pack in &set.packs {
+            for entry in &pack.index {
+                if !set.deleted.contains(&entry.hash) && seen.insert(entry.hash.clone()) {
+                    entries.push((entry.hash.clone(), entry.object_type));
+                }
+            }
+        }
+        Ok(entries)
+    }
+
+    /// Hide a packed object until the next gc drops it
+    pub(crate) fn forget(&self, hash: &str) -> Result<bool> {
+        if !self.contains(hash)? {
+            return Ok(false);
+        }
+        let mut file = OpenOptions::new().create(true).append(true).open(self.dir.join(DELETED_FILE))?;
+        writeln!(file, "{}", hash)?;
+        Ok(true)
+    }
+
+    /// Read every live packed object, and the number of deleted ones left behind
+    pub(crate) fn read_all(&self) -> Result<(Vec<PackObject>, usize)> {
+        let set = self.current()?;
+        let mut seen = HashSet::new();
+        let mut objects = Vec::new();
+        let mut pruned = 0;
+        for pack in &set.packs {
+            for entry in &pack.index {
+                if !seen.insert(entry.hash.clone()) {
+                    continue;
+                }
+                if set.deleted.contains(&entry.hash) {
+                    pruned += 1;
+                } else if let Some(object) = pack.read(&entry.hash)? {
+                    objects.push(object);
+                }
+            }
+        }
+        Ok((objects, pruned))
+    }
+
+    /// Write `objects` as one new pack and remove every other pack
+    ///
+    /// The new pack becomes visible before the old ones disappear, so readers
+    /// always find every object. Callers must keep writers out meanwhile.
+    pub(crate) fn replace_all(&self, objects: Vec<PackObject>, tmp_path: &Path) -> Result<PackStats> {
+        fs::create_dir_all(&self.dir)?;
+        let previous = self.stamp()?;
+
+        let (bytes, entries, stats) = encode_indexed(objects)?;
+        let name = pack_name(&bytes);
+        let index = bincode::serialize(&Index { version: PACK_VERSION, entries }).map_err(serialization_error)?;
+
+        // Index last: a pack is only read once its index exists
+        for (extension, data) in [("pack", &bytes), ("idx", &index)] {
+            let tmp_file = tmp_path.join(uuid::Uuid::new_v4().to_string());
+            fs::write(&tmp_file, data)?;
+            fs::rename(&tmp_file, self.dir.join(format!("{}.{}", name, extension)))?;
+        }
+
+        // Drop old indexes before their packs so no reader finds an index without a pack
+        let mut old: Vec<PathBuf> = previous.iter()
+            .map(|(file_name, _, _)| self.dir.join(file_name))
+            .filter(|path| path.file_stem().is_some_and(|stem| stem != name.as_str()))
+            .collect();
+        old.sort_by_key(|path| path.extension().is_some_and(|ext| ext != "idx"));
+        for path in old {
+            match fs::remove_file(&path) {
+                Ok(()) => {}
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        Ok(stats)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use odi_core::Issue;
+
+    #[test]
+    fn test_delta_round_trip() {
+        let base = b"The quick brown fox jumps over the lazy dog, again and again and again.".to_vec();
+        let target = b"The quick red fox jumps over the lazy dog, again and again and once more.".to_vec();
+
+        let ops = diff(&base, &target);
+        assert!(ops.iter().any(|op| matches!(op, DeltaOp::Copy { .. })));
+        assert_eq!(patch(&base, &ops).unwrap(), target);
+        assert_eq!(patch(&base, &diff(&base, b"")).unwrap(), b"");
+        assert_eq!(patch(b"", &diff(b"", &target)).unwrap(), target);
+    }
+
+    #[test]
+    fn test_versions_are_delta_encoded() {
+        let mut issue = Issue::new("Packed".to_string(), "alice".to_string());
+        issue.description = Some("A long description that stays the same between versions. ".repeat(20));
+
+        let mut objects = Vec::new();
+        for (version, status) in ["open", "in-progress", "resolved"].iter().enumerate() {
+            issue.title = format!("Packed ({})", status);
+            issue.updated_at = issue.created_at + chrono::Duration::minutes(version as i64);
+            objects.push(PackObject::new(ObjectType::Issue, odi_core::format::seal(&issue).unwrap()));
+        }
+
+        let (bytes, stats) = encode(objects.clone()).unwrap();
+        assert_eq!(stats.objects, 3);
+        assert_eq!(stats.deltas, 2);
+
+        let decoded = decode(&bytes).unwrap();
+        let mut hashes: Vec<_> = decoded.iter().map(|o| o.hash.clone()).collect();
+        let mut expected: Vec<_> = objects.iter().map(|o| o.hash.clone()).collect();
+        hashes.sort();
+        expected.sort();
+        assert_eq!(hashes, expected);
+        assert!(decoded.iter().all(|o| o.hash == FileSystemStorage::get_object_hash(&o.data)));
+
+        // The newest version comes first, stored whole
+        let header = MAGIC.len() + 4;
+        let length = u32::from_le_bytes(bytes[header..header + 4].try_into().unwrap()) as usize;
+        let first: Entry = bincode::deserialize(&bytes[header + 4..header + 4 + length]).unwrap();
+        assert_eq!(first.hash, objects[2].hash);
+        assert!(matches!(first.payload, Payload::Full(_)));
+    }
+}
@@ -0,0 +1,398 @@
+//! History of ref updates
+//!
+//! Every ref write or delete through `FileSystemStorage` appends an entry to
+//! `.odi/logs/<ref name>`, one JSON object per line, recording the old and new
+//! object hashes, the command and user responsible, and the time. Entries
+//! written by one command share an operation ID, so [`undo`] can roll back
+//! everything a bad pull or bulk edit touched, and [`restore`] can point a
+//! single ref back at any version it held, including deleted ones.
+//!
+//! Objects are kept when their ref is deleted, so the reflog can always find
+//! them again.
+
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::storage::{FileSystemStorage, ObjectStorage, ObjectType};
+use crate::{FsError, Result};
+
+/// Directory inside `.odi` holding reflogs
+pub const LOGS_DIR: &str = "logs";
+
+/// One change to a ref
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReflogEntry {
+    /// Object the ref pointed at before, `None` if it was created
+    pub old: Option<String>,
+    /// Object the ref points at after, `None` if it was deleted
+    pub new: Option<String>,
+    pub object_type: ObjectType,
+    /// Shared by every entry written by the same command
+    pub operation: String,
+    pub command: String,
+    pub user: String,
+    pub time: DateTime<Utc>,
+    /// Operation rolled back by this entry's operation, if it was an undo
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub undoes: Option<String>,
+}
+
+/// Who is changing refs and why, recorded with every entry
+#[derive(Debug, Clone)]
+pub struct ReflogContext {
+    pub operation: String,
+    pub command: String,
+    pub user: String,
+    pub undoes: Option<String>,
+}
+
+impl ReflogContext {
+    /// Context for a new operation
+    pub fn new(command: impl Into<String>, user: impl Into<String>) -> Self {
+        Self {
+            operation: uuid::Uuid::new_v4().to_string(),
+            command: command.into(),
+            user: user.into(),
+            undoes: None,
+        }
+    }
+
+    pub(crate) fn entry(&self, old: Option<String>, new: Option<String>, object_type: ObjectType) -> ReflogEntry {
+        ReflogEntry {
+            old,
+            new,
+            object_type,
+            operation: self.operation.clone(),
+            command: self.command.clone(),
+            user: self.user.clone(),
+            time: Utc::now(),
+            undoes: self.undoes.clone(),
+        }
+    }
+}
+
+impl Default for ReflogContext {
+    fn default() -> Self {
+        Self::new("", std::env::var("USER").unwrap_or_else(|_| "unknown".to_string()))
+    }
+}
+
+/// All ref changes made by one command
+#[derive(Debug, Clone, Serialize)]
+pub struct Operation {
+    pub id: String,
+    pub command: String,
+    pub user: String,
+    pub time: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub undoes: Option<String>,
+    /// Changed refs with their entries, oldest first
+    pub changes: Vec<(String, ReflogEntry)>,
+}
+
+/// Path of the reflog for a ref
+pub(crate) fn log_path(odi_dir: &Path, name: &str) -> PathBuf {
+    odi_dir.join(LOGS_DIR).join(name)
+}
+
+/// Serialize an entry as a reflog line
+pub(crate) fn line(entry: &ReflogEntry) -> Result<Vec<u8>> {
+    let mut line = serde_json::to_vec(entry)?;
+    line.push(b'\n');
+    Ok(line)
+}
+
+/// Append an entry to a ref's reflog
+pub(crate) fn append(odi_dir: &Path, name: &str, entry: &ReflogEntry) -> Result<()> {
+    let path = log_path(odi_dir, name);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    OpenOptions::new().create(true).append(true).open(path)?.write_all(&line(entry)?)?;
+    Ok(())
+}
+
+/// Entries for one ref, oldest first
+pub fn read(odi_dir: &Path, name: &str) -> Result<Vec<ReflogEntry>> {
+    match fs::read_to_string(log_path(odi_dir, name)) {
+        Ok(contents) => contents.lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| Ok(serde_json::from_str(line)?))
+            .collect(),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Names of every ref with a reflog
+fn names(odi_dir: &Path) -> Result<Vec<String>> {
+    fn walk(dir: &Path, prefix: &str, names: &mut Vec<String>) -> Result<()> {
+        for file in fs::read_dir(dir)? {
+            let file = file?;
+            let name = match prefix {
+                "" => file.file_name().to_string_lossy().to_string(),
+                _ => format!("{}/{}", prefix, file.file_name().to_string_lossy()),
+            };
+            if file.file_type()?.is_dir() {
+                walk(&file.path(), &name, names)?;
+            } else {
+                names.push(name);
+            }
+        }
+        Ok(())
+    }
+
+    let mut names = Vec::new();
+    let logs = odi_dir.join(LOGS_DIR);
+    if logs.exists() {
+        walk(&logs, "", &mut names)?;
+    }
+    Ok(names)
+}
+
+/// Every entry of every ref, with ref names, oldest first
+pub fn read_all(odi_dir: &Path) -> Result<Vec<(String, ReflogEntry)>> {
+    let mut entries = Vec::new();
+    for name in names(odi_dir)? {
+        entries.extend(read(odi_dir, &name)?.into_iter().map(|entry| (name.clone(), entry)));
+    }
+    entries.sort_by_key(|(_, entry)| entry.time);
+    Ok(entries)
+}
+
+/// Point every entry at the new names of objects rewritten under new hashes,
+/// such as by key rotation
+pub(crate) fn rename_objects(storage: &FileSystemStorage, renamed: &HashMap<String, String>) -> Result<()> {
+    let rename = |hash: &mut Option<String>| match hash.as_ref().and_then(|hash| renamed.get(hash)) {
+        Some(new_hash) => {
+            *hash = Some(new_hash.clone());
+            true
+        }
+        None => false,
+    };
+
+    for name in names(storage.root_path())? {
+        let mut contents = Vec::new();
+        let mut changed = false;
+        for mut entry in read(storage.root_path(), &name)? {
+            changed |= rename(&mut entry.old) | rename(&mut entry.new);
+            contents.extend(line(&entry)?);
+        }
+        if changed {
+            storage.write_atomic(&log_path(storage.root_path(), &name), &contents)?;
+        }
+    }
+    Ok(())
+}
+
+/// Every operation, newest first
+pub fn operations(odi_dir: &Path) -> Result<Vec<Operation>> {
+    let mut operations: Vec<Operation> = Vec::new();
+    let mut positions: HashMap<String, usize> = HashMap::new();
+
+    for (name, entry) in read_all(odi_dir)? {
+        let position = *positions.entry(entry.operation.clone()).or_insert_with(|| {
+            operations.push(Operation {
+                id: entry.operation.clone(),
+                command: entry.command.clone(),
+                user: entry.user.clone(),
+                time: entry.time,
+                undoes: entry.undoes.clone(),
+                changes: Vec::new(),
+            });
+            operations.len() - 1
+        });
+        operations[position].changes.push((name, entry));
+    }
+
+    operations.reverse();
+    Ok(operations)
+}
+
+/// Most recent operation that isn't an undo and hasn't been undone
+pub fn last_undoable(odi_dir: &Path) -> Result<Option<Operation>> {
+    let operations = operations(odi_dir)?;
+    let undone: HashSet<&str> = operations.iter().filter_map(|op| op.undoes.as_deref()).collect();
+    Ok(operations.iter()
+        .find(|op| op.undoes.is_none() && !undone.contains(op.id.as_str()))
+        .cloned())
+}
+
+/// Point a ref at `hash`, or delete it when `hash` is `None`
+fn set_ref(storage: &FileSystemStorage, name: &str, hash: Option<&str>, object_type: ObjectType) -> Result<()> {
+    match hash {
+        Some(hash) => storage.create_ref(name, hash, object_type),
+        None => storage.delete_ref(name).map(|_| ()),
+    }
+}
+
+fn missing_object(name: &str, hash: &str) -> FsError {
+    FsError::StorageError {
+        message: format!("Object {} for {} no longer exists", hash, name),
+    }
+}
+
+/// Roll back every ref change of the most recent operation that can be undone
+///
+/// Refs changed again since are left alone and reported as an error, unless
+/// `force` is set. Returns the operation that was undone, if any.
+pub fn undo(storage: &FileSystemStorage, force: bool) -> Result<Option<Operation>> {
+    let _guards = storage.acquire_all_guards()?;
+    let Some(operation) = last_undoable(storage.root_path())? else {
+        return Ok(None);
+    };
+
+    // Per ref: the value before the operation, and the value it left behind
+    let mut targets: Vec<(&str, Option<&str>, Option<&str>, ObjectType)> = Vec::new();
+    for (name, entry) in &operation.changes {
+        match targets.iter_mut().find(|(target, ..)| target == name) {
+            Some(target) => target.2 = entry.new.as_deref(),
+            None => targets.push((name, entry.old.as_deref(), entry.new.as_deref(), entry.object_type)),
+        }
+    }
+
+    for (name, before, after, _) in &targets {
+        let current = storage.get_ref(name)?.map(|object_ref| object_ref.hash);
+        if !force && current.as_deref() != *after {
+            return Err(FsError::StorageError {
+                message: format!("{} has changed since '{}'; use --force to roll it back anyway", name, operation.command),
+            });
+        }
+        if let Some(hash) = before {
+            if !storage.object_exists(hash)? {
+                return Err(missing_object(name, hash));
+            }
+        }
+    }
+
+    // Each undo is an operation of its own, even when one storage undoes twice
+    let context = storage.reflog_context();
+    let undoing = storage.clone().with_reflog_context(ReflogContext {
+        undoes: Some(operation.id.clone()),
+        ..ReflogContext::new(context.command.clone(), context.user.clone())
+    });
+    for (name, before, _, object_type) in &targets {
+        set_ref(&undoing, name, *before, *object_type)?;
+    }
+    Ok(Some(operation))
+}
+
+/// Point a ref back at the version recorded by one of its reflog entries
+///
+/// `index` counts back from the newest entry (0). A deletion entry restores
+/// the version that was deleted. Returns the entry restored to.
+pub fn restore(storage: &FileSystemStorage, name: &str, index: usize) -> Result<ReflogEntry> {
+    let _guards = storage.acquire_all_guards()?;
+    let entries = read(storage.root_path(), name)?;
+    let entry = entries.iter().rev().nth(index).cloned().ok_or_else(|| FsError::StorageError {
+        message: format!("{} has no reflog entry {}", name, index),
+    })?;
+
+    let hash = entry.new.as_deref().or(entry.old.as_deref()).ok_or_else(|| FsError::StorageError {
+        message: format!("Reflog entry {} of {} records no object", index, name),
+    })?;
+    if !storage.object_exists(hash)? {
+        return Err(missing_object(name, hash));
+    }
+
+    storage.create_ref(name, hash, entry.object_type)?;
+    Ok(entry)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::StorageEngine;
+    use odi_core::Issue;
+    use tempfile::TempDir;
+
+    async fn save(storage: &FileSystemStorage, issue: &Issue) -> String {
+        let hash = storage.write_object(issue).await.unwrap();
+        storage.write_ref(&format!("issues/{}", issue.id), &hash, ObjectType::Issue).await.unwrap();
+        hash.as_str().to_string()
+    }
+
+    #[tokio::test]
+    async fn test_undo_rolls_back_an_operation() {
+        let dir = TempDir::new().unwrap();
+        let base = FileSystemStorage::new(dir.path().to_path_buf()).unwrap();
+        let mut issue = Issue::new("Original".to_string(), "alice".to_string());
+        let name = format!("issues/{}", issue.id);
+
+        let create = base.clone().with_reflog_context(ReflogContext::new("odi issue create", "alice"));
+        let original = save(&create, &issue).await;
+
+        // A bad bulk change touches two refs
+        let bulk = base.clone().with_reflog_context(ReflogContext::new("odi pull", "bob"));
+        issue.title = "Clobbered".to_string();
+        save(&bulk, &issue).await;
+        let other = Issue::new("Pulled".to_string(), "bob".to_string());
+        save(&bulk, &other).await;
+
+        let undone = undo(&base, false).unwrap().unwrap();
+        assert_eq!((undone.command.as_str(), undone.changes.len()), ("odi pull", 2));
+        assert_eq!(base.get_ref(&name).unwrap().unwrap().hash, original);
+        assert!(base.get_ref(&format!("issues/{}", other.id)).unwrap().is_none());
+
+        // The next undo goes further back instead of redoing
+        assert_eq!(undo(&base, false).unwrap().unwrap().command, "odi issue create");
+        assert!(base.get_ref(&name).unwrap().is_none());
+        assert!(undo(&base, false).unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_restore_deleted_ref() {
+        let dir = TempDir::new().unwrap();
+        let storage = FileSystemStorage::new(dir.path().to_path_buf()).unwrap();
+        let issue = Issue::new("Deleted".to_string(), "alice".to_string());
+        let name = format!("issues/{}", issue.id);
+
+        let hash = save(&storage, &issue).await;
+        StorageEngine::delete_ref(&storage, &name).await.unwrap();
+
+        let entries = read(dir.path(), &name).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[1].old.as_deref(), Some(hash.as_str()));
+        assert!(entries[1].new.is_none());
+
+        restore(&storage, &name, 0).unwrap();
+        let restored: Vec<Issue> = storage.read_all("issues/").await.unwrap();
+        assert_eq!(restored[0].title, "Deleted");
+    }
+
+    #[tokio::test]
+    async fn test_restore_after_key_rotation() {
+        let dir = TempDir::new().unwrap();
+        let source = crate::KeySource::Passphrase("secret".to_string());
+        let plain = FileSystemStorage::new(dir.path().to_path_buf()).unwrap();
+        let storage = plain.clone().with_cipher(crate::crypto::create(plain.root_path(), &source).unwrap());
+        let mut issue = Issue::new("First".to_string(), "alice".to_string());
+        let name = format!("issues/{}", issue.id);
+
+        save(&storage, &issue).await;
+        issue.title = "Second".to_string();
+        save(&storage, &issue).await;
+
+        let new_source = crate::KeySource::Passphrase("rotated".to_string());
+        storage.rotate_key(&source, Some(&new_source)).unwrap();
+        let rotated = plain.with_cipher(crate::crypto::unlock(dir.path(), &new_source).unwrap())
+            .with_reflog_context(ReflogContext::new("odi issue restore", "alice"));
+
+        // Rotation moves the history along and adds nothing to it
+        let entries = read(dir.path(), &name).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[1].new, Some(rotated.get_ref(&name).unwrap().unwrap().hash));
+
+        restore(&rotated, &name, 1).unwrap();
+        let restored: Vec<Issue> = rotated.read_all("issues/").await.unwrap();
+        assert_eq!(restored[0].title, "First");
+        assert_eq!(undo(&rotated, false).unwrap().unwrap().changes.len(), 1);
+        let undone: Vec<Issue> = rotated.read_all("issues/").await.unwrap();
+        assert_eq!(undone[0].title, "Second");
+    }
+}
@@ -0,0 +1,769 @@
+//! Full-text search index
+//!
+//! An inverted index maps each stemmed term to the objects and fields it
+//! appears in, with its positions, so searches answer from the index instead
+//! of reading every object. Results are ranked with BM25, with title matches
+//! counting more than description matches.
+//!
+//! The index lives in `.odi/index/`: `search.idx` holds a snapshot and
+//! `search.log` the objects written since, one JSON line each, so a write only
+//! appends the terms of the object it changed. Every search first checks the
+//! index against the refs by object hash, picking up objects changed without
+//! going through a repository (a Git merge, `odi undo`), folds the log into a
+//! new snapshot, and then runs the query. The index holds issue text in the
+//! clear, so encrypted workspaces build it in memory for each search instead.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::storage::{FileSystemStorage, ObjectHash, StorageEngine};
+use crate::Result;
+use odi_core::{CoreError, Issue, Project};
+
+/// Directory of the index inside `.odi`
+pub const INDEX_DIR: &str = "index";
+const SNAPSHOT_FILE: &str = "search.idx";
+const LOG_FILE: &str = "search.log";
+/// Lock held while the index files are read or written
+const SEARCH_LOCK: &str = "search";
+/// Snapshots of another version are rebuilt rather than read
+const INDEX_VERSION: u32 = 1;
+
+/// BM25 term frequency saturation
+const K1: f64 = 1.2;
+/// BM25 field length normalization
+const B: f64 = 0.75;
+
+/// Ref namespaces whose objects are indexed
+const NAMESPACES: [&str; 2] = ["issues/", "projects/"];
+
+/// Indexed part of an object
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Field {
+    /// Issue title or project name
+    Title,
+    Description,
+}
+
+impl Field {
+    /// How much a match in this field counts towards the score
+    fn weight(self) -> f64 {
+        match self {
+            Field::Title => 3.0,
+            Field::Description => 1.0,
+        }
+    }
+}
+
+/// Searchable text of one object
+#[derive(Debug, Clone)]
+pub struct Document {
+    /// Ref name of the object, like `issues/<id>`
+    pub name: String,
+    pub fields: Vec<(Field, String)>,
+}
+
+impl Document {
+    pub fn issue(issue: &Issue) -> Self {
+        Self::new(format!("issues/{}", issue.id), &issue.title, issue.description.as_deref())
+    }
+
+    pub fn project(project: &Project) -> Self {
+        Self::new(format!("projects/{}", project.id), &project.name, project.description.as_deref())
+    }
+
+    fn new(name: String, title: &str, description: Option<&str>) -> Self {
+        let mut fields = vec![(Field::Title, title.to_string())];
+        if let Some(description) = description {
+            fields.push((Field::Description, description.to_string()));
+        }
+        Self { name, fields }
+    }
+
+    /// Stemmed terms of each field, in order
+    fn terms(&self) -> Vec<(Field, Vec<String>)> {
+        self.fields.iter()
+            .map(|(field, text)| (*field, tokenize(text).into_iter().map(|token| token.term).collect()))
+            .collect()
+    }
+}
+
+/// A word of some text: its stemmed term and where it is
+#[derive(Debug, Clone, PartialEq)]
+pub struct Token {
+    pub term: String,
+    /// The word in lowercase, before stemming
+    pub word: String,
+    /// Byte range of the word in the text
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Split text into lowercase, stemmed words
+pub fn tokenize(text: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut start = None;
+    for (pos, c) in text.char_indices().chain([(text.len(), ' ')]) {
+        match (start, c.is_alphanumeric()) {
+            (None, true) => start = Some(pos),
+            (Some(from), false) => {
+                let word = text[from..pos].to_lowercase();
+                tokens.push(Token { term: stem(&word), word, start: from, end: pos });
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    tokens
+}
+
+/// Reduce an English word to its stem, so `crashes`, `crashed` and `crashing`
+/// are all found by `crash`
+///
+/// A light version of the Porter stemmer: it handles plurals, verb endings and
+/// common derivational suffixes, and leaves words it doesn't recognize alone.
+pub fn stem(word: &str) -> String {
+    if word.len() <= 3 || !word.bytes().all(|b| b.is_ascii_lowercase()) {
+        return word.to_string();
+    }
+    let mut w = word.to_string();
+
+    // Plurals
+    if w.ends_with("sses") || w.ends_with("ies") {
+        w.truncate(w.len() - 2);
+    } else if w.ends_with('s') && !w.ends_with("ss") && !w.ends_with("us") && !w.ends_with("is") {
+        w.pop();
+    }
+
+    // Verb endings, kept only when a vowel is left
+    if w.ends_with("eed") {
+        if w.len() > 4 {
+            w.pop();
+        }
+    } else if let Some(base) = ["ing", "ed"].iter()
+        .find_map(|suffix| w.strip_suffix(suffix))
+        .filter(|base| base.len() >= 2 && has_vowel(base))
+    {
+        w = base.to_string();
+        let bytes = w.as_bytes();
+        let n = bytes.len();
+        if w.ends_with("at") || w.ends_with("bl") || w.ends_with("iz") {
+            w.push('e');
+        } else if n >= 2 && bytes[n - 1] == bytes[n - 2] && !matches!(bytes[n - 1], b'l' | b's' | b'z') && !is_vowel(bytes[n - 1]) {
+            w.pop();
+        }
+    }
+
+    if w.ends_with('y') && has_vowel(&w[..w.len() - 1]) {
+        w.pop();
+        w.push('i');
+    }
+
+    const SUFFIXES: [(&str, &str); 16] = [
+        ("ational", "ate"), ("tional", "tion"), ("ization", "ize"), ("fulness", "ful"),
+        ("ousness", "ous"), ("iveness", "ive"), ("ation", "ate"), ("alism", "al"),
+        ("aliti", "al"), ("iviti", "ive"), ("ement", ""), ("ness", ""), ("ment", ""),
+        ("able", ""), ("ible", ""), ("ful", ""),
+    ];
+    for (suffix, replacement) in SUFFIXES {
+        if let Some(base) = w.strip_suffix(suffix) {
+            if base.len() >= 3 && has_vowel(base) {
+                w = format!("{}{}", base, replacement);
+            }
+            break;
+        }
+    }
+    if let Some(base) = w.strip_suffix("ion").filter(|base| base.len() >= 3 && (base.ends_with('s') || base.ends_with('t'))) {
+        w = base.to_string();
+    }
+    if w.len() > 4 && w.ends_with('e') {
+        w.pop();
+    }
+    w
+}
+
+fn is_vowel(b: u8) -> bool {
+    matches!(b, b'a' | b'e' | b'i' | b'o' | b'u' | b'y')
+}
+
+fn has_vowel(text: &str) -> bool {
+    text.bytes().any(is_vowel)
+}
+
+/// One part of a search that every result must match
+#[derive(Debug, Clone, PartialEq)]
+enum Clause {
+    Term(String),
+    /// Terms starting with this, as typed and as stemmed
+    Prefix(String, String),
+    /// Terms next to each other in this order, in one field
+    Phrase(Vec<String>),
+}
+
+/// Parsed search terms: words, `"exact phrases"` and `prefixes*`
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchQuery {
+    clauses: Vec<Clause>,
+}
+
+impl SearchQuery {
+    pub fn parse(text: &str) -> odi_core::Result<Self> {
+        let invalid = |message: &str| CoreError::ValidationError {
+            field: "search".to_string(),
+            message: message.to_string(),
+        };
+        let mut clauses = Vec::new();
+        let mut rest = text.trim();
+        while !rest.is_empty() {
+            let part;
+            if let Some(quoted) = rest.strip_prefix('"') {
+                let end = quoted.find('"').ok_or_else(|| invalid("Unterminated quote in search"))?;
+                part = &quoted[..end];
+                rest = &quoted[end + 1..];
+                clauses.extend(Self::words(part));
+            } else {
+                let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+                part = &rest[..end];
+                rest = &rest[end..];
+                match part.strip_suffix('*') {
+                    Some(prefix) if !prefix.is_empty() && prefix.chars().all(char::is_alphanumeric) => {
+                        let prefix = prefix.to_lowercase();
+                        clauses.push(Clause::Prefix(stem(&prefix), prefix));
+                    }
+                    _ => clauses.extend(Self::words(part)),
+                }
+            }
+            rest = rest.trim_start();
+        }
+        if clauses.is_empty() {
+            return Err(invalid("Nothing to search for; give some words"));
+        }
+        Ok(Self { clauses })
+    }
+
+    /// Words typed together, like `"login page"` or `e-mail`: a phrase if there
+    /// are several
+    fn words(text: &str) -> Option<Clause> {
+        let mut terms: Vec<String> = tokenize(text).into_iter().map(|token| token.term).collect();
+        match terms.len() {
+            0 => None,
+            1 => terms.pop().map(Clause::Term),
+            _ => Some(Clause::Phrase(terms)),
+        }
+    }
+
+    /// Which words of some text the query matches; words of a phrase only
+    /// count where the whole phrase is
+    fn matched(&self, tokens: &[Token]) -> Vec<bool> {
+        let mut matched = vec![false; tokens.len()];
+        for clause in &self.clauses {
+            for (i, token) in tokens.iter().enumerate() {
+                match clause {
+                    Clause::Term(term) => matched[i] |= token.term == *term,
+                    Clause::Prefix(stemmed, typed) => {
+                        matched[i] |= token.term.starts_with(stemmed.as_str()) || token.word.starts_with(typed.as_str());
+                    }
+                    Clause::Phrase(terms) => {
+                        let window = tokens[i..].iter().take(terms.len());
+                        if window.len() == terms.len() && window.zip(terms).all(|(token, term)| token.term == *term) {
+                            matched[i..i + terms.len()].iter_mut().for_each(|m| *m = true);
+                        }
+                    }
+                }
+            }
+        }
+        matched
+    }
+}
+
+/// Part of a field's text around the first match, with the matches marked
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Snippet {
+    pub field: Field,
+    pub text: String,
+    /// Byte ranges of `text` holding matched words
+    pub highlights: Vec<(usize, usize)>,
+}
+
+impl Snippet {
+    /// Snippets of the fields of a document that match the query, about
+    /// `width` characters long
+    pub fn of(document: &Document, query: &SearchQuery, width: usize) -> Vec<Snippet> {
+        document.fields.iter()
+            .filter_map(|(field, text)| Self::of_text(*field, text, query, width))
+            .collect()
+    }
+
+    fn of_text(field: Field, text: &str, query: &SearchQuery, width: usize) -> Option<Snippet> {
+        let tokens = tokenize(text);
+        let tokens: Vec<&Token> = tokens.iter().zip(query.matched(&tokens)).filter(|(_, matched)| *matched).map(|(token, _)| token).collect();
+        let first = tokens.first()?;
+
+        // Start a few words before the first match, on a word boundary
+        let mut start = floor_char_boundary(text, first.start.saturating_sub(width / 3));
+        if start > 0 {
+            start = text[start..first.start].find(char::is_whitespace).map_or(first.start, |gap| start + gap + 1);
+        }
+        let mut end = floor_char_boundary(text, (start + width).min(text.len()));
+        if end < text.len() {
+            end = text[..end].rfind(char::is_whitespace).filter(|&gap| gap > first.end).unwrap_or(end.max(first.end));
+        }
+
+        let prefix = if start > 0 { "…" } else { "" };
+        let suffix = if end < text.len() { "…" } else { "" };
+        let body = text[start..end].replace(['\n', '\r'], " ");
+        let highlights = tokens.iter()
+            .filter(|token| token.start >= start && token.end <= end)
+            .map(|token| (token.start - start + prefix.len(), token.end - start + prefix.len()))
+            .collect();
+        Some(Snippet { field, text: format!("{}{}{}", prefix, body, suffix), highlights })
+    }
+
+    /// The snippet with each match wrapped in `open` and `close`
+    pub fn highlighted(&self, open: &str, close: &str) -> String {
+        let mut out = String::new();
+        let mut pos = 0;
+        for &(start, end) in &self.highlights {
+            out.push_str(&self.text[pos..start]);
+            out.push_str(open);
+            out.push_str(&self.text[start..end]);
+            out.push_str(close);
+            pos = end;
+        }
+        out.push_str(&self.text[pos..]);
+        out
+    }
+}
+
+fn floor_char_boundary(text: &str, mut index: usize) -> usize {
+    while !text.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+/// An object matching a search
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchHit {
+    /// Ref name of the object
+    pub name: String,
+    pub score: f64,
+}
+
+/// Where a term appears in one field of one object
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Posting {
+    doc: String,
+    field: Field,
+    positions: Vec<u32>,
+}
+
+/// What the index knows of one object
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct DocEntry {
+    /// Object hash the entry was made from; empty if not known
+    hash: String,
+    /// Number of words in each field
+    lengths: BTreeMap<Field, u32>,
+    /// Terms with a posting for this object, to remove them again
+    terms: Vec<String>,
+}
+
+/// A change to the index, as written to the log
+#[derive(Debug, Serialize, Deserialize)]
+enum Change {
+    Put { name: String, hash: String, fields: Vec<(Field, Vec<String>)> },
+    Remove { name: String },
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Snapshot {
+    version: u32,
+    docs: HashMap<String, DocEntry>,
+    terms: BTreeMap<String, Vec<Posting>>,
+}
+
+/// Inverted index of the searchable text of issues and projects
+#[derive(Debug, Default)]
+pub struct SearchIndex {
+    snapshot: Snapshot,
+}
+
+impl SearchIndex {
+    /// Add an object, replacing what was indexed for it before
+    pub fn put(&mut self, document: &Document, hash: &str) {
+        self.apply(Change::Put { name: document.name.clone(), hash: hash.to_string(), fields: document.terms() });
+    }
+
+    /// Remove an object; false if it wasn't indexed
+    pub fn remove(&mut self, name: &str) -> bool {
+        let Some(entry) = self.snapshot.docs.remove(name) else {
+            return false;
+        };
+        for term in entry.terms {
+            if let Some(postings) = self.snapshot.terms.get_mut(&term) {
+                postings.retain(|posting| posting.doc != name);
+                if postings.is_empty() {
+                    self.snapshot.terms.remove(&term);
+                }
+            }
+        }
+        true
+    }
+
+    /// Number of indexed objects
+    pub fn len(&self) -> usize {
+        self.snapshot.docs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.snapshot.docs.is_empty()
+    }
+
+    fn apply(&mut self, change: Change) {
+        match change {
+            Change::Remove { name } => {
+                self.remove(&name);
+            }
+            Change::Put { name, hash, fields } => {
+                self.remove(&name);
+                let mut entry = DocEntry { hash, ..Default::default() };
+                for (field, terms) in fields {
+                    let mut positions: BTreeMap<String, Vec<u32>> = BTreeMap::new();
+                    for (position, term) in terms.iter().enumerate() {
+                        positions.entry(term.clone()).or_default().push(position as u32);
+                    }
+                    *entry.lengths.entry(field).or_default() += terms.len() as u32;
+                    for (term, positions) in positions {
+                        if !entry.terms.contains(&term) {
+                            entry.terms.push(term.clone());
+                        }
+                        self.snapshot.terms.entry(term).or_default().push(Posting { doc: name.clone(), field, positions });
+                    }
+                }
+                self.snapshot.docs.insert(name, entry);
+            }
+        }
+    }
+
+    /// Objects matching every part of the query, best first
+    pub fn search(&self, query: &SearchQuery, limit: Option<usize>) -> Vec<SearchHit> {
+        let total = self.snapshot.docs.len() as f64;
+        let mut average: HashMap<Field, f64> = HashMap::new();
+        for entry in self.snapshot.docs.values() {
+            for (field, length) in &entry.lengths {
+                *average.entry(*field).or_default() += *length as f64 / total;
+            }
+        }
+
+        let mut scores: Option<HashMap<&str, f64>> = None;
+        for clause in &query.clauses {
+            let matches = self.clause_matches(clause);
+            let df = matches.len() as f64;
+            let idf = (1.0 + (total - df + 0.5) / (df + 0.5)).ln();
+
+            let mut clause_scores: HashMap<&str, f64> = HashMap::new();
+            for (doc, fields) in matches {
+                let Some(entry) = self.snapshot.docs.get(doc) else {
+                    continue;
+                };
+                let tf: f64 = fields.iter().map(|(field, count)| {
+                    let length = entry.lengths.get(field).copied().unwrap_or(0) as f64;
+                    let avg = average.get(field).copied().filter(|avg| *avg > 0.0).unwrap_or(1.0);
+                    field.weight() * *count as f64 / (1.0 - B + B * length / avg)
+                }).sum();
+                clause_scores.insert(doc, idf * tf / (K1 + tf));
+            }
+
+            // Every clause must match, so only documents matching all so far survive
+            scores = Some(match scores {
+                None => clause_scores,
+                Some(scores) => scores.into_iter()
+                    .filter_map(|(doc, score)| clause_scores.get(doc).map(|more| (doc, score + more)))
+                    .collect(),
+            });
+        }
+
+        let mut hits: Vec<SearchHit> = scores.unwrap_or_default().into_iter()
+            .map(|(doc, score)| SearchHit { name: doc.to_string(), score })
+            .collect();
+        hits.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.name.cmp(&b.name)));
+        if let Some(limit) = limit {
+            hits.truncate(limit);
+        }
+        hits
+    }
+
+    /// Objects a clause matches, with how often it matches in each field
+    fn clause_matches(&self, clause: &Clause) -> HashMap<&str, HashMap<Field, u32>> {
+        let mut matches: HashMap<&str, HashMap<Field, u32>> = HashMap::new();
+        fn add<'a>(matches: &mut HashMap<&'a str, HashMap<Field, u32>>, posting: &'a Posting, count: u32) {
+            if count > 0 {
+                *matches.entry(posting.doc.as_str()).or_default().entry(posting.field).or_default() += count;
+            }
+        }
+        let terms = &self.snapshot.terms;
+
+        match clause {
+            Clause::Term(term) => {
+                for posting in terms.get(term).into_iter().flatten() {
+                    add(&mut matches, posting, posting.positions.len() as u32);
+                }
+            }
+            Clause::Prefix(stemmed, typed) => {
+                let mut seen = HashSet::new();
+                for prefix in [stemmed, typed] {
+                    let range = terms.range::<str, _>((std::ops::Bound::Included(prefix.as_str()), std::ops::Bound::Unbounded));
+                    for (term, postings) in range.take_while(|(term, _)| term.starts_with(prefix.as_str())) {
+                        if seen.insert(term) {
+                            for posting in postings {
+                                add(&mut matches, posting, posting.positions.len() as u32);
+                            }
+                        }
+                    }
+                }
+            }
+            Clause::Phrase(phrase) => {
+                // Positions of the later words, by object and field
+                let later: Vec<HashMap<(&str, Field), &Vec<u32>>> = phrase[1..].iter()
+                    .map(|term| terms.get(term).into_iter().flatten()
+                        .map(|posting| ((posting.doc.as_str(), posting.field), &posting.positions))
+                        .collect())
+                    .collect();
+                for posting in terms.get(&phrase[0]).into_iter().flatten() {
+                    let key = (posting.doc.as_str(), posting.field);
+                    let count = posting.positions.iter()
+                        .filter(|&&start| later.iter().enumerate().all(|(i, positions)| {
+                            positions.get(&key).is_some_and(|positions| positions.binary_search(&(start + i as u32 + 1)).is_ok())
+                        }))
+                        .count();
+                    add(&mut matches, posting, count as u32);
+                }
+            }
+        }
+        matches
+    }
+
+    /// Read the index of a workspace, or an empty one if there is none or it
+    /// can't be read
+    fn load(dir: &Path) -> Self {
+        let mut snapshot = fs::read(dir.join(SNAPSHOT_FILE)).ok()
+            .and_then(|bytes| bincode::deserialize::<Snapshot>(&bytes).ok())
+            .filter(|snapshot| snapshot.version == INDEX_VERSION)
+            .unwrap_or_default();
+        snapshot.version = INDEX_VERSION;
+        let mut index = Self { snapshot };
+
+        // A line cut short by a crash is dropped; checking the refs restores it
+        if let Ok(log) = fs::read_to_string(dir.join(LOG_FILE)) {
+            for change in log.lines().filter_map(|line| serde_json::from_str::<Change>(line).ok()) {
+                index.apply(change);
+            }
+        }
+        index
+    }
+
+    /// Write the index as a new snapshot and empty the log
+    fn save(&self, dir: &Path) -> Result<()> {
+        fs::create_dir_all(dir)?;
+        let bytes = bincode::serialize(&self.snapshot).map_err(|e| crate::FsError::SerializationError {
+            message: format!("Failed to serialize search index: {}", e),
+        })?;
+        let tmp = dir.join(format!("{}.tmp", SNAPSHOT_FILE));
+        fs::write(&tmp, bytes)?;
+        fs::rename(&tmp, dir.join(SNAPSHOT_FILE))?;
+        match fs::remove_file(dir.join(LOG_FILE)) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Index what changed in the refs since the index was written
+    async fn refresh(&mut self, storage: &FileSystemStorage) -> Result<bool> {
+        let mut changed = false;
+        for namespace in NAMESPACES {
+            let refs = storage.read_refs(namespace).await?;
+            let current: HashSet<&str> = refs.iter().map(|(name, _)| name.as_str()).collect();
+            let gone: Vec<String> = self.snapshot.docs.keys()
+                .filter(|name| name.starts_with(namespace) && !current.contains(name.as_str()))
+                .cloned()
+                .collect();
+            for name in gone {
+                changed |= self.remove(&name);
+            }
+
+            let stale: Vec<&(String, ObjectHash)> = refs.iter()
+                .filter(|(name, hash)| self.snapshot.docs.get(name).is_none_or(|entry| entry.hash != hash.as_str()))
+                .collect();
+            if stale.is_empty() {
+                continue;
+            }
+            let hashes: Vec<ObjectHash> = stale.iter().map(|(_, hash)| hash.clone()).collect();
+            let documents: Vec<Option<Document>> = if namespace == "issues/" {
+                storage.read_objects::<Issue>(&hashes).await?.iter().map(|issue| issue.as_ref().map(Document::issue)).collect()
+            } else {
+                storage.read_objects::<Project>(&hashes).await?.iter().map(|project| project.as_ref().map(Document::project)).collect()
+            };
+            for ((name, hash), document) in stale.into_iter().zip(documents) {
+                if let Some(document) = document {
+                    // Index under the ref name, whatever the object says its ID is
+                    self.put(&Document { name: name.clone(), ..document }, hash.as_str());
+                    changed = true;
+                }
+            }
+        }
+        Ok(changed)
+    }
+}
+
+/// The search index of a workspace, brought up to date with its refs
+pub async fn open(storage: &FileSystemStorage) -> Result<SearchIndex> {
+    if storage.is_encrypted() {
+        let mut index = SearchIndex::default();
+        index.refresh(storage).await?;
+        return Ok(index);
+    }
+
+    let _guard = StorageEngine::lock(storage, SEARCH_LOCK).await?;
+    let dir = storage.root_path().join(INDEX_DIR);
+    let replayed = dir.join(LOG_FILE).exists();
+    let mut index = SearchIndex::load(&dir);
+    if index.refresh(storage).await? || replayed || !dir.join(SNAPSHOT_FILE).exists() {
+        index.save(&dir)?;
+    }
+    Ok(index)
+}
+
+/// Note in the index of a workspace that an object was written
+///
+/// Only appends to the log; workspaces that have never been searched have no
+/// index to keep up to date.
+pub async fn record_write(storage: &FileSystemStorage, document: &Document, hash: &ObjectHash) -> Result<()> {
+    append(storage, Change::Put { name: document.name.clone(), hash: hash.as_str().to_string(), fields: document.terms() }).await
+}
+
+/// Note in the index of a workspace that the object of a ref was deleted
+pub async fn record_delete(storage: &FileSystemStorage, name: &str) -> Result<()> {
+    append(storage, Change::Remove { name: name.to_string() }).await
+}
+
+async fn append(storage: &FileSystemStorage, change: Change) -> Result<()> {
+    let dir = storage.root_path().join(INDEX_DIR);
+    if storage.is_encrypted() || !dir.join(SNAPSHOT_FILE).exists() {
+        return Ok(());
+    }
+    let mut line = serde_json::to_vec(&change)?;
+    line.push(b'\n');
+
+    let _guard = StorageEngine::lock(storage, SEARCH_LOCK).await?;
+    let mut log = fs::OpenOptions::new().create(true).append(true).open(dir.join(LOG_FILE))?;
+    log.write_all(&line)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn document(name: &str, title: &str, description: &str) -> Document {
+        Document::new(name.to_string(), title, Some(description))
+    }
+
+    #[test]
+    fn test_stemming() {
+        for word in ["crash", "crashes", "crashed", "crashing"] {
+            assert_eq!(stem(word), "crash", "{}", word);
+        }
+        assert_eq!(stem("running"), "run");
+        assert_eq!(stem("connection"), stem("connected"));
+        assert_eq!(stem("policies"), stem("policy"));
+        assert_eq!(stem("created"), stem("create"));
+        assert_eq!(stem("api"), "api");
+        assert_eq!(stem("café"), "café");
+    }
+
+    #[test]
+    fn test_ranking_and_queries() {
+        let mut index = SearchIndex::default();
+        index.put(&document("issues/1", "Login page crashes", "Seen on Safari"), "h1");
+        index.put(&document("issues/2", "Slow dashboard", "The login page crashed once while loading"), "h2");
+        index.put(&document("issues/3", "Page for logins", "Crash reporting for the page"), "h3");
+
+        // Title matches rank above description matches
+        let hits = index.search(&SearchQuery::parse("crash").unwrap(), None);
+        let names: Vec<&str> = hits.iter().map(|hit| hit.name.as_str()).collect();
+        assert_eq!(names[0], "issues/1");
+        assert_eq!(names.len(), 3);
+
+        // Phrases need the words together and in order
+        let hits = index.search(&SearchQuery::parse("\"login page\"").unwrap(), None);
+        let names: Vec<&str> = hits.iter().map(|hit| hit.name.as_str()).collect();
+        assert_eq!(names, vec!["issues/1", "issues/2"]);
+
+        let hits = index.search(&SearchQuery::parse("dash* load*").unwrap(), None);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].name, "issues/2");
+
+        // Updates replace the old terms
+        index.put(&document("issues/1", "Logout button", "Nothing happens"), "h4");
+        assert_eq!(index.search(&SearchQuery::parse("crash").unwrap(), None).len(), 2);
+        assert!(index.remove("issues/2"));
+        assert_eq!(index.search(&SearchQuery::parse("crash").unwrap(), Some(5)).len(), 1);
+        assert!(!index.snapshot.terms.contains_key("dashboard"));
+
+        assert!(SearchQuery::parse("  ").is_err());
+        assert!(SearchQuery::parse("\"login").is_err());
+    }
+
+    #[test]
+    fn test_snippets() {
+        let query = SearchQuery::parse("crash").unwrap();
+        let text = "Steps: open the app, sign in with a long password and wait; the whole thing crashes after a minute or so on older phones";
+        let snippet = Snippet::of_text(Field::Description, text, &query, 40).unwrap();
+        assert!(snippet.text.starts_with('…') && snippet.text.ends_with('…'));
+        assert!(snippet.highlighted("[", "]").contains("[crashes]"));
+        assert!(Snippet::of_text(Field::Title, "Slow page", &query, 40).is_none());
+
+        let snippet = Snippet::of_text(Field::Title, "Crash on start", &query, 40).unwrap();
+        assert_eq!(snippet.highlighted("<", ">"), "<Crash> on start");
+
+        let query = SearchQuery::parse("\"login page\"").unwrap();
+        let snippet = Snippet::of_text(Field::Description, "After login, the login page hangs", &query, 80).unwrap();
+        assert_eq!(snippet.highlighted("<", ">"), "After login, the <login> <page> hangs");
+    }
+
+    #[tokio::test]
+    async fn test_index_follows_refs() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = FileSystemStorage::new(dir.path().to_path_buf()).unwrap();
+        let issue = Issue::new("Login crash".to_string(), "alice".to_string());
+        let name = format!("issues/{}", issue.id);
+        let hash = storage.write_object(&issue).await.unwrap();
+        storage.write_ref(&name, &hash, crate::ObjectType::Issue).await.unwrap();
+
+        let index = open(&storage).await.unwrap();
+        assert_eq!(index.search(&SearchQuery::parse("crash").unwrap(), None)[0].name, name);
+        assert!(dir.path().join(INDEX_DIR).join(SNAPSHOT_FILE).exists());
+
+        // Writes through a repository are logged, others are found by hash
+        let mut renamed = issue.clone();
+        renamed.title = "Logout crash".to_string();
+        let renamed_hash = storage.write_object(&renamed).await.unwrap();
+        storage.write_ref(&name, &renamed_hash, crate::ObjectType::Issue).await.unwrap();
+        record_write(&storage, &Document::issue(&renamed), &renamed_hash).await.unwrap();
+        assert!(dir.path().join(INDEX_DIR).join(LOG_FILE).exists());
+
+        let index = open(&storage).await.unwrap();
+        assert_eq!(index.search(&SearchQuery::parse("logout").unwrap(), None).len(), 1);
+        assert!(index.search(&SearchQuery::parse("login").unwrap(), None).is_empty());
+        assert!(!dir.path().join(INDEX_DIR).join(LOG_FILE).exists());
+
+        StorageEngine::delete_ref(&storage, &name).await.unwrap();
+        assert!(open(&storage).await.unwrap().is_empty());
+    }
+}
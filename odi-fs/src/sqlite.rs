@@ -0,0 +1,775 @@
+//! SQLite storage backend
+//!
+//! An alternative to the object store for deployments where a single database
+//! file is more practical than thousands of small gzip objects. Every entity is
+//! kept as a versioned JSON document in one `records` table keyed by kind and ID, so the
+//! schema never has to follow changes to the domain types. Filtering and ordering
+//! reuse the query semantics from odi-core, so both backends answer queries
+//! identically.
+//!
+//! Concurrency is handled by SQLite itself: mutations run in `BEGIN IMMEDIATE`
+//! transactions and contending processes wait up to the configured lock timeout.
+
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use rusqlite::{params, Connection, OptionalExtension, TransactionBehavior};
+
+use crate::lock::LockOptions;
+use crate::repository::storage_error;
+use crate::{FsError, Result};
+use odi_core::*;
+
+/// Database file inside the `.odi` directory
+pub const DATABASE_FILE: &str = "odi.db";
+
+/// Schema version recorded in `PRAGMA user_version`
+const SCHEMA_VERSION: i64 = 1;
+
+/// Longest busy timeout SQLite accepts: `i32::MAX` milliseconds, about 24 days
+const MAX_BUSY_TIMEOUT: Duration = Duration::from_millis(i32::MAX as u64);
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS records (
+        kind TEXT NOT NULL,
+        key TEXT NOT NULL,
+        data TEXT NOT NULL,
+        updated_at TEXT NOT NULL,
+        PRIMARY KEY (kind, key)
+    ) WITHOUT ROWID;
+";
+
+const ISSUES: &str = "issues";
+const PROJECTS: &str = "projects";
+const WORKSPACES: &str = "workspaces";
+const LABELS: &str = "labels";
+const VIEWS: &str = "views";
+const USERS: &str = "users";
+const TEAMS: &str = "teams";
+const REMOTES: &str = "remotes";
+
+/// Handle to a workspace's SQLite database, shared by the SQLite repositories
+#[derive(Clone)]
+pub struct SqliteStorage {
+    conn: Arc<Mutex<Connection>>,
+    path: PathBuf,
+}
+
+/// Typed access to the `records` table within a read or a transaction
+pub struct Records<'a> {
+    conn: &'a Connection,
+}
+
+impl Records<'_> {
+    /// Read one record
+    pub fn get<T: Versioned>(&self, kind: &str, key: &str) -> Result<Option<T>> {
+        let data: Option<String> = self.conn
+            .query_row(
+                "SELECT data FROM records WHERE kind = ?1 AND key = ?2",
+                params![kind, key],
+                |row| row.get(0),
+            )
+            .optional()?;
+
+        match data {
+            Some(data) => Ok(Some(odi_core::format::open(data.as_bytes())?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Insert or replace one record
+    pub fn put<T: Versioned>(&self, kind: &str, key: &str, value: &T) -> Result<()> {
+        let data = serde_json::to_string(&odi_core::format::Envelope::wrap(value)?)?;
+        self.conn.execute(
+            "INSERT OR REPLACE INTO records (kind, key, data, updated_at) VALUES (?1, ?2, ?3, ?4)",
+            params![kind, key, data, chrono::Utc::now().to_rfc3339()],
+        )?;
+        Ok(())
+    }
+
+    /// Delete one record, returning whether it existed
+    pub fn delete(&self, kind: &str, key: &str) -> Result<bool> {
+        let deleted = self.conn.execute(
+            "DELETE FROM records WHERE kind = ?1 AND key = ?2",
+            params![kind, key],
+        )?;
+        Ok(deleted > 0)
+    }
+
+    /// Read every record of a kind whose key starts with `key_prefix`, ordered by key
+    pub fn list<T: Versioned>(&self, kind: &str, key_prefix: &str) -> Result<Vec<T>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT data FROM records WHERE kind = ?1 AND substr(key, 1, length(?2)) = ?2 ORDER BY key",
+        )?;
+        let rows = stmt.query_map(params![kind, key_prefix], |row| row.get::<_, String>(0))?;
+
+        let mut values = Vec::new();
+        for data in rows {
+            values.push(odi_core::format::open(data?.as_bytes())?);
+        }
+        Ok(values)
+    }
+}
+
+impl SqliteStorage {
+    /// Open (creating if needed) the database in a `.odi` directory
+    pub fn open(odi_dir: &Path, options: &LockOptions) -> Result<Self> {
+        std::fs::create_dir_all(odi_dir)?;
+        let path = odi_dir.join(DATABASE_FILE);
+        let conn = Connection::open(&path)?;
+
+        // Wait for other writers as long as a storage lock would, within what SQLite takes
+        let busy_timeout = options.timeout.map_or(MAX_BUSY_TIMEOUT, |timeout| timeout.min(MAX_BUSY_TIMEOUT));
+        conn.busy_timeout(busy_timeout)?;
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+
+        let version: i64 = conn.pragma_query_value(None, "user_version", |row| row.get(0))?;
+        if version > SCHEMA_VERSION {
+            return Err(FsError::StorageError {
+                message: format!(
+                    "{} uses schema version {}, but this version of odi supports up to {}",
+                    path.display(), version, SCHEMA_VERSION
+                ),
+            });
+        }
+        conn.execute_batch(SCHEMA)?;
+        conn.pragma_update(None, "user_version", SCHEMA_VERSION)?;
+
+        Ok(Self {
+            conn: Arc::new(Mutex::new(conn)),
+            path,
+        })
+    }
+
+    /// Path of the database file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Run read-only work against the database off the async runtime
+    pub async fn read<F, R>(&self, f: F) -> Result<R>
+    where
+        F: FnOnce(&Records<'_>) -> Result<R> + Send + 'static,
+        R: Send + 'static,
+    {
+        let conn = self.conn.clone();
+        tokio::task::spawn_blocking(move || {
+            let conn = conn.lock().map_err(|e| FsError::StorageError { message: e.to_string() })?;
+            f(&Records { conn: &conn })
+        })
+        .await
+        .map_err(|e| FsError::StorageError { message: e.to_string() })?
+    }
+
+    /// Run work in a write transaction, committed only if `f` succeeds
+    pub async fn write<F, R>(&self, f: F) -> Result<R>
+    where
+        F: FnOnce(&Records<'_>) -> Result<R> + Send + 'static,
+        R: Send + 'static,
+    {
+        let conn = self.conn.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut conn = conn.lock().map_err(|e| FsError::StorageError { message: e.to_string() })?;
+            let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+            let result = f(&Records { conn: &tx })?;
+            tx.commit()?;
+            Ok(result)
+        })
+        .await
+        .map_err(|e| FsError::StorageError { message: e.to_string() })?
+    }
+
+    async fn get<T: Versioned + Send + 'static>(&self, kind: &'static str, key: &str) -> odi_core::Result<Option<T>> {
+        let key = key.to_string();
+        self.read(move |records| records.get(kind, &key)).await.map_err(storage_error)
+    }
+
+    async fn put<T: Versioned + Send + 'static>(&self, kind: &'static str, key: &str, value: T) -> odi_core::Result<T> {
+        let key = key.to_string();
+        self.write(move |records| {
+            records.put(kind, &key, &value)?;
+            Ok(value)
+        })
+        .await
+        .map_err(storage_error)
+    }
+
+    async fn delete(&self, kind: &'static str, key: &str) -> odi_core::Result<bool> {
+        let key = key.to_string();
+        self.write(move |records| records.delete(kind, &key)).await.map_err(storage_error)
+    }
+
+    async fn list<T: Versioned + Send + 'static>(&self, kind: &'static str, key_prefix: &str) -> odi_core::Result<Vec<T>> {
+        let key_prefix = key_prefix.to_string();
+        self.read(move |records| records.list(kind, &key_prefix)).await.map_err(storage_error)
+    }
+
+    /// Read-modify-write one record in a single transaction
+    async fn modify<T, F>(&self, kind: &'static str, key: &str, f: F) -> odi_core::Result<Option<T>>
+    where
+        T: Versioned + Send + 'static,
+        F: FnOnce(&mut T) + Send + 'static,
+    {
+        let key = key.to_string();
+        self.write(move |records| {
+            let Some(mut value) = records.get::<T>(kind, &key)? else {
+                return Ok(None);
+            };
+            f(&mut value);
+            records.put(kind, &key, &value)?;
+            Ok(Some(value))
+        })
+        .await
+        .map_err(storage_error)
+    }
+}
+
+/// Rewrite every record as a current-version envelope, for workspace migrations
+///
+/// Does nothing if the workspace has no database.
+pub(crate) fn reseal_records(odi_dir: &Path) -> Result<usize> {
+    let path = odi_dir.join(DATABASE_FILE);
+    if !path.exists() {
+        return Ok(0);
+    }
+
+    let mut conn = Connection::open(&path)?;
+    let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+    let rows = {
+        let mut stmt = tx.prepare("SELECT kind, key, data FROM records")?;
+        let rows = stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?)))?;
+        rows.collect::<rusqlite::Result<Vec<_>>>()?
+    };
+
+    let mut rewritten = 0;
+    for (kind, key, data) in rows {
+        let Some(envelope_kind) = crate::version::kind_for_namespace(&kind) else {
+            continue;
+        };
+        let sealed = String::from_utf8(odi_core::format::reseal(data.as_bytes(), envelope_kind)?)
+            .map_err(|e| FsError::SerializationError { message: e.to_string() })?;
+        if sealed != data {
+            tx.execute(
+                "UPDATE records SET data = ?1 WHERE kind = ?2 AND key = ?3",
+                params![sealed, kind, key],
+            )?;
+            rewritten += 1;
+        }
+    }
+    tx.commit()?;
+    Ok(rewritten)
+}
+
+/// Issue repository implementation using SQLite storage
+pub struct SqliteIssueRepository {
+    storage: SqliteStorage,
+}
+
+impl SqliteIssueRepository {
+    pub fn new(storage: SqliteStorage) -> Self {
+        Self { storage }
+    }
+}
+
+#[async_trait::async_trait]
+impl IssueRepository for SqliteIssueRepository {
+    async fn create(&self, issue: Issue) -> odi_core::Result<Issue> {
+        let key = issue.id.to_string();
+        self.storage.put(ISSUES, &key, issue).await
+    }
+
+    async fn get(&self, id: &IssueId) -> odi_core::Result<Option<Issue>> {
+        self.storage.get(ISSUES, &id.to_string()).await
+    }
+
+    async fn update(&self, id: &IssueId, update: IssueUpdate) -> odi_core::Result<Option<Issue>> {
+        self.storage.modify(ISSUES, &id.to_string(), move |issue: &mut Issue| update.apply_to(issue)).await
+    }
+
+    async fn delete(&self, id: &IssueId) -> odi_core::Result<bool> {
+        self.storage.delete(ISSUES, &id.to_string()).await
+    }
+
+    async fn list(&self, query: IssueQuery) -> odi_core::Result<Vec<Issue>> {
+        let issues = self.storage.list(ISSUES, "").await?;
+        Ok(query.apply(issues))
+    }
+
+    async fn count(&self, query: IssueQuery) -> odi_core::Result<usize> {
+        Ok(self.list(query).await?.len())
+    }
+
+    async fn get_assigned_to(&self, user_id: &UserId) -> odi_core::Result<Vec<Issue>> {
+        self.list(IssueQuery { assignee: Some(user_id.clone()), ..Default::default() }).await
+    }
+
+    async fn get_authored_by(&self, user_id: &UserId) -> odi_core::Result<Vec<Issue>> {
+        self.list(IssueQuery { author: Some(user_id.clone()), ..Default::default() }).await
+    }
+
+    async fn get_by_project(&self, project_id: &ProjectId) -> odi_core::Result<Vec<Issue>> {
+        self.list(IssueQuery { project_id: Some(project_id.clone()), ..Default::default() }).await
+    }
+
+    async fn search(&self, query: &str) -> odi_core::Result<Vec<Issue>> {
+        let needle = query.to_lowercase();
+        let issues = self.list(IssueQuery::default()).await?;
+        Ok(issues.into_iter()
+            .filter(|issue| issue.title.to_lowercase().contains(&needle)
+                || issue.description.as_ref().is_some_and(|d| d.to_lowercase().contains(&needle)))
+            .collect())
+    }
+
+    async fn get_recent(&self, limit: usize) -> odi_core::Result<Vec<Issue>> {
+        let mut issues = self.list(IssueQuery::default()).await?;
+        issues.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+        issues.truncate(limit);
+        Ok(issues)
+    }
+}
+
+/// Project repository implementation using SQLite storage
+pub struct SqliteProjectRepository {
+    storage: SqliteStorage,
+}
+
+impl SqliteProjectRepository {
+    pub fn new(storage: SqliteStorage) -> Self {
+        Self { storage }
+    }
+
+    fn label_key(project_id: &ProjectId, label_id: &LabelId) -> String {
+        format!("{}/{}", project_id, label_id)
+    }
+
+    fn view_key(project_id: &ProjectId, name: &ViewName) -> String {
+        format!("{}/{}", project_id, name)
+    }
+}
+
+#[async_trait::async_trait]
+impl ProjectRepository for SqliteProjectRepository {
+    async fn create_project(&self, project: Project) -> odi_core::Result<Project> {
+        let key = project.id.clone();
+        self.storage.put(PROJECTS, &key, project).await
+    }
+
+    async fn get_project(&self, id: &ProjectId) -> odi_core::Result<Option<Project>> {
+        self.storage.get(PROJECTS, id).await
+    }
+
+    async fn update_project(&self, id: &ProjectId, update: ProjectUpdate) -> odi_core::Result<Option<Project>> {
+        self.storage.modify(PROJECTS, id, move |project: &mut Project| update.apply_to(project)).await
+    }
+
+    async fn delete_project(&self, id: &ProjectId) -> odi_core::Result<bool> {
+        self.storage.delete(PROJECTS, id).await
+    }
+
+    async fn list_projects(&self, query: ProjectQuery) -> odi_core::Result<Vec<Project>> {
+        let projects = self.storage.list(PROJECTS, "").await?;
+        Ok(query.apply(projects))
+    }
+
+    async fn count_projects(&self, query: ProjectQuery) -> odi_core::Result<usize> {
+        Ok(self.list_projects(query).await?.len())
+    }
+
+    async fn search_projects(&self, query: &str) -> odi_core::Result<Vec<Project>> {
+        let needle = query.to_lowercase();
+        let projects = self.list_projects(ProjectQuery::default()).await?;
+        Ok(projects.into_iter()
+            .filter(|project| project.name.to_lowercase().contains(&needle)
+                || project.description.as_ref().is_some_and(|d| d.to_lowercase().contains(&needle)))
+            .collect())
+    }
+
+    async fn create_workspace(&self, workspace: Workspace) -> odi_core::Result<Workspace> {
+        let key = workspace.id.clone();
+        self.storage.put(WORKSPACES, &key, workspace).await
+    }
+
+    async fn get_workspace(&self, id: &WorkspaceId) -> odi_core::Result<Option<Workspace>> {
+        self.storage.get(WORKSPACES, id).await
+    }
+
+    async fn get_workspace_by_path(&self, path: &std::path::PathBuf) -> odi_core::Result<Option<Workspace>> {
+        let workspaces = self.list_workspaces(WorkspaceQuery::default()).await?;
+        Ok(workspaces.into_iter().find(|workspace| workspace.path == *path))
+    }
+
+    async fn update_workspace(&self, id: &WorkspaceId, update: WorkspaceUpdate) -> odi_core::Result<Option<Workspace>> {
+        self.storage.modify(WORKSPACES, id, move |workspace: &mut Workspace| update.apply_to(workspace)).await
+    }
+
+    async fn delete_workspace(&self, id: &WorkspaceId) -> odi_core::Result<bool> {
+        self.storage.delete(WORKSPACES, id).await
+    }
+
+    async fn list_workspaces(&self, query: WorkspaceQuery) -> odi_core::Result<Vec<Workspace>> {
+        let workspaces = self.storage.list(WORKSPACES, "").await?;
+        Ok(query.apply(workspaces))
+    }
+
+    async fn count_workspaces(&self, query: WorkspaceQuery) -> odi_core::Result<usize> {
+        Ok(self.list_workspaces(query).await?.len())
+    }
+
+    async fn get_project_workspaces(&self, project_id: &ProjectId) -> odi_core::Result<Vec<Workspace>> {
+        self.list_workspaces(WorkspaceQuery { project_id: Some(project_id.clone()), ..Default::default() }).await
+    }
+
+    async fn get_workspace_projects(&self, workspace_id: &WorkspaceId) -> odi_core::Result<Vec<Project>> {
+        self.list_projects(ProjectQuery { workspace_id: Some(workspace_id.clone()), ..Default::default() }).await
+    }
+
+    async fn link_project_workspace(&self, project_id: &ProjectId, workspace_id: &WorkspaceId) -> odi_core::Result<()> {
+        let (project_id, workspace_id) = (project_id.clone(), workspace_id.clone());
+        self.storage.write(move |records| {
+            if let Some(mut project) = records.get::<Project>(PROJECTS, &project_id)? {
+                if !project.workspaces.contains(&workspace_id) {
+                    project.workspaces.push(workspace_id.clone());
+                    project.updated_at = chrono::Utc::now();
+                    records.put(PROJECTS, &project_id, &project)?;
+                }
+            }
+            if let Some(mut workspace) = records.get::<Workspace>(WORKSPACES, &workspace_id)? {
+                if !workspace.projects.contains(&project_id) {
+                    workspace.projects.push(project_id.clone());
+                    records.put(WORKSPACES, &workspace_id, &workspace)?;
+                }
+            }
+            Ok(())
+        })
+        .await
+        .map_err(storage_error)
+    }
+
+    async fn unlink_project_workspace(&self, project_id: &ProjectId, workspace_id: &WorkspaceId) -> odi_core::Result<()> {
+        let (project_id, workspace_id) = (project_id.clone(), workspace_id.clone());
+        self.storage.write(move |records| {
+            if let Some(mut project) = records.get::<Project>(PROJECTS, &project_id)? {
+                project.workspaces.retain(|w| *w != workspace_id);
+                project.updated_at = chrono::Utc::now();
+                records.put(PROJECTS, &project_id, &project)?;
+            }
+            if let Some(mut workspace) = records.get::<Workspace>(WORKSPACES, &workspace_id)? {
+                workspace.projects.retain(|p| *p != project_id);
+                records.put(WORKSPACES, &workspace_id, &workspace)?;
+            }
+            Ok(())
+        })
+        .await
+        .map_err(storage_error)
+    }
+
+    async fn create_label(&self, project_id: &ProjectId, label: Label) -> odi_core::Result<Label> {
+        let key = Self::label_key(project_id, &label.id);
+        self.storage.put(LABELS, &key, label).await
+    }
+
+    async fn get_label(&self, project_id: &ProjectId, label_id: &LabelId) -> odi_core::Result<Option<Label>> {
+        self.storage.get(LABELS, &Self::label_key(project_id, label_id)).await
+    }
+
+    async fn update_label(&self, project_id: &ProjectId, label_id: &LabelId, label: Label) -> odi_core::Result<Option<Label>> {
+        // The label may be renamed, so replace the old record in the same transaction
+        let old_key = Self::label_key(project_id, label_id);
+        let new_key = Self::label_key(project_id, &label.id);
+        self.storage.write(move |records| {
+            records.delete(LABELS, &old_key)?;
+            records.put(LABELS, &new_key, &label)?;
+            Ok(Some(label))
+        })
+        .await
+        .map_err(storage_error)
+    }
+
+    async fn delete_label(&self, project_id: &ProjectId, label_id: &LabelId) -> odi_core::Result<bool> {
+        self.storage.delete(LABELS, &Self::label_key(project_id, label_id)).await
+    }
+
+    async fn list_labels(&self, project_id: &ProjectId, query: LabelQuery) -> odi_core::Result<Vec<Label>> {
+        let labels = self.storage.list(LABELS, &format!("{}/", project_id)).await?;
+        Ok(query.apply(labels))
+    }
+
+    async fn get_all_labels(&self, query: LabelQuery) -> odi_core::Result<Vec<Label>> {
+        let prefix = match &query.project_id {
+            Some(project_id) => format!("{}/", project_id),
+            None => String::new(),
+        };
+        let labels = self.storage.list(LABELS, &prefix).await?;
+        Ok(query.apply(labels))
+    }
+
+    async fn save_view(&self, project_id: &ProjectId, view: View) -> odi_core::Result<View> {
+        let key = Self::view_key(project_id, &view.name);
+        self.storage.put(VIEWS, &key, view).await
+    }
+
+    async fn get_view(&self, project_id: &ProjectId, name: &ViewName) -> odi_core::Result<Option<View>> {
+        self.storage.get(VIEWS, &Self::view_key(project_id, name)).await
+    }
+
+    async fn delete_view(&self, project_id: &ProjectId, name: &ViewName) -> odi_core::Result<bool> {
+        self.storage.delete(VIEWS, &Self::view_key(project_id, name)).await
+    }
+
+    async fn list_views(&self, project_id: &ProjectId) -> odi_core::Result<Vec<View>> {
+        // Keys are <project>/<name>, so views come back ordered by name
+        self.storage.list(VIEWS, &format!("{}/", project_id)).await
+    }
+
+    async fn add_project_issue(&self, _project_id: &ProjectId, _issue_id: &IssueId) -> odi_core::Result<()> {
+        // Issues carry their project_id; nothing to store here
+        Ok(())
+    }
+
+    async fn remove_project_issue(&self, _project_id: &ProjectId, _issue_id: &IssueId) -> odi_core::Result<()> {
+        // Issues carry their project_id; nothing to store here
+        Ok(())
+    }
+
+    async fn add_project_team(&self, project_id: &ProjectId, team_id: &TeamId) -> odi_core::Result<()> {
+        let team_id = team_id.clone();
+        self.storage.modify(PROJECTS, project_id, move |project: &mut Project| {
+            if !project.teams.contains(&team_id) {
+                project.teams.push(team_id);
+                project.updated_at = chrono::Utc::now();
+            }
+        }).await?;
+        Ok(())
+    }
+
+    async fn remove_project_team(&self, project_id: &ProjectId, team_id: &TeamId) -> odi_core::Result<()> {
+        let team_id = team_id.clone();
+        self.storage.modify(PROJECTS, project_id, move |project: &mut Project| {
+            project.teams.retain(|t| *t != team_id);
+            project.updated_at = chrono::Utc::now();
+        }).await?;
+        Ok(())
+    }
+}
+
+/// User repository implementation using SQLite storage
+pub struct SqliteUserRepository {
+    storage: SqliteStorage,
+}
+
+impl SqliteUserRepository {
+    pub fn new(storage: SqliteStorage) -> Self {
+        Self { storage }
+    }
+}
+
+#[async_trait::async_trait]
+impl UserRepository for SqliteUserRepository {
+    async fn create_user(&self, user: User) -> odi_core::Result<User> {
+        let key = user.id.clone();
+        self.storage.put(USERS, &key, user).await
+    }
+
+    async fn get_user(&self, id: &UserId) -> odi_core::Result<Option<User>> {
+        self.storage.get(USERS, id).await
+    }
+
+    async fn get_user_by_email(&self, email: &str) -> odi_core::Result<Option<User>> {
+        let users = self.list_users(UserQuery::default()).await?;
+        Ok(users.into_iter().find(|user| user.email == email))
+    }
+
+    async fn update_user(&self, id: &UserId, update: UserUpdate) -> odi_core::Result<Option<User>> {
+        self.storage.modify(USERS, id, move |user: &mut User| update.apply_to(user)).await
+    }
+
+    async fn delete_user(&self, id: &UserId) -> odi_core::Result<bool> {
+        self.storage.delete(USERS, id).await
+    }
+
+    async fn list_users(&self, query: UserQuery) -> odi_core::Result<Vec<User>> {
+        let users = self.storage.list(USERS, "").await?;
+        Ok(query.apply(users))
+    }
+
+    async fn count_users(&self, query: UserQuery) -> odi_core::Result<usize> {
+        Ok(self.list_users(query).await?.len())
+    }
+
+    async fn search_users(&self, _query: &str) -> odi_core::Result<Vec<User>> {
+        Ok(Vec::new())
+    }
+
+    async fn get_user_teams(&self, _user_id: &UserId) -> odi_core::Result<Vec<Team>> {
+        Ok(Vec::new())
+    }
+
+    async fn get_team_members(&self, team_id: &TeamId) -> odi_core::Result<Vec<User>> {
+        self.list_users(UserQuery { team_id: Some(team_id.clone()), ..Default::default() }).await
+    }
+
+    async fn add_team_member(&self, team_id: &TeamId, user_id: &UserId) -> odi_core::Result<()> {
+        let team_id = team_id.clone();
+        self.storage.modify(USERS, user_id, move |user: &mut User| {
+            if !user.teams.contains(&team_id) {
+                user.teams.push(team_id);
+            }
+        }).await?;
+        Ok(())
+    }
+
+    async fn remove_team_member(&self, team_id: &TeamId, user_id: &UserId) -> odi_core::Result<()> {
+        let team_id = team_id.clone();
+        self.storage.modify(USERS, user_id, move |user: &mut User| {
+            user.teams.retain(|t| *t != team_id);
+        }).await?;
+        Ok(())
+    }
+
+    async fn create_team(&self, team: Team) -> odi_core::Result<Team> {
+        let key = team.id.clone();
+        self.storage.put(TEAMS, &key, team).await
+    }
+
+    async fn get_team(&self, id: &TeamId) -> odi_core::Result<Option<Team>> {
+        self.storage.get(TEAMS, id).await
+    }
+
+    async fn update_team(&self, id: &TeamId, update: TeamUpdate) -> odi_core::Result<Option<Team>> {
+        self.storage.modify(TEAMS, id, move |team: &mut Team| update.apply_to(team)).await
+    }
+
+    async fn delete_team(&self, id: &TeamId) -> odi_core::Result<bool> {
+        self.storage.delete(TEAMS, id).await
+    }
+
+    async fn list_teams(&self, query: TeamQuery) -> odi_core::Result<Vec<Team>> {
+        let teams = self.storage.list(TEAMS, "").await?;
+        Ok(query.apply(teams))
+    }
+
+    async fn count_teams(&self, query: TeamQuery) -> odi_core::Result<usize> {
+        Ok(self.list_teams(query).await?.len())
+    }
+}
+
+/// Remote repository implementation using SQLite storage
+pub struct SqliteRemoteRepository {
+    storage: SqliteStorage,
+}
+
+impl SqliteRemoteRepository {
+    pub fn new(storage: SqliteStorage) -> Self {
+        Self { storage }
+    }
+}
+
+#[async_trait::async_trait]
+impl RemoteRepository for SqliteRemoteRepository {
+    async fn create(&self, remote: Remote) -> odi_core::Result<Remote> {
+        // Remote names are unique, as they are with config-file remotes
+        self.storage.write(move |records| {
+            let existing: Vec<Remote> = records.list(REMOTES, "")?;
+            if existing.iter().any(|r| r.name == remote.name) {
+                return Ok(Err(CoreError::ValidationError {
+                    field: "remote".to_string(),
+                    message: format!("Remote '{}' already exists", remote.name),
+                }));
+            }
+            records.put(REMOTES, &remote.id, &remote)?;
+            Ok(Ok(remote))
+        })
+        .await
+        .map_err(storage_error)?
+    }
+
+    async fn get(&self, id: &RemoteId) -> odi_core::Result<Option<Remote>> {
+        self.storage.get(REMOTES, id).await
+    }
+
+    async fn update(&self, id: &RemoteId, remote: Remote) -> odi_core::Result<Option<Remote>> {
+        self.storage.modify(REMOTES, id, move |existing: &mut Remote| *existing = remote).await
+    }
+
+    async fn delete(&self, id: &RemoteId) -> odi_core::Result<bool> {
+        self.storage.delete(REMOTES, id).await
+    }
+
+    async fn list(&self) -> odi_core::Result<Vec<Remote>> {
+        self.storage.list(REMOTES, "").await
+    }
+
+    async fn get_by_project(&self, project_id: &ProjectId) -> odi_core::Result<Vec<Remote>> {
+        let remotes = self.list().await?;
+        Ok(remotes.into_iter().filter(|remote| remote.has_project(project_id)).collect())
+    }
+
+    async fn exists(&self, name: &str) -> odi_core::Result<bool> {
+        Ok(self.get_by_name(name).await?.is_some())
+    }
+
+    async fn get_by_name(&self, name: &str) -> odi_core::Result<Option<Remote>> {
+        let remotes = self.list().await?;
+        Ok(remotes.into_iter().find(|remote| remote.name == name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_issue_round_trip() {
+        let dir = TempDir::new().unwrap();
+        let storage = SqliteStorage::open(dir.path(), &LockOptions::default()).unwrap();
+        let repo = SqliteIssueRepository::new(storage.clone());
+
+        let issue = Issue::new("Crash on start".to_string(), "alice".to_string());
+        repo.create(issue.clone()).await.unwrap();
+
+        let updated = repo.update(&issue.id, IssueUpdate::new().status(IssueStatus::InProgress)).await.unwrap().unwrap();
+        assert_eq!(updated.status, IssueStatus::InProgress);
+
+        // A second handle sees the committed state
+        let reopened = SqliteIssueRepository::new(SqliteStorage::open(dir.path(), &LockOptions::default()).unwrap());
+        let listed = reopened.list(IssueQuery::new().status(IssueStatus::InProgress)).await.unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].title, "Crash on start");
+
+        assert!(repo.delete(&issue.id).await.unwrap());
+        assert!(repo.get(&issue.id).await.unwrap().is_none());
+    }
+
+    #[test]
+    fn test_open_waiting_forever() {
+        let dir = TempDir::new().unwrap();
+        SqliteStorage::open(dir.path(), &LockOptions::new().wait_forever()).unwrap();
+        SqliteStorage::open(dir.path(), &LockOptions::new().timeout(Duration::from_secs(30 * 24 * 3600))).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_labels_are_project_scoped() {
+        let dir = TempDir::new().unwrap();
+        let repo = SqliteProjectRepository::new(SqliteStorage::open(dir.path(), &LockOptions::default()).unwrap());
+
+        let label = Label::new("bug".to_string(), "bug".to_string(), "#ff0000".to_string());
+        repo.create_label(&"core".to_string(), label.clone()).await.unwrap();
+        repo.create_label(&"web".to_string(), label).await.unwrap();
+
+        assert_eq!(repo.list_labels(&"core".to_string(), LabelQuery::default()).await.unwrap().len(), 1);
+        assert_eq!(repo.get_all_labels(LabelQuery::default()).await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_remote_names_are_unique() {
+        let dir = TempDir::new().unwrap();
+        let repo = SqliteRemoteRepository::new(SqliteStorage::open(dir.path(), &LockOptions::default()).unwrap());
+
+        let remote = Remote::new("origin".to_string(), "origin".to_string(), "ssh://git@example.com/odi".to_string());
+        repo.create(remote.clone()).await.unwrap();
+        assert!(repo.create(remote).await.is_err());
+        assert!(repo.exists("origin").await.unwrap());
+    }
+}
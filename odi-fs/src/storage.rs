@@ -1,10 +1,19 @@
 use crate::Result;
+use crate::crypto::{self, Cipher, KeySource};
+use crate::lock::{self, LockGuard, LockOptions, REPOSITORY_LOCKS};
+use crate::pack::{GcStats, PackObject, PackStore};
+use crate::reflog::{self, ReflogContext};
+use odi_core::Versioned;
 use flate2::{read::GzDecoder, write::GzEncoder, Compression};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
-use std::fs::{self, OpenOptions};
+use std::collections::{HashMap, HashSet};
+use std::fs;
 use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
 use chrono::{DateTime, Utc};
 
 /// Object type in the storage system
@@ -15,6 +24,7 @@ pub enum ObjectType {
     Team,
     Project,
     Label,
+    View,
     Remote,
 }
 
@@ -93,27 +103,95 @@ pub trait ObjectStorage {
     fn is_locked(&self, name: &str) -> Result<bool>;
 }
 
+/// Domain types that can be persisted through a [`StorageEngine`]
+///
+/// Objects are stored inside a versioned envelope (see `odi_core::format`).
+pub trait StoredObject: Versioned + Send + Sync + 'static {
+    /// Object type recorded alongside the serialized data
+    const OBJECT_TYPE: ObjectType;
+}
+
+impl StoredObject for odi_core::Issue {
+    const OBJECT_TYPE: ObjectType = ObjectType::Issue;
+}
+
+impl StoredObject for odi_core::User {
+    const OBJECT_TYPE: ObjectType = ObjectType::User;
+}
+
+impl StoredObject for odi_core::Team {
+    const OBJECT_TYPE: ObjectType = ObjectType::Team;
+}
+
+impl StoredObject for odi_core::Project {
+    const OBJECT_TYPE: ObjectType = ObjectType::Project;
+}
+
+// Workspaces have no object type of their own and are stored alongside projects
+impl StoredObject for odi_core::Workspace {
+    const OBJECT_TYPE: ObjectType = ObjectType::Project;
+}
+
+impl StoredObject for odi_core::Label {
+    const OBJECT_TYPE: ObjectType = ObjectType::Label;
+}
+
+impl StoredObject for odi_core::View {
+    const OBJECT_TYPE: ObjectType = ObjectType::View;
+}
+
+impl StoredObject for odi_core::Remote {
+    const OBJECT_TYPE: ObjectType = ObjectType::Remote;
+}
+
 /// High-level storage engine interface for Git-like operations
+///
+/// Objects are written in the same format as [`ObjectStorage`], so both
+/// interfaces can be used on the same store.
 #[async_trait::async_trait]
 pub trait StorageEngine {
+    /// Create the object store layout under `path`
     async fn initialize(&self, path: &Path) -> Result<()>;
-    async fn write_object<T: serde::Serialize + Send + Sync>(&self, obj: &T) -> Result<ObjectHash>;
-    async fn read_object<T: serde::de::DeserializeOwned + Send>(&self, hash: &ObjectHash) -> Result<Option<T>>;
-    async fn delete_object(&self, hash: &ObjectHash) -> Result<()>;
+    async fn write_object<T: StoredObject>(&self, obj: &T) -> Result<ObjectHash>;
+    async fn read_object<T: StoredObject>(&self, hash: &ObjectHash) -> Result<Option<T>>;
+    /// Read many objects concurrently, preserving the order of `hashes`
+    async fn read_objects<T: StoredObject>(&self, hashes: &[ObjectHash]) -> Result<Vec<Option<T>>>;
+    async fn delete_object(&self, hash: &ObjectHash) -> Result<bool>;
     async fn list_objects(&self, object_type: ObjectType) -> Result<Vec<ObjectHash>>;
-    async fn write_ref(&self, name: &str, hash: &ObjectHash) -> Result<()>;
+    async fn write_ref(&self, name: &str, hash: &ObjectHash, object_type: ObjectType) -> Result<()>;
     async fn read_ref(&self, name: &str) -> Result<Option<ObjectHash>>;
+    async fn delete_ref(&self, name: &str) -> Result<bool>;
+    /// Names of all refs starting with `prefix`
     async fn list_refs(&self, prefix: &str) -> Result<Vec<String>>;
-    async fn lock(&self, resource: &str) -> Result<Lock>;
-    async fn unlock(&self, lock: Lock) -> Result<()>;
+    /// Load every object pointed to by a ref starting with `prefix`
+    async fn read_all<T: StoredObject>(&self, prefix: &str) -> Result<Vec<T>>;
+    async fn lock(&self, resource: &str) -> Result<LockGuard>;
+    async fn unlock(&self, lock: LockGuard) -> Result<()>;
 }
 
+/// Default limit on concurrent reads in bulk operations
+const DEFAULT_READ_PARALLELISM: usize = 32;
+
 #[derive(Clone)]
 pub struct FileSystemStorage {
     root_path: PathBuf,
     objects_path: PathBuf,
     refs_path: PathBuf,
     locks_path: PathBuf,
+    tmp_path: PathBuf,
+    lock_options: LockOptions,
+    /// Maximum number of concurrent file reads in bulk operations
+    read_parallelism: usize,
+    /// Guards handed out through `ObjectStorage::acquire_lock`, held until `release_lock`
+    held_locks: Arc<Mutex<HashMap<String, LockGuard>>>,
+    /// Objects moved into packfiles by `gc`
+    packs: Arc<PackStore>,
+    /// Whether the workspace has a keyring and objects are written encrypted
+    encrypted: bool,
+    /// Unlocked data keys; without them encrypted objects can't be read or written
+    cipher: Option<Arc<Cipher>>,
+    /// Command and user recorded in the reflog for ref changes
+    reflog: ReflogContext,
 }
 
 impl FileSystemStorage {
@@ -121,20 +199,250 @@ impl FileSystemStorage {
         let objects_path = root_path.join("objects");
         let refs_path = root_path.join("refs");
         let locks_path = root_path.join("locks");
+        let tmp_path = root_path.join("tmp");
         
         // Create directories if they don't exist
         fs::create_dir_all(&objects_path)?;
         fs::create_dir_all(&refs_path)?;
         fs::create_dir_all(&locks_path)?;
+        fs::create_dir_all(&tmp_path)?;
+        
+        let packs = Arc::new(PackStore::new(&objects_path));
+        let encrypted = crypto::is_encrypted(&root_path);
         
         Ok(Self {
             root_path,
             objects_path,
             refs_path,
             locks_path,
+            tmp_path,
+            lock_options: LockOptions::default(),
+            read_parallelism: DEFAULT_READ_PARALLELISM,
+            held_locks: Arc::new(Mutex::new(HashMap::new())),
+            packs,
+            encrypted,
+            cipher: None,
+            reflog: ReflogContext::default(),
         })
     }
     
+    /// Set how lock acquisition waits for contended locks
+    pub fn with_lock_options(mut self, options: LockOptions) -> Self {
+        self.lock_options = options;
+        self
+    }
+    
+    /// Set how many files bulk reads may have open at once
+    pub fn with_read_parallelism(mut self, limit: usize) -> Self {
+        self.read_parallelism = limit.max(1);
+        self
+    }
+    
+    /// Use unlocked keys to read and write objects of an encrypted workspace
+    pub fn with_cipher(mut self, cipher: Cipher) -> Self {
+        self.encrypted = true;
+        self.cipher = Some(Arc::new(cipher));
+        self
+    }
+    
+    /// Whether objects in this workspace are encrypted
+    pub fn is_encrypted(&self) -> bool {
+        self.encrypted
+    }
+    
+    /// Whether encrypted objects can be read, i.e. the workspace is unencrypted or unlocked
+    pub fn is_unlocked(&self) -> bool {
+        !self.encrypted || self.cipher.is_some()
+    }
+    
+    /// Record ref changes in the reflog under this command and user
+    pub fn with_reflog_context(mut self, context: ReflogContext) -> Self {
+        self.reflog = context;
+        self
+    }
+    
+    /// Command and user ref changes are recorded under
+    pub fn reflog_context(&self) -> &ReflogContext {
+        &self.reflog
+    }
+    
+    /// Lock options used by this storage
+    pub fn lock_options(&self) -> &LockOptions {
+        &self.lock_options
+    }
+    
+    /// The `.odi` directory this storage lives in
+    pub fn root_path(&self) -> &Path {
+        &self.root_path
+    }
+    
+    /// Acquire a lock that is released when the returned guard is dropped
+    pub fn acquire_guard(&self, name: &str) -> Result<LockGuard> {
+        lock::acquire(&self.locks_path, name, &self.lock_options)
+    }
+    
+    /// Acquire the locks of every repository, for operations that rewrite the whole store
+    pub fn acquire_all_guards(&self) -> Result<Vec<LockGuard>> {
+        REPOSITORY_LOCKS.iter().map(|name| self.acquire_guard(name)).collect()
+    }
+    
+    /// Acquire a lock only if it is free right now
+    pub fn try_acquire_guard(&self, name: &str) -> Result<Option<LockGuard>> {
+        lock::try_acquire(&self.locks_path, name)
+    }
+    
+    /// Pack every loose object, and the contents of existing packs, into one packfile
+    ///
+    /// Successive versions of the same entity are delta-encoded, and objects
+    /// deleted since the last gc are dropped. Holds every repository lock while
+    /// running.
+    pub fn gc(&self) -> Result<GcStats> {
+        if self.encrypted {
+            return Err(crate::FsError::StorageError {
+                message: "Packing is not supported in encrypted workspaces".to_string(),
+            });
+        }
+        
+        let _guards = self.acquire_all_guards()?;
+        
+        let (mut objects, pruned) = self.packs.read_all()?;
+        let loose = self.loose_hashes()?;
+        for hash in &loose {
+            let serialized = fs::read(self.get_object_path(hash)?)?;
+            let object = Self::decode_object(&serialized)?;
+            objects.push(PackObject { hash: hash.clone(), object_type: object.object_type, data: object.data });
+        }
+        
+        if loose.is_empty() && pruned == 0 {
+            return Ok(GcStats::default());
+        }
+        
+        let pack = self.packs.replace_all(objects, &self.tmp_path)?;
+        
+        // Every loose object is now packed
+        for hash in &loose {
+            let path = self.get_object_path(hash)?;
+            fs::remove_file(&path)?;
+            if let Some(dir) = path.parent() {
+                // Fails harmlessly while the fan-out directory still has objects
+                let _ = fs::remove_dir(dir);
+            }
+        }
+        
+        Ok(GcStats { loose: loose.len(), pruned, pack })
+    }
+    
+    /// Check every object against its hash
+    ///
+    /// Encrypted objects are addressed by the hash of their ciphertext, so this
+    /// needs no key. Returns the number of objects checked and the hashes of
+    /// those that don't match.
+    pub fn verify_objects(&self) -> Result<(usize, Vec<String>)> {
+        let loose = self.loose_hashes()?;
+        let mut corrupt = Vec::new();
+        
+        for hash in &loose {
+            let serialized = fs::read(self.get_object_path(hash)?)?;
+            let actual = if crypto::is_sealed(&serialized) {
+                Some(Self::get_object_hash(&serialized))
+            } else {
+                Self::decode_object(&serialized).ok().map(|object| Self::get_object_hash(&object.data))
+            };
+            if actual.as_deref() != Some(hash.as_str()) {
+                corrupt.push(hash.clone());
+            }
+        }
+        
+        let packed = self.packs.entries()?;
+        for (hash, _) in &packed {
+            match self.packs.get(hash) {
+                Ok(Some(object)) if Self::get_object_hash(&object.data) == *hash => {}
+                _ => corrupt.push(hash.clone()),
+            }
+        }
+        
+        Ok((loose.len() + packed.len(), corrupt))
+    }
+    
+    /// Re-encrypt every object under a fresh data key
+    ///
+    /// The keyring is rewrapped under `new_source` when given, which changes the
+    /// passphrase or key file. Refs and reflogs are pointed at the re-encrypted
+    /// objects, which hold the same data, so earlier versions can still be
+    /// restored and the rotation itself isn't logged. Old data keys are dropped
+    /// once every object and ref has moved over, so an interrupted rotation can
+    /// simply be run again.
+    /// Holds every repository lock while running. Returns the number of objects
+    /// re-encrypted.
+    pub fn rotate_key(&self, source: &KeySource, new_source: Option<&KeySource>) -> Result<usize> {
+        if !self.encrypted {
+            return Err(crate::FsError::StorageError {
+                message: "Workspace is not encrypted".to_string(),
+            });
+        }
+        
+        let _guards = self.acquire_all_guards()?;
+        
+        let rotated = self.clone().with_cipher(crypto::add_key(&self.root_path, source, new_source)?);
+        let mut renamed = HashMap::new();
+        for hash in self.loose_hashes()? {
+            let serialized = fs::read(self.get_object_path(&hash)?)?;
+            let object = Self::decode_file(rotated.cipher.as_deref(), &hash, &serialized)?;
+            let (new_hash, sealed) = rotated.encode_file(object.object_type, &object.data)?;
+            let path = self.get_object_path(&new_hash)?;
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            self.write_atomic(&path, &sealed)?;
+            renamed.insert(hash, new_hash);
+        }
+        
+        for object_ref in ObjectStorage::list_refs(self)? {
+            if let Some(new_hash) = renamed.get(&object_ref.hash) {
+                let serialized = Self::encode_ref(&object_ref.name, new_hash, object_ref.object_type)?;
+                self.write_atomic(&self.refs_path.join(&object_ref.name), &serialized)?;
+            }
+        }
+        reflog::rename_objects(self, &renamed)?;
+        for hash in renamed.keys() {
+            fs::remove_file(self.get_object_path(hash)?)?;
+        }
+        
+        crypto::retire_old_keys(&self.root_path)?;
+        Ok(renamed.len())
+    }
+    
+    /// Hashes of every loose (unpacked) object
+    fn loose_hashes(&self) -> Result<Vec<String>> {
+        let mut hashes = Vec::new();
+        for dir in fs::read_dir(&self.objects_path)? {
+            let dir = dir?;
+            let dir_name = dir.file_name().to_string_lossy().to_string();
+            if !dir.file_type()?.is_dir() || !is_fan_out(&dir_name) {
+                continue;
+            }
+            for file in fs::read_dir(dir.path())? {
+                let file = file?;
+                if file.file_type()?.is_file() {
+                    hashes.push(format!("{}{}", dir_name, file.file_name().to_string_lossy()));
+                }
+            }
+        }
+        Ok(hashes)
+    }
+    
+    /// Write a file by renaming a fully written temporary file into place,
+    /// so concurrent readers never observe a partial write
+    pub(crate) fn write_atomic(&self, path: &Path, data: &[u8]) -> Result<()> {
+        let tmp_file = self.tmp_path.join(uuid::Uuid::new_v4().to_string());
+        fs::write(&tmp_file, data)?;
+        if let Err(e) = fs::rename(&tmp_file, path) {
+            let _ = fs::remove_file(&tmp_file);
+            return Err(e.into());
+        }
+        Ok(())
+    }
+    
     /// Initialize storage in .odi/objects directory
     pub fn init() -> Result<Self> {
         let root_path = PathBuf::from(".odi");
@@ -142,10 +450,16 @@ impl FileSystemStorage {
         Self::new(root_path)
     }
     
-    fn get_object_path(&self, hash: &str) -> PathBuf {
+    fn get_object_path(&self, hash: &str) -> Result<PathBuf> {
+        // A corrupt ref could hold anything; only hex hashes name objects
+        if hash.len() <= 2 || !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(crate::FsError::StorageError {
+                message: format!("Invalid object hash '{}'", hash),
+            });
+        }
         // Split hash into directory structure: first 2 chars as dir, rest as filename
         let (dir, file) = hash.split_at(2);
-        self.objects_path.join(dir).join(file)
+        Ok(self.objects_path.join(dir).join(file))
     }
     
     fn list_refs_recursive(&self, dir_path: &Path, prefix: &str, refs: &mut Vec<ObjectRef>) -> Result<()> {
@@ -170,6 +484,249 @@ impl FileSystemStorage {
         Ok(())
     }
     
+    /// Build the on-disk form of an object, returning its hash and bytes
+    fn encode_object(object_type: ObjectType, data: &[u8]) -> Result<(String, Vec<u8>)> {
+        let hash = Self::get_object_hash(data);
+        let compressed_data = Self::compress_data(data)?;
+        let storage_object = StorageObject {
+            object_type,
+            hash: hash.clone(),
+            size: data.len(),
+            compressed_size: compressed_data.len(),
+            data: compressed_data,
+        };
+        
+        let serialized = bincode::serialize(&storage_object).map_err(|e| {
+            crate::FsError::SerializationError { message: e.to_string() }
+        })?;
+        Ok((hash, serialized))
+    }
+    
+    /// Build an object file, encrypting it in encrypted workspaces
+    ///
+    /// Encrypted files are addressed by the hash of the file itself rather than
+    /// the plaintext, so they are never deduplicated.
+    fn encode_file(&self, object_type: ObjectType, data: &[u8]) -> Result<(String, Vec<u8>)> {
+        let (hash, serialized) = Self::encode_object(object_type, data)?;
+        if !self.encrypted {
+            return Ok((hash, serialized));
+        }
+        
+        let cipher = self.cipher.as_ref().ok_or(crate::FsError::KeyRequired)?;
+        let sealed = cipher.encrypt(&serialized)?;
+        Ok((Self::get_object_hash(&sealed), sealed))
+    }
+    
+    /// Parse an object file, decrypting it first if needed
+    fn decode_file(cipher: Option<&Cipher>, hash: &str, serialized: &[u8]) -> Result<StorageObject> {
+        if !crypto::is_sealed(serialized) {
+            return Self::decode_object(serialized);
+        }
+        
+        let cipher = cipher.ok_or(crate::FsError::KeyRequired)?;
+        let mut object = Self::decode_object(&cipher.decrypt(serialized)?)?;
+        object.hash = hash.to_string();
+        Ok(object)
+    }
+    
+    /// Parse an object file read from somewhere other than this store, such as Git history
+    #[cfg(feature = "git-integration")]
+    pub(crate) fn decode_foreign(&self, hash: &str, serialized: &[u8]) -> Result<StorageObject> {
+        Self::decode_file(self.cipher.as_deref(), hash, serialized)
+    }
+    
+    /// Parse the on-disk form of an object, decompressing its data
+    fn decode_object(serialized: &[u8]) -> Result<StorageObject> {
+        let mut storage_object: StorageObject = bincode::deserialize(serialized).map_err(|e| {
+            crate::FsError::SerializationError { message: e.to_string() }
+        })?;
+        storage_object.data = Self::decompress_data(&storage_object.data)?;
+        Ok(storage_object)
+    }
+    
+    pub(crate) fn encode_ref(name: &str, hash: &str, object_type: ObjectType) -> Result<Vec<u8>> {
+        let object_ref = ObjectRef {
+            name: name.to_string(),
+            hash: hash.to_string(),
+            object_type,
+        };
+        
+        bincode::serialize(&object_ref).map_err(|e| {
+            crate::FsError::SerializationError { message: e.to_string() }
+        })
+    }
+    
+    pub(crate) fn decode_ref(serialized: &[u8]) -> Result<ObjectRef> {
+        bincode::deserialize(serialized).map_err(|e| {
+            crate::FsError::SerializationError { message: e.to_string() }
+        })
+    }
+    
+    /// Asynchronous counterpart of `write_atomic`
+    async fn write_atomic_async(&self, path: &Path, data: &[u8]) -> Result<()> {
+        let tmp_file = self.tmp_path.join(uuid::Uuid::new_v4().to_string());
+        tokio::fs::write(&tmp_file, data).await?;
+        if let Err(e) = tokio::fs::rename(&tmp_file, path).await {
+            let _ = tokio::fs::remove_file(&tmp_file).await;
+            return Err(e.into());
+        }
+        Ok(())
+    }
+    
+    /// Read a file, treating a missing file as `None`
+    async fn read_optional(path: &Path) -> Result<Option<Vec<u8>>> {
+        match tokio::fs::read(path).await {
+            Ok(data) => Ok(Some(data)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+    
+    /// Remove a file, reporting whether it existed
+    async fn remove_optional(path: &Path) -> Result<bool> {
+        match tokio::fs::remove_file(path).await {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(e.into()),
+        }
+    }
+    
+    async fn read_storage_object(&self, hash: &str) -> Result<Option<StorageObject>> {
+        // Hashes shorter than the fan-out prefix cannot name a stored object
+        if hash.len() <= 2 {
+            return Ok(None);
+        }
+        
+        match Self::read_optional(&self.get_object_path(hash)?).await? {
+            Some(serialized) => {
+                // Decryption and decompression are CPU-bound; keep them off the async worker threads
+                let cipher = self.cipher.clone();
+                let hash = hash.to_string();
+                let object = tokio::task::spawn_blocking(move || Self::decode_file(cipher.as_deref(), &hash, &serialized))
+                    .await
+                    .map_err(join_error)??;
+                Ok(Some(object))
+            }
+            None => {
+                let packs = self.packs.clone();
+                let hash = hash.to_string();
+                tokio::task::spawn_blocking(move || packs.get(&hash))
+                    .await
+                    .map_err(join_error)?
+            }
+        }
+    }
+    
+    /// Asynchronous counterpart of `reflog::append`
+    async fn append_reflog(&self, name: &str, entry: &reflog::ReflogEntry) -> Result<()> {
+        let path = reflog::log_path(&self.root_path, name);
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        let mut file = tokio::fs::OpenOptions::new().create(true).append(true).open(path).await?;
+        // tokio finishes file writes in the background unless flushed
+        tokio::io::AsyncWriteExt::write_all(&mut file, &reflog::line(entry)?).await?;
+        tokio::io::AsyncWriteExt::flush(&mut file).await?;
+        Ok(())
+    }
+    
+    async fn read_object_ref(&self, name: &str) -> Result<Option<ObjectRef>> {
+        match Self::read_optional(&self.refs_path.join(name)).await? {
+            Some(serialized) => Ok(Some(Self::decode_ref(&serialized)?)),
+            None => Ok(None),
+        }
+    }
+    
+    /// Load the objects under a ref prefix that `keep` accepts
+    ///
+    /// Objects are tested as they are read, so those rejected are dropped at
+    /// once instead of being held until every object is loaded.
+    pub async fn read_matching<T, F>(&self, prefix: &str, keep: F) -> Result<Vec<T>>
+    where
+        T: StoredObject,
+        F: Fn(&T) -> bool + Send + Sync + 'static,
+    {
+        let names = StorageEngine::list_refs(self, prefix).await?;
+        let keep = Arc::new(keep);
+        
+        let objects = self.run_bounded(names, |storage, name| {
+            let keep = keep.clone();
+            async move {
+                let object = match storage.read_object_ref(&name).await? {
+                    Some(object_ref) => storage.read_object::<T>(&ObjectHash::new(object_ref.hash)).await?,
+                    None => None,
+                };
+                Ok(object.filter(|object| keep(object)))
+            }
+        }).await?;
+        
+        Ok(objects.into_iter().flatten().collect())
+    }
+    
+    /// Names and object hashes of the refs under a prefix
+    pub async fn read_refs(&self, prefix: &str) -> Result<Vec<(String, ObjectHash)>> {
+        let names = StorageEngine::list_refs(self, prefix).await?;
+        
+        let refs = self.run_bounded(names, |storage, name| async move {
+            Ok(storage.read_object_ref(&name).await?
+                .map(|object_ref| (name, ObjectHash::new(object_ref.hash))))
+        }).await?;
+        
+        Ok(refs.into_iter().flatten().collect())
+    }
+    
+    /// Run `task` for every item with at most `read_parallelism` running at once,
+    /// returning the results in input order
+    async fn run_bounded<I, R, F, Fut>(&self, items: Vec<I>, task: F) -> Result<Vec<R>>
+    where
+        I: Send + 'static,
+        R: Send + 'static,
+        F: Fn(FileSystemStorage, I) -> Fut,
+        Fut: std::future::Future<Output = Result<R>> + Send + 'static,
+    {
+        let semaphore = Arc::new(Semaphore::new(self.read_parallelism));
+        let mut tasks = JoinSet::new();
+        let count = items.len();
+        
+        for (index, item) in items.into_iter().enumerate() {
+            let semaphore = semaphore.clone();
+            let future = task(self.clone(), item);
+            tasks.spawn(async move {
+                let _permit = semaphore.acquire_owned().await.map_err(join_error)?;
+                future.await.map(|result| (index, result))
+            });
+        }
+        
+        let mut results: Vec<Option<R>> = (0..count).map(|_| None).collect();
+        while let Some(joined) = tasks.join_next().await {
+            let (index, result) = joined.map_err(join_error)??;
+            results[index] = Some(result);
+        }
+        
+        Ok(results.into_iter().flatten().collect())
+    }
+    
+    /// Hashes of every object file in the store
+    async fn object_hashes(&self) -> Result<Vec<String>> {
+        let mut hashes = Vec::new();
+        let mut dirs = tokio::fs::read_dir(&self.objects_path).await?;
+        
+        while let Some(dir) = dirs.next_entry().await? {
+            let dir_name = dir.file_name().to_string_lossy().to_string();
+            if !dir.file_type().await?.is_dir() || !is_fan_out(&dir_name) {
+                continue;
+            }
+            let mut files = tokio::fs::read_dir(dir.path()).await?;
+            while let Some(file) = files.next_entry().await? {
+                if file.file_type().await?.is_file() {
+                    hashes.push(format!("{}{}", dir_name, file.file_name().to_string_lossy()));
+                }
+            }
+        }
+        
+        Ok(hashes)
+    }
+    
     fn compress_data(data: &[u8]) -> Result<Vec<u8>> {
         let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
         encoder.write_all(data)?;
@@ -186,11 +743,11 @@ impl FileSystemStorage {
 
 impl ObjectStorage for FileSystemStorage {
     fn store_object(&self, object_type: ObjectType, data: &[u8]) -> Result<String> {
-        let hash = Self::get_object_hash(data);
-        let object_path = self.get_object_path(&hash);
+        let (hash, serialized) = self.encode_file(object_type, data)?;
+        let object_path = self.get_object_path(&hash)?;
         
-        // Don't store if already exists
-        if object_path.exists() {
+        // Don't store if already exists, loose or packed
+        if self.object_exists(&hash)? {
             return Ok(hash);
         }
         
@@ -199,50 +756,30 @@ impl ObjectStorage for FileSystemStorage {
             fs::create_dir_all(parent)?;
         }
         
-        // Compress and store
-        let compressed_data = Self::compress_data(data)?;
-        let storage_object = StorageObject {
-            object_type,
-            hash: hash.clone(),
-            size: data.len(),
-            compressed_size: compressed_data.len(),
-            data: compressed_data,
-        };
-        
-        let serialized = bincode::serialize(&storage_object).map_err(|e| {
-            crate::FsError::SerializationError { message: e.to_string() }
-        })?;
-        
-        fs::write(object_path, serialized)?;
+        self.write_atomic(&object_path, &serialized)?;
         Ok(hash)
     }
     
     fn retrieve_object(&self, hash: &str) -> Result<Option<StorageObject>> {
-        let object_path = self.get_object_path(hash);
+        let object_path = self.get_object_path(hash)?;
         
         if !object_path.exists() {
-            return Ok(None);
+            return self.packs.get(hash);
         }
         
         let serialized = fs::read(object_path)?;
-        let mut storage_object: StorageObject = bincode::deserialize(&serialized).map_err(|e| {
-            crate::FsError::SerializationError { message: e.to_string() }
-        })?;
-        
-        // Decompress the data
-        storage_object.data = Self::decompress_data(&storage_object.data)?;
-        Ok(Some(storage_object))
+        Ok(Some(Self::decode_file(self.cipher.as_deref(), hash, &serialized)?))
     }
     
     fn delete_object(&self, hash: &str) -> Result<bool> {
-        let object_path = self.get_object_path(hash);
+        let object_path = self.get_object_path(hash)?;
         
-        if object_path.exists() {
+        let deleted_loose = object_path.exists();
+        if deleted_loose {
             fs::remove_file(object_path)?;
-            Ok(true)
-        } else {
-            Ok(false)
         }
+        let deleted_packed = self.packs.forget(hash)?;
+        Ok(deleted_loose || deleted_packed)
     }
     
     fn list_objects(&self, filter_type: Option<ObjectType>) -> Result<Vec<String>> {
@@ -251,8 +788,8 @@ impl ObjectStorage for FileSystemStorage {
         // Traverse objects directory
         for entry in fs::read_dir(&self.objects_path)? {
             let entry = entry?;
-            if entry.file_type()?.is_dir() {
-                let dir_name = entry.file_name().to_string_lossy().to_string();
+            let dir_name = entry.file_name().to_string_lossy().to_string();
+            if entry.file_type()?.is_dir() && is_fan_out(&dir_name) {
                 for file_entry in fs::read_dir(entry.path())? {
                     let file_entry = file_entry?;
                     if file_entry.file_type()?.is_file() {
@@ -274,11 +811,19 @@ impl ObjectStorage for FileSystemStorage {
             }
         }
         
+        // Packed objects have their type in the pack index
+        let loose: HashSet<String> = objects.iter().cloned().collect();
+        for (hash, object_type) in self.packs.entries()? {
+            if filter_type.is_none_or(|filter| filter == object_type) && !loose.contains(&hash) {
+                objects.push(hash);
+            }
+        }
+        
         Ok(objects)
     }
     
     fn object_exists(&self, hash: &str) -> Result<bool> {
-        Ok(self.get_object_path(hash).exists())
+        Ok(self.get_object_path(hash)?.exists() || self.packs.contains(hash)?)
     }
     
     fn get_object_hash(data: &[u8]) -> String {
@@ -289,23 +834,20 @@ impl ObjectStorage for FileSystemStorage {
     
     fn create_ref(&self, name: &str, hash: &str, object_type: ObjectType) -> Result<()> {
         let ref_path = self.refs_path.join(name);
+        let old = self.get_ref(name)?.map(|object_ref| object_ref.hash);
         
         // Create parent directories if they don't exist
         if let Some(parent) = ref_path.parent() {
             fs::create_dir_all(parent)?;
         }
         
-        let object_ref = ObjectRef {
-            name: name.to_string(),
-            hash: hash.to_string(),
-            object_type,
-        };
-        
-        let serialized = bincode::serialize(&object_ref).map_err(|e| {
-            crate::FsError::SerializationError { message: e.to_string() }
-        })?;
+        let serialized = Self::encode_ref(name, hash, object_type)?;
+        self.write_atomic(&ref_path, &serialized)?;
         
-        fs::write(ref_path, serialized)?;
+        if old.as_deref() != Some(hash) {
+            let entry = self.reflog.entry(old, Some(hash.to_string()), object_type);
+            reflog::append(&self.root_path, name, &entry)?;
+        }
         Ok(())
     }
     
@@ -317,21 +859,20 @@ impl ObjectStorage for FileSystemStorage {
         }
         
         let serialized = fs::read(ref_path)?;
-        let object_ref: ObjectRef = bincode::deserialize(&serialized).map_err(|e| {
-            crate::FsError::SerializationError { message: e.to_string() }
-        })?;
-        
-        Ok(Some(object_ref))
+        Ok(Some(Self::decode_ref(&serialized)?))
     }
     
     fn delete_ref(&self, name: &str) -> Result<bool> {
         let ref_path = self.refs_path.join(name);
         
-        if ref_path.exists() {
-            fs::remove_file(ref_path)?;
-            Ok(true)
-        } else {
-            Ok(false)
+        match self.get_ref(name)? {
+            Some(old) => {
+                fs::remove_file(ref_path)?;
+                let entry = self.reflog.entry(Some(old.hash), None, old.object_type);
+                reflog::append(&self.root_path, name, &entry)?;
+                Ok(true)
+            }
+            None => Ok(false),
         }
     }
     
@@ -342,59 +883,382 @@ impl ObjectStorage for FileSystemStorage {
     }
     
     fn acquire_lock(&self, name: &str) -> Result<StorageLock> {
-        let lock_path = self.locks_path.join(format!("{}.lock", name));
+        let guard = self.acquire_guard(name)?;
+        let lock = guard.lock().clone();
         
-        // Check if already locked
-        if lock_path.exists() {
-            return Err(crate::FsError::LockError {
-                message: format!("Resource '{}' is already locked", name),
-            });
+        self.held_locks.lock()
+            .map_err(|_| crate::FsError::LockError { message: "Lock table poisoned".to_string() })?
+            .insert(name.to_string(), guard);
+        
+        Ok(lock)
+    }
+    
+    fn release_lock(&self, lock: &StorageLock) -> Result<()> {
+        let mut held = self.held_locks.lock()
+            .map_err(|_| crate::FsError::LockError { message: "Lock table poisoned".to_string() })?;
+        
+        match held.get(&lock.name) {
+            Some(guard) if guard.lock().process_id == lock.process_id && guard.lock().acquired_at == lock.acquired_at => {
+                if let Some(guard) = held.remove(&lock.name) {
+                    guard.release()?;
+                }
+                Ok(())
+            }
+            _ => {
+                // Verify this is not someone else's lock
+                match lock::holder(&self.locks_path, &lock.name)? {
+                    Some(existing) if existing.process_id != lock.process_id || existing.acquired_at != lock.acquired_at => {
+                        Err(crate::FsError::LockError {
+                            message: "Cannot release lock owned by different process".to_string(),
+                        })
+                    }
+                    _ => Ok(()),
+                }
+            }
         }
+    }
+    
+    fn is_locked(&self, name: &str) -> Result<bool> {
+        Ok(lock::holder(&self.locks_path, name)?.is_some())
+    }
+}
+
+#[async_trait::async_trait]
+impl StorageEngine for FileSystemStorage {
+    async fn initialize(&self, path: &Path) -> Result<()> {
+        for dir in ["objects", "refs", "locks", "tmp"] {
+            tokio::fs::create_dir_all(path.join(dir)).await?;
+        }
+        Ok(())
+    }
+    
+    async fn write_object<T: StoredObject>(&self, obj: &T) -> Result<ObjectHash> {
+        let data = odi_core::format::seal(obj)?;
+        let storage = self.clone();
+        let (hash, serialized) = tokio::task::spawn_blocking(move || storage.encode_file(T::OBJECT_TYPE, &data))
+            .await
+            .map_err(join_error)??;
         
-        let lock = StorageLock {
-            name: name.to_string(),
-            acquired_at: Utc::now(),
-            process_id: std::process::id(),
-        };
+        let object_path = self.get_object_path(&hash)?;
         
-        let serialized = bincode::serialize(&lock).map_err(|e| {
-            crate::FsError::SerializationError { message: e.to_string() }
-        })?;
+        // Content addressed: an existing object already holds these bytes
+        if !tokio::fs::try_exists(&object_path).await? && !self.packs.contains(&hash)? {
+            if let Some(parent) = object_path.parent() {
+                tokio::fs::create_dir_all(parent).await?;
+            }
+            self.write_atomic_async(&object_path, &serialized).await?;
+        }
+        
+        Ok(ObjectHash::new(hash))
+    }
+    
+    async fn read_object<T: StoredObject>(&self, hash: &ObjectHash) -> Result<Option<T>> {
+        match self.read_storage_object(hash.as_str()).await? {
+            Some(object) => Ok(Some(odi_core::format::open(&object.data)?)),
+            None => Ok(None),
+        }
+    }
+    
+    async fn read_objects<T: StoredObject>(&self, hashes: &[ObjectHash]) -> Result<Vec<Option<T>>> {
+        self.run_bounded(hashes.to_vec(), |storage, hash| async move {
+            storage.read_object::<T>(&hash).await
+        }).await
+    }
+    
+    async fn delete_object(&self, hash: &ObjectHash) -> Result<bool> {
+        if hash.as_str().len() <= 2 {
+            return Ok(false);
+        }
+        let deleted_loose = Self::remove_optional(&self.get_object_path(hash.as_str())?).await?;
+        let deleted_packed = self.packs.forget(hash.as_str())?;
+        Ok(deleted_loose || deleted_packed)
+    }
+    
+    async fn list_objects(&self, object_type: ObjectType) -> Result<Vec<ObjectHash>> {
+        let hashes = self.object_hashes().await?;
         
-        // Use exclusive create to ensure atomicity
-        let mut file = OpenOptions::new()
-            .write(true)
-            .create_new(true)
-            .open(&lock_path)?;
-        file.write_all(&serialized)?;
+        // The type is only recorded inside each object, so every candidate is read
+        let matches = self.run_bounded(hashes, move |storage, hash| async move {
+            let matches = storage.read_storage_object(&hash).await?
+                .is_some_and(|object| object.object_type == object_type);
+            Ok(matches.then(|| ObjectHash::new(hash)))
+        }).await?;
         
-        Ok(lock)
+        let mut matches: Vec<ObjectHash> = matches.into_iter().flatten().collect();
+        let loose: HashSet<String> = matches.iter().map(|hash| hash.as_str().to_string()).collect();
+        for (hash, packed_type) in self.packs.entries()? {
+            if packed_type == object_type && !loose.contains(&hash) {
+                matches.push(ObjectHash::new(hash));
+            }
+        }
+        Ok(matches)
     }
     
-    fn release_lock(&self, lock: &StorageLock) -> Result<()> {
-        let lock_path = self.locks_path.join(format!("{}.lock", lock.name));
-        
-        if lock_path.exists() {
-            // Verify this is our lock
-            let serialized = fs::read(&lock_path)?;
-            let existing_lock: StorageLock = bincode::deserialize(&serialized).map_err(|e| {
-                crate::FsError::SerializationError { message: e.to_string() }
-            })?;
+    async fn write_ref(&self, name: &str, hash: &ObjectHash, object_type: ObjectType) -> Result<()> {
+        let ref_path = self.refs_path.join(name);
+        let old = self.read_object_ref(name).await?.map(|object_ref| object_ref.hash);
+        if let Some(parent) = ref_path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        
+        let serialized = Self::encode_ref(name, hash.as_str(), object_type)?;
+        self.write_atomic_async(&ref_path, &serialized).await?;
+        
+        if old.as_deref() != Some(hash.as_str()) {
+            let entry = self.reflog.entry(old, Some(hash.as_str().to_string()), object_type);
+            self.append_reflog(name, &entry).await?;
+        }
+        Ok(())
+    }
+    
+    async fn read_ref(&self, name: &str) -> Result<Option<ObjectHash>> {
+        Ok(self.read_object_ref(name).await?.map(|object_ref| ObjectHash::new(object_ref.hash)))
+    }
+    
+    async fn delete_ref(&self, name: &str) -> Result<bool> {
+        let Some(old) = self.read_object_ref(name).await? else {
+            return Ok(false);
+        };
+        let deleted = Self::remove_optional(&self.refs_path.join(name)).await?;
+        if deleted {
+            let entry = self.reflog.entry(Some(old.hash), None, old.object_type);
+            self.append_reflog(name, &entry).await?;
+        }
+        Ok(deleted)
+    }
+    
+    async fn list_refs(&self, prefix: &str) -> Result<Vec<String>> {
+        // Only walk the directory the prefix points into
+        let base = match prefix.rfind('/') {
+            Some(pos) => &prefix[..pos],
+            None => "",
+        };
+        
+        let mut names = Vec::new();
+        let mut pending = vec![base.to_string()];
+        
+        while let Some(dir_name) = pending.pop() {
+            let mut entries = match tokio::fs::read_dir(self.refs_path.join(&dir_name)).await {
+                Ok(entries) => entries,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(e.into()),
+            };
             
-            if existing_lock.process_id != lock.process_id || existing_lock.acquired_at != lock.acquired_at {
-                return Err(crate::FsError::LockError {
-                    message: "Cannot release lock owned by different process".to_string(),
-                });
+            while let Some(entry) = entries.next_entry().await? {
+                let file_name = entry.file_name().to_string_lossy().to_string();
+                let full_name = if dir_name.is_empty() {
+                    file_name
+                } else {
+                    format!("{}/{}", dir_name, file_name)
+                };
+                
+                let file_type = entry.file_type().await?;
+                if file_type.is_dir() {
+                    pending.push(full_name);
+                } else if file_type.is_file() && full_name.starts_with(prefix) {
+                    names.push(full_name);
+                }
             }
-            
-            fs::remove_file(lock_path)?;
         }
         
-        Ok(())
+        names.sort();
+        Ok(names)
     }
     
-    fn is_locked(&self, name: &str) -> Result<bool> {
-        let lock_path = self.locks_path.join(format!("{}.lock", name));
-        Ok(lock_path.exists())
+    async fn read_all<T: StoredObject>(&self, prefix: &str) -> Result<Vec<T>> {
+        let names = StorageEngine::list_refs(self, prefix).await?;
+        
+        let objects = self.run_bounded(names, |storage, name| async move {
+            match storage.read_object_ref(&name).await? {
+                Some(object_ref) => storage.read_object::<T>(&ObjectHash::new(object_ref.hash)).await,
+                None => Ok(None),
+            }
+        }).await?;
+        
+        Ok(objects.into_iter().flatten().collect())
+    }
+    
+    async fn lock(&self, resource: &str) -> Result<LockGuard> {
+        lock::acquire_async(&self.locks_path, resource, &self.lock_options).await
+    }
+    
+    async fn unlock(&self, lock: LockGuard) -> Result<()> {
+        lock.release()
+    }
+}
+
+/// Whether a directory under `objects/` is a loose-object fan-out directory
+fn is_fan_out(dir_name: &str) -> bool {
+    dir_name.len() == 2 && dir_name.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+fn join_error(e: impl std::fmt::Display) -> crate::FsError {
+    crate::FsError::StorageError { message: e.to_string() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use odi_core::{Issue, User};
+    use tempfile::TempDir;
+
+    fn storage(dir: &TempDir) -> FileSystemStorage {
+        FileSystemStorage::new(dir.path().join(".odi")).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_write_and_read_object() {
+        let dir = TempDir::new().unwrap();
+        let storage = storage(&dir);
+
+        let issue = Issue::new("Engine issue".to_string(), "alice".to_string());
+        let hash = storage.write_object(&issue).await.unwrap();
+
+        let read: Issue = storage.read_object(&hash).await.unwrap().unwrap();
+        assert_eq!(read.id, issue.id);
+        assert_eq!(read.title, issue.title);
+
+        // Identical content is stored once under the same hash
+        assert_eq!(storage.write_object(&issue).await.unwrap(), hash);
+
+        // Objects are readable through the synchronous interface too
+        let object = storage.retrieve_object(hash.as_str()).unwrap().unwrap();
+        assert_eq!(object.object_type, ObjectType::Issue);
+
+        let missing: Option<Issue> = storage.read_object(&ObjectHash::from("00ff")).await.unwrap();
+        assert!(missing.is_none());
+
+        // Hashes read from a corrupt ref are refused, not split
+        assert!(storage.retrieve_object("a").is_err());
+        assert!(storage.retrieve_object("../config").is_err());
+        assert!(ObjectStorage::delete_object(&storage, "").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_list_objects_by_type() {
+        let dir = TempDir::new().unwrap();
+        let storage = storage(&dir);
+
+        let user = User::new("bob".to_string(), "Bob".to_string(), "bob@example.com".to_string());
+        storage.write_object(&user).await.unwrap();
+        let issue_hash = storage.write_object(&Issue::new("One".to_string(), "bob".to_string())).await.unwrap();
+
+        let issues = StorageEngine::list_objects(&storage, ObjectType::Issue).await.unwrap();
+        assert_eq!(issues, vec![issue_hash]);
+        assert_eq!(StorageEngine::list_objects(&storage, ObjectType::User).await.unwrap().len(), 1);
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_refs_and_bulk_reads() {
+        let dir = TempDir::new().unwrap();
+        let storage = storage(&dir).with_read_parallelism(2);
+
+        let mut hashes = Vec::new();
+        for i in 0..10 {
+            let issue = Issue::new(format!("Issue {}", i), "carol".to_string());
+            let hash = storage.write_object(&issue).await.unwrap();
+            storage.write_ref(&format!("issues/{}", issue.id), &hash, ObjectType::Issue).await.unwrap();
+            hashes.push(hash);
+        }
+        let user = User::new("carol".to_string(), "Carol".to_string(), "carol@example.com".to_string());
+        let user_hash = storage.write_object(&user).await.unwrap();
+        storage.write_ref("users/carol", &user_hash, ObjectType::User).await.unwrap();
+
+        assert_eq!(StorageEngine::list_refs(&storage, "issues/").await.unwrap().len(), 10);
+        assert_eq!(StorageEngine::list_refs(&storage, "users/").await.unwrap(), vec!["users/carol".to_string()]);
+        assert_eq!(storage.read_ref("users/carol").await.unwrap(), Some(user_hash));
+
+        let issues: Vec<Issue> = storage.read_all("issues/").await.unwrap();
+        assert_eq!(issues.len(), 10);
+        let matching: Vec<Issue> = storage.read_matching("issues/", |issue: &Issue| issue.title.ends_with('3')).await.unwrap();
+        assert_eq!(matching.len(), 1);
+        assert_eq!(matching[0].title, "Issue 3");
+
+        // Bulk reads keep the order of the requested hashes
+        let read: Vec<Option<Issue>> = storage.read_objects(&hashes).await.unwrap();
+        for (i, issue) in read.into_iter().enumerate() {
+            assert_eq!(issue.unwrap().title, format!("Issue {}", i));
+        }
+
+        assert!(StorageEngine::delete_ref(&storage, "users/carol").await.unwrap());
+        assert!(storage.read_ref("users/carol").await.unwrap().is_none());
+        assert!(StorageEngine::delete_object(&storage, &hashes[0]).await.unwrap());
+        assert!(!StorageEngine::delete_object(&storage, &hashes[0]).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_async_lock() {
+        let dir = TempDir::new().unwrap();
+        let storage = storage(&dir).with_lock_options(LockOptions::new().no_wait());
+
+        let guard = storage.lock("issues").await.unwrap();
+        assert!(storage.is_locked("issues").unwrap());
+        assert!(storage.lock("issues").await.is_err());
+
+        storage.unlock(guard).await.unwrap();
+        assert!(!storage.is_locked("issues").unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_gc_packs_objects() {
+        let dir = TempDir::new().unwrap();
+        let storage = storage(&dir);
+
+        let mut issue = Issue::new("Packed".to_string(), "alice".to_string());
+        let first = storage.write_object(&issue).await.unwrap();
+        issue.title = "Packed again".to_string();
+        let second = storage.write_object(&issue).await.unwrap();
+        storage.write_ref(&format!("issues/{}", issue.id), &second, ObjectType::Issue).await.unwrap();
+
+        let stats = storage.gc().unwrap();
+        assert_eq!(stats.loose, 2);
+        assert_eq!(stats.pack.objects, 2);
+        assert!(storage.loose_hashes().unwrap().is_empty());
+
+        // Reads are transparent through both interfaces
+        let all: Vec<Issue> = storage.read_all("issues/").await.unwrap();
+        assert_eq!(all[0].title, "Packed again");
+        assert_eq!(storage.retrieve_object(first.as_str()).unwrap().unwrap().object_type, ObjectType::Issue);
+        assert_eq!(StorageEngine::list_objects(&storage, ObjectType::Issue).await.unwrap().len(), 2);
+
+        // Deleted packed objects are hidden, then dropped by the next gc
+        assert!(StorageEngine::delete_object(&storage, &first).await.unwrap());
+        assert!(storage.retrieve_object(first.as_str()).unwrap().is_none());
+        let stats = storage.gc().unwrap();
+        assert_eq!((stats.pruned, stats.pack.objects), (1, 1));
+        assert!(storage.retrieve_object(second.as_str()).unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_encrypted_objects() {
+        let dir = TempDir::new().unwrap();
+        let source = KeySource::Passphrase("secret".to_string());
+        let plain = storage(&dir);
+        let storage = plain.clone().with_cipher(crypto::create(plain.root_path(), &source).unwrap());
+
+        let issue = Issue::new("Customer data".to_string(), "alice".to_string());
+        let hash = storage.write_object(&issue).await.unwrap();
+        storage.write_ref(&format!("issues/{}", issue.id), &hash, ObjectType::Issue).await.unwrap();
+
+        let file = fs::read(storage.get_object_path(hash.as_str()).unwrap()).unwrap();
+        assert!(crypto::is_sealed(&file));
+        let read: Issue = storage.read_object(&hash).await.unwrap().unwrap();
+        assert_eq!(read.title, "Customer data");
+
+        // Without the key objects can't be read, but can still be verified
+        let locked = FileSystemStorage::new(storage.root_path().to_path_buf()).unwrap();
+        assert!(locked.is_encrypted() && !locked.is_unlocked());
+        assert!(matches!(locked.read_object::<Issue>(&hash).await, Err(crate::FsError::KeyRequired)));
+        assert_eq!(locked.verify_objects().unwrap(), (1, vec![]));
+        assert!(locked.gc().is_err());
+
+        // Rotation re-encrypts objects and moves refs over
+        let new_source = KeySource::Passphrase("rotated".to_string());
+        assert_eq!(storage.rotate_key(&source, Some(&new_source)).unwrap(), 1);
+        assert!(crypto::unlock(storage.root_path(), &source).is_err());
+        let rotated = locked.with_cipher(crypto::unlock(storage.root_path(), &new_source).unwrap());
+        let all: Vec<Issue> = rotated.read_all("issues/").await.unwrap();
+        assert_eq!(all[0].title, "Customer data");
+        assert!(!ObjectStorage::object_exists(&rotated, hash.as_str()).unwrap());
+    }
+}
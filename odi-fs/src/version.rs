@@ -0,0 +1,211 @@
+//! Workspace format version and migrations
+//!
+//! The format version a workspace was written with is recorded in
+//! `.odi/version`. Workspaces created before the file existed are version 0.
+//! Opening a workspace checks the recorded version: a newer one is refused, an
+//! older one has to be upgraded with `odi migrate` first. Migrations run one
+//! version step at a time, and the version file is rewritten after every step,
+//! so an interrupted migration resumes where it stopped.
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+use odi_core::{Issue, Label, Project, Remote, Team, User, Versioned, View, Workspace};
+
+use crate::storage::{FileSystemStorage, ObjectStorage};
+use crate::{FsError, Result};
+
+/// File inside the `.odi` directory holding the format version
+pub const VERSION_FILE: &str = "version";
+
+/// Format version written by this version of odi
+pub const CURRENT_VERSION: u32 = odi_core::FORMAT_VERSION;
+
+/// One step upgrading a workspace from `from` to `from + 1`
+pub struct Migration {
+    /// Version this step upgrades from
+    pub from: u32,
+    /// Human-readable summary of the step
+    pub description: &'static str,
+    /// Apply the step, returning the number of records rewritten
+    run: fn(&FileSystemStorage) -> Result<usize>,
+}
+
+/// All migrations, in version order
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        from: 0,
+        description: "Wrap stored objects in versioned envelopes",
+        run: reseal_objects,
+    },
+    Migration {
+        from: 1,
+        description: "Convert issue Git references to typed links",
+        run: reseal_objects,
+    },
+];
+
+/// Read the format version of a `.odi` directory
+pub fn read_version(odi_dir: &Path) -> Result<u32> {
+    match fs::read_to_string(odi_dir.join(VERSION_FILE)) {
+        Ok(contents) => contents.trim().parse().map_err(|_| FsError::StorageError {
+            message: format!("Invalid workspace version '{}'", contents.trim()),
+        }),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(0),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Record the format version of a `.odi` directory
+pub fn write_version(odi_dir: &Path, version: u32) -> Result<()> {
+    fs::write(odi_dir.join(VERSION_FILE), format!("{}\n", version))?;
+    Ok(())
+}
+
+/// Ensure a workspace can be opened by this version of odi
+pub fn check_version(odi_dir: &Path) -> Result<()> {
+    let found = read_version(odi_dir)?;
+    if found > CURRENT_VERSION {
+        return Err(FsError::UnsupportedWorkspace { found, supported: CURRENT_VERSION });
+    }
+    if found < CURRENT_VERSION {
+        return Err(FsError::MigrationRequired { found, current: CURRENT_VERSION });
+    }
+    Ok(())
+}
+
+/// Migrations needed to bring a `.odi` directory up to the current version
+pub fn pending_migrations(odi_dir: &Path) -> Result<Vec<&'static Migration>> {
+    let found = read_version(odi_dir)?;
+    if found > CURRENT_VERSION {
+        return Err(FsError::UnsupportedWorkspace { found, supported: CURRENT_VERSION });
+    }
+    Ok(MIGRATIONS.iter().filter(|m| m.from >= found).collect())
+}
+
+/// Upgrade a workspace to the current version one step at a time
+///
+/// `on_step` is called after each step with the number of records it rewrote.
+/// Returns the number of steps applied.
+pub fn migrate<F>(storage: &FileSystemStorage, mut on_step: F) -> Result<usize>
+where
+    F: FnMut(&Migration, usize),
+{
+    // Keep every repository out while objects are being rewritten
+    let _guards = storage.acquire_all_guards()?;
+
+    let odi_dir = storage.root_path();
+    let pending = pending_migrations(odi_dir)?;
+    for migration in &pending {
+        let rewritten = (migration.run)(storage)?;
+        write_version(odi_dir, migration.from + 1)?;
+        on_step(migration, rewritten);
+    }
+    Ok(pending.len())
+}
+
+/// Envelope kind for a ref namespace or SQLite record kind
+pub(crate) fn kind_for_namespace(namespace: &str) -> Option<&'static str> {
+    match namespace {
+        "issues" => Some(Issue::KIND),
+        "projects" => Some(Project::KIND),
+        "workspaces" => Some(Workspace::KIND),
+        "labels" => Some(Label::KIND),
+        "views" => Some(View::KIND),
+        "users" => Some(User::KIND),
+        "teams" => Some(Team::KIND),
+        "remotes" => Some(Remote::KIND),
+        _ => None,
+    }
+}
+
+/// Rewrite every referenced object as an envelope of the current version
+///
+/// Objects are upgraded straight to the current version, so after the first
+/// step the later ones find nothing left to rewrite.
+fn reseal_objects(storage: &FileSystemStorage) -> Result<usize> {
+    let mut rewritten = 0;
+    let mut replaced = HashSet::new();
+
+    for object_ref in ObjectStorage::list_refs(storage)? {
+        let namespace = object_ref.name.split('/').next().unwrap_or_default();
+        let Some(kind) = kind_for_namespace(namespace) else {
+            continue;
+        };
+        let Some(object) = storage.retrieve_object(&object_ref.hash)? else {
+            continue;
+        };
+
+        let data = odi_core::format::reseal(&object.data, kind)?;
+        let hash = storage.store_object(object_ref.object_type, &data)?;
+        if hash != object_ref.hash {
+            storage.create_ref(&object_ref.name, &hash, object_ref.object_type)?;
+            replaced.insert(object_ref.hash);
+            rewritten += 1;
+        }
+    }
+
+    // Identical objects can be shared between refs; only drop those nothing points to
+    let live: HashSet<String> = ObjectStorage::list_refs(storage)?.into_iter().map(|r| r.hash).collect();
+    for hash in replaced.difference(&live) {
+        ObjectStorage::delete_object(storage, hash)?;
+    }
+
+    #[cfg(feature = "sqlite")]
+    {
+        rewritten += crate::sqlite::reseal_records(storage.root_path())?;
+    }
+
+    Ok(rewritten)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{ObjectType, StorageEngine};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_check_version() {
+        let dir = TempDir::new().unwrap();
+        assert!(matches!(check_version(dir.path()), Err(FsError::MigrationRequired { found: 0, .. })));
+
+        write_version(dir.path(), CURRENT_VERSION).unwrap();
+        assert!(check_version(dir.path()).is_ok());
+
+        write_version(dir.path(), CURRENT_VERSION + 1).unwrap();
+        assert!(matches!(check_version(dir.path()), Err(FsError::UnsupportedWorkspace { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_migrate_legacy_objects() {
+        let dir = TempDir::new().unwrap();
+        let storage = FileSystemStorage::new(dir.path().to_path_buf()).unwrap();
+
+        // Objects written before envelopes existed are bare JSON
+        let issue = Issue::new("Legacy".to_string(), "alice".to_string());
+        let ref_name = format!("issues/{}", issue.id);
+        let legacy = serde_json::to_vec(&issue).unwrap();
+        let old_hash = storage.store_object(ObjectType::Issue, &legacy).unwrap();
+        storage.create_ref(&ref_name, &old_hash, ObjectType::Issue).unwrap();
+
+        let mut steps = Vec::new();
+        let applied = migrate(&storage, |m, n| steps.push((m.from, n))).unwrap();
+        assert_eq!(applied, 2);
+        assert_eq!(steps, vec![(0, 1), (1, 0)]);
+        assert_eq!(read_version(dir.path()).unwrap(), CURRENT_VERSION);
+
+        let new_ref = storage.get_ref(&ref_name).unwrap().unwrap();
+        assert!(!ObjectStorage::object_exists(&storage, &old_hash).unwrap());
+        let object = storage.retrieve_object(&new_ref.hash).unwrap().unwrap();
+        let envelope = odi_core::format::Envelope::parse(&object.data).unwrap();
+        assert_eq!((envelope.odi, envelope.kind.as_str()), (CURRENT_VERSION, "issue"));
+
+        let loaded: Vec<Issue> = storage.read_all("issues/").await.unwrap();
+        assert_eq!(loaded[0].title, "Legacy");
+
+        // Nothing left to do on a second run
+        assert_eq!(migrate(&storage, |_, _| {}).unwrap(), 0);
+    }
+}
@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
-use odi_core::{Issue, IssueId, Remote, IssueStatus, Priority};
+use odi_core::{CoreError, Issue, IssueId, Remote, IssueStatus, Priority};
 use crate::{Result, NetError};
 use crate::protocol::{Protocol, ProtocolHandler, HttpsHandler, SshHandler};
 use crate::auth::Credential;
@@ -22,6 +22,36 @@ pub struct IssueMetadata {
     pub checksum: String,
 }
 
+/// Remote directory holding packfiles
+const PACKS_DIR: &str = "packs";
+
+/// Protocol handler for a remote URL
+fn handler_for(remote_url: &str) -> Result<Box<dyn ProtocolHandler>> {
+    let url = url::Url::parse(remote_url).map_err(|e| NetError::Sync {
+        message: format!("Invalid remote URL '{}': {}", remote_url, e),
+    })?;
+    
+    match url.scheme() {
+        "ssh" => Ok(Box::new(SshHandler::new())),
+        "https" => Ok(Box::new(HttpsHandler::new())),
+        scheme => Err(NetError::Sync {
+            message: format!("Unsupported protocol: {}", scheme),
+        }),
+    }
+}
+
+/// Decode a transmitted issue, accepting bincode written before envelopes existed
+fn decode_issue(data: &[u8]) -> Result<Issue> {
+    match odi_core::format::open(data) {
+        Ok(issue) => Ok(issue),
+        // Refuse issues written by a newer odi rather than guessing at them
+        Err(e @ CoreError::UnsupportedFormat { .. }) => Err(NetError::Sync { message: e.to_string() }),
+        Err(_) => bincode::deserialize(data).map_err(|e| NetError::Sync {
+            message: format!("Failed to deserialize remote issue: {}", e),
+        }),
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct SyncClient {
     pub remote_url: String,
@@ -54,6 +84,21 @@ pub trait RemoteSync: Send + Sync {
     async fn download_issue(&self, client: &SyncClient, id: &IssueId) -> Result<Issue>;
     async fn upload_issue(&self, client: &SyncClient, issue: &Issue) -> Result<()>;
     async fn get_sync_state(&self, client: &SyncClient) -> Result<RemoteSyncState>;
+    
+    /// Names of the packfiles stored on the remote
+    async fn list_packs(&self, _client: &SyncClient) -> Result<Vec<String>> {
+        Ok(Vec::new())
+    }
+    
+    /// Download a whole packfile
+    async fn download_pack(&self, _client: &SyncClient, name: &str) -> Result<Vec<u8>> {
+        Err(NetError::Protocol { message: format!("Remote does not support packfiles (requested '{}')", name) })
+    }
+    
+    /// Upload a whole packfile under `name`
+    async fn upload_pack(&self, _client: &SyncClient, name: &str, _data: &[u8]) -> Result<()> {
+        Err(NetError::Protocol { message: format!("Remote does not support packfiles (uploading '{}')", name) })
+    }
 }
 
 pub struct DefaultRemoteSync;
@@ -132,7 +177,7 @@ impl RemoteSync for DefaultRemoteSync {
             message: format!("Invalid remote URL '{}': {}", client.remote_url, e),
         })?;
         
-        println!("📡 Listing issues from remote: {}", client.remote_url);
+        eprintln!("📡 Listing issues from remote: {}", client.remote_url);
         
         // Get protocol and create handler
         let protocol = if url.scheme() == "ssh" {
@@ -163,7 +208,7 @@ impl RemoteSync for DefaultRemoteSync {
                             match handler.download_object(&client.remote_url, &format!("issues/{}", object_name)).await {
                                 Ok(issue_data) => {
                                     // Deserialize the issue to extract metadata
-                                    match bincode::deserialize::<Issue>(&issue_data) {
+                                    match decode_issue(&issue_data) {
                                         Ok(issue) => {
                                             metadata.push(IssueMetadata {
                                                 id: issue.id,
@@ -175,12 +220,12 @@ impl RemoteSync for DefaultRemoteSync {
                                             });
                                         },
                                         Err(e) => {
-                                            println!("⚠️  Failed to deserialize issue {}: {}", id, e);
+                                            eprintln!("⚠️  Failed to deserialize issue {}: {}", id, e);
                                         }
                                     }
                                 },
                                 Err(e) => {
-                                    println!("⚠️  Failed to download issue {}: {}", id, e);
+                                    eprintln!("⚠️  Failed to download issue {}: {}", id, e);
                                 }
                             }
                         }
@@ -202,7 +247,7 @@ impl RemoteSync for DefaultRemoteSync {
             message: format!("Invalid remote URL '{}': {}", client.remote_url, e),
         })?;
         
-        println!("📥 Downloading issue {} from remote: {}", id, client.remote_url);
+        eprintln!("📥 Downloading issue {} from remote: {}", id, client.remote_url);
         
         // Get protocol and create handler
         let protocol = if url.scheme() == "ssh" {
@@ -223,11 +268,7 @@ impl RemoteSync for DefaultRemoteSync {
         // Download issue using protocol handler
         let issue_data = handler.download_object(&client.remote_url, &format!("issues/{}.bin", id)).await?;
         
-        let issue: Issue = bincode::deserialize(&issue_data).map_err(|e| NetError::Sync {
-            message: format!("Failed to deserialize remote issue: {}", e),
-        })?;
-        
-        Ok(issue)
+        decode_issue(&issue_data)
     }
 
     async fn upload_issue(&self, client: &SyncClient, issue: &Issue) -> Result<()> {
@@ -236,7 +277,7 @@ impl RemoteSync for DefaultRemoteSync {
             message: format!("Invalid remote URL '{}': {}", client.remote_url, e),
         })?;
         
-        println!("📤 Uploading issue {} to remote: {}", issue.id, client.remote_url);
+        eprintln!("📤 Uploading issue {} to remote: {}", issue.id, client.remote_url);
         
         // Get protocol and create handler
         let protocol = if url.scheme() == "ssh" {
@@ -254,8 +295,8 @@ impl RemoteSync for DefaultRemoteSync {
             Protocol::HTTPS => Box::new(HttpsHandler::new()),
         };
 
-        // Serialize the issue
-        let issue_data = bincode::serialize(issue).map_err(|e| NetError::Sync {
+        // Serialize the issue inside a versioned envelope
+        let issue_data = odi_core::format::seal(issue).map_err(|e| NetError::Sync {
             message: format!("Failed to serialize issue: {}", e),
         })?;
         
@@ -265,6 +306,28 @@ impl RemoteSync for DefaultRemoteSync {
         Ok(())
     }
 
+    async fn list_packs(&self, client: &SyncClient) -> Result<Vec<String>> {
+        let handler = handler_for(&client.remote_url)?;
+        
+        match handler.list_objects(&client.remote_url, PACKS_DIR).await {
+            Ok(names) => Ok(names.into_iter().filter(|name| name.ends_with(".pack")).collect()),
+            // Remote might not exist yet or have no packs
+            Err(_) => Ok(Vec::new()),
+        }
+    }
+    
+    async fn download_pack(&self, client: &SyncClient, name: &str) -> Result<Vec<u8>> {
+        eprintln!("📥 Downloading pack {} from remote: {}", name, client.remote_url);
+        let handler = handler_for(&client.remote_url)?;
+        handler.download_object(&client.remote_url, &format!("{}/{}", PACKS_DIR, name)).await
+    }
+    
+    async fn upload_pack(&self, client: &SyncClient, name: &str, data: &[u8]) -> Result<()> {
+        eprintln!("📤 Uploading pack {} ({} bytes) to remote: {}", name, data.len(), client.remote_url);
+        let handler = handler_for(&client.remote_url)?;
+        handler.upload_object(&client.remote_url, &format!("{}/{}", PACKS_DIR, name), data).await
+    }
+
     async fn get_sync_state(&self, client: &SyncClient) -> Result<RemoteSyncState> {
         // Get the list of issues to calculate state
         let issues = self.list_issues(client).await?;
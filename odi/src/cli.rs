@@ -3,6 +3,7 @@
 use clap::{Parser, Subcommand};
 
 use crate::commands::*;
+use crate::output::{self, Output, OutputFormat};
 use crate::{Result, AppContext};
 
 /// ODI - Distributed Issue Tracking
@@ -21,6 +22,14 @@ use crate::{Result, AppContext};
 #[command(version = env!("CARGO_PKG_VERSION"))]
 #[command(long_about = "ODI provides distributed, offline-first issue tracking similar to Git.\nUse 'odi <command> --help' for detailed command information.")]
 pub struct Cli {
+    /// Output format: text for people, or json, yaml, csv or ndjson for scripts
+    #[arg(long, global = true, value_enum, default_value_t)]
+    pub format: OutputFormat,
+
+    /// Fields to print in structured formats, e.g. 'id,title,status'; dotted paths reach nested fields
+    #[arg(long, global = true, value_delimiter = ',', value_name = "FIELDS")]
+    pub fields: Vec<String>,
+
     #[command(subcommand)]
     pub command: Commands,
 }
@@ -64,14 +73,75 @@ pub enum Commands {
     #[command(about = "Label management commands\n\nCreate and manage labels to categorize issues.")]
     Label(LabelArgs),
     
+    /// Saved views
+    #[command(about = "Saved views of issues\n\nSave queries with their sort order and columns under a name, for yourself in ~/.odiconfig\nor shared with a project, and list them with 'odi issue list --view <name>'.")]
+    View(ViewArgs),
+    
+    /// Full-text search
+    #[command(about = "Search the text of issues and projects\n\nFinds words in titles, names and descriptions, in any form ('crash' finds 'crashed'),\nand lists the best matches first. Use \"quoted words\" for phrases and word* for prefixes.")]
+    Search(SearchArgs),
+    
+    /// Markdown file mirror of issues
+    #[command(about = "Mirror issues to Markdown files in the working tree\n\nExport writes each issue to a file with YAML front matter, such as issues/1a2b3c4d-login-bug.md;\nimport applies edits of those files back to the issues, merging them with changes made in odi.")]
+    Mirror(MirrorArgs),
+    
     /// Filesystem check and repair
     #[command(about = "Check and repair ODI data integrity\n\nValidate object store and fix corruption issues.")]
     Fsck(FsckArgs),
+    
+    /// Offline bundles
+    #[command(about = "Transfer issues between workspaces as bundle files\n\nA bundle is a single checksummed file of objects and refs for sites with no network path between them.\nBundles can also be added as read-only remotes: odi remote add usb bundle:///media/usb/site.odib")]
+    Bundle(BundleArgs),
+    
+    /// Pack the object store
+    #[command(about = "Pack loose objects into a packfile\n\nDelta-encodes successive versions of each issue and drops deleted objects.")]
+    Gc(GcArgs),
+    
+    /// Encryption key management
+    #[command(about = "Manage the encryption key of an encrypted workspace\n\nThe key is read from ODI_PASSPHRASE or ODI_KEY_FILE.")]
+    Key(KeyArgs),
+    
+    /// Git integration
+    #[command(about = "Connect issues with the project's Git history\n\nScan commit messages for issue references and link the commits to those issues.")]
+    Git(GitArgs),
+    
+    /// Release notes
+    #[command(about = "Generate release notes from the issues of a release\n\nLists issues linked to commits in a range such as v1.0..v1.1, or resolved between its ends,\ngrouped by kind of change or by label, as Markdown, JSON or a Keep a Changelog section.")]
+    Changelog(ChangelogArgs),
+    
+    /// TODO comments
+    #[command(name = "scan-todos", about = "Create issues from TODO, FIXME and HACK comments\n\nWalks the working tree, skipping files Git ignores, and creates an issue for each new comment,\nlinked to its file and line. Comments keep their issue when rescanned, even after moving;\n--rewrite adds the issue key to each comment, as in 'TODO(1a2b3c4d): ...'.")]
+    ScanTodos(ScanTodosArgs),
+    
+    /// Git hooks
+    #[command(about = "Install Git hooks that connect commits with issues\n\nprepare-commit-msg references the issue named by the branch, commit-msg checks referenced\nissues exist and are open, and post-checkout moves the branch's issue to InProgress.\nExisting hooks are kept and run first.")]
+    Hooks(HooksArgs),
+    
+    /// Git merge driver
+    #[command(name = "merge-driver", about = "Merge committed .odi directories when Git branches meet\n\nInstalls a Git merge driver that merges issues changed on both branches field by field.\nChanges that can't be combined are recorded for review instead of corrupting refs.")]
+    MergeDriver(MergeDriverArgs),
+    
+    /// Show the history of ref changes
+    #[command(about = "Show the history of ref changes\n\nLists every operation that changed issues, projects, users or remotes, newest first.\nWith an issue ID, lists that issue's versions for 'odi issue restore --to'.")]
+    Reflog(ReflogArgs),
+    
+    /// Undo the last operation
+    #[command(about = "Undo the last operation\n\nRolls back every ref changed by the most recent command, such as a bad pull.\nRun it again to step further back.")]
+    Undo(UndoArgs),
+    
+    /// Upgrade the workspace format
+    #[command(about = "Upgrade the workspace to the current format version\n\nRewrites stored objects one format version at a time.\nRequired after upgrading odi when a workspace was written by an older version.")]
+    Migrate(MigrateArgs),
+    
+    /// Convert workspace data to another storage backend
+    #[command(about = "Convert workspace data to another storage backend\n\nCopies all issues, projects, users and remotes to the target backend (fs or sqlite)\nand switches the workspace over to it.")]
+    MigrateStorage(MigrateStorageArgs),
 }
 
 impl Cli {
     /// Execute the CLI command
     pub async fn execute(&self) -> Result<()> {
+        output::init(Output { format: self.format, fields: self.fields.clone() })?;
         match &self.command {
             Commands::Init(args) => {
                 // Init doesn't need context as it creates the workspace
@@ -125,12 +195,109 @@ impl Cli {
                 let ctx = AppContext::new(None).await?;
                 args.execute(&ctx).await
             },
+            Commands::View(args) => {
+                // Require workspace for view commands
+                AppContext::require_workspace(None)?;
+                let ctx = AppContext::new(None).await?;
+                args.execute(&ctx).await
+            },
+            Commands::Search(args) => {
+                // Require workspace for search
+                AppContext::require_workspace(None)?;
+                let ctx = AppContext::new(None).await?;
+                args.execute(&ctx).await
+            },
+            Commands::Mirror(args) => {
+                // Require workspace for mirror commands
+                AppContext::require_workspace(None)?;
+                let ctx = AppContext::new(None).await?;
+                args.execute(&ctx).await
+            },
             Commands::Fsck(args) => {
                 // Require workspace for fsck
                 AppContext::require_workspace(None)?;
                 let ctx = AppContext::new(None).await?;
                 args.execute(&ctx).await
             },
+            Commands::Bundle(BundleArgs { command: BundleSubcommand::Verify { file } }) => {
+                // Verifying a bundle doesn't need a workspace
+                bundle::verify_bundle(file)
+            },
+            Commands::Bundle(args) => {
+                // Require workspace for creating and applying bundles
+                AppContext::require_workspace(None)?;
+                let ctx = AppContext::new(None).await?;
+                args.execute(&ctx).await
+            },
+            Commands::Gc(args) => {
+                // Require workspace for gc
+                AppContext::require_workspace(None)?;
+                let ctx = AppContext::new(None).await?;
+                args.execute(&ctx).await
+            },
+            Commands::Key(args) => {
+                // Require workspace for key management
+                AppContext::require_workspace(None)?;
+                let ctx = AppContext::new(None).await?;
+                args.execute(&ctx).await
+            },
+            Commands::Git(args) => {
+                // Require workspace for Git integration
+                AppContext::require_workspace(None)?;
+                let ctx = AppContext::new(None).await?;
+                args.execute(&ctx).await
+            },
+            Commands::Changelog(args) => {
+                // Require workspace for changelogs
+                AppContext::require_workspace(None)?;
+                let ctx = AppContext::new(None).await?;
+                args.execute(&ctx).await
+            },
+            Commands::ScanTodos(args) => {
+                // Require workspace for TODO scanning
+                AppContext::require_workspace(None)?;
+                let ctx = AppContext::new(None).await?;
+                args.execute(&ctx).await
+            },
+            Commands::Hooks(args) => {
+                // Require workspace for Git hooks
+                AppContext::require_workspace(None)?;
+                let ctx = AppContext::new(None).await?;
+                args.execute(&ctx).await
+            },
+            Commands::MergeDriver(MergeDriverArgs { command: MergeDriverSubcommand::Run { base, ours, theirs, path } }) => {
+                // Git runs the driver from the top of the working tree, not the workspace
+                merge_driver::run(base, ours, theirs, path)
+            },
+            Commands::MergeDriver(args) => {
+                // Require workspace for installing the merge driver
+                AppContext::require_workspace(None)?;
+                let ctx = AppContext::new(None).await?;
+                args.execute(&ctx).await
+            },
+            Commands::Reflog(args) => {
+                // Require workspace for reflog
+                AppContext::require_workspace(None)?;
+                let ctx = AppContext::new(None).await?;
+                args.execute(&ctx).await
+            },
+            Commands::Undo(args) => {
+                // Require workspace for undo
+                AppContext::require_workspace(None)?;
+                let ctx = AppContext::new(None).await?;
+                args.execute(&ctx).await
+            },
+            Commands::Migrate(args) => {
+                // Migration runs before the workspace can be opened normally
+                AppContext::require_workspace(None)?;
+                args.execute().await
+            },
+            Commands::MigrateStorage(args) => {
+                // Require workspace for storage migration
+                AppContext::require_workspace(None)?;
+                let ctx = AppContext::new(None).await?;
+                args.execute(&ctx).await
+            },
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,175 @@
+//! Bundle command implementation
+
+use chrono::{DateTime, NaiveDate, Utc};
+use clap::{Args, Subcommand};
+use odi_core::{IssueQuery, LabelQuery, ProjectQuery, TeamQuery, UserQuery};
+use odi_fs::{Bundle, BundleWriter};
+use std::path::{Path, PathBuf};
+use serde::Serialize;
+use crate::{Result, AppContext, OdiError};
+use crate::output::{self, say};
+use super::pull::PullSummary;
+
+/// A bundle file written by `odi bundle create`
+#[derive(Serialize)]
+struct BundleFile<'a> {
+    file: &'a Path,
+    objects: usize,
+    bytes: usize,
+}
+
+#[derive(Args)]
+pub struct BundleArgs {
+    #[command(subcommand)]
+    pub command: BundleSubcommand,
+}
+
+#[derive(Subcommand)]
+pub enum BundleSubcommand {
+    /// Write issues, projects, labels, views, users and teams to a bundle file
+    Create {
+        /// Bundle file to write
+        file: PathBuf,
+        /// Only include objects changed since this date (YYYY-MM-DD or RFC 3339)
+        #[arg(long, value_parser = parse_since)]
+        since: Option<DateTime<Utc>>,
+        /// Only include this project's issues and labels
+        #[arg(long, short)]
+        project: Option<String>,
+    },
+    /// Check a bundle's checksum and contents without applying it
+    Verify {
+        /// Bundle file to check
+        file: PathBuf,
+    },
+    /// Merge a bundle into this workspace
+    Unbundle {
+        /// Bundle file to apply
+        file: PathBuf,
+        /// Only apply this project's issues and labels
+        #[arg(long, short)]
+        project: Option<String>,
+    },
+}
+
+impl BundleArgs {
+    pub async fn execute(&self, ctx: &AppContext) -> Result<()> {
+        match &self.command {
+            BundleSubcommand::Create { file, since, project } => {
+                create_bundle(ctx, file, *since, project.as_deref()).await
+            },
+            BundleSubcommand::Verify { file } => verify_bundle(file),
+            BundleSubcommand::Unbundle { file, project } => {
+                say!("Applying bundle {}", file.display());
+                let bundle = Bundle::load(file)?;
+                let mut summary = PullSummary::default();
+                super::pull::apply_bundle(ctx, &bundle, project.as_deref(), &mut summary).await?;
+                summary.print()
+            },
+        }
+    }
+}
+
+fn parse_since(value: &str) -> std::result::Result<DateTime<Utc>, String> {
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return Ok(date.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc());
+    }
+    DateTime::parse_from_rfc3339(value)
+        .map(|date| date.with_timezone(&Utc))
+        .map_err(|_| format!("invalid date '{}': expected YYYY-MM-DD or RFC 3339", value))
+}
+
+async fn create_bundle(ctx: &AppContext, file: &Path, since: Option<DateTime<Utc>>, project_id: Option<&str>) -> Result<()> {
+    let writer = collect_objects(ctx, since, project_id).await?;
+    if writer.is_empty() {
+        return Err(OdiError::Validation {
+            message: "Nothing to bundle".to_string()
+        });
+    }
+
+    let count = writer.len();
+    let bytes = writer.finish()?;
+    std::fs::write(file, &bytes)?;
+
+    output::record(&BundleFile { file, objects: count, bytes: bytes.len() }, || {
+        println!("✓ Wrote {} objects to {} ({} bytes)", count, file.display(), bytes.len());
+    })
+}
+
+/// Every object changed since a time, optionally only those of one project
+pub(crate) async fn collect_objects(ctx: &AppContext, since: Option<DateTime<Utc>>, project_id: Option<&str>) -> Result<BundleWriter> {
+    let changed = |at: DateTime<Utc>| since.is_none_or(|since| at >= since);
+    let in_project = |id: Option<&str>| project_id.is_none_or(|project| id == Some(project));
+    let mut writer = BundleWriter::new()
+        .with_since(since)
+        .with_project(project_id.map(str::to_string));
+
+    let issues = ctx.issue_repository().list(IssueQuery {
+        project_id: project_id.map(str::to_string),
+        ..Default::default()
+    }).await?;
+    for issue in issues.iter().filter(|issue| changed(issue.updated_at)) {
+        writer.add(&format!("issues/{}", issue.id), issue)?;
+    }
+
+    let project_repo = ctx.project_repository();
+    for project in project_repo.list_projects(ProjectQuery::default()).await? {
+        if !in_project(Some(&project.id)) {
+            continue;
+        }
+        for label in project_repo.list_labels(&project.id, LabelQuery::default()).await? {
+            if changed(label.created_at) {
+                writer.add(&format!("labels/{}/{}", project.id, label.id), &label)?;
+            }
+        }
+        for view in project_repo.list_views(&project.id).await? {
+            if changed(view.updated_at) {
+                writer.add(&format!("views/{}/{}", project.id, view.name), &view)?;
+            }
+        }
+        if changed(project.updated_at) {
+            writer.add(&format!("projects/{}", project.id), &project)?;
+        }
+    }
+
+    // Users and teams are shared by every project, so they travel with any bundle
+    let user_repo = ctx.user_repository();
+    for user in user_repo.list_users(UserQuery::default()).await? {
+        if changed(user.created_at) {
+            writer.add(&format!("users/{}", user.id), &user)?;
+        }
+    }
+    for team in user_repo.list_teams(TeamQuery::default()).await? {
+        if changed(team.updated_at) {
+            writer.add(&format!("teams/{}", team.id), &team)?;
+        }
+    }
+    Ok(writer)
+}
+
+/// Check a bundle file and print what it holds; needs no workspace
+pub fn verify_bundle(file: &Path) -> Result<()> {
+    let bundle = Bundle::load(file)?;
+    let manifest = &bundle.manifest;
+    if output::structured() {
+        return output::print_record(manifest);
+    }
+
+    println!("✓ {} is a valid bundle", file.display());
+    println!("  Created: {}", manifest.created_at.format("%Y-%m-%d %H:%M:%S UTC"));
+    if let Some(since) = manifest.since {
+        println!("  Since: {}", since.format("%Y-%m-%d %H:%M:%S UTC"));
+    }
+    if let Some(project) = &manifest.project {
+        println!("  Project: {}", project);
+    }
+    for namespace in ["issues", "projects", "labels", "views", "users", "teams"] {
+        let count = manifest.refs.iter()
+            .filter(|r| r.name.split('/').next() == Some(namespace))
+            .count();
+        if count > 0 {
+            println!("  {}: {}", namespace, count);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,102 @@
+//! Changelog command implementation
+
+use clap::Args;
+use std::path::PathBuf;
+use odi_core::{Changelog, ChangelogGrouping, GitTarget, IssueQuery, IssueStatus, LinkRole};
+use odi_fs::GitIntegration;
+use super::git::git_repository;
+use crate::{Result, AppContext, OdiError};
+use crate::output::{self, say};
+
+#[derive(Args)]
+pub struct ChangelogArgs {
+    /// Release range, e.g. 'v1.0..v1.1'; 'v1.0..' ends at HEAD, and a single revision covers all history up to it
+    pub range: String,
+
+    /// Group issues by kind of change or by label
+    #[arg(long, value_enum, default_value_t)]
+    pub group_by: ChangelogGrouping,
+
+    /// Text layout; --format json or yaml prints the changelog as data instead
+    #[arg(long, value_enum, default_value_t = ChangelogStyle::Markdown)]
+    pub style: ChangelogStyle,
+
+    /// Write the changelog to a file instead of standard output
+    #[arg(long, short)]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy)]
+pub enum ChangelogStyle {
+    Markdown,
+    KeepAChangelog,
+}
+
+impl ChangelogArgs {
+    pub async fn execute(&self, ctx: &AppContext) -> Result<()> {
+        let changelog = build_changelog(ctx, &self.range, self.group_by).await?;
+        let rendered = match self.style {
+            _ if output::structured() => output::get().render(std::slice::from_ref(&changelog), false)?,
+            ChangelogStyle::Markdown => changelog.to_markdown(),
+            ChangelogStyle::KeepAChangelog => changelog.to_keep_a_changelog(),
+        };
+
+        match &self.output {
+            Some(path) => {
+                std::fs::write(path, rendered)?;
+                let count: usize = changelog.sections.iter().map(|section| section.entries.len()).sum();
+                say!("✓ Wrote {} issues to {}", count, path.display());
+            }
+            None => print!("{}", rendered),
+        }
+        Ok(())
+    }
+}
+
+/// Issues linked to commits in a range, or resolved between its two ends
+async fn build_changelog(ctx: &AppContext, range: &str, grouping: ChangelogGrouping) -> Result<Changelog> {
+    let (repo, git) = git_repository(ctx)?;
+    let identity = repo.identity();
+    let (from, to) = match range.split_once("..") {
+        Some((from, to)) => (Some(from), if to.is_empty() { "HEAD" } else { to }),
+        None => (None, range),
+    };
+
+    let find = |revision: &str| -> Result<odi_fs::GitCommit> {
+        git.find_commit(revision)?.ok_or_else(|| OdiError::Validation {
+            message: format!("Unknown revision '{}'", revision),
+        })
+    };
+    let since = from.map(find).transpose()?.map(|commit| commit.timestamp);
+    let until = find(to)?.timestamp;
+    let commits = git.list_commits(&match from {
+        Some(from) => format!("{}..{}", from, to),
+        None => to.to_string(),
+    })?;
+
+    let mut issues = Vec::new();
+    for issue in ctx.issue_repository().list(IssueQuery::default()).await? {
+        // Links may hold abbreviated SHAs; mentions alone don't put an issue in a release
+        let linked: Vec<String> = commits.iter()
+            .filter(|commit| issue.git_refs.iter().any(|link| {
+                link.role != LinkRole::Mentions
+                    && (link.repository.is_none() || link.repository == identity)
+                    && matches!(&link.target, GitTarget::Commit { sha } if commit.sha.starts_with(sha.as_str()))
+            }))
+            .map(|commit| commit.sha.clone())
+            .collect();
+
+        let resolved_at = issue.closed_at.unwrap_or(issue.updated_at);
+        let resolved_in_range = matches!(issue.status, IssueStatus::Resolved | IssueStatus::Closed)
+            && since.is_none_or(|since| resolved_at > since)
+            && resolved_at <= until;
+
+        if !linked.is_empty() || resolved_in_range {
+            issues.push((resolved_at, issue, linked));
+        }
+    }
+    issues.sort_by_key(|(resolved_at, _, _)| *resolved_at);
+
+    let issues = issues.into_iter().map(|(_, issue, linked)| (issue, linked)).collect();
+    Ok(Changelog::new(from.map(str::to_string), to.to_string(), Some(until), issues, grouping))
+}
@@ -2,8 +2,26 @@
 
 use clap::{Args, Subcommand};
 use crate::{Result, AppContext, OdiError};
+use crate::output::{self, say};
+use odi_core::{IssueStatus, LinkRole};
 use odi_fs::{Config, save_config, ConfigLoader, FileConfigLoader};
 
+/// A configuration setting, as printed in structured formats
+#[derive(serde::Serialize)]
+struct ConfigEntry {
+    key: String,
+    value: String,
+    /// Worked out from other settings rather than stored
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    derived: bool,
+}
+
+impl ConfigEntry {
+    fn new(key: impl Into<String>, value: impl ToString) -> Self {
+        Self { key: key.into(), value: value.to_string(), derived: false }
+    }
+}
+
 #[derive(Args)]
 pub struct ConfigArgs {
     #[command(subcommand)]
@@ -31,7 +49,7 @@ pub enum ConfigSubcommand {
     },
     /// Reset configuration section
     Reset { 
-        /// Configuration section to reset (e.g., user, project, remotes)
+        /// Configuration section to reset (e.g., user, project, storage, git, remotes)
         section: String 
     },
     /// List all configuration values
@@ -64,14 +82,11 @@ async fn get_config_value(ctx: &AppContext, key: &str) -> Result<()> {
     let config = ctx.config();
     
     match get_value_by_path(config, key) {
-        Some(value) => {
-            println!("{}", value);
-            Ok(())
-        },
+        Some(value) => output::record(&ConfigEntry::new(key, &value), || println!("{}", value)),
         None => {
-            return Err(OdiError::Config { 
+            Err(OdiError::Config { 
                 message: format!("Configuration key '{}' not found", key)
-            });
+            })
         }
     }
 }
@@ -97,45 +112,57 @@ async fn set_config_value(ctx: &AppContext, key: &str, value: &str) -> Result<()
         }
     })?;
     
-    println!("Configuration updated: {} = {}", key, value);
-    Ok(())
+    output::record(&ConfigEntry::new(key, value), || println!("Configuration updated: {} = {}", key, value))
 }
 
 async fn list_config_values(ctx: &AppContext) -> Result<()> {
     let config = ctx.config();
     
-    println!("USER");
-    println!("  user.name = {}", config.user.name);
-    println!("  user.email = {}", config.user.email);
-    println!();
-    
-    println!("PROJECT");
-    println!("  project.name = {}", config.project.name);
+    let mut entries = vec![
+        ConfigEntry::new("user.name", &config.user.name),
+        ConfigEntry::new("user.email", &config.user.email),
+        ConfigEntry::new("project.name", &config.project.name),
+    ];
     if let Some(ref description) = config.project.description {
-        println!("  project.description = {}", description);
+        entries.push(ConfigEntry::new("project.description", description));
     }
     if let Some(ref branch) = config.project.default_branch {
-        println!("  project.default_branch = {}", branch);
+        entries.push(ConfigEntry::new("project.default_branch", branch));
     }
-    println!();
-    
-    if !config.remotes.is_empty() {
-        println!("REMOTES");
-        for (name, remote) in &config.remotes {
-            println!("  remotes.{}.url = {}", name, remote.url);
-            // Protocol is derived from URL, not stored separately
-            let protocol = if remote.url.starts_with("ssh://") || remote.url.contains('@') {
-                "ssh"
-            } else if remote.url.starts_with("https://") {
-                "https"
-            } else {
-                "unknown"
-            };
-            println!("  remotes.{}.protocol = {} (derived)", name, protocol);
-        }
+    entries.push(ConfigEntry::new("storage.lock_timeout", config.storage.lock_timeout));
+    entries.push(ConfigEntry::new("storage.backend", config.storage.backend));
+    entries.push(ConfigEntry::new("git.branch_template", &config.git.branch_template));
+    for (role, status) in &config.git.transitions {
+        entries.push(ConfigEntry::new(format!("git.transitions.{}", role), format!("{:?}", status)));
+    }
+    for (name, remote) in &config.remotes {
+        entries.push(ConfigEntry::new(format!("remotes.{}.url", name), &remote.url));
+        // Protocol is derived from URL, not stored separately
+        let protocol = if remote.url.starts_with("ssh://") || remote.url.contains('@') {
+            "ssh"
+        } else if remote.url.starts_with("https://") {
+            "https"
+        } else {
+            "unknown"
+        };
+        entries.push(ConfigEntry { derived: true, ..ConfigEntry::new(format!("remotes.{}.protocol", name), protocol) });
     }
     
-    Ok(())
+    output::records(&entries, || {
+        let mut section = "";
+        for entry in &entries {
+            let entry_section = entry.key.split('.').next().unwrap_or_default();
+            if entry_section != section {
+                if !section.is_empty() {
+                    println!();
+                }
+                println!("{}", entry_section.to_uppercase());
+                section = entry_section;
+            }
+            let derived = if entry.derived { " (derived)" } else { "" };
+            println!("  {} = {}{}", entry.key, entry.value, derived);
+        }
+    })
 }
 
 fn get_value_by_path(config: &Config, key: &str) -> Option<String> {
@@ -147,6 +174,12 @@ fn get_value_by_path(config: &Config, key: &str) -> Option<String> {
         ["project", "name"] => Some(config.project.name.clone()),
         ["project", "description"] => config.project.description.clone(),
         ["project", "default_branch"] => config.project.default_branch.clone(),
+        ["storage", "lock_timeout"] => Some(config.storage.lock_timeout.to_string()),
+        ["storage", "backend"] => Some(config.storage.backend.to_string()),
+        ["git", "branch_template"] => Some(config.git.branch_template.clone()),
+        ["git", "transitions", role] => {
+            config.git.transitions.get(&parse_role(role).ok()?).map(|status| format!("{:?}", status))
+        },
         ["remotes", remote_name, "url"] => {
             config.remotes.get(*remote_name).map(|r| r.url.clone())
         },
@@ -166,6 +199,13 @@ fn get_value_by_path(config: &Config, key: &str) -> Option<String> {
     }
 }
 
+/// Link role named in a `git.transitions.<role>` key
+fn parse_role(role: &str) -> Result<LinkRole> {
+    <LinkRole as clap::ValueEnum>::from_str(role, true).map_err(|_| OdiError::Config { 
+        message: format!("Unknown link role '{}'. Expected fixes, implements or mentions", role) 
+    })
+}
+
 fn set_value_by_path(config: &mut Config, key: &str, value: &str) -> Result<()> {
     let parts: Vec<&str> = key.split('.').collect();
     
@@ -208,6 +248,30 @@ fn set_value_by_path(config: &mut Config, key: &str, value: &str) -> Result<()>
                 Some(value.to_string())
             };
         },
+        ["storage", "lock_timeout"] => {
+            config.storage.lock_timeout = value.trim().parse().map_err(|_| OdiError::Validation { 
+                message: "Lock timeout must be a whole number of seconds".to_string() 
+            })?;
+        },
+        ["storage", "backend"] => {
+            return Err(OdiError::Config { 
+                message: "Switching backends requires converting existing data. Use 'odi migrate-storage --to <backend>' instead.".to_string() 
+            });
+        },
+        ["git", "branch_template"] => {
+            if !value.contains("{key}") && !value.contains("{id}") {
+                return Err(OdiError::Validation { 
+                    message: "Branch template must contain {key} or {id} so branches can be traced back to their issue".to_string() 
+                });
+            }
+            config.git.branch_template = value.to_string();
+        },
+        ["git", "transitions", role] => {
+            let status = <IssueStatus as clap::ValueEnum>::from_str(value.trim(), true).map_err(|_| OdiError::Validation { 
+                message: "Transition status must be one of: open, in-progress, resolved, closed".to_string() 
+            })?;
+            config.git.transitions.insert(parse_role(role)?, status);
+        },
         ["remotes", remote_name, "url"] => {
             if let Some(remote) = config.remotes.get_mut(*remote_name) {
                 remote.url = value.to_string();
@@ -224,7 +288,7 @@ fn set_value_by_path(config: &mut Config, key: &str, value: &str) -> Result<()>
         },
         _ => {
             return Err(OdiError::Config { 
-                message: format!("Unknown configuration key: {}. Supported keys: user.name, user.email, project.name, project.description, project.default_branch, remotes.<name>.url", key) 
+                message: format!("Unknown configuration key: {}. Supported keys: user.name, user.email, project.name, project.description, project.default_branch, storage.lock_timeout, git.branch_template, git.transitions.<role>, remotes.<name>.url", key) 
             });
         }
     }
@@ -253,6 +317,15 @@ async fn unset_config_value(ctx: &AppContext, key: &str) -> Result<()> {
         ["project", "default_branch"] => {
             config.project.default_branch = None;
         },
+        ["storage", "lock_timeout"] => {
+            config.storage.lock_timeout = odi_fs::StorageConfig::default().lock_timeout;
+        },
+        ["git", "branch_template"] => {
+            config.git.branch_template = odi_fs::GitConfig::default().branch_template;
+        },
+        ["git", "transitions", role] => {
+            config.git.transitions.remove(&parse_role(role)?);
+        },
         ["remotes", remote_name, "url"] => {
             if let Some(remote) = config.remotes.get_mut(*remote_name) {
                 remote.url = String::new(); // Set to empty string rather than removing
@@ -277,7 +350,7 @@ async fn unset_config_value(ctx: &AppContext, key: &str) -> Result<()> {
     // Save updated config
     save_config(&config)?;
     
-    println!("Unset configuration: {}", key);
+    say!("Unset configuration: {}", key);
     Ok(())
 }
 
@@ -288,21 +361,33 @@ async fn reset_config_section(ctx: &AppContext, section: &str) -> Result<()> {
         "user" => {
             config.user.name = String::new();
             config.user.email = String::new();
-            println!("Reset user configuration");
+            say!("Reset user configuration");
         },
         "project" => {
             config.project.name = String::new();
             config.project.description = None;
             config.project.default_branch = None;
-            println!("Reset project configuration");
+            say!("Reset project configuration");
+        },
+        "storage" => {
+            // The backend holds the workspace data and only changes through migrate-storage
+            config.storage = odi_fs::StorageConfig {
+                backend: config.storage.backend,
+                ..Default::default()
+            };
+            say!("Reset storage configuration");
+        },
+        "git" => {
+            config.git = odi_fs::GitConfig::default();
+            say!("Reset git configuration");
         },
         "remotes" => {
             config.remotes.clear();
-            println!("Reset remotes configuration");
+            say!("Reset remotes configuration");
         },
         _ => {
             return Err(OdiError::Config { 
-                message: format!("Unknown configuration section: {}. Supported sections: user, project, remotes", section) 
+                message: format!("Unknown configuration section: {}. Supported sections: user, project, storage, git, remotes", section) 
             });
         }
     }
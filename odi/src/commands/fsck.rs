@@ -1,8 +1,9 @@
 //! Filesystem check and repair commands
 
 use clap::{Parser, Subcommand};
-use odi_core::{IssueRepository, ProjectRepository, UserRepository};
+use serde::Serialize;
 use crate::{Result, AppContext};
+use crate::output::{self, say};
 
 /// Filesystem check and repair commands
 #[derive(Parser)]
@@ -56,7 +57,7 @@ impl FsckArgs {
 }
 
 async fn check_integrity(ctx: &AppContext, dry_run: bool, verbose: bool) -> Result<()> {
-    println!("🔍 Checking ODI object store integrity...");
+    say!("🔍 Checking ODI object store integrity...");
     
     let issue_repo = ctx.issue_repository();
     let project_repo = ctx.project_repository();
@@ -66,48 +67,54 @@ async fn check_integrity(ctx: &AppContext, dry_run: bool, verbose: bool) -> Resu
     let mut objects_checked = 0;
     
     // T079: Enhanced object store integrity checking
-    println!("🗄️  Checking object store structure...");
+    say!("🗄️  Checking object store structure...");
     check_object_store_structure(ctx, &mut issues_found, &mut objects_checked, verbose).await?;
     
+    // Object hashes were verified above; reading contents needs the key
+    if !ctx.storage()?.is_unlocked() {
+        say!("🔒 Workspace is encrypted; set ODI_PASSPHRASE or ODI_KEY_FILE to check issues, projects and users too");
+        return report_integrity(issues_found, objects_checked, dry_run);
+    }
+    
     // Check issues
-    println!("📋 Checking issues...");
+    say!("📋 Checking issues...");
     match issue_repo.list(odi_core::issue::IssueQuery::default()).await {
         Ok(issues) => {
             for issue in issues {
                 objects_checked += 1;
                 
                 if verbose {
-                    println!("  ✓ Issue: {} ({})", issue.title, issue.id);
+                    say!("  ✓ Issue: {} ({})", issue.title, issue.id);
                 }
                 
                 // Validate issue fields
                 if issue.title.trim().is_empty() {
                     issues_found += 1;
-                    println!("  ⚠️  Issue {} has empty title", issue.id);
+                    say!("  ⚠️  Issue {} has empty title", issue.id);
                 }
                 
                 // Check if created_at is in the future (corrupted timestamp)
                 if issue.created_at > chrono::Utc::now() {
                     issues_found += 1;
-                    println!("  ⚠️  Issue {} has future creation date: {}", issue.id, issue.created_at);
+                    say!("  ⚠️  Issue {} has future creation date: {}", issue.id, issue.created_at);
                 }
                 
                 // Check if updated_at is before created_at
                 if issue.updated_at < issue.created_at {
                     issues_found += 1;
-                    println!("  ⚠️  Issue {} has update date before creation date", issue.id);
+                    say!("  ⚠️  Issue {} has update date before creation date", issue.id);
                 }
             }
-            println!("  📊 Checked {} issues", objects_checked);
+            say!("  📊 Checked {} issues", objects_checked);
         },
         Err(e) => {
             issues_found += 1;
-            println!("  ✗ Failed to list issues: {}", e);
+            say!("  ✗ Failed to list issues: {}", e);
         }
     }
     
     // Check projects  
-    println!("📁 Checking projects...");
+    say!("📁 Checking projects...");
     let mut project_count = 0;
     match project_repo.list_projects(odi_core::project::ProjectQuery::default()).await {
         Ok(projects) => {
@@ -116,31 +123,31 @@ async fn check_integrity(ctx: &AppContext, dry_run: bool, verbose: bool) -> Resu
                 objects_checked += 1;
                 
                 if verbose {
-                    println!("  ✓ Project: {} ({})", project.name, project.id);
+                    say!("  ✓ Project: {} ({})", project.name, project.id);
                 }
                 
                 // Validate project fields
                 if project.name.trim().is_empty() {
                     issues_found += 1;
-                    println!("  ⚠️  Project {} has empty name", project.id);
+                    say!("  ⚠️  Project {} has empty name", project.id);
                 }
                 
                 // Check timestamps
                 if project.created_at > chrono::Utc::now() {
                     issues_found += 1;
-                    println!("  ⚠️  Project {} has future creation date: {}", project.id, project.created_at);
+                    say!("  ⚠️  Project {} has future creation date: {}", project.id, project.created_at);
                 }
             }
-            println!("  📊 Checked {} projects", project_count);
+            say!("  📊 Checked {} projects", project_count);
         },
         Err(e) => {
             issues_found += 1;
-            println!("  ✗ Failed to list projects: {}", e);
+            say!("  ✗ Failed to list projects: {}", e);
         }
     }
     
     // Check users and teams
-    println!("👥 Checking users and teams...");
+    say!("👥 Checking users and teams...");
     let mut user_count = 0;
     match user_repo.list_users(odi_core::user::UserQuery::default()).await {
         Ok(users) => {
@@ -149,25 +156,25 @@ async fn check_integrity(ctx: &AppContext, dry_run: bool, verbose: bool) -> Resu
                 objects_checked += 1;
                 
                 if verbose {
-                    println!("  ✓ User: {} ({})", user.name, user.id);
+                    say!("  ✓ User: {} ({})", user.name, user.id);
                 }
                 
                 // Validate user fields
                 if user.name.trim().is_empty() {
                     issues_found += 1;
-                    println!("  ⚠️  User {} has empty name", user.id);
+                    say!("  ⚠️  User {} has empty name", user.id);
                 }
                 
                 if !user.email.contains('@') {
                     issues_found += 1;
-                    println!("  ⚠️  User {} has invalid email: {}", user.id, user.email);
+                    say!("  ⚠️  User {} has invalid email: {}", user.id, user.email);
                 }
             }
-            println!("  📊 Checked {} users", user_count);
+            say!("  📊 Checked {} users", user_count);
         },
         Err(e) => {
             issues_found += 1;
-            println!("  ✗ Failed to list users: {}", e);
+            say!("  ✗ Failed to list users: {}", e);
         }
     }
     
@@ -179,34 +186,72 @@ async fn check_integrity(ctx: &AppContext, dry_run: bool, verbose: bool) -> Resu
                 objects_checked += 1;
                 
                 if verbose {
-                    println!("  ✓ Team: {} ({})", team.name, team.id);
+                    say!("  ✓ Team: {} ({})", team.name, team.id);
                 }
                 
                 // Validate team fields
                 if team.name.trim().is_empty() {
                     issues_found += 1;
-                    println!("  ⚠️  Team {} has empty name", team.id);
+                    say!("  ⚠️  Team {} has empty name", team.id);
                 }
             }
-            println!("  📊 Checked {} teams", team_count);
+            say!("  📊 Checked {} teams", team_count);
         },
         Err(e) => {
             issues_found += 1;
-            println!("  ✗ Failed to list teams: {}", e);
+            say!("  ✗ Failed to list teams: {}", e);
         }
     }
     
+    report_integrity(issues_found, objects_checked, dry_run)
+}
+
+/// Result of `odi fsck check`, the record printed in structured output
+#[derive(Serialize)]
+struct IntegrityReport {
+    objects_checked: u32,
+    issues_found: u32,
+}
+
+/// Statistics of `odi fsck stats`, the record printed in structured output
+#[derive(Serialize)]
+struct StorageStats {
+    issues: usize,
+    projects: usize,
+    users: usize,
+    teams: usize,
+    /// Issue counts by status and priority, with --detailed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    breakdown: Option<IssueBreakdown>,
+    estimated_bytes: usize,
+}
+
+#[derive(Serialize)]
+struct IssueBreakdown {
+    open: usize,
+    in_progress: usize,
+    closed: usize,
+    high_priority: usize,
+    medium_priority: usize,
+    low_priority: usize,
+}
+
+/// Print the integrity check summary, failing if anything was found
+fn report_integrity(issues_found: u32, objects_checked: u32, dry_run: bool) -> Result<()> {
+    if output::structured() {
+        output::print_record(&IntegrityReport { objects_checked, issues_found })?;
+    }
     // Summary
-    println!("\n📋 Integrity Check Summary:");
-    println!("  Objects checked: {}", objects_checked);
-    println!("  Issues found: {}", issues_found);
+    say!("\n📋 Integrity Check Summary:");
+    say!("  Objects checked: {}", objects_checked);
+    say!("  Issues found: {}", issues_found);
     
     if issues_found == 0 {
-        println!("✅ Object store is healthy!");
+        say!("✅ Object store is healthy!");
     } else {
-        println!("⚠️  Found {} integrity issues", issues_found);
+        say!("⚠️  Found {} integrity issues", issues_found);
         if !dry_run {
-            println!("💡 Run 'odi fsck repair' to attempt automatic fixes");
+            say!("💡 Run 'odi fsck repair' to attempt automatic fixes");
         }
     }
     
@@ -229,13 +274,13 @@ async fn check_object_store_structure(
     use std::path::Path;
     
     
-    let storage = ctx.storage();
+    let storage = ctx.storage()?;
     
     // Check .odi directory structure
     let odi_path = Path::new(".odi");
     if !odi_path.exists() {
         *issues_found += 1;
-        println!("  ✗ .odi directory does not exist");
+        say!("  ✗ .odi directory does not exist");
         return Ok(());
     }
     
@@ -243,7 +288,7 @@ async fn check_object_store_structure(
     let objects_path = odi_path.join("objects");
     if !objects_path.exists() {
         *issues_found += 1;
-        println!("  ✗ .odi/objects directory does not exist");
+        say!("  ✗ .odi/objects directory does not exist");
         return Ok(());
     }
     
@@ -251,7 +296,7 @@ async fn check_object_store_structure(
     let config_path = odi_path.join("config");
     if !config_path.exists() {
         *issues_found += 1;
-        println!("  ⚠️  .odi/config file does not exist");
+        say!("  ⚠️  .odi/config file does not exist");
     }
     
     // Scan objects directory for .bin files
@@ -268,10 +313,10 @@ async fn check_object_store_structure(
                             // Check if data is valid (not empty, reasonable size)
                             if data.is_empty() {
                                 *issues_found += 1;
-                                println!("  ⚠️  Empty object file: {:?}", path);
+                                say!("  ⚠️  Empty object file: {:?}", path);
                             } else if data.len() > 10 * 1024 * 1024 { // 10MB limit
                                 *issues_found += 1;
-                                println!("  ⚠️  Unusually large object file: {:?} ({} bytes)", path, data.len());
+                                say!("  ⚠️  Unusually large object file: {:?} ({} bytes)", path, data.len());
                             } else {
                                 // Try to deserialize as different object types
                                 let mut valid_object = false;
@@ -280,40 +325,40 @@ async fn check_object_store_structure(
                                 if let Ok(_) = bincode::deserialize::<odi_core::Issue>(&data) {
                                     valid_object = true;
                                     if verbose {
-                                        println!("    ✓ Valid Issue object: {:?}", path);
+                                        say!("    ✓ Valid Issue object: {:?}", path);
                                     }
                                 }
                                 // Try Project 
                                 else if let Ok(_) = bincode::deserialize::<odi_core::Project>(&data) {
                                     valid_object = true;
                                     if verbose {
-                                        println!("    ✓ Valid Project object: {:?}", path);
+                                        say!("    ✓ Valid Project object: {:?}", path);
                                     }
                                 }
                                 // Try User
                                 else if let Ok(_) = bincode::deserialize::<odi_core::User>(&data) {
                                     valid_object = true;
                                     if verbose {
-                                        println!("    ✓ Valid User object: {:?}", path);
+                                        say!("    ✓ Valid User object: {:?}", path);
                                     }
                                 }
                                 // Try Remote
                                 else if let Ok(_) = bincode::deserialize::<odi_core::Remote>(&data) {
                                     valid_object = true;
                                     if verbose {
-                                        println!("    ✓ Valid Remote object: {:?}", path);
+                                        say!("    ✓ Valid Remote object: {:?}", path);
                                     }
                                 }
                                 
                                 if !valid_object {
                                     *issues_found += 1;
-                                    println!("  ⚠️  Corrupted or unknown object type: {:?}", path);
+                                    say!("  ⚠️  Corrupted or unknown object type: {:?}", path);
                                 }
                             }
                         },
                         Err(e) => {
                             *issues_found += 1;
-                            println!("  ✗ Cannot read object file {:?}: {}", path, e);
+                            say!("  ✗ Cannot read object file {:?}: {}", path, e);
                         }
                     }
                 }
@@ -321,7 +366,18 @@ async fn check_object_store_structure(
         }
     } else {
         *issues_found += 1;
-        println!("  ✗ Cannot read objects directory");
+        say!("  ✗ Cannot read objects directory");
+    }
+    
+    // Every object must match its hash; encrypted objects are checked without decrypting
+    let (verified, corrupt) = storage.verify_objects()?;
+    *objects_checked += verified as u32;
+    for hash in &corrupt {
+        *issues_found += 1;
+        say!("  ✗ Object {} does not match its hash", hash);
+    }
+    if verbose {
+        say!("    ✓ Verified {} object hashes", verified - corrupt.len());
     }
     
     // Check for orphaned files (non-.bin files in objects directory)
@@ -331,24 +387,24 @@ async fn check_object_store_structure(
                 let path = entry.path();
                 if path.is_file() && path.extension().and_then(|ext| ext.to_str()) != Some("bin") {
                     *issues_found += 1;
-                    println!("  ⚠️  Orphaned file in objects directory: {:?}", path);
+                    say!("  ⚠️  Orphaned file in objects directory: {:?}", path);
                 }
             }
         }
     }
     
-    println!("  📊 Scanned objects directory: {} objects", *objects_checked);
+    say!("  📊 Scanned objects directory: {} objects", *objects_checked);
     
     Ok(())
 }
 
 async fn repair_objects(_ctx: &AppContext, _force: bool, _verbose: bool) -> Result<()> {
-    println!("🔧 Object repair functionality not yet implemented");
-    println!("💡 This feature will automatically fix common object store issues");
-    println!("   - Orphaned references");
-    println!("   - Corrupted timestamps");  
-    println!("   - Missing object files");
-    println!("   - Invalid field values");
+    say!("🔧 Object repair functionality not yet implemented");
+    say!("💡 This feature will automatically fix common object store issues");
+    say!("   - Orphaned references");
+    say!("   - Corrupted timestamps");  
+    say!("   - Missing object files");
+    say!("   - Invalid field values");
     
     Err(crate::OdiError::Command { 
         message: "Repair functionality is not yet implemented".to_string() 
@@ -356,18 +412,14 @@ async fn repair_objects(_ctx: &AppContext, _force: bool, _verbose: bool) -> Resu
 }
 
 async fn show_storage_stats(ctx: &AppContext, detailed: bool) -> Result<()> {
-    println!("📊 ODI Storage Statistics");
-    println!();
-    
     let issue_repo = ctx.issue_repository();
     let project_repo = ctx.project_repository();
     let user_repo = ctx.user_repository();
     
     // Count objects by type
-    let issue_count = match issue_repo.list(odi_core::issue::IssueQuery::default()).await {
-        Ok(issues) => issues.len(),
-        Err(_) => 0,
-    };
+    let issues = issue_repo.list(odi_core::issue::IssueQuery::default()).await
+        .unwrap_or_default();
+    let issue_count = issues.len();
     
     let project_count = match project_repo.list_projects(odi_core::project::ProjectQuery::default()).await {
         Ok(projects) => projects.len(),
@@ -384,38 +436,20 @@ async fn show_storage_stats(ctx: &AppContext, detailed: bool) -> Result<()> {
         Err(_) => 0,
     };
     
-    println!("📈 Object Counts:");
-    println!("  Issues:   {}", issue_count);
-    println!("  Projects: {}", project_count);
-    println!("  Users:    {}", user_count);
-    println!("  Teams:    {}", team_count);
-    println!("  Total:    {}", issue_count + project_count + user_count + team_count);
-    
-    if detailed {
-        println!("\n📋 Issue Breakdown:");
-        let issues = issue_repo.list(odi_core::issue::IssueQuery::default()).await
-            .unwrap_or_default();
-        
-        let open_count = issues.iter().filter(|i| i.status == odi_core::IssueStatus::Open).count();
-        let closed_count = issues.iter().filter(|i| i.status == odi_core::IssueStatus::Closed).count();
-        let in_progress_count = issues.iter().filter(|i| i.status == odi_core::IssueStatus::InProgress).count();
-        
-        println!("  Open:        {}", open_count);
-        println!("  In Progress: {}", in_progress_count); 
-        println!("  Closed:      {}", closed_count);
-        
-        let high_priority = issues.iter().filter(|i| i.priority == odi_core::Priority::High).count();
-        let medium_priority = issues.iter().filter(|i| i.priority == odi_core::Priority::Medium).count();
-        let low_priority = issues.iter().filter(|i| i.priority == odi_core::Priority::Low).count();
-        
-        println!("\n🎯 Priority Breakdown:");
-        println!("  High:   {}", high_priority);
-        println!("  Medium: {}", medium_priority);
-        println!("  Low:    {}", low_priority);
-    }
+    let breakdown = detailed.then(|| {
+        let with_status = |status: odi_core::IssueStatus| issues.iter().filter(|i| i.status == status).count();
+        let with_priority = |priority: odi_core::Priority| issues.iter().filter(|i| i.priority == priority).count();
+        IssueBreakdown {
+            open: with_status(odi_core::IssueStatus::Open),
+            in_progress: with_status(odi_core::IssueStatus::InProgress),
+            closed: with_status(odi_core::IssueStatus::Closed),
+            high_priority: with_priority(odi_core::Priority::High),
+            medium_priority: with_priority(odi_core::Priority::Medium),
+            low_priority: with_priority(odi_core::Priority::Low),
+        }
+    });
     
     // Try to estimate disk usage (this is a rough approximation)
-    println!("\n💾 Estimated Storage:");
     let avg_issue_size = 250; // Rough estimate based on our binary serialization
     let avg_project_size = 150;
     let avg_user_size = 120;
@@ -426,6 +460,42 @@ async fn show_storage_stats(ctx: &AppContext, detailed: bool) -> Result<()> {
                      (user_count * avg_user_size) +
                      (team_count * avg_team_size);
     
+    let stats = StorageStats {
+        issues: issue_count,
+        projects: project_count,
+        users: user_count,
+        teams: team_count,
+        breakdown,
+        estimated_bytes: total_bytes,
+    };
+    output::record(&stats, || print_storage_stats(&stats))
+}
+
+fn print_storage_stats(stats: &StorageStats) {
+    println!("📊 ODI Storage Statistics");
+    println!();
+    
+    println!("📈 Object Counts:");
+    println!("  Issues:   {}", stats.issues);
+    println!("  Projects: {}", stats.projects);
+    println!("  Users:    {}", stats.users);
+    println!("  Teams:    {}", stats.teams);
+    println!("  Total:    {}", stats.issues + stats.projects + stats.users + stats.teams);
+    
+    if let Some(breakdown) = &stats.breakdown {
+        println!("\n📋 Issue Breakdown:");
+        println!("  Open:        {}", breakdown.open);
+        println!("  In Progress: {}", breakdown.in_progress); 
+        println!("  Closed:      {}", breakdown.closed);
+        
+        println!("\n🎯 Priority Breakdown:");
+        println!("  High:   {}", breakdown.high_priority);
+        println!("  Medium: {}", breakdown.medium_priority);
+        println!("  Low:    {}", breakdown.low_priority);
+    }
+    
+    println!("\n💾 Estimated Storage:");
+    let total_bytes = stats.estimated_bytes;
     if total_bytes < 1024 {
         println!("  Size: {} bytes", total_bytes);
     } else if total_bytes < 1024 * 1024 {
@@ -433,6 +503,4 @@ async fn show_storage_stats(ctx: &AppContext, detailed: bool) -> Result<()> {
     } else {
         println!("  Size: {:.1} MB", total_bytes as f64 / (1024.0 * 1024.0));
     }
-    
-    Ok(())
-}
\ No newline at end of file
+}
@@ -0,0 +1,29 @@
+//! Object store garbage collection command
+
+use clap::Args;
+use crate::{Result, AppContext};
+use crate::output::{self, say};
+
+/// Pack loose objects into a packfile
+#[derive(Args)]
+pub struct GcArgs {}
+
+impl GcArgs {
+    pub async fn execute(&self, ctx: &AppContext) -> Result<()> {
+        say!("Packing objects...");
+        let stats = ctx.storage()?.gc()?;
+
+        output::record(&stats, || {
+            if stats.loose == 0 && stats.pruned == 0 {
+                println!("✓ Nothing to pack");
+                return;
+            }
+
+            println!("✓ Packed {} objects ({} loose, {} stored as deltas) into {} bytes",
+                stats.pack.objects, stats.loose, stats.pack.deltas, stats.pack.size);
+            if stats.pruned > 0 {
+                println!("✓ Dropped {} deleted objects", stats.pruned);
+            }
+        })
+    }
+}
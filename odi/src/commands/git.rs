@@ -0,0 +1,156 @@
+//! Git command implementation
+
+use clap::{Args, Subcommand};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use odi_core::{GitRef, Issue, IssueId, IssueQuery, IssueUpdate, IssueStatus, parse_references};
+use odi_fs::{DefaultGitIntegration, GitIntegration, GitRepository};
+use serde::Serialize;
+use crate::{Result, AppContext, OdiError};
+use crate::output::{self, say};
+
+#[derive(Args)]
+pub struct GitArgs {
+    #[command(subcommand)]
+    pub command: GitSubcommand,
+}
+
+#[derive(Subcommand)]
+pub enum GitSubcommand {
+    /// Link issues referenced in commit messages
+    #[command(about = "Link issues referenced in commit messages\n\nReads trailers such as 'Fixes: 1a2b3c4d' and keywords such as 'refs #1a2b3c4d',\nlinks each commit to the issues it names, and applies the configured [git.transitions]\nto commits on the default branch. Safe to run repeatedly, e.g. from a post-merge hook.")]
+    Scan {
+        /// Commits to scan, e.g. 'main' or 'v1.0..HEAD'
+        #[arg(default_value = "HEAD")]
+        range: String,
+    },
+}
+
+impl GitArgs {
+    pub async fn execute(&self, ctx: &AppContext) -> Result<()> {
+        match &self.command {
+            GitSubcommand::Scan { range } => scan(ctx, range).await,
+        }
+    }
+}
+
+/// The workspace's Git repository, which links are checked against
+pub(crate) fn git_repository(ctx: &AppContext) -> Result<(GitRepository, DefaultGitIntegration)> {
+    let repo = DefaultGitIntegration::detect_repository(ctx.workspace_path())?.ok_or_else(|| OdiError::Validation {
+        message: "No Git repository found for this workspace".to_string()
+    })?;
+    let git = DefaultGitIntegration::new(repo.path.clone());
+    Ok((repo, git))
+}
+
+/// A path given on the command line, relative to the root of the working tree
+pub(crate) fn repository_path(repo_root: &Path, path: &str) -> Result<String> {
+    let root = repo_root.canonicalize()?;
+    let absolute = std::env::current_dir()?.join(path);
+    // Deleted files can't be canonicalized, so fall back to their parent
+    let absolute = absolute.canonicalize().or_else(|_| match (absolute.parent(), absolute.file_name()) {
+        (Some(parent), Some(name)) => parent.canonicalize().map(|parent| parent.join(name)),
+        _ => Err(std::io::Error::from(std::io::ErrorKind::NotFound)),
+    });
+    let relative = match absolute {
+        Ok(absolute) => absolute.strip_prefix(&root).map(|relative| relative.to_string_lossy().replace('\\', "/")).ok(),
+        Err(_) => None,
+    };
+    relative.ok_or_else(|| OdiError::Validation {
+        message: format!("'{}' is not inside the Git working tree {}", path, repo_root.display())
+    })
+}
+
+/// Find the single issue a commit message reference names
+pub(crate) fn resolve<'a>(issues: &'a [Issue], reference: &str) -> std::result::Result<&'a Issue, String> {
+    let mut matches = issues.iter().filter(|issue| issue.matches_reference(reference));
+    match (matches.next(), matches.next()) {
+        (Some(issue), None) => Ok(issue),
+        (Some(_), Some(_)) => Err(format!("'{}' matches more than one issue", reference)),
+        (None, _) => Err(format!("no issue matches '{}'", reference)),
+    }
+}
+
+/// Result of `odi git scan`, the record printed in structured output
+#[derive(Serialize)]
+struct ScanSummary {
+    commits: usize,
+    links: usize,
+    status_changes: usize,
+}
+
+async fn scan(ctx: &AppContext, range: &str) -> Result<()> {
+    let (repo, git) = git_repository(ctx)?;
+    let identity = repo.identity();
+    let odi_dir = ctx.workspace_path().join(".odi");
+
+    // Transitions only apply once a commit reaches the default branch
+    let default_branch = ctx.config().project.default_branch.clone().unwrap_or_else(|| "main".to_string());
+    let on_default: HashSet<String> = if git.branch_exists(&default_branch)? {
+        git.list_commits(&default_branch)?.into_iter().map(|commit| commit.sha).collect()
+    } else {
+        say!("⚠️  Default branch '{}' not found; no status transitions will be applied", default_branch);
+        HashSet::new()
+    };
+    let transitions = &ctx.config().git.transitions;
+
+    let scanned = odi_fs::git::read_scanned(&odi_dir)?;
+    let mut commits = git.list_commits(range)?;
+    commits.retain(|commit| !scanned.contains(&commit.sha));
+    commits.reverse();
+
+    let issue_repo = ctx.issue_repository();
+    let mut issues = issue_repo.list(IssueQuery::default()).await?;
+    let mut changed: HashMap<IssueId, bool> = HashMap::new();
+    let mut finished = Vec::new();
+    let (mut links, mut moved) = (0, 0);
+
+    for commit in &commits {
+        let merged = on_default.contains(&commit.sha);
+        for reference in parse_references(commit.message.as_deref().unwrap_or("")) {
+            let id = match resolve(&issues, &reference.reference) {
+                Ok(issue) => issue.id,
+                Err(message) => {
+                    say!("⚠️  {}: {}", commit.short_sha(), message);
+                    continue;
+                }
+            };
+            let issue = issues.iter_mut().find(|issue| issue.id == id).expect("resolved issue is listed");
+
+            let link = GitRef::commit(commit.sha.clone(), reference.role).with_repository(identity.clone());
+            if issue.link(link) {
+                say!("✓ {} {} {} {}", commit.short_sha(), reference.role, issue.short_id(), issue.title);
+                links += 1;
+                changed.entry(id).or_insert(false);
+            }
+
+            if let Some(target) = transitions.get(&reference.role).filter(|_| merged) {
+                // Never reopen or step back a closed issue
+                if issue.status != *target && issue.status != IssueStatus::Closed {
+                    say!("  {} {:?} → {:?}", issue.short_id(), issue.status, target);
+                    issue.status = target.clone();
+                    moved += 1;
+                    changed.insert(id, true);
+                }
+            }
+        }
+        if merged {
+            finished.push(commit.sha.clone());
+        }
+    }
+
+    for (id, status_changed) in changed {
+        let issue = issues.iter().find(|issue| issue.id == id).expect("changed issue is listed");
+        let mut update = IssueUpdate::new().git_refs(issue.git_refs.clone());
+        if status_changed {
+            update = update.status(issue.status.clone());
+        }
+        issue_repo.update(&id, update).await?;
+    }
+    odi_fs::git::record_scanned(&odi_dir, &finished)?;
+
+    let summary = ScanSummary { commits: commits.len(), links, status_changes: moved };
+    output::record(&summary, || {
+        println!("Scanned {} commits: {} new links, {} status changes", summary.commits, summary.links, summary.status_changes);
+    })
+}
@@ -0,0 +1,189 @@
+//! Hooks command implementation
+
+use clap::{Args, Subcommand};
+use odi_core::{Issue, IssueQuery, IssueStatus, IssueUpdate, branch_reference, parse_references};
+use odi_fs::hooks::{Installed, Removed};
+use super::git::{git_repository, resolve};
+use crate::{Result, AppContext, OdiError};
+use crate::output::say;
+
+#[derive(Args)]
+pub struct HooksArgs {
+    #[command(subcommand)]
+    pub command: HooksSubcommand,
+}
+
+#[derive(Subcommand)]
+pub enum HooksSubcommand {
+    /// Install the hooks into the workspace's Git repository
+    Install,
+    /// Remove the hooks, putting back any hooks they replaced
+    Uninstall,
+    /// Run a hook; called by the installed hook scripts
+    #[command(hide = true)]
+    Run {
+        hook: String,
+        /// Arguments Git passed to the hook
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+}
+
+impl HooksArgs {
+    pub async fn execute(&self, ctx: &AppContext) -> Result<()> {
+        match &self.command {
+            HooksSubcommand::Install => install(ctx),
+            HooksSubcommand::Uninstall => uninstall(ctx),
+            HooksSubcommand::Run { hook, args } => match hook.as_str() {
+                "prepare-commit-msg" => prepare_commit_msg(ctx, args).await,
+                "commit-msg" => commit_msg(ctx, args).await,
+                "post-checkout" => post_checkout(ctx, args).await,
+                other => Err(OdiError::Command {
+                    message: format!("Unknown hook '{}'", other),
+                }),
+            },
+        }
+    }
+}
+
+fn install(ctx: &AppContext) -> Result<()> {
+    let (repo, _) = git_repository(ctx)?;
+    for (hook, outcome) in odi_fs::hooks::install(&repo.hooks_dir)? {
+        match outcome {
+            Installed::Created => say!("✓ Installed {}", hook),
+            Installed::Chained => say!("✓ Installed {} (runs the existing hook first, kept as {}{})", hook, hook, odi_fs::hooks::BACKUP_SUFFIX),
+            Installed::Updated => say!("✓ Updated {}", hook),
+        }
+    }
+    say!("Hooks installed in {}", repo.hooks_dir.display());
+    Ok(())
+}
+
+fn uninstall(ctx: &AppContext) -> Result<()> {
+    let (repo, _) = git_repository(ctx)?;
+    for (hook, outcome) in odi_fs::hooks::uninstall(&repo.hooks_dir)? {
+        match outcome {
+            Removed::Removed => say!("✓ Removed {}", hook),
+            Removed::Restored => say!("✓ Removed {} and restored the hook it replaced", hook),
+            Removed::NotInstalled => say!("  {} not installed", hook),
+        }
+    }
+    Ok(())
+}
+
+/// The issue named by the current branch, if any
+async fn branch_issue(ctx: &AppContext) -> Result<Option<Issue>> {
+    let (repo, _) = git_repository(ctx)?;
+    let Some(reference) = repo.current_branch.as_deref().and_then(branch_reference) else {
+        return Ok(None);
+    };
+    let issues = ctx.issue_repository().list(IssueQuery::default()).await?;
+    Ok(resolve(&issues, &reference).ok().cloned())
+}
+
+/// Message text without Git's comment lines
+fn strip_comments(message: &str) -> String {
+    message.lines().filter(|line| !line.starts_with('#')).collect::<Vec<_>>().join("\n")
+}
+
+/// Add a trailer to a commit message, before Git's comment lines
+fn add_trailer(message: &str, trailer: &str) -> String {
+    let comments_at = if message.starts_with('#') {
+        0
+    } else {
+        message.find("\n#").map_or(message.len(), |i| i + 1)
+    };
+    let (body, comments) = message.split_at(comments_at);
+    let body = body.trim_end();
+
+    // Join an existing trailer block rather than starting a new paragraph
+    let in_trailers = body.rsplit_once("\n\n").is_some_and(|(_, last)| {
+        last.lines().all(|line| line.split_once(": ").is_some_and(|(key, _)| !key.is_empty() && !key.contains(' ')))
+    });
+    let separator = if in_trailers { "\n" } else { "\n\n" };
+
+    let mut result = format!("{}{}{}\n", body, separator, trailer);
+    if !comments.is_empty() {
+        result.push('\n');
+        result.push_str(comments);
+    }
+    result
+}
+
+/// Reference the branch's issue in the message being written
+async fn prepare_commit_msg(ctx: &AppContext, args: &[String]) -> Result<()> {
+    let Some(file) = args.first() else {
+        return Ok(());
+    };
+    // Merges, squashes and amends keep the message they already have
+    if matches!(args.get(1).map(String::as_str), Some("merge" | "squash" | "commit")) {
+        return Ok(());
+    }
+    let Some(issue) = branch_issue(ctx).await? else {
+        return Ok(());
+    };
+
+    let message = std::fs::read_to_string(file)?;
+    let referenced = parse_references(&strip_comments(&message))
+        .iter()
+        .any(|reference| issue.matches_reference(&reference.reference));
+    if !referenced {
+        std::fs::write(file, add_trailer(&message, &format!("Refs: {}", issue.short_id())))?;
+    }
+    Ok(())
+}
+
+/// Refuse messages referencing issues that are resolved or closed
+///
+/// References that match no issue only get a warning: they may name an issue
+/// not pulled yet, or be an ID-like word that isn't meant as one.
+async fn commit_msg(ctx: &AppContext, args: &[String]) -> Result<()> {
+    let Some(file) = args.first() else {
+        return Ok(());
+    };
+    let references = parse_references(&strip_comments(&std::fs::read_to_string(file)?));
+    if references.is_empty() {
+        return Ok(());
+    }
+
+    let issues = ctx.issue_repository().list(IssueQuery::default()).await?;
+    let mut problems = Vec::new();
+    for reference in references {
+        match resolve(&issues, &reference.reference) {
+            Ok(issue) if matches!(issue.status, IssueStatus::Resolved | IssueStatus::Closed) => {
+                problems.push(format!("issue {} ({}) is {:?}", issue.short_id(), issue.title, issue.status));
+            }
+            Ok(_) => {}
+            Err(message) => say!("odi: warning: {}", message),
+        }
+    }
+
+    if problems.is_empty() {
+        return Ok(());
+    }
+    Err(OdiError::Validation {
+        message: format!(
+            "Commit message references issues that can't take commits:\n  {}\nFix the message, or commit with --no-verify to skip this check",
+            problems.join("\n  ")
+        ),
+    })
+}
+
+/// Start work on the issue of a branch that was checked out
+async fn post_checkout(ctx: &AppContext, args: &[String]) -> Result<()> {
+    // The third argument is 1 for a branch checkout, 0 for a file checkout
+    if args.get(2).map(String::as_str) != Some("1") {
+        return Ok(());
+    }
+    let Some(mut issue) = branch_issue(ctx).await? else {
+        return Ok(());
+    };
+    if issue.status != IssueStatus::Open {
+        return Ok(());
+    }
+
+    issue.update_status(IssueStatus::InProgress).map_err(|message| OdiError::Validation { message })?;
+    ctx.issue_repository().update(&issue.id, IssueUpdate::new().status(IssueStatus::InProgress)).await?;
+    say!("odi: issue {} ({}) is now in progress", issue.short_id(), issue.title);
+    Ok(())
+}
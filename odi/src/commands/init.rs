@@ -2,9 +2,10 @@
 
 use clap::Args;
 use std::path::PathBuf;
-use odi_fs::{FileSystemStorage, save_config, Config, UserConfig, ProjectConfig};
+use odi_fs::{DefaultGitIntegration, FileSystemStorage, GitHead, GitIntegration, KeySource, save_config, write_version, Config, UserConfig, ProjectConfig, StorageConfig, GitConfig, CURRENT_VERSION};
 use std::collections::HashMap;
 use crate::Result;
+use crate::output::say;
 
 /// Arguments for init command
 #[derive(Args)]
@@ -30,12 +31,20 @@ pub struct InitArgs {
     /// Use alternative config file
     #[arg(long, help = "Use custom config file path")]
     pub config: Option<PathBuf>,
+    
+    /// Encrypt stored objects
+    #[arg(long, help = "Encrypt stored objects with a passphrase from ODI_PASSPHRASE, or --key-file")]
+    pub encrypted: bool,
+    
+    /// Key file for an encrypted workspace
+    #[arg(long, requires = "encrypted", help = "Unlock the encrypted workspace with this key file (at least 32 bytes)")]
+    pub key_file: Option<PathBuf>,
 }
 
 impl InitArgs {
     /// Execute the init command
     pub async fn execute(&self) -> Result<()> {
-        println!("🚀 Initializing ODI workspace...");
+        say!("🚀 Initializing ODI workspace...");
         
         // Check if already initialized
         if std::path::Path::new(".odi").exists() {
@@ -44,15 +53,35 @@ impl InitArgs {
             });
         }
         
+        // Resolve the key before creating anything, so a missing one leaves no half-made workspace
+        let key_source = if self.encrypted {
+            match &self.key_file {
+                Some(path) => Some(KeySource::KeyFile(path.clone())),
+                None => Some(KeySource::Passphrase(std::env::var(odi_fs::crypto::PASSPHRASE_ENV).map_err(|_| {
+                    crate::OdiError::Validation {
+                        message: "An encrypted workspace needs a passphrase in ODI_PASSPHRASE or a --key-file".to_string()
+                    }
+                })?)),
+            }
+        } else {
+            None
+        };
+        
         // T073: Integrate odi-core with odi-fs for persistent storage
-        let _storage = FileSystemStorage::init().map_err(|e| {
+        let storage = FileSystemStorage::init().map_err(|e| {
             crate::OdiError::Storage { 
                 message: format!("Failed to initialize storage: {}", e) 
             }
         })?;
+        write_version(storage.root_path(), CURRENT_VERSION)?;
         
-        println!("✅ Created .odi directory structure");
-        println!("✅ Initialized storage engine");
+        say!("✅ Created .odi directory structure");
+        say!("✅ Initialized storage engine");
+        
+        if let Some(source) = &key_source {
+            odi_fs::crypto::create(storage.root_path(), source)?;
+            say!("🔒 Enabled encryption at rest");
+        }
         
         // T077: Implement configuration loading and validation
         // Create default configuration
@@ -67,6 +96,8 @@ impl InitArgs {
                 default_branch: Some("main".to_string()),
             },
             remotes: HashMap::new(),
+            storage: StorageConfig::default(),
+            git: GitConfig::default(),
         };
         
         save_config(&config).map_err(|e| {
@@ -75,27 +106,46 @@ impl InitArgs {
             }
         })?;
         
-        println!("✅ Created default configuration");
+        say!("✅ Created default configuration");
         
         // Handle optional parameters
         if let Some(project_name) = &self.project {
-            println!("📋 Project '{}' will be created", project_name);
+            say!("📋 Project '{}' will be created", project_name);
         }
         
-        if let Some(_git_path) = &self.git_repo {
-            println!("🔗 Git integration will be set up");
+        let git_path = self.git_repo.clone().unwrap_or_else(|| PathBuf::from("."));
+        match DefaultGitIntegration::detect_repository(&git_path) {
+            Ok(Some(repo)) => {
+                let head = match &repo.head {
+                    GitHead::Branch { name, .. } => format!("branch {}", name),
+                    GitHead::Unborn { name } => format!("branch {} (no commits yet)", name),
+                    GitHead::Detached { sha } => format!("detached at {}", &sha[..7]),
+                };
+                let worktree = if repo.is_worktree { ", linked worktree" } else { "" };
+                say!("🔗 Found Git repository at {} ({}{})", repo.path.display(), head, worktree);
+                let mut remotes: Vec<_> = repo.remotes.iter().collect();
+                remotes.sort();
+                for (name, url) in remotes {
+                    say!("   {} → {}", name, url);
+                }
+            }
+            Ok(None) if self.git_repo.is_some() => {
+                say!("⚠️  No Git repository found at {}", git_path.display());
+            }
+            Ok(None) => {}
+            Err(e) => say!("⚠️  Could not read Git repository: {}", e),
         }
         
         if let Some(remote_url) = &self.remote {
-            println!("🌐 Remote '{}' will be configured", remote_url);
+            say!("🌐 Remote '{}' will be configured", remote_url);
         }
         
-        println!("\n🎉 ODI workspace initialized successfully!");
-        println!("💡 Next steps:");
-        println!("   • Run 'odi project create <name>' to create a project");
-        println!("   • Run 'odi issue create' to create your first issue");
-        println!("   • Run 'odi config set user.name \"Your Name\"' to set your name");
-        println!("   • Run 'odi config set user.email \"you@example.com\"' to set your email");
+        say!("\n🎉 ODI workspace initialized successfully!");
+        say!("💡 Next steps:");
+        say!("   • Run 'odi project create <name>' to create a project");
+        say!("   • Run 'odi issue create' to create your first issue");
+        say!("   • Run 'odi config set user.name \"Your Name\"' to set your name");
+        say!("   • Run 'odi config set user.email \"you@example.com\"' to set your email");
         
         Ok(())
     }
@@ -2,7 +2,10 @@
 
 use clap::{Args, Subcommand};
 use crate::{Result, AppContext};
-use odi_core::{Issue, IssueStatus, Priority, IssueRepository};
+use crate::output::{self, say};
+use odi_core::{Cursor, Filter, GitRef, GitTarget, Issue, IssueDocument, IssueQuery, IssueStatus, IssueUpdate, LinkRole, Priority, QueryContext};
+use odi_fs::{DefaultGitIntegration, GitIntegration};
+use super::git::{git_repository, repository_path, resolve};
 
 #[derive(Args)]
 pub struct IssueArgs {
@@ -15,7 +18,8 @@ pub enum IssueSubcommand {
     /// Create a new issue
     Create { 
         /// Issue title
-        title: String,
+        #[arg(required_unless_present = "edit")]
+        title: Option<String>,
         /// Issue description
         #[arg(long, short)]
         description: Option<String>,
@@ -28,21 +32,38 @@ pub enum IssueSubcommand {
         /// Issue ID (will be generated if not provided)
         #[arg(long)]
         id: Option<String>,
+        /// Write the issue in your editor, starting from the other options
+        #[arg(long, short)]
+        edit: bool,
     },
     /// List all issues
+    #[command(long_about = "List issues, optionally those matching a query\n\nQueries combine terms such as status:open, label:bug,-wontfix, assignee:@me, updated:>7d,\n\"login page\" and sort:-priority; terms must all hold unless OR is written between them.\nSee 'Issue queries' in docs/commands.md.")]
     List {
+        /// Query, e.g. 'status:open label:bug,-wontfix assignee:@me updated:>7d "login page" sort:-priority'
+        /// (after the options, as terms may start with '-')
+        #[arg(value_name = "QUERY", allow_hyphen_values = true, trailing_var_arg = true)]
+        query: Vec<String>,
         /// Filter by status
         #[arg(long, value_enum)]
         status: Option<IssueStatus>,
         /// Filter by project
         #[arg(long, short)]
         project: Option<String>,
-        /// Filter by issue ID
+        /// Filter by issue ID prefix, like 'id:' in a query
         #[arg(long)]
         id: Option<String>,
-        /// Filter by issue description
+        /// Filter by text in the title or description, like a quoted term in a query
         #[arg(long)]
         description: Option<String>,
+        /// Show at most this many issues, with a cursor for the next page
+        #[arg(long, short = 'n')]
+        limit: Option<usize>,
+        /// Continue after the cursor printed with the previous page
+        #[arg(long, value_name = "CURSOR")]
+        after: Option<String>,
+        /// List a saved view, narrowed by the query if one is given
+        #[arg(long, value_name = "NAME")]
+        view: Option<String>,
     },
     /// Show issue details
     Show {
@@ -52,6 +73,12 @@ pub enum IssueSubcommand {
         #[arg(long, short)]
         project: Option<String>,
     },
+    /// Edit an issue in your editor
+    #[command(about = "Edit an issue in your editor\n\nOpens the issue from $VISUAL or $EDITOR as Markdown with YAML front matter (title, status,\npriority, assignees, labels, project) and the description as the body. Saved documents with\nerrors are reopened with the errors written in; save an empty document to cancel.")]
+    Edit {
+        /// Issue ID or ID prefix
+        id: String,
+    },
     /// Assign issue to user
     Assign {
         /// Issue ID
@@ -82,14 +109,61 @@ pub enum IssueSubcommand {
         #[arg(long, short)]
         project: Option<String>,
     },
+    /// Link a commit to an issue
+    LinkCommit {
+        /// Issue ID
+        id: String,
+        /// Commit SHA, abbreviated SHA or any revision such as HEAD
+        commit: String,
+        /// How the commit relates to the issue
+        #[arg(long, value_enum, default_value = "mentions")]
+        role: LinkRole,
+    },
+    /// Link a branch to an issue
+    LinkBranch {
+        /// Issue ID
+        id: String,
+        /// Branch name (defaults to the current branch)
+        branch: Option<String>,
+        /// How the branch relates to the issue
+        #[arg(long, value_enum, default_value = "implements")]
+        role: LinkRole,
+    },
+    /// Start work on an issue on its own branch
+    #[command(about = "Start work on an issue on its own branch\n\nCreates a branch named from git.branch_template (default {key}-{slug}) and checks it out,\nassigns the issue to you, moves it to InProgress and links the branch to it.")]
+    Start {
+        /// Issue ID or ID prefix
+        id: String,
+    },
+    /// Finish work on an issue started with 'odi issue start'
+    #[command(about = "Finish work on an issue started with 'odi issue start'\n\nChecks the issue is in progress and assigned to you, you are on its branch with everything\ncommitted and at least one commit beyond the default branch, then moves the issue to\nResolved and checks out the default branch.")]
+    Finish {
+        /// Issue ID or ID prefix (defaults to the issue named by the current branch)
+        id: Option<String>,
+    },
+    /// List issues related to a file, directory or range of lines
+    #[command(name = "for-path", about = "List issues related to a file, directory or range of lines\n\nFinds issues with linked commits that changed the path, or with a line range such as\nsrc/auth.rs:40-60, the commits that last changed those lines (git blame), plus issues\nwhose description mentions the path.")]
+    ForPath {
+        /// Path relative to the current directory, optionally with ':LINE' or ':START-END'
+        #[arg(value_name = "PATH[:LINES]")]
+        spec: String,
+    },
+    /// Roll an issue back to a version from its reflog, including deleted issues
+    Restore {
+        /// Issue ID
+        id: String,
+        /// Reflog entry to restore, counting back from the newest (0); see 'odi reflog <id>'
+        #[arg(long, value_name = "ENTRY")]
+        to: usize,
+    },
 }
 
 impl IssueArgs {
     pub async fn execute(&self, ctx: &AppContext) -> Result<()> {
         match &self.command {
-            IssueSubcommand::Create { title, description, project, priority, id } => {
+            IssueSubcommand::Create { title, description, project, priority, id, edit } => {
                 let mut issue = Issue::new(
-                    title.clone(),
+                    title.clone().unwrap_or_default(),
                     std::env::var("USER").unwrap_or_else(|_| "unknown".to_string()), // Get from environment
                 );
                 
@@ -125,53 +199,63 @@ impl IssueArgs {
                     issue.project_id = Some(project_id.clone());
                 }
                 
+                if *edit {
+                    match edit_issue_document(ctx, &issue).await? {
+                        Some((_, update)) => update.apply_to(&mut issue),
+                        None => {
+                            say!("Aborted: the document was empty, no issue created");
+                            return Ok(());
+                        }
+                    }
+                }
+                
                 let created_issue = ctx.issue_repository().create(issue).await
                     .map_err(crate::OdiError::Core)?;
                 
-                println!("Created issue: {} ({})", created_issue.title, created_issue.id);
-                Ok(())
+                output::record(&created_issue, || {
+                    println!("Created issue: {} ({})", created_issue.title, created_issue.id);
+                })
             },
-            IssueSubcommand::List { status, project, id, description } => {
-                // Build query with filters
-                let mut query = odi_core::IssueQuery::default();
-                
-                if let Some(s) = status {
-                    query.status = Some(s.clone());
+            IssueSubcommand::List { query, status, project, id, description, limit, after, view } => {
+                let context = QueryContext::new(Some(ctx.config().user.name.clone()));
+                let view = match view {
+                    Some(name) => Some(super::view::find_view(ctx, name, project.as_deref()).await?),
+                    None => None,
+                };
+                let mut query = match &view {
+                    Some(view) => view.to_query(&query_text(query)?, &context)?,
+                    None => IssueQuery::parse(&query_text(query)?, &context)?,
+                };
+                query.status = status.clone();
+                query.project_id = project.clone();
+                if let Some(id) = id {
+                    query = query.filter(Filter::IdPrefix(id.to_lowercase()));
                 }
-                
-                if let Some(p) = project {
-                    query.project_id = Some(p.clone());
+                if let Some(text) = description {
+                    query = query.filter(Filter::Text(text.to_lowercase()));
                 }
+                if let Some(cursor) = after {
+                    query = query.after(cursor.parse::<Cursor>()?)?;
+                }
+                // One more than a page tells whether there is a next one
+                query.limit = limit.map(|limit| limit + 1);
                 
-                let issues = ctx.issue_repository().list(query).await
+                let mut filtered_issues = ctx.issue_repository().list(query.clone()).await
                     .map_err(crate::OdiError::Core)?;
-                
-                // Apply additional filtering that's not in the repository query
-                let filtered_issues: Vec<_> = issues.into_iter().filter(|issue| {
-                    // Filter by ID if specified
-                    if let Some(filter_id) = id {
-                        if !issue.id.to_string().contains(filter_id) {
-                            return false;
-                        }
-                    }
-                    
-                    // Filter by description if specified
-                    if let Some(filter_desc) = description {
-                        match &issue.description {
-                            Some(desc) => {
-                                if !desc.to_lowercase().contains(&filter_desc.to_lowercase()) {
-                                    return false;
-                                }
-                            },
-                            None => return false, // No description, but filter requires one
-                        }
+                let next = match limit {
+                    Some(limit) if filtered_issues.len() > *limit => {
+                        filtered_issues.truncate(*limit);
+                        filtered_issues.last().map(|issue| query.cursor(issue))
                     }
-                    
-                    true
-                }).collect();
+                    _ => None,
+                };
                 
-                if filtered_issues.is_empty() {
+                if output::structured() {
+                    output::print_records(&filtered_issues)?;
+                } else if filtered_issues.is_empty() {
                     println!("No issues found.");
+                } else if let Some(view) = view.as_ref().filter(|view| !view.columns.is_empty()) {
+                    super::view::print_table(&filtered_issues, &view.columns);
                 } else {
                     println!("Issues:");
                     for issue in filtered_issues {
@@ -189,6 +273,9 @@ impl IssueArgs {
                                  assignee_str);
                     }
                 }
+                if let Some(cursor) = next {
+                    say!("More issues: add --after {}", cursor);
+                }
                 Ok(())
             },
             IssueSubcommand::Show { id, project: _ } => {
@@ -207,6 +294,7 @@ impl IssueArgs {
                 };
                 
                 match ctx.issue_repository().get(&issue_id).await.map_err(crate::OdiError::Core)? {
+                    Some(issue) if output::structured() => output::print_record(&issue)?,
                     Some(issue) => {
                         println!("Issue: {}", issue.title);
                         println!("ID: {}", issue.id);
@@ -235,6 +323,24 @@ impl IssueArgs {
                         if let Some(closed_at) = &issue.closed_at {
                             println!("Closed: {}", closed_at.format("%Y-%m-%d %H:%M:%S"));
                         }
+                        
+                        if !issue.git_refs.is_empty() {
+                            // Commits the local repository doesn't have are shown without a summary
+                            let git = DefaultGitIntegration::new(ctx.workspace_path().to_path_buf());
+                            println!("Links:");
+                            for link in &issue.git_refs {
+                                let mut line = format!("  {} {}", link.role, link.target);
+                                if let GitTarget::Commit { sha } = &link.target {
+                                    if let Some(commit) = git.find_commit(sha).ok().flatten() {
+                                        line = format!("{} {}", line, commit.summary());
+                                    }
+                                }
+                                if let Some(repository) = &link.repository {
+                                    line = format!("{} ({})", line, repository);
+                                }
+                                println!("{}", line);
+                            }
+                        }
                     },
                     None => {
                         eprintln!("❌ Issue Not Found");
@@ -248,24 +354,423 @@ impl IssueArgs {
                 }
                 Ok(())
             },
+            IssueSubcommand::Edit { id } => edit_issue(ctx, id).await,
             IssueSubcommand::Assign { id, user, project: _ } => {
                 // TODO: Implement issue assignment functionality
-                println!("Assigning issue {} to user {}", id, user);
-                println!("Note: Issue assignment functionality not yet implemented");
+                say!("Assigning issue {} to user {}", id, user);
+                say!("Note: Issue assignment functionality not yet implemented");
                 Ok(())
             },
             IssueSubcommand::Status { id, status, project: _ } => {
                 // TODO: Implement issue status update functionality
-                println!("Setting issue {} status to {:?}", id, status);
-                println!("Note: Issue status update functionality not yet implemented");
+                say!("Setting issue {} status to {:?}", id, status);
+                say!("Note: Issue status update functionality not yet implemented");
                 Ok(())
             },
             IssueSubcommand::Label { id, label, project: _ } => {
                 // TODO: Implement issue labeling functionality
-                println!("Adding label '{}' to issue {}", label, id);
-                println!("Note: Issue labeling functionality not yet implemented");
+                say!("Adding label '{}' to issue {}", label, id);
+                say!("Note: Issue labeling functionality not yet implemented");
                 Ok(())
             },
+            IssueSubcommand::LinkCommit { id, commit, role } => {
+                let (repo, git) = git_repository(ctx)?;
+                let commit = git.find_commit(commit)?.ok_or_else(|| crate::OdiError::Validation {
+                    message: format!("No commit '{}' in the Git repository at {}", commit, repo.path.display())
+                })?;
+                let link = GitRef::commit(commit.sha.clone(), *role).with_repository(repo.identity());
+                let description = format!("commit {} {}", commit.short_sha(), commit.summary());
+                add_link(ctx, id, link, &description).await
+            },
+            IssueSubcommand::LinkBranch { id, branch, role } => {
+                let (repo, git) = git_repository(ctx)?;
+                let branch = match branch {
+                    Some(branch) => branch.clone(),
+                    None => repo.current_branch.clone().ok_or_else(|| crate::OdiError::Validation {
+                        message: "HEAD is detached; name the branch to link".to_string()
+                    })?,
+                };
+                if !git.branch_exists(&branch)? {
+                    return Err(crate::OdiError::Validation {
+                        message: format!("No branch '{}' in the Git repository at {}", branch, repo.path.display())
+                    });
+                }
+                let link = GitRef::branch(branch.clone(), *role).with_repository(repo.identity());
+                add_link(ctx, id, link, &format!("branch {}", branch)).await
+            },
+            IssueSubcommand::Start { id } => start_issue(ctx, id).await,
+            IssueSubcommand::Finish { id } => finish_issue(ctx, id.as_deref()).await,
+            IssueSubcommand::ForPath { spec } => issues_for_path(ctx, spec).await,
+            IssueSubcommand::Restore { id, to } => {
+                let issue_id = match uuid::Uuid::parse_str(id) {
+                    Ok(uuid) => uuid,
+                    Err(_) => {
+                        eprintln!("❌ Invalid Issue ID");
+                        eprintln!("Issue ID must be a valid UUID");
+                        eprintln!("💡 Tip: Use 'odi reflog' to find issues that were changed or deleted");
+                        return Err(crate::OdiError::Core(odi_core::CoreError::ValidationError {
+                            field: "issue_id".to_string(),
+                            message: format!("Invalid UUID format: {}", id)
+                        }));
+                    }
+                };
+                
+                let storage = super::reflog::reflog_storage(ctx)?;
+                let entry = odi_fs::reflog::restore(storage, &format!("issues/{}", issue_id), *to)?;
+                output::record(&entry, || {
+                    println!("✓ Restored issue {} to {}@{{{}}} ({} by {} at {})",
+                        issue_id,
+                        issue_id,
+                        to,
+                        entry.command,
+                        entry.user,
+                        entry.time.format("%Y-%m-%d %H:%M:%S"),
+                    );
+                })
+            },
         }
     }
 }
+
+/// A query given as command-line words
+///
+/// Query terms may start with `-`, so everything after the first one is taken
+/// as the query; options that ended up there are refused rather than matched
+/// as text.
+pub(super) fn query_text(words: &[String]) -> Result<String> {
+    if let Some(option) = words.iter().find(|word| word.starts_with("--") && word.len() > 2) {
+        return Err(crate::OdiError::Core(odi_core::CoreError::ValidationError {
+            field: "query".to_string(),
+            message: format!("'{}' was read as part of the query; give options before the query", option),
+        }));
+    }
+    Ok(words.iter().filter(|word| *word != "--").cloned().collect::<Vec<_>>().join(" "))
+}
+
+/// Validate a link and add it to an issue
+async fn add_link(ctx: &AppContext, id: &str, link: GitRef, description: &str) -> Result<()> {
+    link.validate()?;
+    let issue_id = uuid::Uuid::parse_str(id).map_err(|_| crate::OdiError::Core(odi_core::CoreError::ValidationError {
+        field: "issue_id".to_string(),
+        message: format!("Invalid UUID format: {}", id)
+    }))?;
+    
+    let issue_repo = ctx.issue_repository();
+    let mut issue = issue_repo.get(&issue_id).await?.ok_or_else(|| crate::OdiError::Core(odi_core::CoreError::ValidationError {
+        field: "issue_id".to_string(),
+        message: format!("Issue '{}' not found", id)
+    }))?;
+    
+    let role = link.role;
+    if !issue.link(link) {
+        return output::record(&issue, || println!("Issue already {} {}", role, description));
+    }
+    let issue = issue_repo.update(&issue_id, IssueUpdate::new().git_refs(issue.git_refs.clone())).await?.unwrap_or(issue);
+    output::record(&issue, || println!("✓ Linked {} to issue {} ({})", description, issue.title, role))
+}
+
+/// Find an issue by ID or ID prefix
+async fn find_issue(ctx: &AppContext, id: &str) -> Result<Issue> {
+    let issues = ctx.issue_repository().list(IssueQuery::default()).await?;
+    let issue = resolve(&issues, id).map_err(|message| crate::OdiError::Validation { message })?;
+    Ok(issue.clone())
+}
+
+/// Edit an issue as a document and save the changes as one update
+async fn edit_issue(ctx: &AppContext, id: &str) -> Result<()> {
+    let issue = find_issue(ctx, id).await?;
+    let Some((document, update)) = edit_issue_document(ctx, &issue).await? else {
+        say!("Aborted: the document was empty, issue {} is unchanged", issue.short_id());
+        return Ok(());
+    };
+    if document == IssueDocument::from_issue(&issue) {
+        return output::record(&issue, || println!("No changes to issue {} ({})", issue.short_id(), issue.title));
+    }
+    let issue = ctx.issue_repository().update(&issue.id, update).await?.ok_or_else(|| crate::OdiError::Validation {
+        message: format!("Issue {} was deleted while it was being edited", issue.short_id())
+    })?;
+    output::record(&issue, || println!("✓ Updated issue {} ({})", issue.short_id(), issue.title))
+}
+
+/// Open an issue as a document in the user's editor until it's saved without
+/// errors, and return it with the update it makes; `None` if it was emptied
+///
+/// Documents with errors are reopened with the errors written into them. If
+/// one is saved again without changes the edit is given up, keeping the file.
+async fn edit_issue_document(ctx: &AppContext, issue: &Issue) -> Result<Option<(IssueDocument, IssueUpdate)>> {
+    let path = ctx.storage()?.root_path().join("tmp").join(format!("issue-{}.md", issue.short_id()));
+    let mut text = IssueDocument::from_issue(issue).to_markdown();
+    loop {
+        std::fs::write(&path, &text)?;
+        run_editor(&path)?;
+        let edited = std::fs::read_to_string(&path)?;
+        if edited.trim().is_empty() {
+            std::fs::remove_file(&path)?;
+            return Ok(None);
+        }
+
+        let errors = match check_document(ctx, issue, &edited).await? {
+            Ok(checked) => {
+                std::fs::remove_file(&path)?;
+                return Ok(Some(checked));
+            }
+            Err(errors) => errors,
+        };
+        if edited == text {
+            return Err(crate::OdiError::Validation {
+                message: format!("{}\nThe document was saved without fixing these; it is kept in {}", errors, path.display())
+            });
+        }
+        eprintln!("❌ The document has errors; reopening it:\n{}", errors);
+        text = IssueDocument::annotate(&edited, &errors);
+    }
+}
+
+/// The document and its update, or the errors in it, one per line
+async fn check_document(ctx: &AppContext, issue: &Issue, text: &str) -> Result<std::result::Result<(IssueDocument, IssueUpdate), String>> {
+    let invalid = |error: odi_core::CoreError| match error {
+        odi_core::CoreError::ValidationError { message, .. } => message,
+        other => other.to_string(),
+    };
+    let document = match IssueDocument::parse(text) {
+        Ok(document) => document,
+        Err(error) => return Ok(Err(invalid(error))),
+    };
+    let update = match document.update(issue) {
+        Ok(update) => update,
+        Err(error) => return Ok(Err(invalid(error))),
+    };
+    if let Some(project) = document.project.as_ref().filter(|project| issue.project_id.as_ref() != Some(*project)) {
+        if ctx.project_repository().get_project(project).await?.is_none() {
+            return Ok(Err(format!("Project '{}' not found; 'odi project list' shows the projects", project)));
+        }
+    }
+    Ok(Ok((document, update)))
+}
+
+/// Run the user's editor on a file: $VISUAL, then $EDITOR, then vi, as Git does
+fn run_editor(path: &std::path::Path) -> Result<()> {
+    let editor = ["VISUAL", "EDITOR"].iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|editor| !editor.trim().is_empty())
+        .unwrap_or_else(|| "vi".to_string());
+    // Through the shell, so editors with arguments like 'code --wait' work
+    let status = std::process::Command::new("sh")
+        .arg("-c")
+        .arg(format!("{} \"$@\"", editor))
+        .arg(&editor)
+        .arg(path)
+        .status()
+        .map_err(|e| crate::OdiError::Validation { message: format!("Failed to start editor '{}': {}", editor, e) })?;
+    if !status.success() {
+        return Err(crate::OdiError::Validation {
+            message: format!("Editor '{}' exited with {}; nothing was changed", editor, status)
+        });
+    }
+    Ok(())
+}
+
+/// Create or switch to the issue's branch and mark the issue as started
+async fn start_issue(ctx: &AppContext, id: &str) -> Result<()> {
+    let (repo, git) = git_repository(ctx)?;
+    let mut issue = find_issue(ctx, id).await?;
+    if matches!(issue.status, IssueStatus::Resolved | IssueStatus::Closed) {
+        return Err(crate::OdiError::Validation {
+            message: format!("Issue {} is {:?}; reopen it before starting work", issue.short_id(), issue.status)
+        });
+    }
+
+    let branch = issue.branch_name(&ctx.config().git.branch_template);
+    let link = GitRef::branch(branch.clone(), LinkRole::Implements).with_repository(repo.identity());
+    link.validate()?;
+
+    // Starting again resumes work on the existing branch
+    let created = !git.branch_exists(&branch)?;
+    if created {
+        git.create_branch(&branch)?;
+    }
+    git.checkout_branch(&branch)?;
+
+    let user = ctx.config().user.name.clone();
+    issue.add_assignee(user.clone());
+    if issue.status == IssueStatus::Open {
+        issue.update_status(IssueStatus::InProgress).map_err(|message| crate::OdiError::Validation { message })?;
+    }
+    issue.link(link);
+    ctx.issue_repository().update(&issue.id, IssueUpdate::new()
+        .assignees(issue.assignees.clone())
+        .status(issue.status.clone())
+        .git_refs(issue.git_refs.clone())).await?;
+
+    let action = if created { "Created and switched to" } else { "Switched to" };
+    output::record(&issue, || {
+        println!("{} branch {}", action, branch);
+        println!("✓ Started issue {} ({}), assigned to {}", issue.short_id(), issue.title, user);
+    })
+}
+
+/// Check work on an issue is complete, resolve it and leave its branch
+async fn finish_issue(ctx: &AppContext, id: Option<&str>) -> Result<()> {
+    let (repo, git) = git_repository(ctx)?;
+    let current = repo.current_branch.clone().ok_or_else(|| crate::OdiError::Validation {
+        message: "HEAD is detached; check out the issue's branch first".to_string()
+    })?;
+    let id = match id {
+        Some(id) => id.to_string(),
+        None => odi_core::branch_reference(&current).ok_or_else(|| crate::OdiError::Validation {
+            message: format!("Branch '{}' doesn't name an issue; give the issue ID", current)
+        })?,
+    };
+    let mut issue = find_issue(ctx, &id).await?;
+    let fail = |message: String| Err(crate::OdiError::Validation { message });
+
+    if issue.status != IssueStatus::InProgress {
+        return fail(format!("Issue {} is {:?}, not InProgress; start it with 'odi issue start'", issue.short_id(), issue.status));
+    }
+    let user = &ctx.config().user.name;
+    if !issue.assignees.contains(user) {
+        return fail(format!("Issue {} is not assigned to {}", issue.short_id(), user));
+    }
+    let on_branch = issue.git_refs.iter().any(|link| matches!(&link.target, GitTarget::Branch { name } if *name == current));
+    if !on_branch {
+        return fail(format!("Branch '{}' is not linked to issue {}; check out the issue's branch", current, issue.short_id()));
+    }
+    if git.has_uncommitted_changes()? {
+        return fail(format!("Branch '{}' has uncommitted changes; commit or stash them first", current));
+    }
+
+    let default_branch = ctx.config().project.default_branch.clone().unwrap_or_else(|| "main".to_string());
+    let has_default = git.branch_exists(&default_branch)?;
+    if has_default && git.list_commits(&format!("{}..{}", default_branch, current))?.is_empty() {
+        return fail(format!("Branch '{}' has no commits beyond {}", current, default_branch));
+    }
+
+    issue.update_status(IssueStatus::Resolved).map_err(|message| crate::OdiError::Validation { message })?;
+    ctx.issue_repository().update(&issue.id, IssueUpdate::new().status(IssueStatus::Resolved)).await?;
+    say!("✓ Finished issue {} ({}), now Resolved", issue.short_id(), issue.title);
+
+    match git.checkout_branch(&default_branch) {
+        Ok(()) => say!("Switched to branch {}", default_branch),
+        Err(e) => say!("⚠️  Stayed on {}: {}", current, e),
+    }
+    if output::structured() {
+        output::print_record(&issue)?;
+    }
+    Ok(())
+}
+
+/// Split 'path:10' or 'path:10-20' into the path and its line range
+fn parse_path_spec(spec: &str) -> Result<(&str, Option<(usize, usize)>)> {
+    let Some((path, lines)) = spec.rsplit_once(':') else {
+        return Ok((spec, None));
+    };
+    // Anything not made of line numbers is part of the path
+    if lines.is_empty() || !lines.chars().all(|c| c.is_ascii_digit() || c == '-') {
+        return Ok((spec, None));
+    }
+    let invalid = || crate::OdiError::Validation { message: format!("Invalid line range '{}'; use LINE or START-END", lines) };
+    let (start, end) = match lines.split_once('-') {
+        Some((start, end)) => (start.parse().map_err(|_| invalid())?, end.parse().map_err(|_| invalid())?),
+        None => {
+            let line = lines.parse().map_err(|_| invalid())?;
+            (line, line)
+        }
+    };
+    if start == 0 || end < start {
+        return Err(invalid());
+    }
+    Ok((path, Some((start, end))))
+}
+
+/// An issue found by `odi issue for-path`, and how
+#[derive(serde::Serialize)]
+struct PathMatch<'a> {
+    #[serde(flatten)]
+    issue: &'a Issue,
+    /// `commits`, `lines` or `description`
+    via: &'static str,
+    /// Commit SHAs or `path:line` locations that matched
+    matches: Vec<String>,
+}
+
+/// List issues whose commits touched a path or lines, or whose description mentions it
+async fn issues_for_path(ctx: &AppContext, spec: &str) -> Result<()> {
+    let (repo, git) = git_repository(ctx)?;
+    let (path, lines) = parse_path_spec(spec)?;
+    let path = repository_path(&repo.path, path)?;
+    let issues = ctx.issue_repository().list(IssueQuery::default()).await?;
+
+    fn add<'a>(related: &mut Vec<(&'a Issue, Vec<String>)>, issue: &'a Issue, sha: String) {
+        match related.iter_mut().find(|(found, _)| found.id == issue.id) {
+            Some((_, shas)) => shas.push(sha),
+            None => related.push((issue, vec![sha])),
+        }
+    }
+    let mut related = Vec::new();
+    match lines {
+        Some(lines) => {
+            let identity = repo.identity();
+            for sha in git.blame(&path, Some(lines))? {
+                for issue in &issues {
+                    let linked = issue.git_refs.iter().any(|link| {
+                        (link.repository.is_none() || link.repository == identity)
+                            && matches!(&link.target, GitTarget::Commit { sha: linked } if sha.starts_with(linked.as_str()))
+                    });
+                    if linked {
+                        add(&mut related, issue, sha.clone());
+                    }
+                }
+            }
+        }
+        None => {
+            let index = odi_fs::PathIndex::build(&git, &ctx.workspace_path().join(".odi"), &issues)?;
+            for (id, sha) in index.lookup(&path) {
+                if let Some(issue) = issues.iter().find(|issue| issue.id == id) {
+                    add(&mut related, issue, sha);
+                }
+            }
+        }
+    }
+
+    // Lines linked directly, such as the TODO comments 'odi scan-todos' found
+    let directory = format!("{}/", path.trim_end_matches('/'));
+    let at_lines: Vec<(&Issue, Vec<String>)> = issues.iter()
+        .filter(|issue| !related.iter().any(|(found, _)| found.id == issue.id))
+        .filter_map(|issue| {
+            let locations: Vec<String> = issue.git_refs.iter().filter_map(|link| match &link.target {
+                GitTarget::File { path: linked, line } if (*linked == path || linked.starts_with(&directory))
+                    && lines.is_none_or(|(start, end)| (start..=end).contains(line)) => Some(format!("{}:{}", linked, line)),
+                _ => None,
+            }).collect();
+            (!locations.is_empty()).then_some((issue, locations))
+        })
+        .collect();
+
+    let mentioned: Vec<&Issue> = issues.iter()
+        .filter(|issue| issue.description.as_deref().is_some_and(|description| odi_fs::mentions_path(description, &path)))
+        .filter(|issue| !related.iter().chain(&at_lines).any(|(found, _)| found.id == issue.id))
+        .collect();
+
+    if output::structured() {
+        let matches: Vec<PathMatch> = related.iter().map(|(issue, shas)| PathMatch { issue, via: "commits", matches: shas.clone() })
+            .chain(at_lines.iter().map(|(issue, locations)| PathMatch { issue, via: "lines", matches: locations.clone() }))
+            .chain(mentioned.iter().map(|issue| PathMatch { issue, via: "description", matches: Vec::new() }))
+            .collect();
+        return output::print_records(&matches);
+    }
+    if related.is_empty() && at_lines.is_empty() && mentioned.is_empty() {
+        println!("No issues found for {}", spec);
+        return Ok(());
+    }
+    for (issue, shas) in &related {
+        let shas: Vec<&str> = shas.iter().map(|sha| &sha[..sha.len().min(7)]).collect();
+        println!("  {} [{:?}] {} — via commits {}", issue.short_id(), issue.status, issue.title, shas.join(", "));
+    }
+    for (issue, locations) in &at_lines {
+        println!("  {} [{:?}] {} — at {}", issue.short_id(), issue.status, issue.title, locations.join(", "));
+    }
+    for issue in &mentioned {
+        println!("  {} [{:?}] {} — mentioned in description", issue.short_id(), issue.status, issue.title);
+    }
+    Ok(())
+}
@@ -0,0 +1,70 @@
+//! Encryption key management command
+
+use clap::{Args, Subcommand};
+use odi_fs::KeySource;
+use std::path::PathBuf;
+use crate::{Result, AppContext, OdiError};
+use crate::output::say;
+
+/// Environment variable holding the passphrase to switch to on rotation
+const NEW_PASSPHRASE_ENV: &str = "ODI_NEW_PASSPHRASE";
+
+#[derive(Args)]
+pub struct KeyArgs {
+    #[command(subcommand)]
+    pub command: KeySubcommand,
+}
+
+#[derive(Subcommand)]
+pub enum KeySubcommand {
+    /// Re-encrypt every object under a fresh data key
+    ///
+    /// The current key comes from ODI_PASSPHRASE or ODI_KEY_FILE. To change the
+    /// passphrase at the same time set ODI_NEW_PASSPHRASE, or pass --new-key-file.
+    Rotate {
+        /// Unlock the workspace with this key file from now on
+        #[arg(long, value_name = "PATH")]
+        new_key_file: Option<PathBuf>,
+    },
+}
+
+impl KeyArgs {
+    pub async fn execute(&self, ctx: &AppContext) -> Result<()> {
+        match &self.command {
+            KeySubcommand::Rotate { new_key_file } => rotate(ctx, new_key_file.clone()).await,
+        }
+    }
+}
+
+async fn rotate(ctx: &AppContext, new_key_file: Option<PathBuf>) -> Result<()> {
+    let storage = ctx.storage()?;
+    if !storage.is_encrypted() {
+        return Err(OdiError::Command {
+            message: "Workspace is not encrypted; create one with 'odi init --encrypted'".to_string(),
+        });
+    }
+    let source = KeySource::from_env().ok_or(odi_fs::FsError::KeyRequired)?;
+
+    let new_source = match (new_key_file, std::env::var(NEW_PASSPHRASE_ENV).ok()) {
+        (Some(_), Some(_)) => {
+            return Err(OdiError::Validation {
+                message: format!("Use either --new-key-file or {}, not both", NEW_PASSPHRASE_ENV),
+            });
+        }
+        (Some(path), None) => Some(KeySource::KeyFile(path)),
+        (None, Some(passphrase)) => Some(KeySource::Passphrase(passphrase)),
+        (None, None) => None,
+    };
+
+    say!("Rotating encryption key...");
+    let rotated = storage.rotate_key(&source, new_source.as_ref())?;
+
+    say!("✓ Re-encrypted {} objects under a new key", rotated);
+    match new_source {
+        Some(KeySource::KeyFile(path)) => say!("✓ Workspace now unlocks with key file {}", path.display()),
+        Some(KeySource::Passphrase(_)) => say!("✓ Workspace now unlocks with the new passphrase"),
+        None => {}
+    }
+
+    Ok(())
+}
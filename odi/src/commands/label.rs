@@ -2,7 +2,8 @@
 
 use clap::{Args, Subcommand};
 use crate::{Result, AppContext};
-use odi_core::{Label, LabelQuery, ProjectRepository};
+use crate::output;
+use odi_core::{Label, LabelQuery};
 
 #[derive(Args)]
 pub struct LabelArgs {
@@ -112,18 +113,18 @@ async fn create_label(ctx: &AppContext, id: &str, name: &str, color: &str, proje
     };
     
     // Store label
-    project_repo.create_label(&project_id.to_string(), label).await.map_err(|e| crate::OdiError::Storage { 
+    let label = project_repo.create_label(&project_id.to_string(), label).await.map_err(|e| crate::OdiError::Storage { 
         message: format!("Failed to create label: {}", e) 
     })?;
     
-    println!("Created label '{}' in project '{}':", id, project_id);
-    println!("  Name: {}", name);
-    println!("  Color: {}", color);
-    if let Some(desc) = description {
-        println!("  Description: {}", desc);
-    }
-    
-    Ok(())
+    output::record(&label, || {
+        println!("Created label '{}' in project '{}':", id, project_id);
+        println!("  Name: {}", name);
+        println!("  Color: {}", color);
+        if let Some(desc) = description {
+            println!("  Description: {}", desc);
+        }
+    })
 }
 
 async fn list_labels(ctx: &AppContext, project_id: Option<&str>, color_filter: Option<&str>) -> Result<()> {
@@ -161,6 +162,9 @@ async fn list_labels(ctx: &AppContext, project_id: Option<&str>, color_filter: O
         })?
     };
     
+    if output::structured() {
+        return output::print_records(&labels);
+    }
     if labels.is_empty() {
         if let Some(proj_id) = project_id {
             println!("No labels found in project '{}'.", proj_id);
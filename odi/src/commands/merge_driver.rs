@@ -0,0 +1,127 @@
+//! Merge driver command implementation
+
+use clap::{Args, Subcommand};
+use std::path::{Path, PathBuf};
+use odi_fs::GitRepository;
+use odi_fs::merge_driver::MergeOutcome;
+use super::git::git_repository;
+use crate::{Result, AppContext, OdiError};
+use crate::output::{self, say};
+
+#[derive(Args)]
+pub struct MergeDriverArgs {
+    #[command(subcommand)]
+    pub command: MergeDriverSubcommand,
+}
+
+#[derive(Subcommand)]
+pub enum MergeDriverSubcommand {
+    /// Register the driver and mark the workspace's files in .gitattributes
+    Install,
+    /// Unregister the driver and remove its .gitattributes entries
+    Uninstall,
+    /// List conflicts recorded by the driver
+    Conflicts {
+        /// Remove the records once the conflicts are resolved
+        #[arg(long)]
+        clear: bool,
+    },
+    /// Merge one ref; called by Git as %O %A %B %P
+    #[command(hide = true)]
+    Run {
+        base: PathBuf,
+        ours: PathBuf,
+        theirs: PathBuf,
+        path: String,
+    },
+}
+
+impl MergeDriverArgs {
+    pub async fn execute(&self, ctx: &AppContext) -> Result<()> {
+        match &self.command {
+            MergeDriverSubcommand::Install => install(ctx),
+            MergeDriverSubcommand::Uninstall => uninstall(ctx),
+            MergeDriverSubcommand::Conflicts { clear } => conflicts(ctx, *clear),
+            MergeDriverSubcommand::Run { base, ours, theirs, path } => run(base, ours, theirs, path),
+        }
+    }
+}
+
+/// The workspace's `.odi` directory relative to the Git working tree
+fn workspace_prefix(ctx: &AppContext, repo: &GitRepository) -> Result<String> {
+    let odi_dir = ctx.workspace_path().join(".odi").canonicalize()?;
+    let prefix = odi_dir.strip_prefix(repo.path.canonicalize()?).map_err(|_| OdiError::Validation {
+        message: format!("Workspace {} is outside the Git working tree {}", odi_dir.display(), repo.path.display()),
+    })?;
+    Ok(prefix.to_string_lossy().replace('\\', "/"))
+}
+
+fn install(ctx: &AppContext) -> Result<()> {
+    let (repo, _) = git_repository(ctx)?;
+    let prefix = workspace_prefix(ctx, &repo)?;
+    if odi_fs::merge_driver::install(&repo.path, &prefix)? {
+        say!("✓ Updated the odi merge driver for {}", prefix);
+    } else {
+        say!("✓ Installed the odi merge driver for {}", prefix);
+    }
+    say!("Commit .gitattributes; everyone merging these files runs 'odi merge-driver install' once, since Git config isn't shared");
+    Ok(())
+}
+
+fn uninstall(ctx: &AppContext) -> Result<()> {
+    let (repo, _) = git_repository(ctx)?;
+    if odi_fs::merge_driver::uninstall(&repo.path, &workspace_prefix(ctx, &repo)?)? {
+        say!("✓ Removed the odi merge driver");
+    } else {
+        say!("  odi merge driver not installed");
+    }
+    Ok(())
+}
+
+fn conflicts(ctx: &AppContext, clear: bool) -> Result<()> {
+    let odi_dir = ctx.workspace_path().join(".odi");
+    if clear {
+        let count = odi_fs::merge_driver::clear_conflicts(&odi_dir)?;
+        say!("✓ Cleared {} conflict records", count);
+        return Ok(());
+    }
+
+    let records = odi_fs::merge_driver::read_conflicts(&odi_dir)?;
+    if output::structured() {
+        return output::print_records(&records);
+    }
+    if records.is_empty() {
+        println!("No merge conflicts recorded");
+        return Ok(());
+    }
+    for record in &records {
+        println!("{} ({})", record.name, record.detected_at.format("%Y-%m-%d %H:%M"));
+        if let Some(reason) = &record.reason {
+            println!("  not merged, kept ours: {}", reason);
+        }
+        for field in &record.fields {
+            let kept = if field.kept_theirs { "theirs" } else { "ours" };
+            println!("  {}: ours {}, theirs {}; kept {}", field.field, field.ours, field.theirs, kept);
+        }
+    }
+    println!("Fix the kept values with 'odi issue' commands if needed, then run 'odi merge-driver conflicts --clear'");
+    Ok(())
+}
+
+/// Merge a ref for Git; needs no workspace, since Git runs it from the top of the working tree
+pub fn run(base: &Path, ours: &Path, theirs: &Path, path: &str) -> Result<()> {
+    let workdir = std::env::current_dir()?;
+
+    // Git reads a non-zero exit as a conflict and keeps the file we leave behind
+    match odi_fs::merge_driver::merge_ref(&workdir, path, base, ours, theirs)? {
+        MergeOutcome::Clean => Ok(()),
+        MergeOutcome::Merged => {
+            eprintln!("odi: merged both sides' changes to {}; run 'git add .odi' to stage the merged object", path);
+            std::process::exit(1);
+        }
+        MergeOutcome::Conflicted(record) => {
+            eprintln!("odi: conflicting changes to {}; see 'odi merge-driver conflicts', then run 'git add .odi'", record.name);
+            std::process::exit(1);
+        }
+    }
+}
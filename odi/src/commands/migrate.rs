@@ -0,0 +1,59 @@
+//! Workspace format migration command
+
+use clap::Args;
+use std::path::PathBuf;
+use std::time::Duration;
+use odi_fs::{FileSystemStorage, KeySource, LockOptions, ReflogContext, CURRENT_VERSION};
+use crate::Result;
+use crate::output::say;
+
+/// Upgrade the workspace to the current format version
+#[derive(Args)]
+pub struct MigrateArgs {
+    /// List pending migration steps without applying them
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+impl MigrateArgs {
+    pub async fn execute(&self) -> Result<()> {
+        let odi_dir = PathBuf::from(".odi");
+        let found = odi_fs::read_version(&odi_dir)?;
+        let pending = odi_fs::pending_migrations(&odi_dir)?;
+
+        if pending.is_empty() {
+            say!("Workspace format version {} is already up to date", found);
+            return Ok(());
+        }
+
+        if self.dry_run {
+            say!("Workspace format version {} would be upgraded to {}:", found, CURRENT_VERSION);
+            for migration in pending {
+                say!("  {} -> {}: {}", migration.from, migration.from + 1, migration.description);
+            }
+            return Ok(());
+        }
+
+        // Wait for running odi processes as long as any other command would
+        let config = odi_fs::load_config()?;
+        let mut storage = FileSystemStorage::new(odi_dir)?
+            .with_lock_options(LockOptions::new().timeout(Duration::from_secs(config.storage.lock_timeout)))
+            .with_reflog_context(ReflogContext::new("odi migrate", config.user.name.clone()));
+
+        // Encrypted objects can only be rewritten with the key
+        if storage.is_encrypted() {
+            let source = KeySource::from_env().ok_or(odi_fs::FsError::KeyRequired)?;
+            let cipher = odi_fs::crypto::unlock(storage.root_path(), &source)?;
+            storage = storage.with_cipher(cipher);
+        }
+
+        say!("Upgrading workspace format from version {} to {}...", found, CURRENT_VERSION);
+        odi_fs::migrate(&storage, |migration, rewritten| {
+            say!("✅ {} -> {}: {} ({} records rewritten)",
+                migration.from, migration.from + 1, migration.description, rewritten);
+        })?;
+        say!("Workspace is now at format version {}", CURRENT_VERSION);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,137 @@
+//! Storage backend migration command
+
+use clap::Args;
+use odi_core::{IssueQuery, LabelQuery, ProjectQuery, TeamQuery, UserQuery, WorkspaceQuery};
+use odi_fs::StorageBackend;
+use crate::integration::Repositories;
+use crate::{Result, AppContext, OdiError};
+use crate::output::say;
+
+/// Convert workspace data to another storage backend
+#[derive(Args)]
+pub struct MigrateStorageArgs {
+    /// Backend to convert to
+    #[arg(long, value_name = "fs|sqlite")]
+    pub to: StorageBackend,
+}
+
+impl MigrateStorageArgs {
+    pub async fn execute(&self, ctx: &AppContext) -> Result<()> {
+        let from = ctx.config().storage.backend;
+        if from == self.to {
+            return Err(OdiError::Validation {
+                message: format!("Workspace already uses the '{}' storage backend", self.to)
+            });
+        }
+
+        // SQLite records aren't encrypted; don't quietly write customer data out in the clear
+        if ctx.storage()?.is_encrypted() {
+            return Err(OdiError::Validation {
+                message: "Encrypted workspaces can only use the 'fs' storage backend".to_string()
+            });
+        }
+
+        say!("Migrating storage from '{}' to '{}'...", from, self.to);
+
+        let source = Repositories::open(ctx.storage()?, from)?;
+        let target = Repositories::open(ctx.storage()?, self.to)?;
+
+        // The target may hold data from an earlier migration; the source is authoritative
+        clear(&target).await?;
+        let copied = copy(&source, &target).await?;
+
+        // Switch the workspace over only once everything has been copied.
+        // Reload rather than reuse the context's config: config-file remotes may have just been written.
+        let mut config = odi_fs::load_config()?;
+        config.storage.backend = self.to;
+        odi_fs::save_config(&config)?;
+
+        say!("Migrated {} issues, {} projects, {} workspaces, {} labels, {} views, {} users, {} teams, {} remotes",
+            copied.issues, copied.projects, copied.workspaces, copied.labels, copied.views, copied.users, copied.teams, copied.remotes);
+        say!("Workspace now uses the '{}' storage backend", self.to);
+        say!("Data in the '{}' backend was left in place and is no longer read", from);
+
+        Ok(())
+    }
+}
+
+/// Number of records copied per kind
+#[derive(Default)]
+struct Copied {
+    issues: usize,
+    projects: usize,
+    workspaces: usize,
+    labels: usize,
+    views: usize,
+    users: usize,
+    teams: usize,
+    remotes: usize,
+}
+
+async fn clear(repos: &Repositories) -> Result<()> {
+    for issue in repos.issues.list(IssueQuery::default()).await? {
+        repos.issues.delete(&issue.id).await?;
+    }
+    for project in repos.projects.list_projects(ProjectQuery::default()).await? {
+        for label in repos.projects.list_labels(&project.id, LabelQuery::default()).await? {
+            repos.projects.delete_label(&project.id, &label.id).await?;
+        }
+        for view in repos.projects.list_views(&project.id).await? {
+            repos.projects.delete_view(&project.id, &view.name).await?;
+        }
+        repos.projects.delete_project(&project.id).await?;
+    }
+    for workspace in repos.projects.list_workspaces(WorkspaceQuery::default()).await? {
+        repos.projects.delete_workspace(&workspace.id).await?;
+    }
+    for user in repos.users.list_users(UserQuery::default()).await? {
+        repos.users.delete_user(&user.id).await?;
+    }
+    for team in repos.users.list_teams(TeamQuery::default()).await? {
+        repos.users.delete_team(&team.id).await?;
+    }
+    for remote in repos.remotes.list().await? {
+        repos.remotes.delete(&remote.id).await?;
+    }
+    Ok(())
+}
+
+async fn copy(source: &Repositories, target: &Repositories) -> Result<Copied> {
+    let mut copied = Copied::default();
+
+    for project in source.projects.list_projects(ProjectQuery::default()).await? {
+        // Labels and shared views are stored per project
+        for label in source.projects.list_labels(&project.id, LabelQuery::default()).await? {
+            target.projects.create_label(&project.id, label).await?;
+            copied.labels += 1;
+        }
+        for view in source.projects.list_views(&project.id).await? {
+            target.projects.save_view(&project.id, view).await?;
+            copied.views += 1;
+        }
+        target.projects.create_project(project).await?;
+        copied.projects += 1;
+    }
+    for workspace in source.projects.list_workspaces(WorkspaceQuery::default()).await? {
+        target.projects.create_workspace(workspace).await?;
+        copied.workspaces += 1;
+    }
+    for user in source.users.list_users(UserQuery::default()).await? {
+        target.users.create_user(user).await?;
+        copied.users += 1;
+    }
+    for team in source.users.list_teams(TeamQuery::default()).await? {
+        target.users.create_team(team).await?;
+        copied.teams += 1;
+    }
+    for issue in source.issues.list(IssueQuery::default()).await? {
+        target.issues.create(issue).await?;
+        copied.issues += 1;
+    }
+    for remote in source.remotes.list().await? {
+        target.remotes.create(remote).await?;
+        copied.remotes += 1;
+    }
+
+    Ok(copied)
+}
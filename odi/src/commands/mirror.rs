@@ -0,0 +1,442 @@
+//! Mirror command implementation
+//!
+//! Issues are mirrored to Markdown files with front matter, one per issue, in
+//! a directory of the working tree, for people who read and edit issues
+//! without odi. The text of each file as last written or imported is kept in
+//! `.odi/mirror.json`, which tells a file edited since from an issue changed
+//! in odi, and is the base for merging edits made on both sides field by field.
+
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use clap::{Args, Subcommand};
+use serde::{Deserialize, Serialize};
+use crate::{Result, AppContext, OdiError};
+use crate::output::{self, say};
+use odi_core::{Issue, IssueDocument, IssueId, IssueQuery};
+
+/// File in `.odi` recording what was last synced
+const STATE_FILE: &str = "mirror.json";
+/// Directory of the files in the workspace unless another is given
+const DEFAULT_DIR: &str = "issues";
+
+#[derive(Args)]
+pub struct MirrorArgs {
+    #[command(subcommand)]
+    pub command: MirrorSubcommand,
+}
+
+#[derive(Subcommand)]
+pub enum MirrorSubcommand {
+    /// Write every issue to a Markdown file
+    Export {
+        /// Directory of the files, relative to the workspace (default: the last one used, or 'issues')
+        #[arg(long)]
+        dir: Option<PathBuf>,
+        /// Overwrite files edited since they were last synced
+        #[arg(long)]
+        force: bool,
+    },
+    /// Apply edits of the files to their issues, and create issues from new files
+    Import {
+        /// Directory of the files, relative to the workspace (default: the last one used, or 'issues')
+        #[arg(long)]
+        dir: Option<PathBuf>,
+        /// Let the files win over conflicting changes made in odi
+        #[arg(long)]
+        force: bool,
+    },
+    /// Import and export whenever files or issues change, until interrupted
+    Watch {
+        /// Directory of the files, relative to the workspace (default: the last one used, or 'issues')
+        #[arg(long)]
+        dir: Option<PathBuf>,
+        /// Seconds between checks for changes
+        #[arg(long, default_value_t = 2)]
+        interval: u64,
+    },
+}
+
+/// What was last synced between the files and the issues
+#[derive(Serialize, Deserialize, Default)]
+struct MirrorState {
+    /// Directory of the files, relative to the workspace
+    dir: Option<PathBuf>,
+    files: BTreeMap<IssueId, SyncedFile>,
+}
+
+/// File of a mirrored issue as last synced
+#[derive(Serialize, Deserialize, Clone)]
+struct SyncedFile {
+    /// File name in the mirror directory
+    name: String,
+    text: String,
+}
+
+/// Result of an export or import, the record printed in structured output
+#[derive(Serialize, Default)]
+struct MirrorSummary {
+    dir: PathBuf,
+    /// Files written, by name
+    written: Vec<String>,
+    /// Files removed with their issue, or renamed after their issue's title
+    removed: Vec<String>,
+    created: Vec<IssueId>,
+    updated: Vec<IssueId>,
+    /// Files left alone because they couldn't be synced
+    conflicts: Vec<MirrorConflict>,
+}
+
+#[derive(Serialize, Clone, PartialEq, Eq, Hash)]
+struct MirrorConflict {
+    file: String,
+    message: String,
+}
+
+impl MirrorSummary {
+    fn conflict(&mut self, file: &str, message: impl Into<String>) {
+        self.conflicts.push(MirrorConflict { file: file.to_string(), message: message.into() });
+    }
+
+    fn is_empty(&self) -> bool {
+        self.written.is_empty() && self.removed.is_empty() && self.created.is_empty()
+            && self.updated.is_empty() && self.conflicts.is_empty()
+    }
+}
+
+impl MirrorArgs {
+    pub async fn execute(&self, ctx: &AppContext) -> Result<()> {
+        match &self.command {
+            MirrorSubcommand::Export { dir, force } => {
+                let mut mirror = Mirror::open(ctx, dir.as_deref())?;
+                let summary = mirror.export(*force).await?;
+                mirror.save()?;
+                report(&summary, "Exported issues to")
+            },
+            MirrorSubcommand::Import { dir, force } => {
+                let mut mirror = Mirror::open(ctx, dir.as_deref())?;
+                if !mirror.dir.is_dir() {
+                    return Err(OdiError::Validation {
+                        message: format!("No mirror in {}; write one with 'odi mirror export'", mirror.dir.display())
+                    });
+                }
+                let summary = mirror.import(*force).await?;
+                mirror.save()?;
+                report(&summary, "Imported issues from")
+            },
+            MirrorSubcommand::Watch { dir, interval } => {
+                let mut mirror = Mirror::open(ctx, dir.as_deref())?;
+                say!("Watching {} for changes every {}s; press Ctrl-C to stop", mirror.dir.display(), interval);
+                // Conflicts stay until someone resolves them; each is reported once
+                let mut reported = HashSet::new();
+                loop {
+                    // Import first, so edited files aren't overwritten by the export
+                    for (mut summary, action) in [(mirror.import(false).await?, "Imported issues from"), (mirror.export(false).await?, "Exported issues to")] {
+                        summary.conflicts.retain(|conflict| reported.insert(conflict.clone()));
+                        if !summary.is_empty() {
+                            // Conflicts don't stop the watch
+                            let _ = report(&summary, action).map_err(|e| eprintln!("{}", e));
+                        }
+                    }
+                    mirror.save()?;
+                    tokio::select! {
+                        _ = tokio::time::sleep(Duration::from_secs((*interval).max(1))) => {},
+                        _ = tokio::signal::ctrl_c() => break,
+                    }
+                }
+                Ok(())
+            },
+        }
+    }
+}
+
+/// Files and issues of one mirror, with what was last synced
+struct Mirror<'a> {
+    ctx: &'a AppContext,
+    /// Directory of the files
+    dir: PathBuf,
+    state: MirrorState,
+    state_path: PathBuf,
+}
+
+impl<'a> Mirror<'a> {
+    fn open(ctx: &'a AppContext, dir: Option<&Path>) -> Result<Self> {
+        let state_path = ctx.storage()?.root_path().join(STATE_FILE);
+        let mut state: MirrorState = match read_file(&state_path)? {
+            Some(text) => serde_json::from_str(&text).map_err(|e| OdiError::Storage {
+                message: format!("Failed to read {}: {}", state_path.display(), e)
+            })?,
+            None => MirrorState::default(),
+        };
+        let relative = dir.map(Path::to_path_buf)
+            .or_else(|| state.dir.clone())
+            .unwrap_or_else(|| PathBuf::from(DEFAULT_DIR));
+        // Records of another directory say nothing about the files in this one
+        if state.dir.as_ref() != Some(&relative) {
+            state.files.clear();
+            state.dir = Some(relative.clone());
+        }
+        Ok(Self { ctx, dir: ctx.workspace_path().join(&relative), state, state_path })
+    }
+
+    fn save(&self) -> Result<()> {
+        let text = serde_json::to_string_pretty(&self.state).map_err(|e| OdiError::Storage {
+            message: format!("Failed to write {}: {}", self.state_path.display(), e)
+        })?;
+        std::fs::write(&self.state_path, text)?;
+        Ok(())
+    }
+
+    fn summary(&self) -> MirrorSummary {
+        MirrorSummary { dir: self.state.dir.clone().unwrap_or_default(), ..Default::default() }
+    }
+
+    /// Write each issue to its file, skipping files edited since they were
+    /// last synced unless `force` is given
+    async fn export(&mut self, force: bool) -> Result<MirrorSummary> {
+        let mut summary = self.summary();
+        std::fs::create_dir_all(&self.dir)?;
+        let issues = self.ctx.issue_repository().list(IssueQuery::default()).await?;
+
+        for issue in &issues {
+            let text = render(issue);
+            let name = file_name(issue);
+            match self.state.files.get(&issue.id) {
+                Some(synced) => {
+                    let on_disk = read_file(&self.dir.join(&synced.name))?;
+                    if on_disk.as_ref().is_some_and(|on_disk| *on_disk != synced.text) && !force {
+                        summary.conflict(&synced.name, "Edited since it was last synced; import it first, or export --force to overwrite it");
+                        continue;
+                    }
+                    // Follow the title
+                    if synced.name != name && on_disk.is_some() {
+                        std::fs::remove_file(self.dir.join(&synced.name))?;
+                        summary.removed.push(synced.name.clone());
+                    }
+                }
+                None => {
+                    if read_file(&self.dir.join(&name))?.is_some_and(|on_disk| on_disk != text) && !force {
+                        summary.conflict(&name, "Not written by this workspace's mirror; import it first, or export --force to overwrite it");
+                        continue;
+                    }
+                }
+            }
+
+            let path = self.dir.join(&name);
+            if read_file(&path)?.as_deref() != Some(text.as_str()) {
+                std::fs::write(&path, &text)?;
+                summary.written.push(name.clone());
+            }
+            self.state.files.insert(issue.id, SyncedFile { name, text });
+        }
+
+        // Files of deleted issues go too, unless someone edited them
+        let current: HashSet<IssueId> = issues.iter().map(|issue| issue.id).collect();
+        let deleted: Vec<(IssueId, SyncedFile)> = self.state.files.iter()
+            .filter(|(id, _)| !current.contains(id))
+            .map(|(id, synced)| (*id, synced.clone()))
+            .collect();
+        for (id, synced) in deleted {
+            let path = self.dir.join(&synced.name);
+            match read_file(&path)? {
+                Some(on_disk) if on_disk != synced.text && !force => {
+                    summary.conflict(&synced.name, "Its issue was deleted, but the file was edited since it was last synced; remove it by hand");
+                    continue;
+                }
+                Some(_) => {
+                    std::fs::remove_file(&path)?;
+                    summary.removed.push(synced.name);
+                }
+                None => {}
+            }
+            self.state.files.remove(&id);
+        }
+        Ok(summary)
+    }
+
+    /// Apply files edited since they were last synced to their issues, and
+    /// create issues from files without an ID
+    ///
+    /// Fields changed both in a file and in odi are conflicts, left alone
+    /// unless `force` is given, when the file's values win.
+    async fn import(&mut self, force: bool) -> Result<MirrorSummary> {
+        let mut summary = self.summary();
+        let mut names: Vec<String> = std::fs::read_dir(&self.dir)?
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().is_file())
+            .filter_map(|entry| entry.file_name().into_string().ok())
+            .filter(|name| name.ends_with(".md"))
+            .collect();
+        names.sort();
+
+        for name in names {
+            let Some(text) = read_file(&self.dir.join(&name))? else {
+                continue;
+            };
+            if self.state.files.values().any(|synced| synced.name == name && synced.text == text) {
+                continue;
+            }
+            let document = match IssueDocument::parse(&text) {
+                Ok(document) => document,
+                Err(e) => {
+                    summary.conflict(&name, message_of(e).replace('\n', "; "));
+                    continue;
+                }
+            };
+            let issue = match document.id {
+                None => self.create(&name, document, &mut summary).await?,
+                Some(id) => self.update(&name, id, document, force, &mut summary).await?,
+            };
+            let Some(issue) = issue else {
+                continue;
+            };
+
+            // Write back what was applied, with the ID of a new issue and
+            // anything merged in from odi
+            let synced = render(&issue);
+            if synced != text {
+                std::fs::write(self.dir.join(&name), &synced)?;
+                summary.written.push(name.clone());
+            }
+            self.state.files.insert(issue.id, SyncedFile { name, text: synced });
+        }
+        Ok(summary)
+    }
+
+    /// Create the issue of a new file
+    async fn create(&self, name: &str, document: IssueDocument, summary: &mut MirrorSummary) -> Result<Option<Issue>> {
+        let mut issue = Issue::new(document.title.clone(), self.ctx.config().user.name.clone());
+        if let Some(problem) = self.check(&document, None).await? {
+            summary.conflict(name, problem);
+            return Ok(None);
+        }
+        match document.update(&issue) {
+            Ok(update) => update.apply_to(&mut issue),
+            Err(e) => {
+                summary.conflict(name, message_of(e));
+                return Ok(None);
+            }
+        }
+        let issue = self.ctx.issue_repository().create(issue).await?;
+        summary.created.push(issue.id);
+        Ok(Some(issue))
+    }
+
+    /// Merge the edits of a file into its issue
+    async fn update(&self, name: &str, id: IssueId, theirs: IssueDocument, force: bool, summary: &mut MirrorSummary) -> Result<Option<Issue>> {
+        let Some(issue) = self.ctx.issue_repository().get(&id).await? else {
+            summary.conflict(name, format!("No issue {} in this workspace", id));
+            return Ok(None);
+        };
+        let ours = IssueDocument { id: Some(id), ..IssueDocument::from_issue(&issue) };
+        let base = self.state.files.get(&id).and_then(|synced| IssueDocument::parse(&synced.text).ok());
+
+        let merged = match base {
+            Some(base) => {
+                let (merged, conflicts) = IssueDocument::merge(&base, &ours, &theirs);
+                if !conflicts.is_empty() && !force {
+                    summary.conflict(name, format!(
+                        "Changed both in the file and in odi: {}; import --force keeps the file's values, export --force the issue's",
+                        conflicts.join(", ")
+                    ));
+                    return Ok(None);
+                }
+                merged
+            }
+            // With nothing to merge from, only identical documents are in sync
+            None if theirs == ours || force => theirs,
+            None => {
+                summary.conflict(name, "No record of syncing it and it differs from its issue; import --force keeps the file's values, export --force the issue's");
+                return Ok(None);
+            }
+        };
+        if merged == ours {
+            return Ok(Some(issue));
+        }
+
+        if let Some(problem) = self.check(&merged, issue.project_id.as_ref()).await? {
+            summary.conflict(name, problem);
+            return Ok(None);
+        }
+        let update = match merged.update(&issue) {
+            Ok(update) => update,
+            Err(e) => {
+                summary.conflict(name, message_of(e));
+                return Ok(None);
+            }
+        };
+        let issue = self.ctx.issue_repository().update(&id, update).await?.unwrap_or(issue);
+        summary.updated.push(id);
+        Ok(Some(issue))
+    }
+
+    /// Why a document can't be applied, if the project it names is new and
+    /// doesn't exist
+    async fn check(&self, document: &IssueDocument, project: Option<&String>) -> Result<Option<String>> {
+        match document.project.as_ref().filter(|named| Some(*named) != project) {
+            Some(named) if self.ctx.project_repository().get_project(named).await?.is_none() => {
+                Ok(Some(format!("Project '{}' not found; 'odi project list' shows the projects", named)))
+            }
+            _ => Ok(None),
+        }
+    }
+}
+
+/// The file of an issue
+fn render(issue: &Issue) -> String {
+    IssueDocument { id: Some(issue.id), ..IssueDocument::from_issue(issue) }.to_markdown()
+}
+
+/// File name of an issue, its key and title as in branch names: `1a2b3c4d-login-fails.md`
+fn file_name(issue: &Issue) -> String {
+    match issue.slug() {
+        slug if slug.is_empty() => format!("{}.md", issue.short_id()),
+        slug => format!("{}-{}.md", issue.short_id(), slug),
+    }
+}
+
+fn read_file(path: &Path) -> Result<Option<String>> {
+    match std::fs::read_to_string(path) {
+        Ok(text) => Ok(Some(text)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn message_of(error: odi_core::CoreError) -> String {
+    match error {
+        odi_core::CoreError::ValidationError { message, .. } => message,
+        other => other.to_string(),
+    }
+}
+
+/// Print a summary, failing if some files couldn't be synced
+fn report(summary: &MirrorSummary, action: &str) -> Result<()> {
+    output::record(summary, || {
+        let dir = &summary.dir;
+        for id in &summary.created {
+            println!("  created issue {}", id);
+        }
+        for id in &summary.updated {
+            println!("  updated issue {}", id);
+        }
+        for name in &summary.written {
+            println!("  wrote {}", dir.join(name).display());
+        }
+        for name in &summary.removed {
+            println!("  removed {}", dir.join(name).display());
+        }
+        for conflict in &summary.conflicts {
+            println!("⚠️  {}: {}", dir.join(&conflict.file).display(), conflict.message);
+        }
+        println!("✓ {} {}: {} created, {} updated, {} files written, {} removed",
+            action, dir.display(), summary.created.len(), summary.updated.len(), summary.written.len(), summary.removed.len());
+    })?;
+    if !summary.conflicts.is_empty() {
+        return Err(OdiError::Validation {
+            message: format!("{} file(s) in {} could not be synced; see above", summary.conflicts.len(), summary.dir.display())
+        });
+    }
+    Ok(())
+}
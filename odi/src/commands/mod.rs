@@ -1,24 +1,54 @@
 //! CLI command implementations
 
+pub mod bundle;
+pub mod changelog;
 pub mod config;
 pub mod fsck;
+pub mod gc;
+pub mod git;
+pub mod hooks;
 pub mod init;
 pub mod issue;
+pub mod key;
 pub mod label;
+pub mod merge_driver;
+pub mod migrate;
+pub mod migrate_storage;
+pub mod mirror;
 pub mod project;
 pub mod pull;
 pub mod push;
+pub mod reflog;
 pub mod remote;
+pub mod scan_todos;
+pub mod search;
 pub mod team;
+pub mod undo;
+pub mod view;
 
 // Re-exports
+pub use bundle::{BundleArgs, BundleSubcommand};
+pub use changelog::ChangelogArgs;
 pub use config::ConfigArgs;
 pub use fsck::FsckArgs;
+pub use gc::GcArgs;
+pub use git::GitArgs;
+pub use hooks::HooksArgs;
 pub use init::InitArgs;
 pub use issue::IssueArgs;
+pub use key::KeyArgs;
 pub use label::LabelArgs;
+pub use merge_driver::{MergeDriverArgs, MergeDriverSubcommand};
+pub use migrate::MigrateArgs;
+pub use migrate_storage::MigrateStorageArgs;
+pub use mirror::MirrorArgs;
 pub use project::ProjectArgs;
 pub use pull::PullArgs;
 pub use push::PushArgs;
+pub use reflog::ReflogArgs;
 pub use remote::RemoteArgs;
-pub use team::TeamArgs;
\ No newline at end of file
+pub use scan_todos::ScanTodosArgs;
+pub use search::SearchArgs;
+pub use team::TeamArgs;
+pub use undo::UndoArgs;
+pub use view::ViewArgs;
\ No newline at end of file
@@ -2,7 +2,8 @@
 
 use clap::{Args, Subcommand};
 use crate::{Result, AppContext};
-use odi_core::{Project, ProjectRepository};
+use crate::output;
+use odi_core::Project;
 
 #[derive(Args)]
 pub struct ProjectArgs {
@@ -25,9 +26,6 @@ pub enum ProjectSubcommand {
     },
     /// List all projects
     List {
-        /// Output format
-        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
-        format: OutputFormat,
         /// Filter by project ID
         #[arg(long)]
         id: Option<String>,
@@ -47,13 +45,6 @@ pub enum ProjectSubcommand {
     },
 }
 
-#[derive(clap::ValueEnum, Clone, Default)]
-pub enum OutputFormat {
-    #[default]
-    Text,
-    Json,
-}
-
 impl ProjectArgs {
     pub async fn execute(&self, ctx: &AppContext) -> Result<()> {
         match &self.command {
@@ -120,10 +111,11 @@ impl ProjectArgs {
                 let created_project = ctx.project_repository().create_project(project).await
                     .map_err(crate::OdiError::Core)?;
                 
-                println!("Created project: {} ({})", created_project.name, created_project.id);
-                Ok(())
+                output::record(&created_project, || {
+                    println!("Created project: {} ({})", created_project.name, created_project.id);
+                })
             },
-            ProjectSubcommand::List { format, id, description } => {
+            ProjectSubcommand::List { id, description } => {
                 let projects = ctx.project_repository().list_projects(odi_core::ProjectQuery::default()).await
                     .map_err(crate::OdiError::Core)?;
                 
@@ -151,24 +143,16 @@ impl ProjectArgs {
                     true
                 }).collect();
                 
-                match format {
-                    OutputFormat::Json => {
-                        let json_output = serde_json::to_string_pretty(&filtered_projects)
-                            .map_err(|e| crate::OdiError::Io { message: e.to_string() })?;
-                        println!("{}", json_output);
-                    },
-                    OutputFormat::Text => {
-                        if filtered_projects.is_empty() {
-                            println!("No projects found.");
-                        } else {
-                            println!("Projects:");
-                            for project in filtered_projects {
-                                println!("  {} (ID: {}) - {}", project.name, project.id, project.description.as_deref().unwrap_or("No description"));
-                            }
+                output::records(&filtered_projects, || {
+                    if filtered_projects.is_empty() {
+                        println!("No projects found.");
+                    } else {
+                        println!("Projects:");
+                        for project in &filtered_projects {
+                            println!("  {} (ID: {}) - {}", project.name, project.id, project.description.as_deref().unwrap_or("No description"));
                         }
                     }
-                }
-                Ok(())
+                })
             },
             ProjectSubcommand::Show { name } => {
                 let projects = ctx.project_repository().list_projects(odi_core::ProjectQuery::default()).await
@@ -177,7 +161,7 @@ impl ProjectArgs {
                 let project = projects.iter().find(|p| p.name == *name || p.id == *name);
                 
                 match project {
-                    Some(project) => {
+                    Some(project) => output::record(project, || {
                         println!("Project: {}", project.name);
                         println!("ID: {}", project.id);
                         if let Some(desc) = &project.description {
@@ -185,7 +169,7 @@ impl ProjectArgs {
                         }
                         println!("Created: {}", project.created_at.format("%Y-%m-%d %H:%M:%S"));
                         println!("Updated: {}", project.updated_at.format("%Y-%m-%d %H:%M:%S"));
-                    },
+                    })?,
                     None => {
                         eprintln!("❌ Project Not Found");
                         eprintln!("Project '{}' does not exist", name);
@@ -209,19 +193,18 @@ impl ProjectArgs {
                     Some(project) => {
                         // Note: We should implement delete_project in the repository trait
                         // For now, return a placeholder message
-                        println!("Deleted project: {}", name);
                         // TODO: Actually implement deletion once the trait method exists
-                        Ok(())
+                        output::record(project, || println!("Deleted project: {}", name))
                     },
                     None => {
                         eprintln!("❌ Project Not Found");
                         eprintln!("Project '{}' does not exist", name);
                         eprintln!();
                         eprintln!("💡 Tip: Use 'odi project list' to see available projects");
-                        return Err(crate::OdiError::Core(odi_core::CoreError::ValidationError { 
+                        Err(crate::OdiError::Core(odi_core::CoreError::ValidationError { 
                             field: "project".to_string(), 
                             message: format!("Project '{}' not found", name) 
-                        }));
+                        }))
                     }
                 }
             },
@@ -1,9 +1,42 @@
 //! Pull command implementation
 
 use clap::Args;
+use serde::Serialize;
 use crate::{Result, AppContext};
-use odi_core::{RemoteRepository, IssueRepository};
-use odi_net::{RemoteSync, DefaultRemoteSync};
+use crate::output::{self, say};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::Path;
+use odi_core::issue::IssueUpdate;
+use odi_core::{merge_links, Issue, IssueId, Label, Project, Team, User, View};
+use odi_fs::{Bundle, ObjectType};
+use odi_fs::git_store::GitStore;
+use super::bundle::collect_objects;
+use odi_net::{RemoteSync, DefaultRemoteSync, SyncClient};
+
+/// File in `.odi` recording the packs already applied from each remote
+const FETCHED_PACKS_FILE: &str = "fetched-packs.json";
+
+/// What a pull or bundle apply changed, the record printed in structured output
+#[derive(Serialize, Default)]
+pub(crate) struct PullSummary {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    remote: Option<String>,
+    /// Issues created locally
+    created: Vec<IssueId>,
+    /// Local issues updated from incoming copies
+    updated: Vec<IssueId>,
+    /// Users, teams, projects, labels and views added from a bundle
+    objects: usize,
+}
+
+impl PullSummary {
+    pub(crate) fn print(&self) -> Result<()> {
+        if output::structured() {
+            output::print_record(self)?;
+        }
+        Ok(())
+    }
+}
 
 #[derive(Args)]
 pub struct PullArgs {
@@ -26,12 +59,14 @@ pub struct PullArgs {
 
 impl PullArgs {
     pub async fn execute(&self, ctx: &AppContext) -> Result<()> {
-        pull_remote(ctx, self.remote.as_deref(), self.force, self.dry_run, self.project.as_deref()).await
+        let remote = self.remote.as_deref().unwrap_or("origin");
+        let mut summary = PullSummary { remote: Some(remote.to_string()), ..Default::default() };
+        pull_remote(ctx, remote, self.force, self.dry_run, self.project.as_deref(), &mut summary).await?;
+        summary.print()
     }
 }
 
-async fn pull_remote(ctx: &AppContext, remote_name: Option<&str>, _force: bool, dry_run: bool, project_id: Option<&str>) -> Result<()> {
-    let remote_name = remote_name.unwrap_or("origin");
+async fn pull_remote(ctx: &AppContext, remote_name: &str, _force: bool, dry_run: bool, project_id: Option<&str>, summary: &mut PullSummary) -> Result<()> {
     
     // Find the remote
     let remote_repo = ctx.remote_repository();
@@ -49,18 +84,28 @@ async fn pull_remote(ctx: &AppContext, remote_name: Option<&str>, _force: bool,
     };
     
     if dry_run {
-        println!("Dry run: Pulling from {} ({})", remote.name, remote.url);
+        say!("Dry run: Pulling from {} ({})", remote.name, remote.url);
         if let Some(project) = project_id {
-            println!("Filtering by project: {}", project);
+            say!("Filtering by project: {}", project);
         }
-        println!("Would check for remote changes...");
-        println!("No remote changes detected");
+        say!("Would check for remote changes...");
+        say!("No remote changes detected");
         return Ok(());
     }
     
-    println!("Pulling from {} ({})", remote.name, remote.url);
+    say!("Pulling from {} ({})", remote.name, remote.url);
     if let Some(project) = project_id {
-        println!("Filtering by project: {}", project);
+        say!("Filtering by project: {}", project);
+    }
+    
+    // Bundle remotes are read straight from the file
+    if let Some(path) = odi_fs::bundle::path_from_url(&remote.url) {
+        let bundle = Bundle::load(&path)?;
+        return apply_bundle(ctx, &bundle, project_id, summary).await;
+    }
+    
+    if let Some(repository) = odi_fs::git_store::repository_from_url(&remote.url) {
+        return pull_git(ctx, &remote.name, repository, project_id, summary).await;
     }
     
     // Initialize the remote sync client
@@ -69,27 +114,30 @@ async fn pull_remote(ctx: &AppContext, remote_name: Option<&str>, _force: bool,
     // Attempt to connect to the remote
     match sync.connect(&remote).await {
         Ok(client) => {
-            println!("✓ Connected successfully to remote: {}", remote.url);
+            say!("✓ Connected successfully to remote: {}", remote.url);
+            
+            // Whole packs first; per-issue files no newer than what they held are skipped below
+            pull_packs(ctx, &sync, &client, project_id, summary).await?;
             
             // Get sync state from remote
             match sync.get_sync_state(&client).await {
                 Ok(state) => {
-                    println!("✓ Remote sync state retrieved:");
-                    println!("  Total issues: {}", state.total_issues);
-                    println!("  Pending changes: {}", state.pending_changes);
+                    say!("✓ Remote sync state retrieved:");
+                    say!("  Total issues: {}", state.total_issues);
+                    say!("  Pending changes: {}", state.pending_changes);
                     
                     if dry_run {
-                        println!("💡 Dry run mode - no changes made");
+                        say!("💡 Dry run mode - no changes made");
                         return Ok(());
                     }
 
                     // List issues from remote for syncing
                     match sync.list_issues(&client).await {
                         Ok(remote_issues) => {
-                            println!("✓ Found {} issues on remote", remote_issues.len());
+                            say!("✓ Found {} issues on remote", remote_issues.len());
                             
                             if remote_issues.is_empty() {
-                                println!("ℹ️  No issues to pull from remote");
+                                say!("ℹ️  No issues to pull from remote");
                                 return Ok(());
                             }
                             
@@ -109,93 +157,52 @@ async fn pull_remote(ctx: &AppContext, remote_name: Option<&str>, _force: bool,
                                     message: format!("Failed to get local issues: {}", e) 
                                 })?;
                             
-                            let mut downloaded_count = 0;
-                            let mut updated_count = 0;
-                            
+                            // Diverged issues are downloaded whichever side is newer, so
+                            // links made on the remote are merged even when local edits win
+                            let mut downloaded = Vec::new();
                             for remote_issue in remote_issues {
-                                // Check if issue exists locally
-                                if let Some(local_issue) = local_issues.iter().find(|i| i.id == remote_issue.id) {
-                                    // Compare timestamps to see if remote is newer
-                                    if remote_issue.last_modified > local_issue.updated_at {
-                                        // Download and update the issue
-                                        match sync.download_issue(&client, &remote_issue.id).await {
-                                            Ok(issue) => {
-                                                // Check project filter if specified
-                                                if let Some(project) = project_id {
-                                                    if issue.project_id.as_ref() != Some(&project.to_string()) {
-                                                        continue; // Skip this issue
-                                                    }
-                                                }
-                                                
-                                                let update = odi_core::issue::IssueUpdate {
-                                                    title: Some(issue.title.clone()),
-                                                    description: Some(issue.description.clone()),
-                                                    status: Some(issue.status.clone()),
-                                                    priority: Some(issue.priority.clone()),
-                                                    assignees: Some(issue.assignees.clone()),
-                                                    co_authors: Some(issue.co_authors.clone()),
-                                                    labels: Some(issue.labels.clone()),
-                                                    project_id: Some(issue.project_id.clone()),
-                                                };
-                                                
-                                                issue_repo.update(&issue.id, update).await
-                                                    .map_err(|e| crate::OdiError::Storage { 
-                                                        message: format!("Failed to update issue: {}", e) 
-                                                    })?;
-                                                
-                                                updated_count += 1;
-                                                println!("  ↻ Updated issue: {}", issue.title);
-                                            },
-                                            Err(e) => {
-                                                println!("  ⚠️  Failed to download issue {}: {}", remote_issue.id, e);
-                                            }
-                                        }
-                                    }
-                                } else {
-                                    // Issue doesn't exist locally, download it
-                                    match sync.download_issue(&client, &remote_issue.id).await {
-                                        Ok(issue) => {
-                                            // Check project filter if specified
-                                            if let Some(project) = project_id {
-                                                if issue.project_id.as_ref() != Some(&project.to_string()) {
-                                                    continue; // Skip this issue
-                                                }
+                                let local_issue = local_issues.iter().find(|i| i.id == remote_issue.id);
+                                if local_issue.is_some_and(|local| remote_issue.last_modified == local.updated_at) {
+                                    continue;
+                                }
+                                
+                                match sync.download_issue(&client, &remote_issue.id).await {
+                                    Ok(issue) => {
+                                        // Check project filter if specified
+                                        if let Some(project) = project_id {
+                                            if issue.project_id.as_ref() != Some(&project.to_string()) {
+                                                continue; // Skip this issue
                                             }
-                                            
-                                            issue_repo.create(issue.clone()).await
-                                                .map_err(|e| crate::OdiError::Storage { 
-                                                    message: format!("Failed to create issue: {}", e) 
-                                                })?;
-                                            
-                                            downloaded_count += 1;
-                                            println!("  ↓ Downloaded new issue: {}", issue.title);
-                                        },
-                                        Err(e) => {
-                                            println!("  ⚠️  Failed to download issue {}: {}", remote_issue.id, e);
                                         }
+                                        downloaded.push(issue);
+                                    },
+                                    Err(e) => {
+                                        say!("  ⚠️  Failed to download issue {}: {}", remote_issue.id, e);
                                     }
                                 }
                             }
                             
+                            let (downloaded_count, updated_count) = merge_issues(ctx, downloaded, summary).await?;
+                            
                             if downloaded_count > 0 || updated_count > 0 {
-                                println!("✓ Pull completed: {} new, {} updated", downloaded_count, updated_count);
+                                say!("✓ Pull completed: {} new, {} updated", downloaded_count, updated_count);
                             } else {
-                                println!("✓ Pull completed: No changes");
+                                say!("✓ Pull completed: No changes");
                             }
                             return Ok(());
                         },
                         Err(e) => {
-                            println!("⚠️  Could not list remote issues: {}", e);
+                            say!("⚠️  Could not list remote issues: {}", e);
                         }
                     }
                 },
                 Err(e) => {
-                    println!("⚠️  Could not get sync state: {}", e);
+                    say!("⚠️  Could not get sync state: {}", e);
                 }
             }
         },
         Err(e) => {
-            println!("✗ Connection failed: {}", e);
+            say!("✗ Connection failed: {}", e);
             return Err(crate::OdiError::Command { 
                 message: format!("Pull operation failed: {}", e)
             });
@@ -203,4 +210,223 @@ async fn pull_remote(ctx: &AppContext, remote_name: Option<&str>, _force: bool,
     }
     
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Update that makes a local issue match a remote one, keeping the links of both
+fn update_from(issue: &Issue, local: &Issue) -> IssueUpdate {
+    IssueUpdate {
+        title: Some(issue.title.clone()),
+        description: Some(issue.description.clone()),
+        status: Some(issue.status.clone()),
+        priority: Some(issue.priority.clone()),
+        assignees: Some(issue.assignees.clone()),
+        co_authors: Some(issue.co_authors.clone()),
+        labels: Some(issue.labels.clone()),
+        project_id: Some(issue.project_id.clone()),
+        git_refs: Some(merge_links(&local.git_refs, &issue.git_refs)),
+    }
+}
+
+/// Create issues missing locally and update those the incoming copy is newer for
+///
+/// Git links are merged rather than replaced: links only the older copy has
+/// are kept. Returns the number of issues created and updated, and adds their
+/// IDs to `summary`.
+async fn merge_issues(ctx: &AppContext, issues: impl IntoIterator<Item = Issue>, summary: &mut PullSummary) -> Result<(usize, usize)> {
+    let issue_repo = ctx.issue_repository();
+    let mut downloaded_count = 0;
+    let mut updated_count = 0;
+    
+    for issue in issues {
+        let local = issue_repo.get(&issue.id).await
+            .map_err(|e| crate::OdiError::Storage { 
+                message: format!("Failed to get local issue: {}", e) 
+            })?;
+        
+        match local {
+            Some(local) if issue.updated_at > local.updated_at => {
+                issue_repo.update(&issue.id, update_from(&issue, &local)).await
+                    .map_err(|e| crate::OdiError::Storage { 
+                        message: format!("Failed to update issue: {}", e) 
+                    })?;
+                updated_count += 1;
+                summary.updated.push(issue.id);
+                say!("  ↻ Updated issue: {}", issue.title);
+            },
+            Some(local) => {
+                let links = merge_links(&local.git_refs, &issue.git_refs);
+                if links != local.git_refs {
+                    issue_repo.update(&issue.id, IssueUpdate::new().git_refs(links)).await
+                        .map_err(|e| crate::OdiError::Storage { 
+                            message: format!("Failed to update issue: {}", e) 
+                        })?;
+                    updated_count += 1;
+                    summary.updated.push(issue.id);
+                    say!("  ↻ Merged links into issue: {}", local.title);
+                }
+            },
+            None => {
+                say!("  ↓ Downloaded new issue: {}", issue.title);
+                summary.created.push(issue.id);
+                issue_repo.create(issue).await
+                    .map_err(|e| crate::OdiError::Storage { 
+                        message: format!("Failed to create issue: {}", e) 
+                    })?;
+                downloaded_count += 1;
+            },
+        }
+    }
+    
+    Ok((downloaded_count, updated_count))
+}
+
+/// Merge everything in a bundle into the workspace
+///
+/// Issues go through the same newest-wins merge as `pull`, and so do views
+/// shared in projects; users, teams, projects and labels are only created when
+/// missing locally.
+pub(crate) async fn apply_bundle(ctx: &AppContext, bundle: &Bundle, project_id: Option<&str>, summary: &mut PullSummary) -> Result<()> {
+    let in_project = |id: Option<&str>| project_id.is_none_or(|project| id == Some(project));
+    let user_repo = ctx.user_repository();
+    let project_repo = ctx.project_repository();
+    let mut created = 0;
+    
+    for (_, user) in bundle.read_all::<User>("users/")? {
+        if user_repo.get_user(&user.id).await?.is_none() {
+            user_repo.create_user(user).await?;
+            created += 1;
+        }
+    }
+    for (_, team) in bundle.read_all::<Team>("teams/")? {
+        if user_repo.get_team(&team.id).await?.is_none() {
+            user_repo.create_team(team).await?;
+            created += 1;
+        }
+    }
+    for (_, project) in bundle.read_all::<Project>("projects/")? {
+        if in_project(Some(&project.id)) && project_repo.get_project(&project.id).await?.is_none() {
+            project_repo.create_project(project).await?;
+            created += 1;
+        }
+    }
+    for (name, label) in bundle.read_all::<Label>("labels/")? {
+        // Label refs are labels/<project>/<label>
+        let Some(label_project) = name.split('/').nth(1).map(str::to_string) else {
+            continue;
+        };
+        if in_project(Some(&label_project)) && project_repo.get_label(&label_project, &label.id).await?.is_none() {
+            project_repo.create_label(&label_project, label).await?;
+            created += 1;
+        }
+    }
+    for (name, view) in bundle.read_all::<View>("views/")? {
+        // View refs are views/<project>/<name>
+        let Some(view_project) = name.split('/').nth(1).map(str::to_string) else {
+            continue;
+        };
+        if !in_project(Some(&view_project)) {
+            continue;
+        }
+        let local = project_repo.get_view(&view_project, &view.name).await?;
+        if local.is_none_or(|local| view.updated_at > local.updated_at) {
+            project_repo.save_view(&view_project, view).await?;
+            created += 1;
+        }
+    }
+    
+    let issues = bundle.read_all::<Issue>("issues/")?
+        .into_iter()
+        .map(|(_, issue)| issue)
+        .filter(|issue| in_project(issue.project_id.as_deref()));
+    let (downloaded_count, updated_count) = merge_issues(ctx, issues, summary).await?;
+    summary.objects += created;
+    
+    say!("✓ Applied bundle: {} new issues, {} updated, {} other objects added", downloaded_count, updated_count, created);
+    Ok(())
+}
+
+/// Fetch refs/odi from a Git remote, merge it into the workspace and commit the merge
+///
+/// Committing the merge lets the next push fast-forward the remote.
+async fn pull_git(ctx: &AppContext, remote_name: &str, repository: &str, project_id: Option<&str>, summary: &mut PullSummary) -> Result<()> {
+    super::push::refuse_encrypted(ctx)?;
+    let store = GitStore::open(ctx.workspace_path())?;
+    let Some(fetched) = store.fetch(repository, remote_name)? else {
+        say!("ℹ️  {} has no ODI data yet; push to add it", remote_name);
+        return Ok(());
+    };
+    if store.contains(&fetched)? {
+        say!("✓ Already up to date");
+        return Ok(());
+    }
+    
+    apply_bundle(ctx, &store.snapshot(&fetched)?, project_id, summary).await?;
+    let writer = collect_objects(ctx, None, None).await?;
+    store.commit(writer, Some(&fetched), &format!("Merge ODI data from {}", remote_name))?;
+    Ok(())
+}
+
+/// Packs already applied, by remote URL
+///
+/// Pack names are hashes of their content, so a pack once applied never needs
+/// downloading again.
+fn read_fetched_packs(path: &Path) -> Result<BTreeMap<String, BTreeSet<String>>> {
+    match std::fs::read_to_string(path) {
+        Ok(text) => serde_json::from_str(&text).map_err(|e| crate::OdiError::Storage {
+            message: format!("Failed to read {}: {}", path.display(), e)
+        }),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(BTreeMap::new()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Apply the issues held in the packfiles on the remote not applied before
+async fn pull_packs(ctx: &AppContext, sync: &DefaultRemoteSync, client: &SyncClient, project_id: Option<&str>, summary: &mut PullSummary) -> Result<()> {
+    let state_path = ctx.storage()?.root_path().join(FETCHED_PACKS_FILE);
+    let mut fetched = read_fetched_packs(&state_path)?;
+    let already = fetched.entry(client.remote_url.clone()).or_default();
+    
+    let names: Vec<String> = sync.list_packs(client).await.map_err(|e| crate::OdiError::Command { 
+        message: format!("Failed to list remote packs: {}", e)
+    })?
+        .into_iter()
+        .filter(|name| !already.contains(name))
+        .collect();
+    if names.is_empty() {
+        return Ok(());
+    }
+    
+    // Packs may hold several versions of an issue; keep only the newest
+    let mut newest: HashMap<IssueId, Issue> = HashMap::new();
+    for name in &names {
+        let bytes = sync.download_pack(client, name).await.map_err(|e| crate::OdiError::Command { 
+            message: format!("Failed to download pack {}: {}", name, e)
+        })?;
+        for object in odi_fs::pack::decode(&bytes)? {
+            if object.object_type != ObjectType::Issue {
+                continue;
+            }
+            let issue: Issue = odi_core::format::open(&object.data)?;
+            if project_id.is_some_and(|project| issue.project_id.as_deref() != Some(project)) {
+                continue;
+            }
+            if newest.get(&issue.id).is_none_or(|seen| issue.updated_at > seen.updated_at) {
+                newest.insert(issue.id, issue);
+            }
+        }
+    }
+    
+    let (downloaded_count, updated_count) = merge_issues(ctx, newest.into_values(), summary).await?;
+    
+    // A pull of one project leaves the other issues of a pack unapplied
+    if project_id.is_none() {
+        already.extend(names.iter().cloned());
+        let text = serde_json::to_string_pretty(&fetched).map_err(|e| crate::OdiError::Storage {
+            message: format!("Failed to write {}: {}", state_path.display(), e)
+        })?;
+        std::fs::write(&state_path, text)?;
+    }
+    
+    say!("✓ Applied {} packs: {} new, {} updated", names.len(), downloaded_count, updated_count);
+    Ok(())
+}
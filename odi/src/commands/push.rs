@@ -2,8 +2,34 @@
 
 use clap::Args;
 use crate::{Result, AppContext};
-use odi_core::{RemoteRepository, IssueRepository};
-use odi_net::{RemoteSync, DefaultRemoteSync};
+use crate::output::{self, say};
+use odi_core::{Issue, IssueId};
+use odi_fs::{ObjectType, PackObject};
+use odi_fs::git_store::GitStore;
+use super::bundle::collect_objects;
+use odi_net::{RemoteSync, DefaultRemoteSync, SyncClient};
+
+/// Outcome of a push, as printed in structured formats
+#[derive(serde::Serialize)]
+struct PushSummary<'a> {
+    remote: &'a str,
+    /// Issues sent to the remote
+    uploaded: Vec<IssueId>,
+    /// Issues the remote already had up to date
+    skipped: usize,
+    /// Commit of refs/odi pushed to a Git remote
+    #[serde(skip_serializing_if = "Option::is_none")]
+    commit: Option<String>,
+}
+
+impl PushSummary<'_> {
+    fn print(&self) -> Result<()> {
+        if output::structured() {
+            output::print_record(self)?;
+        }
+        Ok(())
+    }
+}
 
 #[derive(Args)]
 pub struct PushArgs {
@@ -22,15 +48,19 @@ pub struct PushArgs {
     /// Project to push issues from
     #[arg(long, short)]
     pub project: Option<String>,
+    
+    /// Send changed issues as a single packfile instead of one file per issue
+    #[arg(long)]
+    pub pack: bool,
 }
 
 impl PushArgs {
     pub async fn execute(&self, ctx: &AppContext) -> Result<()> {
-        push_remote(ctx, self.remote.as_deref(), self.force, self.dry_run, self.project.as_deref()).await
+        push_remote(ctx, self.remote.as_deref(), self.force, self.dry_run, self.project.as_deref(), self.pack).await
     }
 }
 
-async fn push_remote(ctx: &AppContext, remote_name: Option<&str>, _force: bool, dry_run: bool, project_id: Option<&str>) -> Result<()> {
+async fn push_remote(ctx: &AppContext, remote_name: Option<&str>, force: bool, dry_run: bool, project_id: Option<&str>, pack: bool) -> Result<()> {
     let remote_name = remote_name.unwrap_or("origin");
     
     // Find the remote
@@ -48,19 +78,34 @@ async fn push_remote(ctx: &AppContext, remote_name: Option<&str>, _force: bool,
         }
     };
     
+    if remote.url.starts_with(odi_fs::bundle::URL_SCHEME) {
+        return Err(crate::OdiError::Validation { 
+            message: format!("Remote '{}' is a read-only bundle; use 'odi bundle create' instead", remote.name)
+        });
+    }
+    
     if dry_run {
-        println!("Dry run: Pushing to {} ({})", remote.name, remote.url);
+        say!("Dry run: Pushing to {} ({})", remote.name, remote.url);
         if let Some(project) = project_id {
-            println!("Filtering by project: {}", project);
+            say!("Filtering by project: {}", project);
         }
-        println!("Would check for local changes...");
-        println!("💡 Dry run mode - no changes made");
+        say!("Would check for local changes...");
+        say!("💡 Dry run mode - no changes made");
         return Ok(());
     }
     
-    println!("Pushing to {} ({})", remote.name, remote.url);
+    say!("Pushing to {} ({})", remote.name, remote.url);
+    
+    // Git remotes get the whole workspace committed under refs/odi
+    if let Some(repository) = odi_fs::git_store::repository_from_url(&remote.url) {
+        if project_id.is_some() {
+            say!("ℹ️  Git remotes hold the whole workspace; --project is ignored");
+        }
+        return push_git(ctx, &remote.name, repository, force).await;
+    }
+    
     if let Some(project) = project_id {
-        println!("Filtering by project: {}", project);
+        say!("Filtering by project: {}", project);
     }
     
     // Initialize the remote sync client
@@ -69,7 +114,7 @@ async fn push_remote(ctx: &AppContext, remote_name: Option<&str>, _force: bool,
     // Attempt to connect to the remote
     match sync.connect(&remote).await {
         Ok(client) => {
-            println!("✓ Connected successfully to remote: {}", remote.url);
+            say!("✓ Connected successfully to remote: {}", remote.url);
             
             // Get local issues to push, with optional project filter
             let issue_repo = ctx.issue_repository();
@@ -88,64 +133,108 @@ async fn push_remote(ctx: &AppContext, remote_name: Option<&str>, _force: bool,
                 })?;
             
             if local_issues.is_empty() {
-                println!("ℹ️  No local issues to push");
-                return Ok(());
+                say!("ℹ️  No local issues to push");
+                return PushSummary { remote: &remote.name, uploaded: Vec::new(), skipped: 0, commit: None }.print();
             }
             
-            println!("📤 Pushing {} local issues to remote", local_issues.len());
+            say!("📤 Pushing {} local issues to remote", local_issues.len());
             
             // Get remote issues for comparison
             let remote_issues = sync.list_issues(&client).await.map_err(|e| crate::OdiError::Command { 
                 message: format!("Failed to list remote issues: {}", e)
             })?;
             
-            let mut uploaded_count = 0;
+            let mut uploaded = Vec::new();
             let mut skipped_count = 0;
+            let mut to_upload = Vec::new();
             
             for local_issue in local_issues {
-                // Check if issue exists on remote
-                if let Some(remote_issue) = remote_issues.iter().find(|r| r.id == local_issue.id) {
-                    // Compare timestamps to see if local is newer
-                    if local_issue.updated_at > remote_issue.last_modified {
-                        // Upload the updated issue
-                        match sync.upload_issue(&client, &local_issue).await {
-                            Ok(_) => {
-                                uploaded_count += 1;
-                                println!("  ↑ Uploaded updated issue: {}", local_issue.title);
-                            },
-                            Err(e) => {
-                                println!("  ⚠️  Failed to upload issue {}: {}", local_issue.id, e);
-                            }
-                        }
-                    } else {
-                        skipped_count += 1;
-                    }
-                } else {
-                    // Issue doesn't exist on remote, upload it
+                // Check if issue exists on remote, and if so whether local is newer
+                match remote_issues.iter().find(|r| r.id == local_issue.id) {
+                    Some(remote_issue) if local_issue.updated_at <= remote_issue.last_modified => skipped_count += 1,
+                    Some(_) => to_upload.push((local_issue, "updated")),
+                    None => to_upload.push((local_issue, "new")),
+                }
+            }
+            
+            if pack && !to_upload.is_empty() {
+                let issues: Vec<_> = to_upload.iter().map(|(issue, _)| issue).collect();
+                upload_pack(&sync, &client, &issues).await?;
+                for (issue, kind) in &to_upload {
+                    uploaded.push(issue.id);
+                    say!("  ↑ Packed {} issue: {}", kind, issue.title);
+                }
+            } else {
+                for (local_issue, kind) in to_upload {
                     match sync.upload_issue(&client, &local_issue).await {
                         Ok(_) => {
-                            uploaded_count += 1;
-                            println!("  ↑ Uploaded new issue: {}", local_issue.title);
+                            uploaded.push(local_issue.id);
+                            say!("  ↑ Uploaded {} issue: {}", kind, local_issue.title);
                         },
                         Err(e) => {
-                            println!("  ⚠️  Failed to upload issue {}: {}", local_issue.id, e);
+                            say!("  ⚠️  Failed to upload issue {}: {}", local_issue.id, e);
                         }
                     }
                 }
             }
             
-            if uploaded_count > 0 {
-                println!("✓ Push completed: {} uploaded, {} skipped", uploaded_count, skipped_count);
+            if !uploaded.is_empty() {
+                say!("✓ Push completed: {} uploaded, {} skipped", uploaded.len(), skipped_count);
             } else {
-                println!("✓ Push completed: No changes to push");
+                say!("✓ Push completed: No changes to push");
             }
-            return Ok(());
+            PushSummary { remote: &remote.name, uploaded, skipped: skipped_count, commit: None }.print()
         },
         Err(e) => {
-            println!("✗ Connection failed: {}", e);
-            return Err(crate::OdiError::Command { 
+            say!("✗ Connection failed: {}", e);
+            Err(crate::OdiError::Command { 
                 message: format!("Push operation failed: {}", e)
-            });
+            })
         }
     }
-}
\ No newline at end of file
+}
+
+/// Refuse Git remotes for encrypted workspaces
+///
+/// refs/odi holds objects unencrypted; don't quietly write customer data out
+/// in the clear.
+pub(super) fn refuse_encrypted(ctx: &AppContext) -> Result<()> {
+    if ctx.storage()?.is_encrypted() {
+        return Err(crate::OdiError::Validation {
+            message: "Encrypted workspaces can't use Git remotes, which store objects unencrypted".to_string()
+        });
+    }
+    Ok(())
+}
+
+/// Commit the workspace to refs/odi and push it to a Git remote
+async fn push_git(ctx: &AppContext, remote_name: &str, repository: &str, force: bool) -> Result<()> {
+    refuse_encrypted(ctx)?;
+    let store = GitStore::open(ctx.workspace_path())?;
+    let writer = collect_objects(ctx, None, None).await?;
+    let count = writer.len();
+    
+    let commit = store.commit(writer, None, "Update ODI data")?;
+    match &commit {
+        Some(commit) => say!("✓ Committed {} objects as {}", count, &commit[..8]),
+        None => say!("ℹ️  No local changes since the last commit"),
+    }
+    store.push(repository, force)?;
+    
+    say!("✓ Push completed: {} is up to date with {}", remote_name, odi_fs::git_store::ODI_REF);
+    PushSummary { remote: remote_name, uploaded: Vec::new(), skipped: 0, commit }.print()
+}
+
+/// Upload issues to a remote as one packfile
+async fn upload_pack(sync: &DefaultRemoteSync, client: &SyncClient, issues: &[&Issue]) -> Result<()> {
+    let mut objects = Vec::new();
+    for issue in issues {
+        objects.push(PackObject::new(ObjectType::Issue, odi_core::format::seal(*issue)?));
+    }
+    
+    let (bytes, _) = odi_fs::pack::encode(objects)?;
+    let name = format!("{}.pack", odi_fs::pack::pack_name(&bytes));
+    sync.upload_pack(client, &name, &bytes).await.map_err(|e| crate::OdiError::Command { 
+        message: format!("Failed to upload pack: {}", e)
+    })
+}
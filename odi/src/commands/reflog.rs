@@ -0,0 +1,99 @@
+//! Reflog command implementation
+
+use clap::Args;
+use odi_fs::{FileSystemStorage, ReflogEntry, StorageBackend};
+use serde::Serialize;
+use std::sync::Arc;
+use crate::{Result, AppContext, OdiError};
+use crate::output::{self, say};
+
+/// A version of an issue, numbered for `odi issue restore --to`
+#[derive(Serialize)]
+struct IssueVersion<'a> {
+    index: usize,
+    #[serde(flatten)]
+    entry: &'a ReflogEntry,
+}
+
+#[derive(Args)]
+pub struct ReflogArgs {
+    /// Show the history of one issue instead of every operation
+    pub issue: Option<String>,
+}
+
+impl ReflogArgs {
+    pub async fn execute(&self, ctx: &AppContext) -> Result<()> {
+        let storage = reflog_storage(ctx)?;
+        match &self.issue {
+            Some(id) => show_issue_log(storage, id),
+            None => show_operations(storage),
+        }
+    }
+}
+
+/// Object store whose ref changes are logged; only the fs backend keeps a reflog
+pub(crate) fn reflog_storage(ctx: &AppContext) -> Result<&Arc<FileSystemStorage>> {
+    if ctx.config().storage.backend != StorageBackend::Fs {
+        return Err(OdiError::Command {
+            message: "The reflog is only kept by the fs storage backend".to_string(),
+        });
+    }
+    ctx.storage()
+}
+
+fn short(hash: Option<&str>) -> &str {
+    hash.map_or("-", |hash| &hash[..hash.len().min(8)])
+}
+
+fn show_operations(storage: &FileSystemStorage) -> Result<()> {
+    let operations = odi_fs::reflog::operations(storage.root_path())?;
+    if output::structured() {
+        return output::print_records(&operations);
+    }
+    if operations.is_empty() {
+        println!("No ref changes recorded");
+        return Ok(());
+    }
+
+    for operation in operations {
+        let undo = if operation.undoes.is_some() { " (undo)" } else { "" };
+        println!("{}  {}  {:<12} {}{}  [{} refs]",
+            &operation.id[..8],
+            operation.time.format("%Y-%m-%d %H:%M:%S"),
+            operation.user,
+            operation.command,
+            undo,
+            operation.changes.len(),
+        );
+    }
+    Ok(())
+}
+
+fn show_issue_log(storage: &FileSystemStorage, id: &str) -> Result<()> {
+    let name = format!("issues/{}", id);
+    let entries = odi_fs::reflog::read(storage.root_path(), &name)?;
+    if output::structured() {
+        let versions: Vec<IssueVersion> = entries.iter().rev().enumerate()
+            .map(|(index, entry)| IssueVersion { index, entry })
+            .collect();
+        return output::print_records(&versions);
+    }
+    if entries.is_empty() {
+        say!("No reflog entries for issue {}", id);
+        return Ok(());
+    }
+
+    // Newest first, numbered the way `odi issue restore --to` expects
+    for (index, entry) in entries.iter().rev().enumerate() {
+        println!("{}@{{{}}}  {}  {:<12} {} -> {}  {}",
+            id,
+            index,
+            entry.time.format("%Y-%m-%d %H:%M:%S"),
+            entry.user,
+            short(entry.old.as_deref()),
+            short(entry.new.as_deref()),
+            entry.command,
+        );
+    }
+    Ok(())
+}
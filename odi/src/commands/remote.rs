@@ -2,7 +2,8 @@
 
 use clap::{Args, Subcommand};
 use crate::{Result, AppContext};
-use odi_core::{Remote, RemoteRepository, IssueRepository};
+use crate::output::{self, say};
+use odi_core::Remote;
 use odi_net::{RemoteSync, DefaultRemoteSync};
 
 #[derive(Args)]
@@ -93,12 +94,16 @@ async fn add_remote(ctx: &AppContext, name: &str, url: &str, _projects: Option<&
     let remote = Remote::new(name.to_string(), name.to_string(), url.to_string());
     
     // Store remote
-    remote_repo.create(remote).await.map_err(|e| crate::OdiError::Storage { 
+    let remote = remote_repo.create(remote).await.map_err(|e| crate::OdiError::Storage { 
         message: format!("Failed to create remote: {}", e) 
     })?;
     
     // Get protocol for display
-    let protocol = if url.starts_with("ssh://") || url.contains('@') {
+    let protocol = if url.starts_with("bundle://") {
+        "None (read-only bundle)"
+    } else if url.starts_with(odi_fs::git_store::URL_SCHEME) {
+        "Git (credentials are handled by git)"
+    } else if url.starts_with("ssh://") || url.contains('@') {
         "SSH"
     } else if url.starts_with("https://") {
         "HTTPS" 
@@ -106,10 +111,10 @@ async fn add_remote(ctx: &AppContext, name: &str, url: &str, _projects: Option<&
         "Unknown"
     };
     
-    println!("Added remote '{}': {}", name, url);
-    println!("Authentication: {}", protocol);
-    
-    Ok(())
+    output::record(&remote, || {
+        println!("Added remote '{}': {}", name, url);
+        println!("Authentication: {}", protocol);
+    })
 }
 
 async fn list_remotes(ctx: &AppContext) -> Result<()> {
@@ -118,6 +123,9 @@ async fn list_remotes(ctx: &AppContext) -> Result<()> {
         message: format!("Failed to list remotes: {}", e) 
     })?;
     
+    if output::structured() {
+        return output::print_records(&remotes);
+    }
     if remotes.is_empty() {
         println!("No remotes configured.");
         println!("Use 'odi remote add <name> <url>' to add a remote.");
@@ -161,6 +169,7 @@ async fn show_remote(ctx: &AppContext, name: &str) -> Result<()> {
     let remote = remotes.iter().find(|r| r.name == name);
     
     match remote {
+        Some(remote) if output::structured() => output::print_record(remote),
         Some(remote) => {
             println!("Remote: {}", remote.name);
             println!("URL: {}", remote.url);
@@ -189,7 +198,7 @@ async fn remove_remote(ctx: &AppContext, name: &str) -> Result<()> {
     })?;
     
     if existed {
-        println!("Removed remote: {}", name);
+        say!("Removed remote: {}", name);
         Ok(())
     } else {
         eprintln!("❌ Remote Not Found");
@@ -202,6 +211,18 @@ async fn remove_remote(ctx: &AppContext, name: &str) -> Result<()> {
         }))
     }
 }
+/// What `odi remote sync-status` found, as printed in structured formats
+#[derive(serde::Serialize, Default)]
+struct SyncStatus {
+    remote: String,
+    url: String,
+    accessible: bool,
+    remote_issues: Option<u32>,
+    pending_changes: Option<u32>,
+    local_issues: Option<usize>,
+    error: Option<String>,
+}
+
 async fn sync_status(ctx: &AppContext, remote_name: Option<&str>) -> Result<()> {
     let remote_name = remote_name.unwrap_or("origin");
     
@@ -215,9 +236,10 @@ async fn sync_status(ctx: &AppContext, remote_name: Option<&str>) -> Result<()>
             message: format!("Remote '{}' not found", remote_name) 
         }))?;
     
-    println!("Synchronization status for remote: {}", remote.name);
-    println!("URL: {}", remote.url);
-    println!();
+    let mut status = SyncStatus { remote: remote.name.clone(), url: remote.url.clone(), ..Default::default() };
+    say!("Synchronization status for remote: {}", remote.name);
+    say!("URL: {}", remote.url);
+    say!("");
     
     // Initialize the remote sync client
     let sync = DefaultRemoteSync::new();
@@ -225,38 +247,47 @@ async fn sync_status(ctx: &AppContext, remote_name: Option<&str>) -> Result<()>
     // Attempt to connect to the remote
     match sync.connect(remote).await {
         Ok(client) => {
-            println!("✓ Remote is accessible");
+            status.accessible = true;
+            say!("✓ Remote is accessible");
             
             // Get sync state from remote
             match sync.get_sync_state(&client).await {
                 Ok(state) => {
-                    println!("✓ Sync state retrieved:");
-                    println!("  Remote issues: {}", state.total_issues);
-                    println!("  Pending changes: {}", state.pending_changes);
+                    say!("✓ Sync state retrieved:");
+                    say!("  Remote issues: {}", state.total_issues);
+                    say!("  Pending changes: {}", state.pending_changes);
                     
                     // Get local issue count
                     let issue_repo = ctx.issue_repository();
                     let local_issues = issue_repo.list(odi_core::issue::IssueQuery::default()).await
                         .map_err(crate::OdiError::Core)?;
                     
-                    println!("  Local issues: {}", local_issues.len());
+                    say!("  Local issues: {}", local_issues.len());
+                    status.remote_issues = Some(state.total_issues);
+                    status.pending_changes = Some(state.pending_changes);
+                    status.local_issues = Some(local_issues.len());
                     
                     if state.pending_changes > 0 {
-                        println!("⚠️  Synchronization needed");
-                        println!("💡 Run 'odi remote pull {}' to sync changes", remote_name);
+                        say!("⚠️  Synchronization needed");
+                        say!("💡 Run 'odi remote pull {}' to sync changes", remote_name);
                     } else {
-                        println!("✓ Remote and local are in sync");
+                        say!("✓ Remote and local are in sync");
                     }
                 },
                 Err(e) => {
-                    println!("✗ Failed to get sync state: {}", e);
+                    say!("✗ Failed to get sync state: {}", e);
+                    status.error = Some(e.to_string());
                 }
             }
         },
         Err(e) => {
-            println!("✗ Cannot connect to remote: {}", e);
+            say!("✗ Cannot connect to remote: {}", e);
+            status.error = Some(e.to_string());
         }
     }
     
+    if output::structured() {
+        output::print_record(&status)?;
+    }
     Ok(())
 }
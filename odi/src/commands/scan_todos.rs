@@ -0,0 +1,155 @@
+//! TODO scanning command implementation
+
+use clap::Args;
+use odi_core::{add_reference, find_todos, GitRef, GitTarget, IssueId, IssueQuery, IssueStatus, IssueUpdate, LinkRole};
+use odi_fs::GitIntegration;
+use super::git::{git_repository, repository_path, resolve};
+use serde::Serialize;
+use crate::{Result, AppContext};
+use crate::output::{self, say};
+
+/// Files larger than this are not scanned; they are rarely hand-written source
+const MAX_FILE_SIZE: u64 = 1024 * 1024;
+
+/// Result of a scan, the record printed in structured output
+#[derive(Serialize, Default)]
+struct ScanSummary {
+    dry_run: bool,
+    created: Vec<IssueId>,
+    updated: Vec<IssueId>,
+    /// Comments the issue key was added to, with --rewrite
+    rewritten: usize,
+    /// Open issues whose comment is no longer found
+    missing: Vec<IssueId>,
+}
+
+#[derive(Args)]
+pub struct ScanTodosArgs {
+    /// Files or directories to scan (defaults to the whole working tree)
+    pub paths: Vec<String>,
+
+    /// Add the issue key to each comment, as in 'TODO(1a2b3c4d): ...'
+    #[arg(long)]
+    pub rewrite: bool,
+
+    /// Show what would change without creating issues or editing files
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+impl ScanTodosArgs {
+    pub async fn execute(&self, ctx: &AppContext) -> Result<()> {
+        let (repo, git) = git_repository(ctx)?;
+        let identity = repo.identity();
+        let scopes = self.paths.iter()
+            .map(|path| repository_path(&repo.path, path))
+            .collect::<Result<Vec<String>>>()?;
+        let in_scope = |file: &str| scopes.is_empty() || scopes.iter().any(|scope| {
+            scope.is_empty() || file == scope || file.starts_with(&format!("{}/", scope))
+        });
+
+        let author = ctx.config().user.name.clone();
+        let mut issues = ctx.issue_repository().list(IssueQuery::default()).await?;
+        let mut seen = Vec::new();
+        let mut summary = ScanSummary { dry_run: self.dry_run, ..Default::default() };
+
+        // ODI's own objects are in the working tree too, but never hold comments
+        let files = git.working_tree_files()?.into_iter()
+            .filter(|file| in_scope(file) && !file.split('/').any(|part| part == ".odi"));
+        for file in files {
+            let full_path = repo.path.join(&file);
+            if std::fs::metadata(&full_path).map_or(true, |metadata| metadata.len() > MAX_FILE_SIZE) {
+                continue;
+            }
+            // Binary files aren't valid UTF-8
+            let Ok(content) = std::fs::read_to_string(&full_path) else {
+                continue;
+            };
+
+            let mut keys = Vec::new();
+            for todo in find_todos(&file, &content) {
+                seen.push(todo.fingerprint.clone());
+                let existing = todo.reference.as_deref()
+                    .and_then(|reference| resolve(&issues, reference).ok())
+                    .map(|issue| issue.id)
+                    .or_else(|| issues.iter().find(|issue| issue.todo_fingerprint() == Some(todo.fingerprint.as_str())).map(|issue| issue.id));
+                let location = format!("{}:{}", todo.path, todo.line);
+
+                match existing.and_then(|id| issues.iter_mut().find(|issue| issue.id == id)) {
+                    Some(issue) => {
+                        // Follow the comment to its current line
+                        let target = GitTarget::File { path: todo.path.clone(), line: todo.line };
+                        if !issue.git_refs.iter().any(|link| link.target == target) {
+                            issue.git_refs.retain(|link| !matches!(&link.target, GitTarget::File { path, .. } if *path == todo.path));
+                            issue.link(GitRef::new(target, LinkRole::Mentions).with_repository(identity.clone()));
+                            if !self.dry_run {
+                                ctx.issue_repository().update(&issue.id, IssueUpdate::new().git_refs(issue.git_refs.clone())).await?;
+                            }
+                            say!("✓ Updated {} {} ({})", issue.short_id(), issue.title, location);
+                            summary.updated.push(issue.id);
+                        }
+                        if todo.reference.is_none() {
+                            keys.push((todo.line, issue.short_id()));
+                        }
+                    }
+                    None => {
+                        let issue = todo.to_issue(author.clone(), identity.clone());
+                        let issue = if self.dry_run { issue } else { ctx.issue_repository().create(issue).await? };
+                        say!("✓ Created {} {} ({})", issue.short_id(), issue.title, location);
+                        keys.push((todo.line, issue.short_id()));
+                        summary.created.push(issue.id);
+                        issues.push(issue);
+                    }
+                }
+            }
+
+            if self.rewrite && !keys.is_empty() {
+                let (content, count) = rewrite_comments(&content, &keys);
+                if count > 0 && !self.dry_run {
+                    std::fs::write(&full_path, content)?;
+                }
+                summary.rewritten += count;
+            }
+        }
+
+        // Issues whose comment is gone may be done; closing them is left to people
+        let missing: Vec<_> = issues.iter()
+            .filter(|issue| scopes.is_empty() && matches!(issue.status, IssueStatus::Open | IssueStatus::InProgress))
+            .filter(|issue| issue.todo_fingerprint().is_some_and(|fingerprint| !seen.iter().any(|seen| seen == fingerprint)))
+            .collect();
+        summary.missing = missing.iter().map(|issue| issue.id).collect();
+
+        output::record(&summary, || {
+            let prefix = if self.dry_run { "Would have: " } else { "" };
+            print!("{}{} created, {} updated", prefix, summary.created.len(), summary.updated.len());
+            if self.rewrite {
+                print!(", {} comments rewritten", summary.rewritten);
+            }
+            println!();
+            for issue in &missing {
+                println!("  {} {}: comment no longer found", issue.short_id(), issue.title);
+            }
+        })
+    }
+}
+
+/// Add issue keys to the comments on the given lines, keeping line endings
+fn rewrite_comments(content: &str, keys: &[(usize, String)]) -> (String, usize) {
+    let mut count = 0;
+    let mut out = String::with_capacity(content.len() + keys.len() * 10);
+    for (index, line) in content.split_inclusive('\n').enumerate() {
+        let body = line.trim_end_matches(['\n', '\r']);
+        let rewritten = keys.iter()
+            .find(|(number, _)| *number == index + 1)
+            .and_then(|(_, key)| add_reference(body, key));
+        match rewritten {
+            Some(rewritten) => {
+                out.push_str(&rewritten);
+                out.push_str(&line[body.len()..]);
+                count += 1;
+            }
+            None => out.push_str(line),
+        }
+    }
+    (out, count)
+}
@@ -0,0 +1,141 @@
+//! Search command implementation
+
+use clap::Args;
+use serde::Serialize;
+use crate::{Result, AppContext};
+use crate::output;
+use odi_core::{IssueQuery, ProjectQuery};
+use odi_fs::search::{self, Document, SearchIndex, SearchQuery, Snippet};
+use odi_fs::StorageBackend;
+
+/// Characters of text shown around a match
+const SNIPPET_WIDTH: usize = 100;
+
+/// Terminal codes around matches in snippets; plain text marks them with `**`
+const BOLD: (&str, &str) = ("\x1b[1m", "\x1b[22m");
+
+#[derive(Args)]
+pub struct SearchArgs {
+    /// Words to search for; "quoted words" must appear together and word* matches prefixes
+    #[arg(required = true, value_name = "TERMS")]
+    pub terms: Vec<String>,
+    /// Only search issues or only projects
+    #[arg(long)]
+    pub kind: Option<SearchKind>,
+    /// Maximum number of results
+    #[arg(long, short = 'n', default_value_t = 20)]
+    pub limit: usize,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, PartialEq, Eq)]
+pub enum SearchKind {
+    Issue,
+    Project,
+}
+
+impl SearchKind {
+    fn of(name: &str) -> Option<Self> {
+        match name.split_once('/')?.0 {
+            "issues" => Some(SearchKind::Issue),
+            "projects" => Some(SearchKind::Project),
+            _ => None,
+        }
+    }
+}
+
+/// A search result, as printed in structured formats
+#[derive(Serialize)]
+struct SearchResult {
+    /// `issue` or `project`
+    kind: &'static str,
+    id: String,
+    /// Issue title or project name
+    title: String,
+    score: f64,
+    snippets: Vec<Snippet>,
+}
+
+impl SearchArgs {
+    pub async fn execute(&self, ctx: &AppContext) -> Result<()> {
+        let query = SearchQuery::parse(&self.terms.join(" "))?;
+        let index = open_index(ctx).await?;
+
+        // Filter before the limit, so --kind still gets up to --limit results
+        let mut results = Vec::new();
+        for hit in index.search(&query, None) {
+            if results.len() == self.limit {
+                break;
+            }
+            let Some(kind) = SearchKind::of(&hit.name) else {
+                continue;
+            };
+            if self.kind.is_some_and(|wanted| wanted != kind) {
+                continue;
+            }
+            let id = hit.name.split_once('/').map(|(_, id)| id.to_string()).unwrap_or_default();
+            // The index may name an object deleted since; it's left out
+            let Some((kind, title, document)) = load(ctx, kind, &id).await? else {
+                continue;
+            };
+            results.push(SearchResult {
+                kind,
+                id,
+                title,
+                score: (hit.score * 1000.0).round() / 1000.0,
+                snippets: Snippet::of(&document, &query, SNIPPET_WIDTH),
+            });
+        }
+
+        output::records(&results, || {
+            if results.is_empty() {
+                println!("No matches for '{}'.", self.terms.join(" "));
+                println!("💡 Tip: Fewer words or word* prefixes find more");
+                return;
+            }
+            let (open, close) = if console::colors_enabled() { BOLD } else { ("**", "**") };
+            for result in &results {
+                let id = if result.kind == "issue" { &result.id[..8] } else { result.id.as_str() };
+                println!("{} {} - {}", result.kind, id, result.title);
+                for snippet in &result.snippets {
+                    let field = match snippet.field {
+                        search::Field::Title => if result.kind == "issue" { "title" } else { "name" },
+                        search::Field::Description => "description",
+                    };
+                    println!("    {}: {}", field, snippet.highlighted(open, close));
+                }
+            }
+        })
+    }
+}
+
+/// The search index of the workspace
+///
+/// Object-store workspaces keep one in `.odi/index/`; for SQLite workspaces
+/// it's built from the database for each search.
+async fn open_index(ctx: &AppContext) -> Result<SearchIndex> {
+    if ctx.config().storage.backend == StorageBackend::Fs {
+        return Ok(search::open(ctx.storage()?).await?);
+    }
+
+    let mut index = SearchIndex::default();
+    for issue in ctx.issue_repository().list(IssueQuery::default()).await? {
+        index.put(&Document::issue(&issue), "");
+    }
+    for project in ctx.project_repository().list_projects(ProjectQuery::default()).await? {
+        index.put(&Document::project(&project), "");
+    }
+    Ok(index)
+}
+
+/// Kind, title and text of a matching object
+async fn load(ctx: &AppContext, kind: SearchKind, id: &str) -> Result<Option<(&'static str, String, Document)>> {
+    Ok(match kind {
+        SearchKind::Issue => match id.parse() {
+            Ok(id) => ctx.issue_repository().get(&id).await?
+                .map(|issue| ("issue", issue.title.clone(), Document::issue(&issue))),
+            Err(_) => None,
+        },
+        SearchKind::Project => ctx.project_repository().get_project(&id.to_string()).await?
+            .map(|project| ("project", project.name.clone(), Document::project(&project))),
+    })
+}
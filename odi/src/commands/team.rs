@@ -2,7 +2,8 @@
 
 use clap::{Args, Subcommand};
 use crate::{Result, AppContext};
-use odi_core::{Team, User, UserRepository};
+use crate::output;
+use odi_core::{Team, User};
 
 #[derive(Args)]
 pub struct TeamArgs {
@@ -65,22 +66,22 @@ impl TeamArgs {
                 let created_team = ctx.user_repository().create_team(team).await
                     .map_err(crate::OdiError::Core)?;
                 
-                println!("Created team: {} ({})", created_team.name, created_team.id);
-                Ok(())
+                output::record(&created_team, || println!("Created team: {} ({})", created_team.name, created_team.id))
             },
             TeamSubcommand::List => {
                 let teams = ctx.user_repository().list_teams(odi_core::TeamQuery::default()).await
                     .map_err(crate::OdiError::Core)?;
                 
-                if teams.is_empty() {
-                    println!("No teams found.");
-                } else {
-                    println!("Teams:");
-                    for team in teams {
-                        println!("  {} - {}", team.name, team.description.as_deref().unwrap_or("No description"));
+                output::records(&teams, || {
+                    if teams.is_empty() {
+                        println!("No teams found.");
+                    } else {
+                        println!("Teams:");
+                        for team in &teams {
+                            println!("  {} - {}", team.name, team.description.as_deref().unwrap_or("No description"));
+                        }
                     }
-                }
-                Ok(())
+                })
             },
             TeamSubcommand::Show { name } => {
                 // Find team by name
@@ -88,6 +89,9 @@ impl TeamArgs {
                     .map_err(crate::OdiError::Core)?;
                 
                 if let Some(team) = teams.iter().find(|t| t.name == *name) {
+                    if output::structured() {
+                        return output::print_record(team);
+                    }
                     println!("Team: {}", team.name);
                     println!("ID: {}", team.id);
                     if let Some(desc) = &team.description {
@@ -119,7 +123,7 @@ impl TeamArgs {
                 if let Some(team) = teams.iter().find(|t| t.name == *name) {
                     ctx.user_repository().delete_team(&team.id).await
                         .map_err(crate::OdiError::Core)?;
-                    println!("Deleted team: {}", name);
+                    output::record(team, || println!("Deleted team: {}", name))?;
                 } else {
                     return Err(crate::OdiError::TeamNotFound(name.clone()));
                 }
@@ -149,7 +153,8 @@ impl TeamArgs {
                         &user.id
                     ).await.map_err(crate::OdiError::Core)?;
                     
-                    println!("Added {} to team {}", user_name, team_name);
+                    let team = ctx.user_repository().get_team(&team.id).await?.unwrap_or_else(|| team.clone());
+                    output::record(&team, || println!("Added {} to team {}", user_name, team_name))?;
                 } else {
                     return Err(crate::OdiError::TeamNotFound(team_name.clone()));
                 }
@@ -170,7 +175,8 @@ impl TeamArgs {
                             &user.id
                         ).await.map_err(crate::OdiError::Core)?;
                         
-                        println!("Removed {} from team {}", user_name, team_name);
+                        let team = ctx.user_repository().get_team(&team.id).await?.unwrap_or_else(|| team.clone());
+                        output::record(&team, || println!("Removed {} from team {}", user_name, team_name))?;
                     } else {
                         return Err(crate::OdiError::UserNotFound(user_name.clone()));
                     }
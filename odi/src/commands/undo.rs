@@ -0,0 +1,38 @@
+//! Undo command implementation
+
+use clap::Args;
+use crate::{Result, AppContext};
+use crate::output::{self, say};
+
+#[derive(Args)]
+pub struct UndoArgs {
+    /// Roll back refs even if they changed after the operation
+    #[arg(long)]
+    pub force: bool,
+}
+
+impl UndoArgs {
+    pub async fn execute(&self, ctx: &AppContext) -> Result<()> {
+        let storage = super::reflog::reflog_storage(ctx)?;
+
+        match odi_fs::reflog::undo(storage, self.force)? {
+            Some(operation) if output::structured() => output::print_record(&operation)?,
+            Some(operation) => {
+                println!("✓ Undid '{}' by {} at {}",
+                    operation.command,
+                    operation.user,
+                    operation.time.format("%Y-%m-%d %H:%M:%S"),
+                );
+                let mut names: Vec<&str> = Vec::new();
+                for (name, _) in &operation.changes {
+                    if !names.contains(&name.as_str()) {
+                        names.push(name);
+                        println!("  rolled back {}", name);
+                    }
+                }
+            },
+            None => say!("Nothing to undo"),
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,259 @@
+//! View command implementation
+
+use clap::{Args, Subcommand};
+use serde::Serialize;
+use crate::{Result, AppContext, OdiError};
+use crate::output;
+use odi_core::{Column, Issue, IssueQuery, ProjectId, ProjectQuery, QueryContext, SortKey, View};
+use odi_fs::{load_user_views, save_user_views, user_config_path};
+
+/// Widest a cell is printed before it's cut short
+const MAX_CELL_WIDTH: usize = 60;
+
+#[derive(Args)]
+pub struct ViewArgs {
+    #[command(subcommand)]
+    pub command: ViewSubcommand,
+}
+
+#[derive(Subcommand)]
+pub enum ViewSubcommand {
+    /// Save a query as a view, replacing any view of the same name
+    Save {
+        /// View name
+        name: String,
+        /// Query, e.g. 'status:open -label:triaged' (after the options, as terms may start with '-')
+        #[arg(value_name = "QUERY", allow_hyphen_values = true, trailing_var_arg = true)]
+        query: Vec<String>,
+        /// Share the view with everyone in this project instead of keeping it for yourself
+        #[arg(long, short)]
+        project: Option<String>,
+        /// Sort order, e.g. '-priority,updated'; replaces any sort: in the query
+        #[arg(long, value_delimiter = ',', value_name = "KEYS", allow_hyphen_values = true)]
+        sort: Vec<SortKey>,
+        /// Columns to list, e.g. 'id,title,priority,assignees'
+        #[arg(long, value_delimiter = ',', value_name = "COLUMNS")]
+        columns: Vec<Column>,
+    },
+    /// List your views and those shared in projects
+    List {
+        /// Only list views shared in this project
+        #[arg(long, short)]
+        project: Option<String>,
+    },
+    /// Delete a view
+    Delete {
+        /// View name
+        name: String,
+        /// Delete the view shared in this project rather than your own
+        #[arg(long, short)]
+        project: Option<String>,
+    },
+}
+
+/// A view and where it's kept, as printed in structured formats
+#[derive(Serialize)]
+struct ViewEntry {
+    #[serde(flatten)]
+    view: View,
+    /// Project the view is shared in; personal views have none
+    #[serde(skip_serializing_if = "Option::is_none")]
+    project: Option<ProjectId>,
+}
+
+impl ViewArgs {
+    pub async fn execute(&self, ctx: &AppContext) -> Result<()> {
+        match &self.command {
+            ViewSubcommand::Save { name, query, project, sort, columns } => {
+                save_view(ctx, name, &super::issue::query_text(query)?, project.as_deref(), sort, columns).await
+            },
+            ViewSubcommand::List { project } => {
+                list_views(ctx, project.as_deref()).await
+            },
+            ViewSubcommand::Delete { name, project } => {
+                delete_view(ctx, name, project.as_deref()).await
+            },
+        }
+    }
+}
+
+async fn save_view(ctx: &AppContext, name: &str, query: &str, project_id: Option<&str>, sort: &[SortKey], columns: &[Column]) -> Result<()> {
+    if !View::validate_name(name) {
+        return Err(OdiError::Validation {
+            message: format!("Invalid view name '{}': must be 1-50 characters, alphanumeric with ._- allowed", name)
+        });
+    }
+    // Refuse queries that would only fail when the view is used
+    IssueQuery::parse(query, &QueryContext::new(Some(ctx.config().user.name.clone())))?;
+
+    let view = View::new(name.to_string(), query.to_string())
+        .with_sort(sort.to_vec())
+        .with_columns(columns.to_vec());
+
+    match project_id {
+        Some(project_id) => {
+            let project_repo = ctx.project_repository();
+            if project_repo.get_project(&project_id.to_string()).await?.is_none() {
+                return Err(OdiError::Validation {
+                    message: format!("Project not found: {}", project_id)
+                });
+            }
+            project_repo.save_view(&project_id.to_string(), view.clone()).await?;
+        }
+        None => {
+            let path = user_config_path();
+            let mut views = load_user_views(&path)?;
+            views.retain(|saved| saved.name != view.name);
+            views.push(view.clone());
+            views.sort_by(|a, b| a.name.cmp(&b.name));
+            save_user_views(&path, &views)?;
+        }
+    }
+
+    let entry = ViewEntry { view, project: project_id.map(str::to_string) };
+    output::record(&entry, || {
+        match project_id {
+            Some(project_id) => println!("Saved view '{}' shared in project '{}'", name, project_id),
+            None => println!("Saved view '{}' in {}", name, user_config_path().display()),
+        }
+        println!("💡 Tip: List its issues with 'odi issue list --view {}'", name);
+    })
+}
+
+async fn list_views(ctx: &AppContext, project_id: Option<&str>) -> Result<()> {
+    let mut entries: Vec<ViewEntry> = Vec::new();
+    if project_id.is_none() {
+        entries.extend(load_user_views(&user_config_path())?.into_iter()
+            .map(|view| ViewEntry { view, project: None }));
+    }
+    for project in shared_in(ctx, project_id).await? {
+        entries.extend(ctx.project_repository().list_views(&project).await?.into_iter()
+            .map(|view| ViewEntry { view, project: Some(project.clone()) }));
+    }
+
+    output::records(&entries, || {
+        if entries.is_empty() {
+            println!("No views saved.");
+            println!("💡 Tip: Save one with 'odi view save <name> <query>'");
+            return;
+        }
+        let mut heading = None;
+        for entry in &entries {
+            if heading != Some(&entry.project) {
+                match &entry.project {
+                    Some(project) => println!("Shared in {}:", project),
+                    None => println!("Your views:"),
+                }
+                heading = Some(&entry.project);
+            }
+            let view = &entry.view;
+            let query = if view.query.is_empty() { "(all issues)" } else { &view.query };
+            print!("  {} - {}", view.name, query);
+            if !view.sort.is_empty() {
+                print!(" [sort: {}]", join(&view.sort));
+            }
+            if !view.columns.is_empty() {
+                print!(" [columns: {}]", join(&view.columns));
+            }
+            println!();
+        }
+    })
+}
+
+async fn delete_view(ctx: &AppContext, name: &str, project_id: Option<&str>) -> Result<()> {
+    let deleted = match project_id {
+        Some(project_id) => ctx.project_repository().delete_view(&project_id.to_string(), &name.to_string()).await?,
+        None => {
+            let path = user_config_path();
+            let mut views = load_user_views(&path)?;
+            let before = views.len();
+            views.retain(|view| view.name != name);
+            let deleted = views.len() != before;
+            if deleted {
+                save_user_views(&path, &views)?;
+            }
+            deleted
+        }
+    };
+    if !deleted {
+        return Err(not_found(name));
+    }
+
+    output::record(&serde_json::json!({ "name": name, "project": project_id, "deleted": true }), || {
+        println!("Deleted view '{}'", name);
+    })
+}
+
+/// The view `odi issue list --view` names
+///
+/// The user's own view of that name comes first, then one shared in
+/// `project_id`, or in any project when none is given.
+pub(crate) async fn find_view(ctx: &AppContext, name: &str, project_id: Option<&str>) -> Result<View> {
+    if let Some(view) = load_user_views(&user_config_path())?.into_iter().find(|view| view.name == name) {
+        return Ok(view);
+    }
+
+    let mut found = Vec::new();
+    for project in shared_in(ctx, project_id).await? {
+        if let Some(view) = ctx.project_repository().get_view(&project, &name.to_string()).await? {
+            found.push((project, view));
+        }
+    }
+    match found.len() {
+        0 => Err(not_found(name)),
+        1 => Ok(found.remove(0).1),
+        _ => Err(OdiError::Validation {
+            message: format!(
+                "View '{}' is shared in several projects ({}); add --project to pick one",
+                name, found.iter().map(|(project, _)| project.as_str()).collect::<Vec<_>>().join(", ")
+            )
+        }),
+    }
+}
+
+/// Print issues as a table of the given columns
+pub(crate) fn print_table(issues: &[Issue], columns: &[Column]) {
+    let rows: Vec<Vec<String>> = issues.iter()
+        .map(|issue| columns.iter().map(|column| truncate(column.cell(issue))).collect())
+        .collect();
+    let headers: Vec<String> = columns.iter().map(|column| column.name().to_uppercase()).collect();
+    let widths: Vec<usize> = (0..columns.len())
+        .map(|i| rows.iter().map(|row| row[i].chars().count()).chain([headers[i].len()]).max().unwrap_or(0))
+        .collect();
+
+    for row in std::iter::once(&headers).chain(&rows) {
+        let line = row.iter().zip(&widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect::<Vec<_>>()
+            .join("  ");
+        println!("{}", line.trim_end());
+    }
+}
+
+/// Projects whose shared views to look at: the one given, or all of them
+async fn shared_in(ctx: &AppContext, project_id: Option<&str>) -> Result<Vec<ProjectId>> {
+    match project_id {
+        Some(project_id) => Ok(vec![project_id.to_string()]),
+        None => Ok(ctx.project_repository().list_projects(ProjectQuery::default()).await?
+            .into_iter()
+            .map(|project| project.id)
+            .collect()),
+    }
+}
+
+fn not_found(name: &str) -> OdiError {
+    OdiError::Validation {
+        message: format!("View '{}' not found; 'odi view list' shows the saved views", name)
+    }
+}
+
+fn truncate(cell: String) -> String {
+    if cell.chars().count() <= MAX_CELL_WIDTH {
+        return cell;
+    }
+    cell.chars().take(MAX_CELL_WIDTH - 1).chain(['…']).collect()
+}
+
+fn join<T: ToString>(items: &[T]) -> String {
+    items.iter().map(ToString::to_string).collect::<Vec<_>>().join(",")
+}
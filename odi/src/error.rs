@@ -59,9 +59,27 @@ impl OdiError {
     /// Convert technical errors to user-friendly messages
     pub fn format_user_friendly(&self) -> String {
         match self {
+            OdiError::Core(odi_core::CoreError::ValidationError { field, message }) if field == "query" => {
+                format!("🔎 Query Error\n{}\n\n💡 Tip: Queries look like 'status:open label:bug assignee:@me'; see 'odi issue list --help'", message)
+            },
+            OdiError::Core(odi_core::CoreError::ValidationError { field, message }) if field == "search" => {
+                format!("🔎 Search Error\n{}\n\n💡 Tip: Searches look like 'login crash', '\"exact phrase\"' or 'auth*'", message)
+            },
             OdiError::Core(core_error) => {
                 format!("🔧 Core Error\n{}\n\n💡 Tip: This is an internal ODI error", core_error)
             },
+            OdiError::Filesystem(fs_error @ odi_fs::FsError::MigrationRequired { .. }) => {
+                format!("📦 Workspace Format Error\n{}\n\n💡 Tip: Run 'odi migrate --dry-run' to see what will change", fs_error)
+            },
+            OdiError::Filesystem(fs_error @ odi_fs::FsError::UnsupportedWorkspace { .. }) => {
+                format!("📦 Workspace Format Error\n{}\n\n💡 Tip: Run 'odi --version' and install a newer release", fs_error)
+            },
+            OdiError::Filesystem(fs_error @ (odi_fs::FsError::KeyRequired | odi_fs::FsError::InvalidKey { .. })) => {
+                format!("🔒 Encryption Error\n{}\n\n💡 Tip: Export ODI_PASSPHRASE, or ODI_KEY_FILE with the path of the workspace key file", fs_error)
+            },
+            OdiError::Filesystem(fs_error @ odi_fs::FsError::GitError { .. }) => {
+                format!("🔀 Git Error\n{}\n\n💡 Tip: Run 'git status' and 'git remote -v' to check the repository and its remotes", fs_error)
+            },
             OdiError::Filesystem(fs_error) => {
                 format!("💾 Filesystem Error\n{}\n\n💡 Tip: Check file permissions and disk space", fs_error)
             },
@@ -9,7 +9,10 @@
 
 use std::path::{Path, PathBuf};
 
-use odi_fs::{FileSystemStorage, FileConfigLoader, Config, ConfigLoader, FsIssueRepository, FsProjectRepository, FsUserRepository, ConfigRemoteRepository};
+use odi_core::{IssueRepository, ProjectRepository, UserRepository, RemoteRepository};
+use odi_core::{InMemoryIssueRepository, InMemoryProjectRepository, InMemoryUserRepository, InMemoryRemoteRepository};
+use odi_fs::{FileSystemStorage, FileConfigLoader, Config, ConfigLoader, FsIssueRepository, FsProjectRepository, FsUserRepository, ConfigRemoteRepository, LockOptions, StorageBackend, KeySource, ReflogContext};
+use std::time::Duration;
 use odi_net::sync::DefaultRemoteSync;
 use crate::{Result, OdiError};
 use std::sync::Arc;
@@ -22,20 +25,70 @@ use std::sync::Arc;
 pub struct AppContext {
     /// Current workspace path
     workspace_path: PathBuf,
-    /// Filesystem storage engine
-    storage: Arc<FileSystemStorage>,
+    /// Filesystem storage engine (absent for in-memory contexts)
+    storage: Option<Arc<FileSystemStorage>>,
     /// Configuration
     config: Config,
     /// Remote sync service
     remote_sync: Arc<DefaultRemoteSync>,
     /// Issue repository
-    issue_repository: Arc<FsIssueRepository>,
+    issue_repository: Arc<dyn IssueRepository>,
     /// Project repository
-    project_repository: Arc<FsProjectRepository>,
+    project_repository: Arc<dyn ProjectRepository>,
     /// User repository  
-    user_repository: Arc<FsUserRepository>,
+    user_repository: Arc<dyn UserRepository>,
     /// Remote repository
-    remote_repository: Arc<ConfigRemoteRepository>,
+    remote_repository: Arc<dyn RemoteRepository>,
+}
+
+/// Repository implementations for one storage backend
+pub struct Repositories {
+    pub issues: Arc<dyn IssueRepository>,
+    pub projects: Arc<dyn ProjectRepository>,
+    pub users: Arc<dyn UserRepository>,
+    pub remotes: Arc<dyn RemoteRepository>,
+}
+
+impl Repositories {
+    /// Open the repositories of a workspace for the given backend
+    pub fn open(storage: &FileSystemStorage, backend: StorageBackend) -> Result<Self> {
+        match backend {
+            StorageBackend::Fs => Ok(Self {
+                issues: Arc::new(FsIssueRepository::new(storage.clone())),
+                projects: Arc::new(FsProjectRepository::new(storage.clone())),
+                users: Arc::new(FsUserRepository::new(storage.clone())),
+                // Git-style: remotes of the object-store backend live in .odi/config
+                remotes: Arc::new(ConfigRemoteRepository::new()),
+            }),
+            #[cfg(feature = "sqlite")]
+            StorageBackend::Sqlite => {
+                let database = odi_fs::SqliteStorage::open(storage.root_path(), storage.lock_options())
+                    .map_err(|e| OdiError::Storage { 
+                        message: format!("Failed to open SQLite database: {}", e) 
+                    })?;
+                Ok(Self {
+                    issues: Arc::new(odi_fs::SqliteIssueRepository::new(database.clone())),
+                    projects: Arc::new(odi_fs::SqliteProjectRepository::new(database.clone())),
+                    users: Arc::new(odi_fs::SqliteUserRepository::new(database.clone())),
+                    remotes: Arc::new(odi_fs::SqliteRemoteRepository::new(database)),
+                })
+            }
+            #[cfg(not(feature = "sqlite"))]
+            StorageBackend::Sqlite => Err(OdiError::Storage {
+                message: "This workspace uses the SQLite storage backend, but odi was built without the 'sqlite' feature".to_string(),
+            }),
+        }
+    }
+    
+    /// Empty repositories that live in memory and never touch disk
+    pub fn in_memory() -> Self {
+        Self {
+            issues: Arc::new(InMemoryIssueRepository::new()),
+            projects: Arc::new(InMemoryProjectRepository::new()),
+            users: Arc::new(InMemoryUserRepository::new()),
+            remotes: Arc::new(InMemoryRemoteRepository::new()),
+        }
+    }
 }
 
 impl AppContext {
@@ -54,34 +107,72 @@ impl AppContext {
         
         // T073: Integrate odi-core with odi-fs for persistent storage
         let storage_path = workspace_path.join(".odi");
-        let storage = Arc::new(
-            FileSystemStorage::new(storage_path)
-                .map_err(|e| OdiError::Storage { 
-                    message: format!("Failed to initialize storage: {}", e) 
-                })?
-        );
+        
+        // Refuse workspaces written by a newer odi, or not yet migrated to this one
+        odi_fs::check_version(&storage_path)?;
+        
+        let mut storage = FileSystemStorage::new(storage_path)
+            .map_err(|e| OdiError::Storage { 
+                message: format!("Failed to initialize storage: {}", e) 
+            })?
+            .with_lock_options(
+                LockOptions::new().timeout(Duration::from_secs(config.storage.lock_timeout))
+            )
+            .with_reflog_context(ReflogContext::new(Self::command_line(), config.user.name.clone()));
+        
+        // Encrypted workspaces stay locked without a key; fsck can still verify them
+        if storage.is_encrypted() {
+            if let Some(source) = KeySource::from_env() {
+                let cipher = odi_fs::crypto::unlock(storage.root_path(), &source)?;
+                storage = storage.with_cipher(cipher);
+            }
+        }
+        let storage = Arc::new(storage);
         
         // T074: Integrate odi-core with odi-net for remote synchronization
         let remote_sync = Arc::new(DefaultRemoteSync::new());
         
-        // T075: Initialize repository implementations
-        let issue_repository = Arc::new(FsIssueRepository::new((*storage).clone()));
-        let project_repository = Arc::new(FsProjectRepository::new((*storage).clone()));
-        let user_repository = Arc::new(FsUserRepository::new((*storage).clone()));
-        let remote_repository = Arc::new(ConfigRemoteRepository::new());
+        // T075: Initialize repository implementations for the configured backend
+        let repositories = Repositories::open(&storage, config.storage.backend)?;
         
         Ok(Self {
             workspace_path,
-            storage,
+            storage: Some(storage),
             config,
             remote_sync,
-            issue_repository,
-            project_repository,
-            user_repository,
-            remote_repository,
+            issue_repository: repositories.issues,
+            project_repository: repositories.projects,
+            user_repository: repositories.users,
+            remote_repository: repositories.remotes,
         })
     }
     
+    /// The command being run, as recorded in the reflog
+    fn command_line() -> String {
+        std::iter::once("odi".to_string())
+            .chain(std::env::args().skip(1))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+    
+    /// Build a context around existing repositories, without an object store
+    ///
+    /// Used with [`Repositories::in_memory`] by tools embedding ODI and by tests.
+    /// Commands that work on the object store itself (fsck, migrate-storage)
+    /// fail on such a context.
+    pub fn from_repositories(workspace_path: PathBuf, config: Config, repositories: Repositories) -> Self {
+        Self {
+            workspace_path,
+            storage: None,
+            config,
+            remote_sync: Arc::new(DefaultRemoteSync::new()),
+            issue_repository: repositories.issues,
+            project_repository: repositories.projects,
+            user_repository: repositories.users,
+            remote_repository: repositories.remotes,
+        }
+    }
+    
     /// Load and validate configuration from hierarchy
     async fn load_configuration(workspace_path: &Path) -> Result<Config> {
         // Load global configuration
@@ -119,8 +210,10 @@ impl AppContext {
     }
     
     /// Get storage engine reference
-    pub fn storage(&self) -> &Arc<FileSystemStorage> {
-        &self.storage
+    pub fn storage(&self) -> Result<&Arc<FileSystemStorage>> {
+        self.storage.as_ref().ok_or_else(|| OdiError::Storage {
+            message: "This context has no object store".to_string(),
+        })
     }
     
     /// Get remote sync service reference
@@ -129,22 +222,22 @@ impl AppContext {
     }
     
     /// Get issue repository reference
-    pub fn issue_repository(&self) -> &Arc<FsIssueRepository> {
+    pub fn issue_repository(&self) -> &Arc<dyn IssueRepository> {
         &self.issue_repository
     }
     
     /// Get project repository reference
-    pub fn project_repository(&self) -> &Arc<FsProjectRepository> {
+    pub fn project_repository(&self) -> &Arc<dyn ProjectRepository> {
         &self.project_repository
     }
     
     /// Get user repository reference
-    pub fn user_repository(&self) -> &Arc<FsUserRepository> {
+    pub fn user_repository(&self) -> &Arc<dyn UserRepository> {
         &self.user_repository
     }
     
     /// Get remote repository reference
-    pub fn remote_repository(&self) -> &Arc<ConfigRemoteRepository> {
+    pub fn remote_repository(&self) -> &Arc<dyn RemoteRepository> {
         &self.remote_repository
     }
     
@@ -188,6 +281,7 @@ impl AppContext {
                     message: format!("Failed to initialize storage: {}", e) 
                 })?
         );
+        odi_fs::write_version(storage.root_path(), odi_fs::CURRENT_VERSION)?;
         
         // Create default configuration
         let config = Config::default();
@@ -201,20 +295,37 @@ impl AppContext {
         let remote_sync = Arc::new(DefaultRemoteSync::new());
         
         // Initialize repository implementations
-        let issue_repository = Arc::new(FsIssueRepository::new((*storage).clone()));
-        let project_repository = Arc::new(FsProjectRepository::new((*storage).clone()));
-        let user_repository = Arc::new(FsUserRepository::new((*storage).clone()));
-        let remote_repository = Arc::new(ConfigRemoteRepository::new());
+        let repositories = Repositories::open(&storage, config.storage.backend)?;
         
         Ok(Self {
             workspace_path: path.to_path_buf(),
-            storage,
+            storage: Some(storage),
             config,
             remote_sync,
-            issue_repository,
-            project_repository,
-            user_repository,
-            remote_repository,
+            issue_repository: repositories.issues,
+            project_repository: repositories.projects,
+            user_repository: repositories.users,
+            remote_repository: repositories.remotes,
         })
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use odi_core::{Issue, IssueQuery};
+
+    #[tokio::test]
+    async fn test_in_memory_context() {
+        let ctx = AppContext::from_repositories(PathBuf::from("/nonexistent"), Config::default(), Repositories::in_memory());
+
+        let issue = Issue::new("Embedded".to_string(), "alice".to_string());
+        ctx.issue_repository().create(issue.clone()).await.unwrap();
+
+        let issues = ctx.issue_repository().list(IssueQuery::default()).await.unwrap();
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].id, issue.id);
+        assert!(ctx.storage().is_err());
+        assert!(!Path::new("/nonexistent").exists());
+    }
+}
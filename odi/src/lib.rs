@@ -6,6 +6,7 @@ pub mod cli;
 pub mod commands;
 pub mod error;
 pub mod integration;
+pub mod output;
 
 pub use error::{OdiError, Result};
 pub use integration::AppContext;
\ No newline at end of file
@@ -5,16 +5,8 @@
 
 use clap::Parser;
 
-mod cli;
-mod commands;
-mod error;
-mod integration;
-
-use cli::Cli;
-use error::OdiError;
-use integration::AppContext;
-
-type Result<T> = std::result::Result<T, OdiError>;
+use odi::cli::Cli;
+use odi::Result;
 
 #[tokio::main]
 async fn main() -> Result<()> {
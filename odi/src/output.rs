@@ -0,0 +1,217 @@
+//! Command output as text or as structured data
+//!
+//! The global `--format` option picks how commands print what they produce.
+//! Text is meant for people and may change between releases. The other formats
+//! print records whose fields are the serialized form of ODI's entities (see
+//! "Output Formats" in docs/commands.md), for scripts and CI. In those formats
+//! progress messages go to standard error, so standard output holds only data.
+
+use std::sync::OnceLock;
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::{OdiError, Result};
+
+/// How commands print their results
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Human-readable text
+    #[default]
+    Text,
+    /// One JSON document: an object, or an array for lists
+    Json,
+    /// One YAML document: a mapping, or a sequence for lists
+    Yaml,
+    /// A header row of field names, then one row per record
+    Csv,
+    /// One JSON object per line, printed as records are produced
+    Ndjson,
+}
+
+/// Output settings from the command line
+#[derive(Debug, Clone, Default)]
+pub struct Output {
+    pub format: OutputFormat,
+    /// Fields to keep, as dotted paths into each record; empty keeps all
+    pub fields: Vec<String>,
+}
+
+static OUTPUT: OnceLock<Output> = OnceLock::new();
+
+/// Set the output settings for this process; later calls are ignored
+pub fn init(output: Output) -> Result<()> {
+    if output.format == OutputFormat::Text && !output.fields.is_empty() {
+        return Err(OdiError::Validation {
+            message: "--fields selects fields of structured output; add --format json, yaml, csv or ndjson".to_string(),
+        });
+    }
+    let _ = OUTPUT.set(output);
+    Ok(())
+}
+
+/// Output settings of this process, text if none were set
+pub fn get() -> &'static Output {
+    OUTPUT.get_or_init(Output::default)
+}
+
+/// Print a progress or status message: to standard output as text, otherwise
+/// to standard error so it stays out of the data
+macro_rules! say {
+    ($($arg:tt)*) => {
+        if $crate::output::get().is_text() {
+            println!($($arg)*)
+        } else {
+            eprintln!($($arg)*)
+        }
+    };
+}
+pub(crate) use say;
+
+/// Whether commands print structured data instead of text
+pub fn structured() -> bool {
+    !get().is_text()
+}
+
+/// Print one record: with `text` as text, otherwise serialized
+pub fn record<T: Serialize>(value: &T, text: impl FnOnce()) -> Result<()> {
+    if structured() {
+        print_record(value)
+    } else {
+        text();
+        Ok(())
+    }
+}
+
+/// Print a list of records: with `text` as text, otherwise serialized
+pub fn records<T: Serialize>(values: &[T], text: impl FnOnce()) -> Result<()> {
+    if structured() {
+        print_records(values)
+    } else {
+        text();
+        Ok(())
+    }
+}
+
+/// Print one record in the structured format
+pub fn print_record<T: Serialize>(value: &T) -> Result<()> {
+    print!("{}", get().render(std::slice::from_ref(value), false)?);
+    Ok(())
+}
+
+/// Print a list of records in the structured format
+pub fn print_records<T: Serialize>(values: &[T]) -> Result<()> {
+    let output = get();
+    if output.format == OutputFormat::Ndjson {
+        // One line per record, stopping quietly once the reader has closed the pipe
+        use std::io::Write;
+        let mut stdout = std::io::stdout().lock();
+        for value in values {
+            match stdout.write_all(output.render(std::slice::from_ref(value), false)?.as_bytes()) {
+                // The reader has what it wanted, as with `| head`
+                Err(e) if e.kind() == std::io::ErrorKind::BrokenPipe => return Ok(()),
+                result => result?,
+            }
+        }
+        return Ok(());
+    }
+    print!("{}", output.render(values, true)?);
+    Ok(())
+}
+
+fn serialization_error(e: impl std::fmt::Display) -> OdiError {
+    OdiError::Io { message: format!("Failed to format output: {}", e) }
+}
+
+impl Output {
+    pub fn is_text(&self) -> bool {
+        self.format == OutputFormat::Text
+    }
+
+    /// Records in this format; a single record is printed bare in JSON and YAML
+    pub fn render<T: Serialize>(&self, values: &[T], list: bool) -> Result<String> {
+        let records = values.iter()
+            .map(|value| serde_json::to_value(value).map(|value| self.select(value)))
+            .collect::<std::result::Result<Vec<Value>, _>>()
+            .map_err(serialization_error)?;
+        let document = match (list, records.as_slice()) {
+            (false, [record]) => record.clone(),
+            _ => Value::Array(records.clone()),
+        };
+
+        match self.format {
+            OutputFormat::Text | OutputFormat::Json => {
+                Ok(serde_json::to_string_pretty(&document).map_err(serialization_error)? + "\n")
+            }
+            OutputFormat::Yaml => serde_yaml::to_string(&document).map_err(serialization_error),
+            OutputFormat::Ndjson => {
+                let mut out = String::new();
+                for record in &records {
+                    out.push_str(&serde_json::to_string(record).map_err(serialization_error)?);
+                    out.push('\n');
+                }
+                Ok(out)
+            }
+            OutputFormat::Csv => Ok(to_csv(&records, &self.fields)),
+        }
+    }
+
+    /// Keep only the requested fields of a record, keyed by their paths
+    fn select(&self, value: Value) -> Value {
+        if self.fields.is_empty() {
+            return value;
+        }
+        let mut selected = Map::new();
+        for field in &self.fields {
+            let pointer = format!("/{}", field.replace('.', "/"));
+            selected.insert(field.clone(), value.pointer(&pointer).cloned().unwrap_or(Value::Null));
+        }
+        Value::Object(selected)
+    }
+}
+
+/// Records as CSV, with the given columns or else every top-level field in the
+/// order first seen
+fn to_csv(records: &[Value], fields: &[String]) -> String {
+    let mut columns: Vec<String> = fields.to_vec();
+    if columns.is_empty() {
+        for record in records {
+            if let Value::Object(map) = record {
+                for key in map.keys() {
+                    if !columns.contains(key) {
+                        columns.push(key.clone());
+                    }
+                }
+            }
+        }
+    }
+
+    let mut out = String::new();
+    let row = |cells: Vec<String>| cells.iter().map(|cell| csv_quote(cell)).collect::<Vec<_>>().join(",") + "\n";
+    out.push_str(&row(columns.clone()));
+    for record in records {
+        out.push_str(&row(columns.iter().map(|column| csv_cell(record.get(column))).collect()));
+    }
+    out
+}
+
+/// A value as one CSV cell: lists of plain values joined with `;`, nested
+/// objects as JSON
+fn csv_cell(value: Option<&Value>) -> String {
+    match value {
+        None | Some(Value::Null) => String::new(),
+        Some(Value::String(text)) => text.clone(),
+        Some(Value::Array(items)) if items.iter().all(|item| !item.is_array() && !item.is_object()) => {
+            items.iter().map(|item| csv_cell(Some(item))).collect::<Vec<_>>().join(";")
+        }
+        Some(other) => other.to_string(),
+    }
+}
+
+fn csv_quote(cell: &str) -> String {
+    if cell.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", cell.replace('"', "\"\""))
+    } else {
+        cell.to_string()
+    }
+}
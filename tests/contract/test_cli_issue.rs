@@ -316,6 +316,41 @@ fn test_issue_list_filter_status() {
         .stdout(predicate::str::contains("In progress issue").not());
 }
 
+#[test]
+fn test_issue_list_negated_query() {
+    // Test that a query starting with '-' isn't read as options
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let mut init_cmd = Command::cargo_bin("odi").expect("Failed to find odi binary");
+    init_cmd.current_dir(temp_dir.path())
+        .arg("init")
+        .assert()
+        .success();
+
+    for (title, priority) in [("Minor typo", "low"), ("Data loss", "critical")] {
+        let mut create_cmd = Command::cargo_bin("odi").expect("Failed to find odi binary");
+        create_cmd.current_dir(temp_dir.path())
+            .args(["issue", "create", title, "--priority", priority])
+            .assert()
+            .success();
+    }
+
+    let mut list_cmd = Command::cargo_bin("odi").expect("Failed to find odi binary");
+    list_cmd.current_dir(temp_dir.path())
+        .args(["issue", "list", "--format", "json", "-priority:low"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Data loss"))
+        .stdout(predicate::str::contains("Minor typo").not());
+
+    // Options after the query are refused instead of being matched as text
+    let mut late_option_cmd = Command::cargo_bin("odi").expect("Failed to find odi binary");
+    late_option_cmd.current_dir(temp_dir.path())
+        .args(["issue", "list", "-priority:low", "--limit", "1"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("give options before the query"));
+}
+
 #[test]
 fn test_issue_invalid_project() {
     // Test issue operations with nonexistent project